@@ -0,0 +1,53 @@
+//! Compares the cost of cloning a `Glob`, which shares its compiled program, with building the
+//! glob again and with cloning an equivalent `Regex`, which a clone of a `Glob` previously
+//! required in addition to cloning its token tree.
+//!
+//! Run with `cargo bench --bench clone`.
+
+use regex::Regex;
+use std::hint;
+use std::time::{Duration, Instant};
+use wax::Glob;
+
+const CLONE_COUNT: usize = 100_000;
+const BUILD_COUNT: usize = 1_000;
+// Glob expressions paired with hand-written regular expressions that match the same paths.
+const EXPRESSIONS: [(&str, &str); 4] = [
+    ("*.rs", r"^[^/]*\.rs$"),
+    ("src/**/*.rs", r"^src/(?:.*/)?[^/]*\.rs$"),
+    ("**/*.{md,txt}", r"^(?:.*/)?[^/]*\.(?:md|txt)$"),
+    (
+        "<[a-z]*/:2,3>mod*.rs",
+        r"^(?:[a-z][^/]*/){2,3}mod[^/]*\.rs$",
+    ),
+];
+
+fn time(count: usize, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..count {
+        f();
+    }
+    start.elapsed() / u32::try_from(count).expect("count overflows `u32`")
+}
+
+fn main() {
+    for (expression, pattern) in EXPRESSIONS {
+        let glob = Glob::new(expression).expect("failed to build glob");
+        let regex = Regex::new(pattern).expect("failed to build regex");
+
+        let clone = time(CLONE_COUNT, || {
+            hint::black_box(glob.clone());
+        });
+        let build = time(BUILD_COUNT, || {
+            hint::black_box(Glob::new(hint::black_box(expression)).unwrap());
+        });
+        let regex = time(CLONE_COUNT, || {
+            hint::black_box(regex.clone());
+        });
+
+        println!(
+            "{:<24} clone: {:>10.2?}  build: {:>10.2?}  regex clone: {:>10.2?}",
+            expression, clone, build, regex,
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Compares the throughput of `Glob::is_match` and `DfaGlob::is_match`.
+//!
+//! Run with `cargo bench --bench dfa --features dfa`.
+
+use std::hint;
+use std::time::{Duration, Instant};
+use wax::{Glob, Program as _};
+
+const PATH_COUNT: usize = 1_000_000;
+const EXPRESSIONS: [&str; 4] = [
+    "src/**/*.rs",
+    "**/*.{md,txt}",
+    "<[a-z]*/:2,3>mod*.rs",
+    "**/(?i)readme*.md",
+];
+
+fn paths() -> Vec<String> {
+    const DIRECTORIES: [&str; 6] = [
+        "src",
+        "doc",
+        "tests",
+        "src/walk",
+        "src/token",
+        "target/debug",
+    ];
+    const NAMES: [&str; 7] = ["lib", "mod", "README", "guide", "main", "parse", "walk"];
+    const EXTENSIONS: [&str; 4] = ["rs", "md", "txt", "toml"];
+
+    (0..PATH_COUNT)
+        .map(|i| {
+            format!(
+                "{}/{}/{}{}.{}",
+                DIRECTORIES[i % DIRECTORIES.len()],
+                DIRECTORIES[(i / DIRECTORIES.len()) % DIRECTORIES.len()],
+                NAMES[i % NAMES.len()],
+                i % 97,
+                EXTENSIONS[i % EXTENSIONS.len()],
+            )
+        })
+        .collect()
+}
+
+fn time(mut f: impl FnMut() -> usize) -> (usize, Duration) {
+    let start = Instant::now();
+    let n = hint::black_box(f());
+    (n, start.elapsed())
+}
+
+fn main() {
+    let paths = paths();
+    for expression in EXPRESSIONS {
+        let glob = Glob::new(expression).expect("failed to build glob");
+        let dfa = glob.clone().compile_dfa().expect("failed to compile DFA");
+
+        let (n, regex) = time(|| {
+            paths
+                .iter()
+                .filter(|path| glob.is_match(path.as_str()))
+                .count()
+        });
+        let (m, automaton) = time(|| {
+            paths
+                .iter()
+                .filter(|path| dfa.is_match(path.as_str()))
+                .count()
+        });
+        assert_eq!(n, m, "matchers disagree for `{}`", expression);
+
+        println!(
+            "{:<24} {:>8} matches  regex: {:>10.2?}  dfa: {:>10.2?} ({} bytes)",
+            expression,
+            n,
+            regex,
+            automaton,
+            dfa.memory_usage(),
+        );
+    }
+}
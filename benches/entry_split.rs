@@ -0,0 +1,87 @@
+//! Compares the cost of `Entry::root_relative_paths`, which reuses a split computed when an entry
+//! is constructed, with splitting entry paths at their depth on every call.
+//!
+//! Run with `cargo bench --bench entry_split`.
+
+use std::hint;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use wax::walk::{Entry, PathExt};
+use wax::Glob;
+
+const ITERATIONS: usize = 1_000;
+
+// This is the split that entries computed on every call before the split was cached.
+fn split_at_depth(path: &Path, depth: usize) -> (&Path, &Path) {
+    let ancestor = path.ancestors().nth(depth).unwrap_or(Path::new(""));
+    let descendant = path.strip_prefix(ancestor).unwrap();
+    (ancestor, descendant)
+}
+
+fn time(mut f: impl FnMut() -> usize) -> (usize, Duration) {
+    let start = Instant::now();
+    let n = hint::black_box(f());
+    (n, start.elapsed())
+}
+
+fn bench<E>(name: &str, entries: &[E])
+where
+    E: Entry,
+{
+    let (n, cached) = time(|| {
+        (0..ITERATIONS)
+            .map(|_| {
+                entries
+                    .iter()
+                    .map(|entry| {
+                        hint::black_box(entry.root_relative_paths())
+                            .1
+                            .as_os_str()
+                            .len()
+                    })
+                    .sum::<usize>()
+            })
+            .sum()
+    });
+    let (m, uncached) = time(|| {
+        (0..ITERATIONS)
+            .map(|_| {
+                entries
+                    .iter()
+                    .map(|entry| {
+                        hint::black_box(split_at_depth(entry.path(), entry.depth()))
+                            .1
+                            .as_os_str()
+                            .len()
+                    })
+                    .sum::<usize>()
+            })
+            .sum()
+    });
+    assert_eq!(n, m, "splits disagree for {}", name);
+
+    println!(
+        "{:<8} {:>6} entries  cached: {:>10.2?}  uncached: {:>10.2?}",
+        name,
+        entries.len(),
+        cached,
+        uncached,
+    );
+}
+
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+
+    let entries: Vec<_> = root
+        .walk()
+        .map(|entry| entry.expect("failed to read file"))
+        .collect();
+    bench("tree", &entries);
+
+    let glob = Glob::new("**/*.rs").expect("failed to build glob");
+    let entries: Vec<_> = glob
+        .walk(&root)
+        .map(|entry| entry.expect("failed to read file"))
+        .collect();
+    bench("glob", &entries);
+}
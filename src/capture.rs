@@ -110,14 +110,22 @@ impl From<OwnedText> for MaybeOwnedText<'static> {
 #[derive(Debug)]
 pub struct MatchedText<'t> {
     inner: MaybeOwnedText<'t>,
+    branch: Option<usize>,
 }
 
 impl<'t> MatchedText<'t> {
     /// Clones any borrowed data into an owning instance.
+    ///
+    /// If the matched text already owns its data (see [`is_owned`]), then this function moves
+    /// that data and does not clone or reallocate it. Matched text in entries yielded by walks is
+    /// always owned.
+    ///
+    /// [`is_owned`]: crate::MatchedText::is_owned
     pub fn into_owned(self) -> MatchedText<'static> {
-        let MatchedText { inner } = self;
+        let MatchedText { inner, branch } = self;
         MatchedText {
             inner: inner.into_owned(),
+            branch,
         }
     }
 
@@ -134,9 +142,23 @@ impl<'t> MatchedText<'t> {
     pub fn to_owned(&self) -> MatchedText<'static> {
         MatchedText {
             inner: self.inner.to_owned(),
+            branch: self.branch,
         }
     }
 
+    /// Returns `true` if the matched text owns its data and borrows nothing.
+    ///
+    /// Matched text from [`Program::matched`] borrows from its [`CandidatePath`]. Matched text
+    /// returned by [`into_owned`] and [`to_owned`] is owned.
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`into_owned`]: crate::MatchedText::into_owned
+    /// [`Program::matched`]: crate::Program::matched
+    /// [`to_owned`]: crate::MatchedText::to_owned
+    pub fn is_owned(&self) -> bool {
+        matches!(self.inner, MaybeOwnedText::Owned(_))
+    }
+
     /// Gets the complete text of a match.
     ///
     /// All [`Program`]s have an implicit capture of the complete text at index zero. This function
@@ -170,7 +192,56 @@ impl<'t> MatchedText<'t> {
         }
     }
 
-    pub fn to_candidate_path(&self) -> CandidatePath {
+    /// Gets the index of the matched branch of a top-level alternative.
+    ///
+    /// Returns `None` if the [`Program`] is not a top-level alternative. A top-level alternative
+    /// is either an [`Any`] or a [`Glob`] with an expression that consists only of an alternation,
+    /// such as `{**/*.rs,**/*.toml}`. Branches are indexed from zero in the order in which they
+    /// appear. If more than one branch matches, then the index of the first such branch is
+    /// returned.
+    ///
+    /// Only [`MatchedText`] from [`Program::matched`] describes the matched branch. The matched
+    /// text of entries yielded by walks does not.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{CandidatePath, Glob, Program};
+    ///
+    /// let glob = Glob::new("{**/*.{jpg,png},**/*.{mp3,ogg}}").unwrap();
+    /// let candidate = CandidatePath::from("music/intro.ogg");
+    /// let matched = glob.matched(&candidate).unwrap();
+    ///
+    /// assert_eq!(matched.matched_branch(), Some(1));
+    /// ```
+    ///
+    /// [`Any`]: crate::Any
+    /// [`Glob`]: crate::Glob
+    /// [`MatchedText`]: crate::MatchedText
+    /// [`Program`]: crate::Program
+    /// [`Program::matched`]: crate::Program::matched
+    pub fn matched_branch(&self) -> Option<usize> {
+        self.branch
+    }
+
+    /// Constructs matched text with the given complete text and no captures.
+    #[cfg(feature = "walk")]
+    pub(crate) fn uncaptured(text: impl Into<String>) -> MatchedText<'static> {
+        MatchedText {
+            inner: OwnedText {
+                matched: text.into(),
+                ranges: vec![],
+            }
+            .into(),
+            branch: None,
+        }
+    }
+
+    pub(crate) fn with_branch(self, branch: Option<usize>) -> Self {
+        MatchedText { branch, ..self }
+    }
+
+    pub fn to_candidate_path(&self) -> CandidatePath<'_> {
         CandidatePath::from(self.complete())
     }
 }
@@ -180,6 +251,7 @@ impl<'t> From<BorrowedText<'t>> for MatchedText<'t> {
     fn from(captures: BorrowedText<'t>) -> Self {
         MatchedText {
             inner: captures.into(),
+            branch: None,
         }
     }
 }
@@ -188,6 +260,7 @@ impl From<OwnedText> for MatchedText<'static> {
     fn from(captures: OwnedText) -> Self {
         MatchedText {
             inner: captures.into(),
+            branch: None,
         }
     }
 }
@@ -9,6 +9,7 @@ use tardar::{
 use thiserror::Error;
 
 use crate::diagnostics::{SpanExt, Spanned};
+use crate::encode;
 use crate::rule::{self, Checked};
 use crate::token::{self, Boundary, ExpressionMetadata, TokenTree, Tokenized};
 use crate::Glob;
@@ -40,9 +41,9 @@ impl<'t> Glob<'t> {
     /// [`Glob::new`]: crate::Glob::new
     pub fn diagnosed(expression: &'t str) -> DiagnosticResult<'t, Self> {
         parse_and_diagnose(expression).and_then_diagnose(|tree| {
-            Glob::compile::<Tokenized<_>>(tree.as_ref())
+            encode::compile::<Tokenized<_>>(tree.as_ref())
                 .into_error_diagnostic()
-                .map_output(|program| Glob { tree, program })
+                .map_output(|program| Glob::from_tree_and_program(tree, program))
         })
     }
 
@@ -86,7 +87,7 @@ pub struct TerminatingSeparatorWarning<'t> {
 
 fn parse_and_diagnose(
     expression: &str,
-) -> DiagnosticResult<Checked<Tokenized<ExpressionMetadata>>> {
+) -> DiagnosticResult<'_, Checked<Tokenized<'_, ExpressionMetadata>>> {
     token::parse(expression)
         .into_error_diagnostic()
         .and_then_diagnose(|tree| rule::check(tree).into_error_diagnostic())
@@ -168,7 +169,7 @@ pub mod harness {
         assert!(
             diagnostics.iter().any(|diagnostic| diagnostic
                 .code()
-                .map_or(false, |code| code.to_string() == expected)),
+                .is_some_and(|code| code.to_string() == expected)),
             "expected diagnostic code `{}`, but not found: diagnostics: `{:?}`",
             expected,
             diagnostics,
@@ -2,13 +2,24 @@ use const_format::formatcp;
 use itertools::{Itertools as _, Position};
 #[cfg(feature = "miette")]
 use miette::Diagnostic;
-use regex::{Error as RegexError, Regex};
+#[cfg(feature = "bytes")]
+use regex::bytes::Regex as BytesRegex;
+use regex::{Error as RegexError, Regex, RegexSet};
+#[cfg(feature = "dfa")]
+use regex_automata::dfa::dense::{self, DFA};
+#[cfg(feature = "dfa")]
+use regex_automata::dfa::StartKind;
 use std::borrow::{Borrow, Cow};
+use std::cmp;
+#[cfg(feature = "bytes")]
+use std::str;
 #[cfg(feature = "miette")]
 use std::fmt::Display;
 use thiserror::Error;
 
-use crate::token::{ConcatenationTree, Token, TokenTopology};
+#[cfg(feature = "unicode-case")]
+use crate::fold;
+use crate::token::{self, ConcatenationTree, LeafKind, NaturalRange, Token, TokenTopology};
 
 /// A regular expression that never matches.
 ///
@@ -48,16 +59,184 @@ const fn main_separator_class_expression() -> &'static str {
     formatcp!("{0}{1}", escape(MAIN_SEPARATOR), MAIN_SEPARATOR)
 }
 
-macro_rules! sepexpr {
-    ($fmt:expr) => {
-        formatcp!($fmt, formatcp!("[{0}]", SEPARATOR_CLASS_EXPRESSION))
+/// A character class expression that matches only forward slash `/`, regardless of platform.
+const SLASH_CLASS_EXPRESSION: &str = "/";
+
+macro_rules! non_hidden_tree_expression {
+    ($class:expr) => {
+        formatcp!("(?:[^{0}.][^{0}]*)?(?:[{0}](?:[^{0}.][^{0}]*)?)*", $class)
     };
 }
 
-macro_rules! nsepexpr {
-    ($fmt:expr) => {
-        formatcp!($fmt, formatcp!("[^{0}]", SEPARATOR_CLASS_EXPRESSION))
-    };
+/// A regular expression that matches a byte of invalid UTF-8 that has been escaped by
+/// [`escape_invalid_utf8`].
+///
+/// The byte `0xFF` never occurs in UTF-8, so no other expression matches any part of this escape.
+#[cfg(feature = "bytes")]
+const INVALID_UTF8_EXPRESSION: &str = r"(?-u:\xFF[\x80-\xFF])";
+
+/// A regular expression that matches the components of a tree wildcard.
+const TREE_EXPRESSION: &str = ".*";
+
+/// A regular expression that matches the components of a tree wildcard that excludes hidden
+/// components.
+///
+/// This expression matches any text in which no component begins with `.`, including empty text.
+const NON_HIDDEN_TREE_EXPRESSION: &str = non_hidden_tree_expression!(SEPARATOR_CLASS_EXPRESSION);
+
+/// A regular expression like [`NON_HIDDEN_TREE_EXPRESSION`] that only considers forward slash `/`
+/// a separator.
+const SLASH_NON_HIDDEN_TREE_EXPRESSION: &str =
+    non_hidden_tree_expression!(SLASH_CLASS_EXPRESSION);
+
+/// Characters that are interpreted as separators by a compiled program.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Separators {
+    /// The separators of the target platform, such as `/` and `\` on Windows.
+    Native,
+    /// Only forward slash `/`, regardless of platform.
+    // Forward slash is the only separator on Unix, so native programs are used there instead.
+    #[cfg_attr(unix, allow(dead_code))]
+    Slash,
+    /// Only the given character, regardless of platform. This is the custom separator of a token
+    /// and is never the target of a program. See `Target::separated_by`.
+    Char(char),
+}
+
+impl Separators {
+    fn class_expression(self) -> Cow<'static, str> {
+        match self {
+            Separators::Native => SEPARATOR_CLASS_EXPRESSION.into(),
+            Separators::Slash => SLASH_CLASS_EXPRESSION.into(),
+            Separators::Char(separator) => regex::escape(separator.encode_utf8(&mut [0; 4])).into(),
+        }
+    }
+
+    /// Gets a regular expression that matches a separator.
+    fn separator(self) -> String {
+        format!("[{}]", self.class_expression())
+    }
+
+    /// Gets a regular expression that matches any character except a separator.
+    fn non_separator(self) -> String {
+        format!("[^{}]", self.class_expression())
+    }
+
+    fn non_hidden_tree_expression(self) -> Cow<'static, str> {
+        match self {
+            Separators::Native => NON_HIDDEN_TREE_EXPRESSION.into(),
+            Separators::Slash => SLASH_NON_HIDDEN_TREE_EXPRESSION.into(),
+            Separators::Char(_) => format!(
+                "(?:[^{0}.][^{0}]*)?(?:[{0}](?:[^{0}.][^{0}]*)?)*",
+                self.class_expression(),
+            )
+            .into(),
+        }
+    }
+}
+
+/// Text that is matched by a compiled program.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Subject {
+    /// UTF-8 encoded text.
+    Text,
+    /// Bytes in which invalid UTF-8 has been escaped by [`escape_invalid_utf8`].
+    ///
+    /// Each byte of invalid UTF-8 is matched like a character by wildcards and negated character
+    /// classes, but is never matched by literals nor other character classes.
+    #[cfg(feature = "bytes")]
+    Bytes,
+}
+
+/// Separators and text that are matched by a compiled program.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Target {
+    separators: Separators,
+    subject: Subject,
+}
+
+impl Target {
+    /// Gets the target of a token with the given separator.
+    ///
+    /// Tokens with a custom separator only interpret that character as a separator, regardless of
+    /// the separators of the program.
+    fn separated_by(self, separator: token::Separator) -> Self {
+        match separator.custom() {
+            Some(separator) => Target {
+                separators: Separators::Char(separator),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// Gets a regular expression that matches a separator.
+    fn separator(self) -> String {
+        self.separators.separator()
+    }
+
+    /// Gets a regular expression that matches any character except a separator.
+    fn non_separator(self) -> String {
+        self.or_invalid_utf8(self.separators.non_separator())
+    }
+
+    /// Gets a regular expression that matches the components of a tree wildcard.
+    ///
+    /// If `excludes_empty` is `true`, then the expression does not match empty text.
+    fn tree_expression(self, excludes_hidden: bool, excludes_empty: bool) -> Cow<'static, str> {
+        // Tree wildcards that exclude hidden components match an optional component followed by
+        // any number of separated optional components. When empty text is excluded, either the
+        // leading component or at least one separated component is matched.
+        let non_hidden = |component: String| -> Cow<'static, str> {
+            let separated = format!("[{}](?:{})?", self.separators.class_expression(), component);
+            if excludes_empty {
+                format!("(?:{0}|{1})(?:{1})*", component, separated).into()
+            }
+            else {
+                format!("(?:{})?(?:{})*", component, separated).into()
+            }
+        };
+        match (self.subject, excludes_hidden) {
+            (Subject::Text, false) if excludes_empty => ".+".into(),
+            (Subject::Text, false) => TREE_EXPRESSION.into(),
+            (Subject::Text, true) if !excludes_empty => {
+                self.separators.non_hidden_tree_expression()
+            },
+            (Subject::Text, true) => {
+                let class = self.separators.class_expression();
+                non_hidden(format!("[^{0}.][^{0}]*", class))
+            },
+            #[cfg(feature = "bytes")]
+            (Subject::Bytes, false) => {
+                let repetition = if excludes_empty { "+" } else { "*" };
+                format!("(?:.|{}){}", INVALID_UTF8_EXPRESSION, repetition).into()
+            },
+            #[cfg(feature = "bytes")]
+            (Subject::Bytes, true) => {
+                let class = self.separators.class_expression();
+                let first = self.or_invalid_utf8(format!("[^{}.]", class));
+                non_hidden(format!("{}{}*", first, self.non_separator()))
+            },
+        }
+    }
+
+    /// Gets a regular expression that also matches escaped invalid UTF-8 when matching bytes.
+    fn or_invalid_utf8(self, expression: String) -> String {
+        match self.subject {
+            Subject::Text => expression,
+            #[cfg(feature = "bytes")]
+            Subject::Bytes => format!("(?:{}|{})", expression, INVALID_UTF8_EXPRESSION),
+        }
+    }
+}
+
+impl From<Separators> for Target {
+    fn from(separators: Separators) -> Self {
+        Target {
+            separators,
+            subject: Subject::Text,
+        }
+    }
 }
 
 /// Describes errors that occur when compiling a glob expression.
@@ -70,11 +249,20 @@ pub struct CompileError {
     kind: CompileErrorKind,
 }
 
+impl CompileError {
+    /// Returns `true` if the estimated size of the program exceeds a size limit.
+    pub fn is_too_complex(&self) -> bool {
+        matches!(self.kind, CompileErrorKind::TooComplex { .. })
+    }
+}
+
 #[derive(Clone, Copy, Debug, Error)]
 #[non_exhaustive]
 enum CompileErrorKind {
     #[error("oversized program")]
     OversizedProgram,
+    #[error("too complex program: estimated size {estimated_size} exceeds limit")]
+    TooComplex { estimated_size: usize },
 }
 
 #[cfg(feature = "miette")]
@@ -83,8 +271,18 @@ impl Diagnostic for CompileError {
     fn code<'a>(&'a self) -> Option<Box<dyn 'a + Display>> {
         Some(Box::new(String::from(match self.kind {
             CompileErrorKind::OversizedProgram => "wax::glob::oversized_program",
+            CompileErrorKind::TooComplex { .. } => "wax::glob::too_complex",
         })))
     }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn 'a + Display>> {
+        match self.kind {
+            CompileErrorKind::TooComplex { .. } => Some(Box::new(String::from(
+                "this error typically occurs when a repetition has a large upper bound",
+            ))),
+            _ => None,
+        }
+    }
 }
 
 trait Escaped {
@@ -127,7 +325,19 @@ impl Grouping {
     }
 }
 
+// Full case foldings are expanded so that text like `ß` and `ss` is equal under simple case
+// folding.
+#[cfg(feature = "unicode-case")]
 pub fn case_folded_eq(left: &str, right: &str) -> bool {
+    simple_case_folded_eq(&fold::expand(left), &fold::expand(right))
+}
+
+#[cfg(not(feature = "unicode-case"))]
+pub fn case_folded_eq(left: &str, right: &str) -> bool {
+    simple_case_folded_eq(left, right)
+}
+
+fn simple_case_folded_eq(left: &str, right: &str) -> bool {
     let regex = Regex::new(&format!("(?i){}", regex::escape(left)))
         .expect("failed to compile literal regular expression");
     if let Some(matched) = regex.find(right) {
@@ -138,30 +348,268 @@ pub fn case_folded_eq(left: &str, right: &str) -> bool {
     }
 }
 
+/// Compiles a token tree if the estimated size of its program does not exceed the given limit.
+///
+/// The estimate is computed from the token tree before the program is compiled, so this
+/// rejects expressions like `<a:0,1000000>` before doing the potentially expensive work of
+/// compiling them.
+pub fn compile_with_size_limit<'t, T>(
+    tree: impl Borrow<T>,
+    limit: usize,
+) -> Result<Regex, CompileError>
+where
+    T: ConcatenationTree<'t>,
+{
+    let estimated_size = self::estimate_size(tree.borrow().concatenation());
+    if estimated_size > limit {
+        Err(CompileError {
+            kind: CompileErrorKind::TooComplex { estimated_size },
+        })
+    }
+    else {
+        self::compile(tree)
+    }
+}
+
 pub fn compile<'t, T>(tree: impl Borrow<T>) -> Result<Regex, CompileError>
+where
+    T: ConcatenationTree<'t>,
+{
+    self::compile_with_separators(tree, Separators::Native)
+}
+
+/// Compiles a token tree into a program that interprets the given characters as separators.
+pub fn compile_with_separators<'t, T>(
+    tree: impl Borrow<T>,
+    separators: Separators,
+) -> Result<Regex, CompileError>
+where
+    T: ConcatenationTree<'t>,
+{
+    Regex::new(&self::pattern::<T>(tree.borrow(), separators)).map_err(self::compile_error)
+}
+
+/// Compiles a token tree into a program that matches bytes escaped by [`escape_invalid_utf8`].
+#[cfg(feature = "bytes")]
+pub fn compile_bytes<'t, T>(tree: impl Borrow<T>) -> Result<BytesRegex, CompileError>
+where
+    T: ConcatenationTree<'t>,
+{
+    BytesRegex::new(&self::pattern::<T>(
+        tree.borrow(),
+        Target {
+            separators: Separators::Native,
+            subject: Subject::Bytes,
+        },
+    ))
+    .map_err(self::compile_error)
+}
+
+/// The maximum size in bytes of a DFA and of its determinization.
+///
+/// Dense DFAs can grow exponentially with the size of a glob, so construction gives up if this
+/// limit is exceeded. This is the same as the default size limit of programs compiled by `regex`.
+#[cfg(feature = "dfa")]
+const DFA_SIZE_LIMIT: usize = 10 * (1 << 20);
+
+/// Compiles a token tree into a dense DFA that can only be used to detect matches.
+///
+/// The DFA only supports anchored searches and does not resolve captures.
+#[cfg(feature = "dfa")]
+pub fn compile_dfa<'t, T>(tree: impl Borrow<T>) -> Result<DFA<Vec<u32>>, CompileError>
+where
+    T: ConcatenationTree<'t>,
+{
+    dense::Builder::new()
+        .configure(
+            dense::Config::new()
+                .start_kind(StartKind::Anchored)
+                .dfa_size_limit(Some(DFA_SIZE_LIMIT))
+                .determinize_size_limit(Some(DFA_SIZE_LIMIT)),
+        )
+        .build(&self::pattern::<T>(tree.borrow(), Separators::Native))
+        .map_err(|error| {
+            if error.is_size_limit_exceeded() {
+                CompileError {
+                    kind: CompileErrorKind::OversizedProgram,
+                }
+            }
+            else {
+                panic!("failed to compile glob")
+            }
+        })
+}
+
+/// Escapes each byte of invalid UTF-8 in the given bytes as `0xFF` followed by that byte.
+///
+/// The escaped bytes are matched by programs compiled by [`compile_bytes`]. Valid UTF-8 is not
+/// copied.
+#[cfg(feature = "bytes")]
+pub fn escape_invalid_utf8(bytes: &[u8]) -> Cow<'_, [u8]> {
+    if str::from_utf8(bytes).is_ok() {
+        return bytes.into();
+    }
+    let mut escaped = Vec::with_capacity(bytes.len() + 1);
+    let mut bytes = bytes;
+    while !bytes.is_empty() {
+        match str::from_utf8(bytes) {
+            Ok(_) => {
+                escaped.extend_from_slice(bytes);
+                break;
+            },
+            Err(error) => {
+                let (valid, invalid) = bytes.split_at(error.valid_up_to());
+                escaped.extend_from_slice(valid);
+                // Truncated sequences at the end of the bytes have no error length.
+                let n = error.error_len().unwrap_or(invalid.len());
+                for &byte in &invalid[..n] {
+                    escaped.extend_from_slice(&[0xFF, byte]);
+                }
+                bytes = &invalid[n..];
+            },
+        }
+    }
+    escaped.into()
+}
+
+/// Compiles a token tree into a program that captures each branch of its top-level alternation.
+///
+/// Returns `None` if the token tree is not a single alternation. Each branch forms exactly one
+/// capture in order, so the index of the first participating capture less one is the index of the
+/// matched branch. This program is separate from the program compiled by [`compile`], because
+/// these captures would otherwise change the indices of captures in matched text.
+pub fn compile_branches<'t, T>(tree: impl Borrow<T>) -> Option<Result<Regex, CompileError>>
+where
+    T: ConcatenationTree<'t>,
+{
+    let alternation = match tree.borrow().concatenation() {
+        [token] => token.as_alternation()?,
+        _ => return None,
+    };
+    let encodings = alternation
+        .tokens()
+        .iter()
+        .map(|token| {
+            let mut pattern = String::new();
+            Grouping::Capture.push_with(&mut pattern, || {
+                let mut pattern = String::new();
+                encode::<Token<_>>(
+                    Grouping::NonCapture,
+                    Some(Position::Only),
+                    Separators::Native.into(),
+                    &mut pattern,
+                    token,
+                );
+                pattern.into()
+            });
+            pattern
+        })
+        .join("|");
+    Some(Regex::new(&format!("^(?:{})$", encodings)).map_err(self::compile_error))
+}
+
+/// Compiles token trees into a single program that matches all of them in one pass.
+///
+/// The patterns in the set are the same as those compiled by [`compile`], so the index of each
+/// tree is the index of its matches in the set.
+pub fn compile_set<'t, T, I>(trees: I) -> Result<RegexSet, CompileError>
+where
+    T: ConcatenationTree<'t>,
+    I: IntoIterator,
+    I::Item: Borrow<T>,
+{
+    RegexSet::new(
+        trees
+            .into_iter()
+            .map(|tree| self::pattern::<T>(tree.borrow(), Separators::Native)),
+    )
+    .map_err(self::compile_error)
+}
+
+/// Compiles token trees into a set program if the sum of their estimated sizes does not exceed the
+/// given limit.
+pub fn compile_set_with_size_limit<'t, T, I>(
+    trees: I,
+    limit: usize,
+) -> Result<RegexSet, CompileError>
+where
+    T: ConcatenationTree<'t>,
+    I: IntoIterator,
+    I::Item: Borrow<T>,
+{
+    let trees: Vec<_> = trees.into_iter().collect();
+    let estimated_size = trees
+        .iter()
+        .map(|tree| self::estimate_size(tree.borrow().concatenation()))
+        .fold(0, usize::saturating_add);
+    if estimated_size > limit {
+        Err(CompileError {
+            kind: CompileErrorKind::TooComplex { estimated_size },
+        })
+    }
+    else {
+        self::compile_set::<T, _>(trees)
+    }
+}
+
+fn pattern<'t, T>(tree: &T, target: impl Into<Target>) -> String
 where
     T: ConcatenationTree<'t>,
 {
     let mut pattern = String::new();
     pattern.push('^');
-    encode(Grouping::Capture, None, &mut pattern, tree);
+    encode::<T>(Grouping::Capture, None, target.into(), &mut pattern, tree);
     pattern.push('$');
-    Regex::new(&pattern).map_err(|error| match error {
+    pattern
+}
+
+fn compile_error(error: RegexError) -> CompileError {
+    match error {
         RegexError::CompiledTooBig(_) => CompileError {
             kind: CompileErrorKind::OversizedProgram,
         },
         _ => panic!("failed to compile glob"),
-    })
+    }
+}
+
+/// Estimates the size of the program compiled from the given tokens.
+///
+/// The estimate is the number of leaf tokens (or characters in literals) in the expanded
+/// expression. Repetitions multiply the size of their sub-glob by their upper bound or, if
+/// unbounded, their lower bound plus one. The estimate saturates rather than overflows.
+fn estimate_size<'t, A>(tokens: &[Token<'t, A>]) -> usize {
+    use crate::token::BranchKind::{Alternation, Concatenation, Repetition};
+    use crate::token::LeafKind::Literal;
+
+    tokens
+        .iter()
+        .map(|token| match token.topology() {
+            TokenTopology::Leaf(Literal(literal)) => cmp::max(literal.text().len(), 1),
+            TokenTopology::Leaf(_) => 1,
+            TokenTopology::Branch(Alternation(alternation)) => {
+                self::estimate_size(alternation.tokens())
+            },
+            TokenTopology::Branch(Concatenation(concatenation)) => {
+                self::estimate_size(concatenation.tokens())
+            },
+            TokenTopology::Branch(Repetition(repetition)) => {
+                let variance = repetition.variance();
+                let n = variance
+                    .upper()
+                    .into_usize()
+                    .unwrap_or_else(|| variance.lower().into_usize().saturating_add(1));
+                self::estimate_size(repetition.tokens()).saturating_mul(cmp::max(n, 1))
+            },
+        })
+        .fold(0, usize::saturating_add)
 }
 
 // TODO: Implement this iteratively.
 // TODO: Encode expressions using the HIR in `regex-syntax` rather than text.
-// TODO: Some versions of `const_format` in `^0.2.0` fail this lint in `formatcp`. See
-//       https://github.com/rodrimati1992/const_format_crates/issues/38
-#[allow(clippy::double_parens)]
 fn encode<'t, T>(
     grouping: Grouping,
     superposition: Option<Position>,
+    target: Target,
     pattern: &mut String,
     tree: impl Borrow<T>,
 ) where
@@ -169,20 +617,34 @@ fn encode<'t, T>(
 {
     use itertools::Position::{First, Last, Middle, Only};
 
-    use crate::token::Archetype::{Character, Range};
+    use crate::token::Archetype::{Character, Intersection, Property, Range, Set};
     use crate::token::BranchKind::{Alternation, Concatenation, Repetition};
     use crate::token::Evaluation::{Eager, Lazy};
-    use crate::token::LeafKind::{Class, Literal, Separator, Wildcard};
+    use crate::token::LeafKind::{Class, Literal, Prefix, Separator, Wildcard};
+    use crate::token::Prefix as PrefixToken;
     use crate::token::Wildcard::{One, Tree, ZeroOrMore};
 
-    fn encode_intermediate_tree(grouping: Grouping, pattern: &mut String) {
-        pattern.push_str(sepexpr!("(?:{0}|{0}"));
-        grouping.push_str(pattern, sepexpr!(".*{0}"));
+    fn encode_intermediate_tree(
+        grouping: Grouping,
+        target: Target,
+        pattern: &mut String,
+        tree: &str,
+    ) {
+        let separator = target.separator();
+        pattern.push_str(&format!("(?:{0}|{0}", separator));
+        grouping.push_str(pattern, &format!("{}{}", tree, separator));
         pattern.push(')');
     }
 
     // TODO: Use `Grouping` everywhere a group is encoded.
     for (position, token) in tree.borrow().concatenation().iter().with_position() {
+        let target = token
+            .as_leaf()
+            .and_then(LeafKind::separator)
+            .map_or(target, |separator| target.separated_by(separator));
+        let tree_expression =
+            |excludes_hidden: bool| target.tree_expression(excludes_hidden, false);
+
         match token.topology() {
             TokenTopology::Leaf(leaf) => match (position, leaf) {
                 (_, Literal(literal)) => {
@@ -190,19 +652,38 @@ fn encode<'t, T>(
                     // TODO: Should Unicode support also be toggled by casing flags?
                     if literal.is_case_insensitive() {
                         pattern.push_str("(?i)");
+                        #[cfg(feature = "unicode-case")]
+                        pattern.push_str(&fold::encode(literal.text()));
+                        #[cfg(not(feature = "unicode-case"))]
+                        pattern.push_str(&literal.text().escaped());
                     }
                     else {
                         pattern.push_str("(?-i)");
+                        pattern.push_str(&literal.text().escaped());
                     }
-                    pattern.push_str(&literal.text().escaped());
                 },
-                (_, Separator(_)) => pattern.push_str(sepexpr!("{0}")),
+                // Prefixes are case-insensitive regardless of flags.
+                (_, Prefix(PrefixToken::Drive(letter))) => {
+                    pattern.push_str(&format!("(?i:{}:)", letter.escaped()));
+                },
+                (_, Prefix(PrefixToken::Unc { server, share })) => {
+                    pattern.push_str(&format!(
+                        "{0}{0}(?i:{1}){0}(?i:{2})",
+                        target.separator(),
+                        server.escaped(),
+                        share.escaped(),
+                    ));
+                },
+                (_, Separator(_)) => pattern.push_str(&target.separator()),
                 (_, Class(class)) => {
                     grouping.push_with(pattern, || {
-                        use crate::token::Class as ClassToken;
+                        use crate::token::Archetype;
 
-                        fn encode_class_archetypes(class: &ClassToken, pattern: &mut String) {
-                            for archetype in class.archetypes() {
+                        fn encode_class_archetypes(
+                            archetypes: &[Archetype],
+                            pattern: &mut String,
+                        ) {
+                            for archetype in archetypes {
                                 match archetype {
                                     Character(literal) => pattern.push_str(&literal.escaped()),
                                     Range(left, right) => {
@@ -210,6 +691,33 @@ fn encode<'t, T>(
                                         pattern.push('-');
                                         pattern.push_str(&right.escaped());
                                     },
+                                    Property { name, is_negated } => {
+                                        pattern.push_str(if *is_negated { "\\P{" } else { "\\p{" });
+                                        pattern.push_str(name);
+                                        pattern.push('}');
+                                    },
+                                    // Nested sets are encoded as nested regular expression classes,
+                                    // which are unions with any neighboring archetypes.
+                                    Set(set) => {
+                                        pattern.push('[');
+                                        if set.is_negated() {
+                                            pattern.push('^');
+                                        }
+                                        encode_class_archetypes(set.archetypes(), pattern);
+                                        pattern.push(']');
+                                    },
+                                    // Union has a higher precedence than intersection in regular
+                                    // expression classes, so the operands are not grouped.
+                                    Intersection(operands) => {
+                                        pattern.push('[');
+                                        for (n, operand) in operands.iter().enumerate() {
+                                            if n > 0 {
+                                                pattern.push_str("&&");
+                                            }
+                                            encode_class_archetypes(operand, pattern);
+                                        }
+                                        pattern.push(']');
+                                    },
                                 }
                             }
                         }
@@ -218,12 +726,13 @@ fn encode<'t, T>(
                         pattern.push('[');
                         if class.is_negated() {
                             pattern.push('^');
-                            encode_class_archetypes(class, &mut pattern);
-                            pattern.push_str(SEPARATOR_CLASS_EXPRESSION);
+                            encode_class_archetypes(class.archetypes(), &mut pattern);
+                            pattern.push_str(&target.separators.class_expression());
                         }
                         else {
-                            encode_class_archetypes(class, &mut pattern);
-                            pattern.push_str(nsepexpr!("&&{0}"));
+                            encode_class_archetypes(class.archetypes(), &mut pattern);
+                            pattern.push_str("&&");
+                            pattern.push_str(&target.separators.non_separator());
                         }
                         pattern.push(']');
                         // TODO: The compiled `Regex` is discarded. Is there a way to check the
@@ -233,7 +742,12 @@ fn encode<'t, T>(
                         // of the separator pattern yields an empty character class (meaning that the
                         // glob expression matches only separator characters on the target platform).
                         if Regex::new(&pattern).is_ok() {
-                            pattern.into()
+                            if class.is_negated() {
+                                target.or_invalid_utf8(pattern).into()
+                            }
+                            else {
+                                pattern.into()
+                            }
                         }
                         else {
                             // If compilation fails, then use `NEVER_EXPRESSION`, which matches
@@ -242,36 +756,76 @@ fn encode<'t, T>(
                         }
                     });
                 },
-                (_, Wildcard(One)) => grouping.push_str(pattern, nsepexpr!("{0}")),
-                (_, Wildcard(ZeroOrMore(Eager))) => grouping.push_str(pattern, nsepexpr!("{0}*")),
-                (_, Wildcard(ZeroOrMore(Lazy))) => grouping.push_str(pattern, nsepexpr!("{0}*?")),
-                (First, Wildcard(Tree { has_root })) => {
+                (_, Wildcard(One(_))) => grouping.push_str(pattern, &target.non_separator()),
+                (_, Wildcard(ZeroOrMore(Eager, _))) => {
+                    grouping.push_str(pattern, &format!("{}*", target.non_separator()))
+                },
+                (_, Wildcard(ZeroOrMore(Lazy, _))) => {
+                    grouping.push_str(pattern, &format!("{}*?", target.non_separator()))
+                },
+                (
+                    First,
+                    Wildcard(Tree {
+                        has_root,
+                        excludes_hidden,
+                        ..
+                    }),
+                ) => {
+                    let tree = tree_expression(*excludes_hidden);
+                    let separator = target.separator();
                     if let Some(Middle | Last) = superposition {
-                        encode_intermediate_tree(grouping, pattern);
+                        encode_intermediate_tree(grouping, target, pattern, &tree);
                     }
                     else if *has_root {
-                        grouping.push_str(pattern, sepexpr!("{0}.*{0}?"));
+                        grouping.push_str(pattern, &format!("{0}{1}{0}?", separator, tree));
                     }
                     else {
-                        pattern.push_str(sepexpr!("(?:{0}?|"));
-                        grouping.push_str(pattern, sepexpr!(".*{0}"));
+                        pattern.push_str(&format!("(?:{0}?|", separator));
+                        grouping.push_str(pattern, &format!("{}{}", tree, separator));
                         pattern.push(')');
                     }
                 },
-                (Middle, Wildcard(Tree { .. })) => {
-                    encode_intermediate_tree(grouping, pattern);
+                (Middle, Wildcard(Tree { excludes_hidden, .. })) => {
+                    encode_intermediate_tree(
+                        grouping,
+                        target,
+                        pattern,
+                        &tree_expression(*excludes_hidden),
+                    );
                 },
-                (Last, Wildcard(Tree { .. })) => {
+                (Last, Wildcard(Tree { excludes_hidden, .. })) => {
+                    let tree = tree_expression(*excludes_hidden);
                     if let Some(First | Middle) = superposition {
-                        encode_intermediate_tree(grouping, pattern);
+                        encode_intermediate_tree(grouping, target, pattern, &tree);
                     }
                     else {
-                        pattern.push_str(sepexpr!("(?:{0}?|{0}"));
-                        grouping.push_str(pattern, ".*");
+                        pattern.push_str(&format!("(?:{0}?|{0}", target.separator()));
+                        grouping.push_str(pattern, &tree);
                         pattern.push(')');
                     }
                 },
-                (Only, Wildcard(Tree { .. })) => grouping.push_str(pattern, ".*"),
+                // A singular tree wildcard in a leading alternation, such as `{**/,}a`, precedes the
+                // components that follow the alternation and so is encoded as a leading tree
+                // wildcard.
+                (Only, Wildcard(Tree { excludes_hidden, .. })) if superposition == Some(First) => {
+                    pattern.push_str(&format!("(?:{0}?|", target.separator()));
+                    grouping.push_str(
+                        pattern,
+                        &format!("{}{}", tree_expression(*excludes_hidden), target.separator()),
+                    );
+                    pattern.push(')');
+                },
+                (
+                    Only,
+                    Wildcard(Tree {
+                        excludes_hidden,
+                        excludes_empty,
+                        ..
+                    }),
+                ) => grouping.push_str(
+                    pattern,
+                    &target.tree_expression(*excludes_hidden, *excludes_empty),
+                ),
             },
             TokenTopology::Branch(branch) => match branch {
                 Alternation(alternation) => {
@@ -284,6 +838,7 @@ fn encode<'t, T>(
                             encode::<Token<_>>(
                                 Grouping::NonCapture,
                                 superposition.or(Some(position)),
+                                target,
                                 &mut pattern,
                                 token,
                             );
@@ -295,22 +850,53 @@ fn encode<'t, T>(
                 },
                 Concatenation(_) => unreachable!(),
                 Repetition(repetition) => {
+                    fn encode_bounds(variance: NaturalRange) -> String {
+                        if let Some(upper) = variance.upper().into_usize() {
+                            format!("{{{},{}}}", variance.lower().into_usize(), upper)
+                        }
+                        else {
+                            format!("{{{},}}", variance.lower().into_usize())
+                        }
+                    }
+
                     let encoding = {
                         let variance = repetition.variance();
-                        let mut pattern = String::new();
-                        pattern.push_str("(?:");
+                        let mut token = String::new();
                         encode::<Token<_>>(
                             Grouping::NonCapture,
                             superposition.or(Some(position)),
-                            &mut pattern,
+                            target,
+                            &mut token,
                             repetition.token(),
                         );
-                        pattern.push_str(&if let Some(upper) = variance.upper().into_usize() {
-                            format!("){{{},{}}}", variance.lower().into_usize(), upper)
+                        let mut pattern = String::new();
+                        pattern.push_str("(?:");
+                        pattern.push_str(&token);
+                        if let Some(separator) = repetition.separator() {
+                            // Separated repetitions are encoded as the repeated token followed by
+                            // the separator and repeated token, so that separators only occur
+                            // between repetitions. For example, `<a:1,3;->` is encoded much
+                            // like `a(?:-a){0,2}`.
+                            pattern.push_str("(?:");
+                            encode::<Token<_>>(
+                                Grouping::NonCapture,
+                                Some(Middle),
+                                target,
+                                &mut pattern,
+                                separator,
+                            );
+                            pattern.push_str(&token);
+                            pattern.push(')');
+                            pattern.push_str(&encode_bounds(repetition.separator_variance()));
+                            pattern.push(')');
+                            if variance.lower().into_usize() == 0 {
+                                pattern.push('?');
+                            }
                         }
                         else {
-                            format!("){{{},}}", variance.lower().into_usize())
-                        });
+                            pattern.push(')');
+                            pattern.push_str(&encode_bounds(variance));
+                        }
                         pattern
                     };
                     grouping.push_str(pattern, &encoding);
@@ -373,4 +959,19 @@ mod tests {
     fn case_folded_eq_not_eq(#[case] lhs: &str, #[case] rhs: &str) {
         harness::assert_case_folded_eq_eq(lhs, rhs, false);
     }
+
+    #[cfg(feature = "unicode-case")]
+    #[rstest]
+    #[case::eszett("ß", "ss")]
+    #[case::eszett("ß", "SS")]
+    #[case::eszett("ß", "ẞ")]
+    #[case::eszett("straße", "STRASSE")]
+    #[case::eszett("STRASSE", "straße")]
+    #[case::final_sigma("ς", "σ")]
+    #[case::final_sigma("ς", "Σ")]
+    #[case::final_sigma("σοφος", "ΣΟΦΟΣ")]
+    #[case::dotted_capital_i("İ", "i\u{307}")]
+    fn case_folded_eq_with_full_case_folding_eq(#[case] lhs: &str, #[case] rhs: &str) {
+        harness::assert_case_folded_eq_eq(lhs, rhs, true);
+    }
 }
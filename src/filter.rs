@@ -166,6 +166,21 @@ impl<T> Filtrate<T> {
     }
 }
 
+impl<T> Residue<TreeResidue<T>> {
+    pub fn filter_tree<I>(self, cancellation: WalkCancellation<'_, I>) -> Self
+    where
+        I: CancelWalk,
+    {
+        match self.into_inner() {
+            TreeResidue::Node(residue) => {
+                cancellation.cancel_walk_tree();
+                Residue::new(TreeResidue::Tree(residue))
+            },
+            residue => Residue::new(residue),
+        }
+    }
+}
+
 impl<T> AsRef<T> for Residue<TreeResidue<T>> {
     fn as_ref(&self) -> &T {
         self.get().get()
@@ -316,7 +331,7 @@ where
         match self {
             Separation::Filtrate(filtrate) => {
                 cancellation.cancel_walk_tree();
-                Separation::from_inner_residue(TreeResidue::Node(f(filtrate.into_inner())))
+                Separation::from_inner_residue(TreeResidue::Tree(f(filtrate.into_inner())))
             },
             Separation::Residue(residue) => match residue.into_inner() {
                 TreeResidue::Node(residue) => {
@@ -0,0 +1,214 @@
+//! Unicode full case folding.
+//!
+//! Regular expressions and the comparison of invariant text use simple case folding, which maps
+//! each character to exactly one character. Full case folding additionally maps some characters to
+//! more than one character, such as the German eszett `ß` to `ss`. This module provides the
+//! expanding mappings of full case folding so that case-insensitive literals can use them.
+
+#![cfg(feature = "unicode-case")]
+
+use std::borrow::Cow;
+
+/// Full case foldings that expand a character into more than one character.
+///
+/// This table is derived from the `F` (full) mappings of `CaseFolding.txt` in Unicode 14.0.0 and is
+/// sorted by character. Mappings that are the same in simple and full case folding are not
+/// included, as these are already supported by regular expressions.
+const FULL_CASE_FOLDINGS: [(char, &str); 104] = [
+    ('\u{00DF}', "ss"),
+    ('\u{0130}', "i\u{0307}"),
+    ('\u{0149}', "\u{02BC}n"),
+    ('\u{01F0}', "j\u{030C}"),
+    ('\u{0390}', "\u{03B9}\u{0308}\u{0301}"),
+    ('\u{03B0}', "\u{03C5}\u{0308}\u{0301}"),
+    ('\u{0587}', "\u{0565}\u{0582}"),
+    ('\u{1E96}', "h\u{0331}"),
+    ('\u{1E97}', "t\u{0308}"),
+    ('\u{1E98}', "w\u{030A}"),
+    ('\u{1E99}', "y\u{030A}"),
+    ('\u{1E9A}', "a\u{02BE}"),
+    ('\u{1E9E}', "ss"),
+    ('\u{1F50}', "\u{03C5}\u{0313}"),
+    ('\u{1F52}', "\u{03C5}\u{0313}\u{0300}"),
+    ('\u{1F54}', "\u{03C5}\u{0313}\u{0301}"),
+    ('\u{1F56}', "\u{03C5}\u{0313}\u{0342}"),
+    ('\u{1F80}', "\u{1F00}\u{03B9}"),
+    ('\u{1F81}', "\u{1F01}\u{03B9}"),
+    ('\u{1F82}', "\u{1F02}\u{03B9}"),
+    ('\u{1F83}', "\u{1F03}\u{03B9}"),
+    ('\u{1F84}', "\u{1F04}\u{03B9}"),
+    ('\u{1F85}', "\u{1F05}\u{03B9}"),
+    ('\u{1F86}', "\u{1F06}\u{03B9}"),
+    ('\u{1F87}', "\u{1F07}\u{03B9}"),
+    ('\u{1F88}', "\u{1F00}\u{03B9}"),
+    ('\u{1F89}', "\u{1F01}\u{03B9}"),
+    ('\u{1F8A}', "\u{1F02}\u{03B9}"),
+    ('\u{1F8B}', "\u{1F03}\u{03B9}"),
+    ('\u{1F8C}', "\u{1F04}\u{03B9}"),
+    ('\u{1F8D}', "\u{1F05}\u{03B9}"),
+    ('\u{1F8E}', "\u{1F06}\u{03B9}"),
+    ('\u{1F8F}', "\u{1F07}\u{03B9}"),
+    ('\u{1F90}', "\u{1F20}\u{03B9}"),
+    ('\u{1F91}', "\u{1F21}\u{03B9}"),
+    ('\u{1F92}', "\u{1F22}\u{03B9}"),
+    ('\u{1F93}', "\u{1F23}\u{03B9}"),
+    ('\u{1F94}', "\u{1F24}\u{03B9}"),
+    ('\u{1F95}', "\u{1F25}\u{03B9}"),
+    ('\u{1F96}', "\u{1F26}\u{03B9}"),
+    ('\u{1F97}', "\u{1F27}\u{03B9}"),
+    ('\u{1F98}', "\u{1F20}\u{03B9}"),
+    ('\u{1F99}', "\u{1F21}\u{03B9}"),
+    ('\u{1F9A}', "\u{1F22}\u{03B9}"),
+    ('\u{1F9B}', "\u{1F23}\u{03B9}"),
+    ('\u{1F9C}', "\u{1F24}\u{03B9}"),
+    ('\u{1F9D}', "\u{1F25}\u{03B9}"),
+    ('\u{1F9E}', "\u{1F26}\u{03B9}"),
+    ('\u{1F9F}', "\u{1F27}\u{03B9}"),
+    ('\u{1FA0}', "\u{1F60}\u{03B9}"),
+    ('\u{1FA1}', "\u{1F61}\u{03B9}"),
+    ('\u{1FA2}', "\u{1F62}\u{03B9}"),
+    ('\u{1FA3}', "\u{1F63}\u{03B9}"),
+    ('\u{1FA4}', "\u{1F64}\u{03B9}"),
+    ('\u{1FA5}', "\u{1F65}\u{03B9}"),
+    ('\u{1FA6}', "\u{1F66}\u{03B9}"),
+    ('\u{1FA7}', "\u{1F67}\u{03B9}"),
+    ('\u{1FA8}', "\u{1F60}\u{03B9}"),
+    ('\u{1FA9}', "\u{1F61}\u{03B9}"),
+    ('\u{1FAA}', "\u{1F62}\u{03B9}"),
+    ('\u{1FAB}', "\u{1F63}\u{03B9}"),
+    ('\u{1FAC}', "\u{1F64}\u{03B9}"),
+    ('\u{1FAD}', "\u{1F65}\u{03B9}"),
+    ('\u{1FAE}', "\u{1F66}\u{03B9}"),
+    ('\u{1FAF}', "\u{1F67}\u{03B9}"),
+    ('\u{1FB2}', "\u{1F70}\u{03B9}"),
+    ('\u{1FB3}', "\u{03B1}\u{03B9}"),
+    ('\u{1FB4}', "\u{03AC}\u{03B9}"),
+    ('\u{1FB6}', "\u{03B1}\u{0342}"),
+    ('\u{1FB7}', "\u{03B1}\u{0342}\u{03B9}"),
+    ('\u{1FBC}', "\u{03B1}\u{03B9}"),
+    ('\u{1FC2}', "\u{1F74}\u{03B9}"),
+    ('\u{1FC3}', "\u{03B7}\u{03B9}"),
+    ('\u{1FC4}', "\u{03AE}\u{03B9}"),
+    ('\u{1FC6}', "\u{03B7}\u{0342}"),
+    ('\u{1FC7}', "\u{03B7}\u{0342}\u{03B9}"),
+    ('\u{1FCC}', "\u{03B7}\u{03B9}"),
+    ('\u{1FD2}', "\u{03B9}\u{0308}\u{0300}"),
+    ('\u{1FD3}', "\u{03B9}\u{0308}\u{0301}"),
+    ('\u{1FD6}', "\u{03B9}\u{0342}"),
+    ('\u{1FD7}', "\u{03B9}\u{0308}\u{0342}"),
+    ('\u{1FE2}', "\u{03C5}\u{0308}\u{0300}"),
+    ('\u{1FE3}', "\u{03C5}\u{0308}\u{0301}"),
+    ('\u{1FE4}', "\u{03C1}\u{0313}"),
+    ('\u{1FE6}', "\u{03C5}\u{0342}"),
+    ('\u{1FE7}', "\u{03C5}\u{0308}\u{0342}"),
+    ('\u{1FF2}', "\u{1F7C}\u{03B9}"),
+    ('\u{1FF3}', "\u{03C9}\u{03B9}"),
+    ('\u{1FF4}', "\u{03CE}\u{03B9}"),
+    ('\u{1FF6}', "\u{03C9}\u{0342}"),
+    ('\u{1FF7}', "\u{03C9}\u{0342}\u{03B9}"),
+    ('\u{1FFC}', "\u{03C9}\u{03B9}"),
+    ('\u{FB00}', "ff"),
+    ('\u{FB01}', "fi"),
+    ('\u{FB02}', "fl"),
+    ('\u{FB03}', "ffi"),
+    ('\u{FB04}', "ffl"),
+    ('\u{FB05}', "st"),
+    ('\u{FB06}', "st"),
+    ('\u{FB13}', "\u{0574}\u{0576}"),
+    ('\u{FB14}', "\u{0574}\u{0565}"),
+    ('\u{FB15}', "\u{0574}\u{056B}"),
+    ('\u{FB16}', "\u{057E}\u{0576}"),
+    ('\u{FB17}', "\u{0574}\u{056D}"),
+];
+
+/// Gets the full case folding of a character if it expands into more than one character.
+fn expansion(x: char) -> Option<&'static str> {
+    FULL_CASE_FOLDINGS
+        .binary_search_by_key(&x, |(key, _)| *key)
+        .ok()
+        .map(|index| FULL_CASE_FOLDINGS[index].1)
+}
+
+/// Gets the characters that expand into the given text.
+fn expanding(text: &str) -> impl '_ + Iterator<Item = char> {
+    FULL_CASE_FOLDINGS
+        .iter()
+        .filter(move |(_, expansion)| *expansion == text)
+        .map(|(key, _)| *key)
+}
+
+/// Replaces characters with their full case folding if it expands into more than one character.
+///
+/// Other characters are not folded, so the output must be compared using simple case folding.
+pub fn expand(text: &str) -> Cow<'_, str> {
+    if text.chars().any(|x| expansion(x).is_some()) {
+        text.chars()
+            .fold(String::with_capacity(text.len()), |mut expanded, x| {
+                match expansion(x) {
+                    Some(expansion) => expanded.push_str(expansion),
+                    None => expanded.push(x),
+                }
+                expanded
+            })
+            .into()
+    }
+    else {
+        text.into()
+    }
+}
+
+/// Encodes text as a regular expression that matches its full case folding.
+///
+/// Characters that expand and the text of their expansions are encoded as alternations of one
+/// another, so `ß` and `ss` match both `ß` and `SS`, for example. The expression must be used in a
+/// case-insensitive context, which provides simple case folding.
+///
+/// Expansions are found from left to right and so overlapping expansions are not considered. For
+/// example, the expression encoded from `sss` matches `ßs`, but not `sß`.
+pub fn encode(text: &str) -> String {
+    fn alternation(expansion: &str) -> String {
+        let mut pattern = format!("(?:{}", regex::escape(expansion));
+        for x in expanding(expansion) {
+            pattern.push('|');
+            pattern.push_str(&regex::escape(x.encode_utf8(&mut [0; 4])));
+        }
+        pattern.push(')');
+        pattern
+    }
+
+    // Gets the longest expansion that case-insensitively begins the text, if any.
+    fn expansion_prefix(text: &[char]) -> Option<(&'static str, usize)> {
+        FULL_CASE_FOLDINGS
+            .iter()
+            .map(|(_, expansion)| *expansion)
+            .filter(|expansion| {
+                let n = expansion.chars().count();
+                n <= text.len()
+                    && expansion
+                        .chars()
+                        .zip(text)
+                        .all(|(lhs, rhs)| lhs.to_lowercase().eq(rhs.to_lowercase()))
+            })
+            .map(|expansion| (expansion, expansion.chars().count()))
+            .max_by_key(|(_, n)| *n)
+    }
+
+    let text: Vec<_> = text.chars().collect();
+    let mut pattern = String::new();
+    let mut index = 0;
+    while index < text.len() {
+        if let Some(expansion) = expansion(text[index]) {
+            pattern.push_str(&alternation(expansion));
+            index += 1;
+        }
+        else if let Some((expansion, n)) = expansion_prefix(&text[index..]) {
+            pattern.push_str(&alternation(expansion));
+            index += n;
+        }
+        else {
+            pattern.push_str(&regex::escape(text[index].encode_utf8(&mut [0; 4])));
+            index += 1;
+        }
+    }
+    pattern
+}
@@ -35,6 +35,7 @@ mod capture;
 mod diagnostics;
 mod encode;
 mod filter;
+mod fold;
 pub mod query;
 mod rule;
 mod token;
@@ -65,24 +66,42 @@ pub mod prelude {
     pub use crate::Program as _;
 }
 
+use itertools::Itertools as _;
 #[cfg(feature = "miette")]
 use miette::Diagnostic;
-use regex::Regex;
+#[cfg(feature = "bytes")]
+use regex::bytes::Regex as BytesRegex;
+use regex::{Regex, RegexSet};
+#[cfg(feature = "dfa")]
+use regex_automata::dfa::{dense::DFA, Automaton as _};
+#[cfg(feature = "dfa")]
+use regex_automata::{Anchored, Input};
 use std::borrow::{Borrow, Cow};
-use std::cmp::Ordering;
+use std::cmp::{self, Ordering};
 use std::convert::Infallible;
 use std::ffi::OsStr;
 use std::fmt::{self, Debug, Display, Formatter};
-use std::path::{Path, PathBuf};
+use std::hash::{Hash, Hasher};
+use std::iter;
+use std::ops::{Deref, Range};
+use std::path::{self, Path, PathBuf};
 use std::str::{self, FromStr};
+use std::sync::atomic::{self, AtomicU8};
+use std::sync::{Arc, OnceLock};
 use thiserror::Error;
 
-use crate::diagnostics::LocatedError;
+use crate::diagnostics::{LocatedError, Span, Spanned};
 use crate::encode::CompileError;
-use crate::query::{CapturingToken, DepthVariance, TextVariance, When};
+#[cfg(not(unix))]
+use crate::encode::Separators;
+use crate::query::{
+    CapturingToken, DepthVariance, GlobComponent, GlobToken, SyntaxKind, TerminalKind, TextVariance,
+    TokenKind, TokenVisitor, When,
+};
 use crate::rule::{Checked, RuleError};
 use crate::token::{
-    ConcatenationTree, ExpressionMetadata, ParseError, Token, TokenTree, Tokenized,
+    ConcatenationTree, Depth, ExpressionMetadata, NaturalRange, ParseError, ParseOptions, Text,
+    Token, TokenTree, Tokenized,
 };
 #[cfg(feature = "walk")]
 use crate::walk::WalkError;
@@ -94,6 +113,74 @@ const PATHS_ARE_CASE_INSENSITIVE: bool = true;
 #[cfg(not(windows))]
 const PATHS_ARE_CASE_INSENSITIVE: bool = false;
 
+#[cfg(windows)]
+const PATHS_HAVE_PREFIXES: bool = true;
+#[cfg(not(windows))]
+const PATHS_HAVE_PREFIXES: bool = false;
+
+static DEFAULT_CASE_SENSITIVITY: AtomicU8 = AtomicU8::new(CaseSensitivity::Platform as u8);
+
+/// Case sensitivity of literals in glob expressions that do not specify a case flag.
+///
+/// See [`set_default_case_sensitivity`].
+///
+/// [`set_default_case_sensitivity`]: crate::set_default_case_sensitivity
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[repr(u8)]
+pub enum CaseSensitivity {
+    /// Literals are case sensitive if and only if file system paths are case sensitive on the
+    /// target platform. This is the default.
+    #[default]
+    Platform = 0,
+    /// Literals are case sensitive.
+    Sensitive = 1,
+    /// Literals are case insensitive.
+    Insensitive = 2,
+}
+
+impl CaseSensitivity {
+    fn is_case_insensitive(self) -> bool {
+        match self {
+            CaseSensitivity::Platform => PATHS_ARE_CASE_INSENSITIVE,
+            CaseSensitivity::Sensitive => false,
+            CaseSensitivity::Insensitive => true,
+        }
+    }
+}
+
+/// Sets the default case sensitivity of literals in glob expressions.
+///
+/// By default, the case sensitivity of literals matches the case sensitivity of file system paths
+/// on the target platform. This function overrides that default for all globs that are
+/// subsequently parsed, such as by tools that accept a `--case-sensitive` flag. Case flags like
+/// `(?i)` in glob expressions take precedence over this default.
+///
+/// The default is process-wide and may be set from any thread, but it is read when a glob
+/// expression is parsed. Globs that have already been parsed are unaffected, so this function
+/// should typically be called once before any globs are constructed.
+///
+/// # Examples
+///
+/// ```rust
+/// use wax::{CaseSensitivity, Glob, Program};
+///
+/// wax::set_default_case_sensitivity(CaseSensitivity::Insensitive);
+/// let glob = Glob::new("README.md").unwrap();
+///
+/// assert!(glob.is_match("readme.md"));
+/// ```
+pub fn set_default_case_sensitivity(sensitivity: CaseSensitivity) {
+    DEFAULT_CASE_SENSITIVITY.store(sensitivity as u8, atomic::Ordering::Relaxed);
+}
+
+fn default_case_sensitivity() -> CaseSensitivity {
+    match DEFAULT_CASE_SENSITIVITY.load(atomic::Ordering::Relaxed) {
+        1 => CaseSensitivity::Sensitive,
+        2 => CaseSensitivity::Insensitive,
+        _ => CaseSensitivity::Platform,
+    }
+}
+
 trait CharExt: Sized {
     /// Returns `true` if the character (code point) has casing.
     fn has_casing(self) -> bool;
@@ -237,6 +324,29 @@ pub trait Program<'t>: Pattern<'t, Error = Infallible> {
     /// with alternations may only be exhaustive for some matched paths. In this case, this
     /// function returns [`Sometimes`].
     ///
+    /// More precisely, a pattern is exhaustive if it ends with a component that has an unbounded
+    /// depth and matches any component, such as a tree wildcard `**` or a repetition like
+    /// `<*/:1,>`. Tree wildcards that do not match hidden files (see
+    /// [`GlobBuilder::tree_matches_hidden`]) are never exhaustive, because they do not match
+    /// hidden sub-trees.
+    ///
+    /// Negations in [`FileIterator::not`] and [`Glob::and_not`] discard entire directory trees
+    /// without reading them **only if the negation is always exhaustive**. Otherwise, each file in
+    /// the directory tree is read and matched against the negation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{Glob, Program};
+    ///
+    /// assert!(Glob::new("target/**").unwrap().is_exhaustive().is_always());
+    /// assert!(Glob::new("{a/**,**/b}").unwrap().is_exhaustive().is_sometimes());
+    /// assert!(Glob::new("**/*.rs").unwrap().is_exhaustive().is_never());
+    /// ```
+    ///
+    /// [`FileIterator::not`]: crate::walk::FileIterator::not
+    /// [`Glob::and_not`]: crate::Glob::and_not
+    /// [`GlobBuilder::tree_matches_hidden`]: crate::GlobBuilder::tree_matches_hidden
     /// [`Sometimes`]: crate::query::When::Sometimes
     fn is_exhaustive(&self) -> When;
 }
@@ -375,6 +485,136 @@ impl BuildError {
         };
         locations.into_iter()
     }
+
+    /// Returns `true` if the glob is too complex to build.
+    ///
+    /// This occurs when the estimated size of a glob exceeds the limit set by
+    /// [`GlobBuilder::size_limit`]. Services that build globs from untrusted expressions can use
+    /// this to distinguish expressions that are too expensive from expressions that are invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::GlobBuilder;
+    ///
+    /// let builder = GlobBuilder::new().size_limit(GlobBuilder::RECOMMENDED_SIZE_LIMIT);
+    /// let error = builder.build("<[a-z]:0,1000000>").unwrap_err();
+    /// assert!(error.is_too_complex());
+    ///
+    /// let error = builder.build("{a,b").unwrap_err();
+    /// assert!(!error.is_too_complex());
+    /// ```
+    ///
+    /// [`GlobBuilder::size_limit`]: crate::GlobBuilder::size_limit
+    pub fn is_too_complex(&self) -> bool {
+        matches!(self.kind, BuildErrorKind::Compile(ref error) if error.is_too_complex())
+    }
+
+    /// Returns `true` if a negation matches all paths.
+    ///
+    /// This occurs when a [`FileIterator::not`] pattern matches every path, which would discard
+    /// every file in a directory tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::FileIterator;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// let error = glob.walk(".").not("**").err().unwrap();
+    /// assert!(error.is_negation_matches_all());
+    /// ```
+    ///
+    /// [`FileIterator::not`]: crate::walk::FileIterator::not
+    #[cfg(feature = "walk")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "walk")))]
+    pub fn is_negation_matches_all(&self) -> bool {
+        matches!(self.kind, BuildErrorKind::NegationMatchesAll)
+    }
+
+    /// Returns `true` if a glob expression or combinator can never match a path.
+    ///
+    /// This occurs when a glob expression has a character class that matches no characters, like
+    /// the empty intersection `[a&&b]` or the reversed range `[z-a]`, and when the patterns
+    /// given to [`all`] match paths with disjoint depths, like `*` and `*/*`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let error = Glob::new("src/[a-z&&0-9].rs").unwrap_err();
+    /// assert!(error.is_unsatisfiable());
+    ///
+    /// let error = wax::all(["*", "*/*"]).unwrap_err();
+    /// assert!(error.is_unsatisfiable());
+    /// ```
+    ///
+    /// [`all`]: crate::all
+    pub fn is_unsatisfiable(&self) -> bool {
+        match self.kind {
+            BuildErrorKind::Rule(ref error) => error.is_unsatisfiable(),
+            BuildErrorKind::UnsatisfiableConjunction => true,
+            _ => false,
+        }
+    }
+
+    /// Gets the kind of syntax that is disallowed by a [`GlobBuilder`], if any.
+    ///
+    /// Returns `None` if the error did not occur because a glob expression has syntax that is
+    /// disallowed via [`GlobBuilder::allow_alternation`], [`GlobBuilder::allow_class`], or
+    /// [`GlobBuilder::allow_repetition`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::query::SyntaxKind;
+    /// use wax::GlobBuilder;
+    ///
+    /// let error = GlobBuilder::new()
+    ///     .allow_alternation(false)
+    ///     .build("*.{rs,toml}")
+    ///     .unwrap_err();
+    /// assert_eq!(error.disallowed_syntax(), Some(SyntaxKind::Alternation));
+    /// ```
+    ///
+    /// [`GlobBuilder`]: crate::GlobBuilder
+    /// [`GlobBuilder::allow_alternation`]: crate::GlobBuilder::allow_alternation
+    /// [`GlobBuilder::allow_class`]: crate::GlobBuilder::allow_class
+    /// [`GlobBuilder::allow_repetition`]: crate::GlobBuilder::allow_repetition
+    pub fn disallowed_syntax(&self) -> Option<SyntaxKind> {
+        match self.kind {
+            BuildErrorKind::Rule(ref error) => error.disallowed_syntax(),
+            _ => None,
+        }
+    }
+
+    /// Formats a parse error as a JSON object for machine-readable diagnostics.
+    ///
+    /// Returns `None` if the error did not occur when parsing a glob expression. The object has
+    /// the form `{"expression":...,"start":...,"ends":[...]}`, where `start` is the least byte
+    /// offset of the error locations and `ends` are the byte offsets at which each error location
+    /// ends. This does not require the `miette` feature and can be consumed by editors and
+    /// language servers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let error = Glob::new("{a,b").unwrap_err();
+    /// let json = error.to_json().unwrap();
+    /// assert!(json.starts_with(r#"{"expression":"{a,b","start":"#));
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn to_json(&self) -> Option<String> {
+        match self.kind {
+            BuildErrorKind::Parse(ref error) => Some(error.to_json()),
+            _ => None,
+        }
+    }
 }
 
 impl From<BuildErrorKind> for BuildError {
@@ -420,12 +660,25 @@ enum BuildErrorKind {
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     Compile(CompileError),
+    #[cfg(feature = "walk")]
+    #[error("negation matches all paths and discards every file")]
+    #[cfg_attr(feature = "miette", diagnostic(code(wax::glob::negation_matches_all)))]
+    NegationMatchesAll,
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     Parse(ParseError<'static>),
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     Rule(RuleError<'static>),
+    #[error("separator {0:?} is a meta-character")]
+    #[cfg_attr(feature = "miette", diagnostic(code(wax::glob::unsupported_separator)))]
+    UnsupportedSeparator(char),
+    #[error("joined globs have different separators")]
+    #[cfg_attr(feature = "miette", diagnostic(code(wax::glob::mismatched_separators)))]
+    MismatchedSeparators,
+    #[error("conjunction matches no paths: patterns match paths with disjoint depths")]
+    #[cfg_attr(feature = "miette", diagnostic(code(wax::glob::unsatisfiable_conjunction)))]
+    UnsatisfiableConjunction,
 }
 
 /// Path that can be matched against a [`Program`].
@@ -435,6 +688,14 @@ enum BuildErrorKind {
 /// valid UTF-8 (such as Windows). This means that some byte sequences cannot be matched, though
 /// this is uncommon in practice.
 ///
+/// This conversion is consistent: paths with non-UTF-8 components (such as arbitrary bytes in
+/// file names on Unix) are never rejected and are matched the same way by [`Program::is_match`]
+/// and when [walking directory trees][`Glob::walk`]. Replacement codepoints can be matched by
+/// wildcards and character classes, such as `?` and `[!a]`, but literals never match the original
+/// bytes of a non-UTF-8 component.
+///
+/// [`Glob::walk`]: crate::Glob::walk
+/// [`Program::is_match`]: crate::Program::is_match
 /// [`Program`]: crate::Program
 #[derive(Clone)]
 pub struct CandidatePath<'b> {
@@ -448,6 +709,41 @@ impl<'b> CandidatePath<'b> {
             text: self.text.into_owned().into(),
         }
     }
+
+    /// Constructs a candidate path by joining the given components with separators.
+    ///
+    /// A separator is not inserted after a component that already ends with a separator, such as
+    /// a root component `/`.
+    fn from_components<I>(components: I) -> CandidatePath<'static>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<OsStr>,
+    {
+        let mut text = String::new();
+        for component in components {
+            if !(text.is_empty() || text.ends_with(|x| x == '/' || path::is_separator(x))) {
+                text.push('/');
+            }
+            text.push_str(&component.as_ref().to_string_lossy());
+        }
+        CandidatePath { text: text.into() }
+    }
+
+    // A lone separator is a root path and is never trimmed.
+    fn trim_trailing_separator(self) -> Self {
+        let CandidatePath { mut text } = self;
+        if text.len() > 1 && text.ends_with(|x| x == '/' || path::is_separator(x)) {
+            match text {
+                Cow::Borrowed(ref mut borrowed) => {
+                    *borrowed = &borrowed[..borrowed.len() - 1];
+                },
+                Cow::Owned(ref mut owned) => {
+                    owned.pop();
+                },
+            }
+        }
+        CandidatePath { text }
+    }
 }
 
 impl AsRef<str> for CandidatePath<'_> {
@@ -488,6 +784,36 @@ impl<'b> From<&'b str> for CandidatePath<'b> {
     }
 }
 
+/// Configuration for matching a [`Glob`] against a path.
+///
+/// See [`Glob::is_match_with`].
+///
+/// # Examples
+///
+/// ```rust
+/// use wax::{Glob, MatchBehavior};
+///
+/// let glob = Glob::new("src/*").unwrap();
+/// let behavior = MatchBehavior {
+///     trailing_slash_insensitive: true,
+/// };
+/// assert!(glob.is_match_with("src/glob/", behavior));
+/// ```
+///
+/// [`Glob`]: crate::Glob
+/// [`Glob::is_match_with`]: crate::Glob::is_match_with
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MatchBehavior {
+    /// Whether or not a single trailing separator in a path and in the glob is ignored.
+    ///
+    /// When `true`, a trailing separator is removed from a candidate path before it is matched
+    /// and any terminating separator in the glob is ignored, so paths like `src/glob` and
+    /// `src/glob/` are matched equivalently by both `src/*` and `src/*/`. This is useful when
+    /// matching directory paths from tools that append separators. A path that consists only of
+    /// a separator is never modified. The default value is `false`.
+    pub trailing_slash_insensitive: bool,
+}
+
 /// Program that can be matched against paths and directory trees.
 ///
 /// `Glob`s are constructed from strings called glob expressions that resemble Unix paths
@@ -516,6 +842,22 @@ impl<'b> From<&'b str> for CandidatePath<'b> {
 /// assert_eq!("lib.rs", glob.matched(&candidate).unwrap().get(2).unwrap());
 /// ```
 ///
+/// The zero-or-more wildcards `*` and `$` match the same paths, but capture different text. The
+/// eager `*` wildcard captures the longest possible text while the lazy `$` wildcard captures the
+/// shortest possible text.
+///
+/// ```rust
+/// use wax::{CandidatePath, Glob, Program};
+///
+/// let candidate = CandidatePath::from("a.tar.gz");
+///
+/// let glob = Glob::new("*.*").unwrap();
+/// assert_eq!("a.tar", glob.matched(&candidate).unwrap().get(1).unwrap());
+///
+/// let glob = Glob::new("$.*").unwrap();
+/// assert_eq!("a", glob.matched(&candidate).unwrap().get(1).unwrap());
+/// ```
+///
 /// To match a `Glob` against a directory tree, the [`walk`] function can be used to get an
 /// iterator over matching paths.
 ///
@@ -530,15 +872,57 @@ impl<'b> From<&'b str> for CandidatePath<'b> {
 /// }
 /// ```
 ///
+/// # Equality
+///
+/// `Glob`s are compared and hashed by the structure of their patterns rather than the text of
+/// their glob expressions. The following differences in syntax are ignored:
+///
+/// - Alternatives with only one branch, such as `{a}` and `a`.
+/// - Nested alternatives, such as `{a,{b,c}}` and `{a,b,c}`.
+/// - Repetitions that occur exactly once, such as `<a:1>` and `a`.
+/// - Equivalent bounds of repetitions, such as `<a:0,>` and `<a>`.
+///
+/// No other normalization is applied and so `Glob`s that match the same paths may not be equal.
+/// For example, the order of alternatives is significant (`{a,b}` is not equal to `{b,a}`),
+/// adjacent literals are not merged (`{a}b` is not equal to `ab`), and character classes are
+/// compared by their members as written (`[ab]` is not equal to `[a-b]`).
+///
+/// ```rust
+/// use wax::Glob;
+///
+/// assert_eq!(Glob::new("{a}/b").unwrap(), Glob::new("a/b").unwrap());
+/// assert_ne!(Glob::new("{a,b}").unwrap(), Glob::new("{b,a}").unwrap());
+/// ```
+///
 /// [`Program`]: crate::Program
 /// [`walk`]: crate::Glob::walk
 #[derive(Clone, Debug)]
 pub struct Glob<'t> {
     tree: Checked<Tokenized<'t, ExpressionMetadata>>,
-    program: Regex,
+    // The compiled program is shared, so cloning a `Glob` does not clone its program.
+    program: Arc<Regex>,
+    // Forward slash `/` is the only separator on Unix. On other platforms, a program that only
+    // interprets `/` as a separator is compiled on demand. See `Glob::is_match_slash`.
+    #[cfg(not(unix))]
+    slash_program: Arc<OnceLock<Regex>>,
+    // A program that captures each branch of a top-level alternation is compiled on demand. See
+    // `MatchedText::matched_branch`.
+    branch_program: Arc<OnceLock<Option<Regex>>>,
+    // Programs for each component of the glob are compiled on demand. See
+    // `Glob::split_components`.
+    component_programs: Arc<OnceLock<Vec<Regex>>>,
+    // A program that matches bytes is compiled on demand. This program is larger than `program`
+    // and may fail to compile even if `program` does not. See `Glob::is_match_bytes`.
+    #[cfg(feature = "bytes")]
+    bytes_program: Arc<OnceLock<Result<BytesRegex, CompileError>>>,
 }
 
 impl<'t> Glob<'t> {
+    /// The maximum number of paths that are enumerated by [`Glob::enumerate`].
+    ///
+    /// [`Glob::enumerate`]: crate::Glob::enumerate
+    pub const ENUMERATION_LIMIT: usize = 1024;
+
     // TODO: Document pattern syntax in the crate documentation and refer to it here.
     /// Constructs a [`Glob`] from a glob expression.
     ///
@@ -552,12 +936,56 @@ impl<'t> Glob<'t> {
     /// [`Glob`]: crate::Glob
     /// [`BuildError`]: crate::BuildError
     pub fn new(expression: &'t str) -> Result<Self, BuildError> {
-        let tree = parse_and_check(expression)?;
-        let program = Glob::compile::<Tokenized<_>>(tree.as_ref())?;
-        Ok(Glob { tree, program })
+        GlobBuilder::new().build(expression)
+    }
+
+    /// Constructs a [`Glob`] from a glob expression that matches the same paths on all supported
+    /// platforms.
+    ///
+    /// This function is the same as [`Glob::new`], but additionally rejects glob expressions that
+    /// can only match paths on some platforms. See [`GlobBuilder::require_portable`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the glob expression fails to build or is not portable. See
+    /// [`BuildError`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// assert!(Glob::new_portable("src/**/*.rs").is_ok());
+    /// assert!(Glob::new_portable(r"reports/2024-01-01T00\:00.txt").is_err());
+    /// ```
+    ///
+    /// [`BuildError`]: crate::BuildError
+    /// [`Glob`]: crate::Glob
+    /// [`Glob::new`]: crate::Glob::new
+    /// [`GlobBuilder::require_portable`]: crate::GlobBuilder::require_portable
+    pub fn new_portable(expression: &'t str) -> Result<Self, BuildError> {
+        GlobBuilder::new().require_portable(true).build(expression)
     }
 
-    // TODO: Describe what an empty glob is. In particular, define what it does and does not match.
+    /// Constructs an empty [`Glob`].
+    ///
+    /// An empty glob is built from the empty glob expression `""`. It matches exactly the empty
+    /// path and nothing else. When walking a directory tree, the empty path is the path of a file
+    /// relative to the root, so an empty glob matches only the root path itself, much like the
+    /// current directory `.` in a path. No directories are read beneath the root.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{Glob, Program};
+    ///
+    /// let glob = Glob::empty();
+    /// assert!(glob.is_empty());
+    /// assert!(glob.is_match(""));
+    /// assert!(!glob.is_match("a"));
+    /// ```
+    ///
+    /// [`Glob`]: crate::Glob
     pub fn empty() -> Self {
         Glob::new("").expect("failed to build empty glob")
     }
@@ -567,6 +995,102 @@ impl<'t> Glob<'t> {
         Glob::new("**").expect("failed to build tree glob")
     }
 
+    /// Concatenates the glob with another glob.
+    ///
+    /// The joined glob matches paths that begin with a path matched by this glob followed by a
+    /// path matched by the given glob. A separator is inserted between the globs unless either
+    /// has a boundary where they meet, such as a tree wildcard `**` or separator `/`. For example,
+    /// joining `src/**` with `*.rs` yields `src/**/*.rs`. Joining with an empty glob yields the
+    /// other glob.
+    ///
+    /// The joined glob expression is checked and compiled again, so joining globs may fail even
+    /// if both globs are valid. Back slashes that are interpreted as separators (see
+    /// [`GlobBuilder::accept_backslash_separators`]) are replaced by forward slashes in the
+    /// joined glob expression, so joining `src\lib.rs` with `x` yields `src/lib.rs/x`.
+    ///
+    /// Flags in this glob do not apply to the given glob. If this glob expression contains flags,
+    /// then the default flags are restored where the globs meet, so joining `(?i)a` with `b`
+    /// yields `(?i)a(?-i)/b` (on platforms that are case sensitive by default).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the joined glob violates a rule or fails to compile. For example,
+    /// joining `**` with `**` fails, because the tree wildcards are adjacent. Joining globs that
+    /// were built with different separators (see [`GlobBuilder::separator`]) also fails. See
+    /// [`BuildError`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{Glob, Program};
+    ///
+    /// let base = Glob::new("src/**").unwrap();
+    /// let glob = base.join(&Glob::new("*.rs").unwrap()).unwrap();
+    /// assert_eq!(glob.to_string(), "src/**/*.rs");
+    /// assert!(glob.is_match("src/walk/glob.rs"));
+    ///
+    /// assert!(Glob::tree().join(&Glob::tree()).is_err());
+    /// ```
+    ///
+    /// [`BuildError`]: crate::BuildError
+    /// [`GlobBuilder::accept_backslash_separators`]: crate::GlobBuilder::accept_backslash_separators
+    /// [`GlobBuilder::separator`]: crate::GlobBuilder::separator
+    pub fn join(&self, other: &Glob<'t>) -> Result<Self, BuildError> {
+        if self.tree.as_ref().separator() != other.tree.as_ref().separator() {
+            return Err(BuildErrorKind::MismatchedSeparators.into());
+        }
+        let tree = rule::check(
+            self.tree
+                .as_ref()
+                .clone()
+                .concatenate(other.tree.as_ref().clone())
+                .normalize_separators(),
+        )?;
+        let program = Glob::compile::<Tokenized<_>>(tree.as_ref())?;
+        Ok(Glob::from_tree_and_program(tree, program))
+    }
+
+    /// Requires that a glob that consists of a tree wildcard matches at least one component.
+    ///
+    /// A tree wildcard `**` matches any path, including an empty path. This function returns a
+    /// glob in which such a tree wildcard must match at least one component, so the glob does not
+    /// match an empty path. This is much like bounding the minimum depth of a walk to one (see
+    /// [`DepthMin`]), but is a property of the glob itself and so also applies to functions like
+    /// [`Program::is_match`]. Any other glob, such as `a/**` or `**/*.rs`, is returned unchanged,
+    /// because a tree wildcard that is a part of a larger glob cannot match an empty path on its
+    /// own.
+    ///
+    /// The glob is compiled again if it changes. [`Program::depth`] reflects that the glob matches
+    /// at least one component and [`Program::is_exhaustive`] is unchanged, as every path beneath
+    /// a matching path still matches. **There is no glob expression for such a tree wildcard**, so
+    /// the glob is displayed as `**` and parsing its display does not preserve this behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{Glob, Program};
+    ///
+    /// let glob = Glob::new("**").unwrap().non_empty();
+    /// assert!(!glob.is_match(""));
+    /// assert!(glob.is_match("a"));
+    /// assert!(glob.is_match("a/b"));
+    /// ```
+    ///
+    /// [`DepthMin`]: crate::walk::DepthMin
+    /// [`Program::depth`]: crate::Program::depth
+    /// [`Program::is_exhaustive`]: crate::Program::is_exhaustive
+    /// [`Program::is_match`]: crate::Program::is_match
+    pub fn non_empty(mut self) -> Self {
+        if self.tree.exclude_empty() {
+            let program = Glob::compile::<Tokenized<_>>(self.tree.as_ref())
+                .expect("failed to compile non-empty tree glob");
+            Glob::from_tree_and_program(self.tree, program)
+        }
+        else {
+            self
+        }
+    }
+
     // TODO: Describe why and when the `Glob` postfix is `None`.
     /// Partitions a [`Glob`] into an invariant [`PathBuf`] prefix and variant [`Glob`] postfix.
     ///
@@ -631,7 +1155,7 @@ impl<'t> Glob<'t> {
             tree.map(|tree| {
                 let program = Glob::compile::<Tokenized<_>>(tree.as_ref())
                     .expect("failed to compile partitioned glob");
-                Glob { tree, program }
+                Glob::from_tree_and_program(tree, program)
             }),
         )
     }
@@ -648,1384 +1172,5375 @@ impl<'t> Glob<'t> {
         (prefix, glob.unwrap_or_else(Glob::tree))
     }
 
-    /// Clones any borrowed data into an owning instance.
+    /// Discards the invariant prefix of a [`Glob`] and gets its variant postfix.
     ///
-    /// # Examples
+    /// This function is the same as [`Glob::partition`], but discards the invariant prefix and
+    /// always returns an owned [`Glob`]. This is useful for repeatedly matching paths that are
+    /// already relative to the prefix, such as paths yielded by another walk that is rooted at
+    /// the prefix. If the [`Glob`] has no variant components, then the [empty][`Glob::empty`]
+    /// [`Glob`] is returned, which matches only the empty path.
     ///
-    /// `Glob`s borrow data in the corresponding glob expression. To move a `Glob` beyond the scope
-    /// of a glob expression, clone the data with this function.
+    /// # Examples
     ///
     /// ```rust
-    /// use wax::{BuildError, Glob};
+    /// use wax::{Glob, Program};
     ///
-    /// fn local() -> Result<Glob<'static>, BuildError> {
-    ///     let expression = String::from("**/*.txt");
-    ///     Glob::new(&expression).map(Glob::into_owned)
-    /// }
+    /// let glob = Glob::new("src/**/*.rs").unwrap().without_invariant_prefix();
+    /// assert!(glob.is_match("walk/glob.rs"));
+    /// assert!(!glob.is_match("src/walk/glob.md"));
     /// ```
-    pub fn into_owned(self) -> Glob<'static> {
-        let Glob { tree, program } = self;
-        Glob {
-            tree: tree.into_owned(),
-            program,
-        }
+    ///
+    /// [`Glob`]: crate::Glob
+    /// [`Glob::empty`]: crate::Glob::empty
+    /// [`Glob::partition`]: crate::Glob::partition
+    pub fn without_invariant_prefix(self) -> Glob<'static> {
+        self.partition_or_empty().1.into_owned()
     }
 
-    /// Gets metadata for capturing sub-expressions.
+    /// Simplifies the glob without changing the paths that it matches.
     ///
-    /// This function returns an iterator over capturing tokens, which describe the index and
-    /// location of sub-expressions that capture [matched text][`MatchedText`]. For example, in the
-    /// expression `src/**/*.rs`, both `**` and `*` form captures.
+    /// Alternations with a single branch are replaced by that branch, nested alternations that
+    /// form an entire branch are merged into the enclosing alternation, empty literals are
+    /// discarded, and adjacent literals are coalesced. This is useful for globs that are
+    /// generated or [joined][`Glob::join`], as the simplified glob has a cleaner expression (see
+    /// [`Display`]) and may compile to a smaller program.
+    ///
+    /// Matching is unchanged, but alternations are capturing and so the indices of captures in
+    /// [`MatchedText`] may differ. If the simplified glob would violate a rule, such as when
+    /// removing the braces from `<{*}:1,2>` yields a singular zero-or-more wildcard in a
+    /// repetition, then the glob is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{Glob, Program};
+    ///
+    /// let glob = Glob::new("{src}/{a,{b,c}}.{rs}").unwrap().simplify();
+    /// assert_eq!(glob.to_string(), "src/{a,b,c}.rs");
+    /// assert!(glob.is_match("src/b.rs"));
+    /// ```
     ///
+    /// [`Display`]: std::fmt::Display
+    /// [`Glob::join`]: crate::Glob::join
     /// [`MatchedText`]: crate::MatchedText
-    pub fn captures(&self) -> impl '_ + Clone + Iterator<Item = CapturingToken> {
-        self.tree
-            .as_ref()
-            .as_token()
-            .concatenation()
-            .iter()
-            .filter(|token| token.is_capturing())
-            .enumerate()
-            .map(|(index, token)| CapturingToken::new(index + 1, *token.annotation()))
+    pub fn simplify(self) -> Glob<'static> {
+        let tree = self.tree.simplify().into_owned();
+        let program = Glob::compile::<Tokenized<_>>(tree.as_ref())
+            .expect("failed to compile simplified glob");
+        Glob::from_tree_and_program(tree, program)
     }
 
-    /// Returns `true` if the glob has literals that have non-nominal semantics on the target
-    /// platform.
+    /// Gets the invariant prefix of the glob, if any.
     ///
-    /// The most notable semantic literals are the relative path components `.` and `..`, which
-    /// refer to a current and parent directory on Unix and Windows operating systems,
-    /// respectively. These are interpreted as literals in glob expressions, and so only logically
-    /// match paths that contain these exact nominal components (semantic meaning is lost).
+    /// The invariant prefix is the path formed by the leading components of the glob expression
+    /// that match exactly one text. This is the same prefix that is split off by
+    /// [`Glob::partition`], but the `Glob` is not consumed. When walking a directory tree, the
+    /// walk begins at the given directory joined with this prefix, so it can be used to report
+    /// or verify that root before calling [`Glob::walk`].
     ///
-    /// See [`Glob::partition`].
+    /// Returns `None` if the glob has no invariant prefix.
     ///
-    /// [`Glob::partition`]: crate::Glob::partition
-    pub fn has_semantic_literals(&self) -> bool {
-        self.tree
-            .as_ref()
-            .as_token()
-            .literals()
-            .any(|(_, literal)| literal.is_semantic_literal())
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/walk/**/*.rs").unwrap();
+    /// assert_eq!(Some(Path::new("src/walk")), glob.invariant_prefix().as_deref());
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// assert!(glob.invariant_prefix().is_none());
+    /// ```
+    ///
+    /// [`Glob::partition`]: crate::Glob::partition
+    /// [`Glob::walk`]: crate::Glob::walk
+    pub fn invariant_prefix(&self) -> Option<PathBuf> {
+        let (_, prefix) = self.tree.as_ref().as_token().invariant_text_prefix();
+        if prefix.is_empty() {
+            None
+        }
+        else {
+            Some(prefix.into())
+        }
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.tree.as_ref().as_token().is_empty()
+    /// Gets the length in bytes of the glob expression that forms its invariant prefix.
+    ///
+    /// This is the offset in the glob expression at which the glob stops being a plain path and
+    /// begins its variant patterns. It is the length of the text in the expression that forms the
+    /// [invariant prefix][`Glob::invariant_prefix`], including any flags and escapes, so it may
+    /// differ from the length of the prefix itself. Returns zero if the glob has no invariant
+    /// prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let expression = "src/w\\{a\\}lk/*.rs";
+    /// let glob = Glob::new(expression).unwrap();
+    /// assert_eq!(&expression[..glob.invariant_prefix_len()], "src/w\\{a\\}lk/");
+    /// ```
+    ///
+    /// [`Glob::invariant_prefix`]: crate::Glob::invariant_prefix
+    pub fn invariant_prefix_len(&self) -> usize {
+        let token = self.tree.as_ref().as_token();
+        let (n, prefix) = token.invariant_text_prefix();
+        match token.concatenation()[..n].last() {
+            Some(token) => {
+                let (start, n) = *token.annotation().span();
+                start + n
+            },
+            // A rooted tree wildcard has an invariant prefix (the root) but the prefix is formed
+            // from part of the wildcard rather than any whole tokens.
+            _ => token
+                .concatenation()
+                .first()
+                .filter(|_| !prefix.is_empty())
+                .map_or(0, |token| token.annotation().span().0 + prefix.len()),
+        }
     }
 
-    fn compile<T>(tree: impl Borrow<T>) -> Result<Regex, CompileError>
-    where
-        T: ConcatenationTree<'t>,
-    {
-        encode::compile(tree)
+    /// Describes the glob in prose.
+    ///
+    /// The description is intended for people, such as when explaining a glob in a command line
+    /// interface. Its text is **not** stable and may change between versions, so it should not be
+    /// parsed or compared.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// assert_eq!(glob.describe(), "any directories, then any characters, then `.rs`");
+    /// ```
+    pub fn describe(&self) -> String {
+        self.tree.as_ref().as_token().describe()
     }
-}
 
-impl Display for Glob<'_> {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{}", self.tree.as_ref().expression())
+    /// Gets the indices of branches of a top-level alternation that are subsumed by another
+    /// branch.
+    ///
+    /// A branch is redundant if any path that it matches is also matched by another branch of
+    /// the alternation, such as the branch `lib.rs` in `{*.rs,lib.rs}`. When branches subsume
+    /// each other, such as in `{a,a}`, only the later branches are redundant. The indices are the
+    /// same as those of [`MatchedText::matched_branch`], so removing these branches does not
+    /// change which paths the glob matches.
+    ///
+    /// This analysis is conservative: a branch is only reported if its redundancy is provable.
+    /// A branch is subsumed by another if the two branches have the same structure or if the
+    /// branch matches exactly one text that the other branch matches. Returns an empty [`Vec`] if
+    /// the glob is not a single alternation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("{*.rs,lib.rs,*.md}").unwrap();
+    /// assert_eq!(glob.redundant_branches(), vec![1]);
+    /// ```
+    ///
+    /// [`MatchedText::matched_branch`]: crate::MatchedText::matched_branch
+    /// [`Vec`]: std::vec::Vec
+    pub fn redundant_branches(&self) -> Vec<usize> {
+        let branches = match self.tree.as_ref().concatenation() {
+            [token] => match token.as_alternation() {
+                Some(alternation) => alternation.tokens(),
+                _ => return vec![],
+            },
+            _ => return vec![],
+        };
+        let normals: Vec<_> = branches.iter().map(Token::normalized).collect();
+        let texts: Vec<_> = branches
+            .iter()
+            .map(|token| {
+                token
+                    .variance::<Text>()
+                    .invariant()
+                    .map(|text| text.to_string().into_owned())
+            })
+            .collect();
+        let programs: Vec<_> = branches
+            .iter()
+            .map(|token| encode::compile::<Token<_>>(token).ok())
+            .collect();
+        let subsumes = |i: usize, j: usize| {
+            i != j
+                && (normals[i] == normals[j]
+                    || texts[j]
+                        .as_ref()
+                        .zip(programs[i].as_ref())
+                        .is_some_and(|(text, program)| program.is_match(text)))
+        };
+        (0..branches.len())
+            .filter(|&j| {
+                (0..branches.len()).any(|i| subsumes(i, j) && !(j < i && subsumes(j, i)))
+            })
+            .collect()
     }
-}
-
-impl FromStr for Glob<'static> {
-    type Err = BuildError;
 
-    fn from_str(expression: &str) -> Result<Self, Self::Err> {
-        Glob::new(expression).map(Glob::into_owned)
+    /// Gets the literal file extensions that the glob can match, if any.
+    ///
+    /// Returns the extensions (without a leading `.`) of the final component of the glob when
+    /// they are invariant, such as in `*.rs` or `*.{md,txt}`. Any path that the glob matches has
+    /// one of these extensions, so they can be used to cheaply filter paths before matching, such
+    /// as when watching a file system. Returns `None` if the glob can match variant extensions or
+    /// file names with no extension.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/**/*.{rs,toml}").unwrap();
+    /// assert_eq!(
+    ///     glob.possible_extensions(),
+    ///     Some(vec![String::from("rs"), String::from("toml")]),
+    /// );
+    ///
+    /// let glob = Glob::new("src/**/*.*").unwrap();
+    /// assert!(glob.possible_extensions().is_none());
+    /// ```
+    pub fn possible_extensions(&self) -> Option<Vec<String>> {
+        self.tree.as_ref().as_token().extensions()
     }
-}
-
-impl<'t> Pattern<'t> for Glob<'t> {
-    type Tokens = Tokenized<'t, ExpressionMetadata>;
-    type Error = Infallible;
-}
 
-impl<'t> Program<'t> for Glob<'t> {
-    fn is_match<'p>(&self, path: impl Into<CandidatePath<'p>>) -> bool {
-        let path = path.into();
-        self.program.is_match(path.as_ref())
+    /// Gets the finite set of paths that the glob matches, if any.
+    ///
+    /// Returns the paths when the glob has no variant patterns besides alternations and bounded
+    /// repetitions of invariant text, such as `config.{toml,yaml,json}` or `<a/:1,2>b`. These
+    /// paths can be queried directly, such as with [`fs::metadata`], rather than walking a
+    /// directory tree. Paths are unique and are ordered by the branches and bounds of the glob.
+    ///
+    /// Returns `None` if the glob can match an unbounded set of paths, such as via wildcards or
+    /// character classes, or if the glob matches more than [`ENUMERATION_LIMIT`] paths.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::PathBuf;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("config.{toml,yaml,json}").unwrap();
+    /// assert_eq!(
+    ///     glob.enumerate(),
+    ///     Some(vec![
+    ///         PathBuf::from("config.toml"),
+    ///         PathBuf::from("config.yaml"),
+    ///         PathBuf::from("config.json"),
+    ///     ]),
+    /// );
+    ///
+    /// let glob = Glob::new("config.*").unwrap();
+    /// assert!(glob.enumerate().is_none());
+    /// ```
+    ///
+    /// [`ENUMERATION_LIMIT`]: crate::Glob::ENUMERATION_LIMIT
+    /// [`fs::metadata`]: std::fs::metadata
+    pub fn enumerate(&self) -> Option<Vec<PathBuf>> {
+        self.tree
+            .as_ref()
+            .as_token()
+            .texts(Glob::ENUMERATION_LIMIT)
+            .map(|texts| texts.into_iter().unique().map(PathBuf::from).collect())
     }
 
-    fn matched<'p>(&self, path: &'p CandidatePath<'_>) -> Option<MatchedText<'p>> {
-        self.program.captures(path.as_ref()).map(From::from)
+    /// Constructs a combinator that matches paths that match the glob but **not** another glob.
+    ///
+    /// The output [`AndNot`] matches paths in memory much like a [`Program`]. Unlike the [`not`]
+    /// combinator, it is not tied to a walk, though it can also [match directory
+    /// trees][`AndNot::walk`]. Glob expressions do not support general negations, so this can be
+    /// used to express differences that cannot be written as a single glob.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/**/*.rs").unwrap();
+    /// let tests = Glob::new("**/tests/**").unwrap();
+    /// let sources = glob.and_not(tests);
+    /// assert!(sources.is_match("src/lib.rs"));
+    /// assert!(!sources.is_match("src/tests/walk.rs"));
+    /// ```
+    ///
+    /// [`AndNot`]: crate::AndNot
+    /// [`AndNot::walk`]: crate::AndNot::walk
+    /// [`not`]: crate::walk::FileIterator::not
+    /// [`Program`]: crate::Program
+    pub fn and_not(self, negation: Glob<'t>) -> AndNot<'t> {
+        AndNot {
+            glob: self,
+            negation,
+        }
     }
 
-    fn depth(&self) -> DepthVariance {
-        self.tree.as_ref().as_token().variance().into()
+    /// Clones any borrowed data into an owning instance.
+    ///
+    /// # Examples
+    ///
+    /// `Glob`s borrow data in the corresponding glob expression. To move a `Glob` beyond the scope
+    /// of a glob expression, clone the data with this function.
+    ///
+    /// ```rust
+    /// use wax::{BuildError, Glob};
+    ///
+    /// fn local() -> Result<Glob<'static>, BuildError> {
+    ///     let expression = String::from("**/*.txt");
+    ///     Glob::new(&expression).map(Glob::into_owned)
+    /// }
+    /// ```
+    pub fn into_owned(self) -> Glob<'static> {
+        let Glob {
+            tree,
+            program,
+            #[cfg(not(unix))]
+            slash_program,
+            branch_program,
+            component_programs,
+            #[cfg(feature = "bytes")]
+            bytes_program,
+        } = self;
+        Glob {
+            tree: tree.into_owned(),
+            program,
+            #[cfg(not(unix))]
+            slash_program,
+            branch_program,
+            component_programs,
+            #[cfg(feature = "bytes")]
+            bytes_program,
+        }
     }
 
-    fn text(&self) -> TextVariance<'t> {
-        self.tree.as_ref().as_token().variance().into()
+    /// Gets metadata for capturing sub-expressions.
+    ///
+    /// This function returns an iterator over capturing tokens, which describe the index and
+    /// location of sub-expressions that capture [matched text][`MatchedText`]. For example, in the
+    /// expression `src/**/*.rs`, both `**` and `*` form captures.
+    ///
+    /// [`MatchedText`]: crate::MatchedText
+    pub fn captures(&self) -> impl '_ + Clone + Iterator<Item = CapturingToken> {
+        self.tree
+            .as_ref()
+            .as_token()
+            .concatenation()
+            .iter()
+            .filter(|token| token.is_capturing())
+            .enumerate()
+            .map(|(index, token)| CapturingToken::new(index + 1, *token.annotation()))
     }
 
-    fn has_root(&self) -> When {
-        self.tree.as_ref().as_token().has_root()
+    /// Gets the number of capturing sub-expressions in the glob expression.
+    ///
+    /// This is the number of [capturing tokens][`Glob::captures`] and does not include the
+    /// implicit capture of the complete match at index zero, so the indices of captures in
+    /// [`MatchedText`] range from one to this count inclusive.
+    ///
+    /// Capture indices are stable: they are determined only by the top-level capturing tokens of
+    /// the glob expression in the order in which they appear. Wildcards, classes, alternations,
+    /// and repetitions capture, but literals and separators do not. Tokens within alternations
+    /// and repetitions never form their own captures, so `{a,*}` forms exactly one capture
+    /// regardless of its contents.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{CandidatePath, Glob, Program};
+    ///
+    /// let glob = Glob::new("src/**/{*,<*/>}.rs").unwrap();
+    /// assert_eq!(glob.capture_count(), 2);
+    ///
+    /// let candidate = CandidatePath::from("src/walk/glob.rs");
+    /// let matched = glob.matched(&candidate).unwrap();
+    /// assert_eq!(matched.get(1), Some("walk/"));
+    /// assert_eq!(matched.get(2), Some("glob"));
+    /// ```
+    ///
+    /// [`Glob::captures`]: crate::Glob::captures
+    /// [`MatchedText`]: crate::MatchedText
+    pub fn capture_count(&self) -> usize {
+        self.captures().count()
     }
 
-    fn is_exhaustive(&self) -> When {
-        self.tree.as_ref().as_token().is_exhaustive()
+    /// Gets the components of the glob expression.
+    ///
+    /// Components are delimited by separators and are ordered by their location in the
+    /// expression. See [`GlobComponent`].
+    ///
+    /// # Examples
+    ///
+    /// The invariant components at the beginning of a glob can be used to complete paths.
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/walk/*.rs").unwrap();
+    /// let prefix: Vec<_> = glob
+    ///     .components()
+    ///     .map_while(|component| component.literal_text())
+    ///     .collect();
+    /// assert_eq!(prefix, ["src", "walk"]);
+    /// ```
+    ///
+    /// [`GlobComponent`]: crate::query::GlobComponent
+    pub fn components(&self) -> impl '_ + Iterator<Item = GlobComponent<'_, 't>> {
+        self.tree
+            .as_ref()
+            .as_token()
+            .components()
+            .map(GlobComponent::from)
     }
-}
 
-impl<'t> TryFrom<&'t str> for Glob<'t> {
-    type Error = BuildError;
-
-    fn try_from(expression: &'t str) -> Result<Self, Self::Error> {
-        Glob::new(expression)
+    /// Gets the top-level tokens of the glob expression.
+    ///
+    /// Tokens are ordered by their location in the expression. Branch tokens, such as
+    /// alternations and repetitions, contain other tokens that can be traversed via
+    /// [`GlobToken::tokens`] or [`Glob::accept`]. See [`GlobToken`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::query::TokenKind;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/*.{rs,toml}").unwrap();
+    /// let kinds: Vec<_> = glob.tokens().map(|token| token.kind()).collect();
+    /// assert_eq!(
+    ///     kinds,
+    ///     [
+    ///         TokenKind::Literal,
+    ///         TokenKind::Separator,
+    ///         TokenKind::ZeroOrMore,
+    ///         TokenKind::Literal,
+    ///         TokenKind::Alternation,
+    ///     ],
+    /// );
+    /// ```
+    ///
+    /// [`Glob::accept`]: crate::Glob::accept
+    /// [`GlobToken`]: crate::query::GlobToken
+    /// [`GlobToken::tokens`]: crate::query::GlobToken::tokens
+    pub fn tokens(&self) -> impl '_ + Clone + Iterator<Item = GlobToken<'_, 't>> {
+        self.tree
+            .as_ref()
+            .as_token()
+            .concatenation()
+            .iter()
+            .map(GlobToken::from)
     }
-}
-
-/// Combinator that matches any of its component [`Program`]s.
-///
-/// An instance of `Any` is constructed using the [`any`] function, which combines multiple
-/// [`Program`]s for more ergonomic and efficient matching.
-///
-/// [`any`]: crate::any
-/// [`Program`]: crate::Program
-#[derive(Clone, Debug)]
-pub struct Any<'t> {
-    tree: Checked<Token<'t, ()>>,
-    program: Regex,
-}
 
-impl<'t> Any<'t> {
-    fn compile(token: &Token<'t, ()>) -> Result<Regex, CompileError> {
-        encode::compile::<Token<_>>(token)
+    /// Visits the top-level tokens of the glob expression with a [`TokenVisitor`].
+    ///
+    /// See [`Glob::tokens`] and [`TokenVisitor`].
+    ///
+    /// [`Glob::tokens`]: crate::Glob::tokens
+    /// [`TokenVisitor`]: crate::query::TokenVisitor
+    pub fn accept<'i, V>(&'i self, visitor: &mut V)
+    where
+        V: TokenVisitor<'i, 't> + ?Sized,
+    {
+        for token in self.tokens() {
+            token.accept(visitor);
+        }
     }
-}
-
-impl<'t> Pattern<'t> for Any<'t> {
-    type Tokens = Token<'t, ()>;
-    type Error = Infallible;
-}
 
-impl<'t> Program<'t> for Any<'t> {
-    fn is_match<'p>(&self, path: impl Into<CandidatePath<'p>>) -> bool {
-        let path = path.into();
-        self.program.is_match(path.as_ref())
+    /// Gets the kind and location of each token in the glob expression.
+    ///
+    /// This function returns an iterator over all tokens in the expression, including the
+    /// concatenations that comprise the expression and the sub-globs of alternations and
+    /// repetitions. Tokens are ordered by their location: tokens that begin earlier in the
+    /// expression precede tokens that begin later, and branch tokens precede the tokens that they
+    /// contain.
+    ///
+    /// # Examples
+    ///
+    /// Spans can be used to map tokens back to their sub-expressions.
+    ///
+    /// ```rust
+    /// use wax::query::TokenKind;
+    /// use wax::Glob;
+    ///
+    /// let expression = "src/**/*.rs";
+    /// let glob = Glob::new(expression).unwrap();
+    /// for (kind, (start, n)) in glob.tokens_with_spans() {
+    ///     if let TokenKind::Tree = kind {
+    ///         assert_eq!(&expression[start..][..n], "/**/");
+    ///     }
+    /// }
+    /// ```
+    pub fn tokens_with_spans(&self) -> impl Clone + Iterator<Item = (TokenKind, Span)> {
+        let mut tokens: Vec<_> = token::walk::forward(self.tree.as_ref())
+            .map(|entry| {
+                let token = entry.into_token();
+                (TokenKind::from(token.topology()), *token.annotation())
+            })
+            .collect();
+        // Walks visit tokens in breadth-first order. Sort tokens by the start of their spans and
+        // then reversed by their length, so that branch tokens precede their children.
+        tokens.sort_by_key(|&(_, (start, n))| (start, cmp::Reverse(n)));
+        tokens.into_iter()
     }
 
-    fn matched<'p>(&self, path: &'p CandidatePath<'_>) -> Option<MatchedText<'p>> {
-        self.program.captures(path.as_ref()).map(From::from)
+    /// Gets the bounds and sub-expression of each repetition in the glob expression.
+    ///
+    /// This function returns an iterator over the repetitions in the expression, including nested
+    /// repetitions, ordered by their location. Each item is a tuple of the lower bound, the upper
+    /// bound (`None` if unbounded), and the repeated sub-expression (excluding any separator).
+    ///
+    /// # Examples
+    ///
+    /// Repetitions can be inspected to reject unbounded patterns.
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("<a*/:0,>*.rs").unwrap();
+    /// let unbounded: Vec<_> = glob
+    ///     .repetitions()
+    ///     .filter(|(_, upper, _)| upper.is_none())
+    ///     .map(|(_, _, expression)| expression)
+    ///     .collect();
+    /// assert_eq!(unbounded, ["a*/"]);
+    /// ```
+    pub fn repetitions(
+        &self,
+    ) -> impl '_ + Clone + Iterator<Item = (usize, Option<usize>, &'_ str)> {
+        let expression = self.tree.as_ref().expression();
+        let mut repetitions: Vec<_> = token::walk::forward(self.tree.as_ref())
+            .filter_map(|entry| {
+                entry.into_token().as_repetition().map(|repetition| {
+                    let (lower, upper) = repetition.bound_specification();
+                    (*repetition.token().annotation(), lower, upper)
+                })
+            })
+            .collect();
+        repetitions.sort_by_key(|&((start, _), _, _)| start);
+        repetitions
+            .into_iter()
+            .map(move |((start, n), lower, upper)| (lower, upper, &expression[start..][..n]))
     }
 
-    fn depth(&self) -> DepthVariance {
-        self.tree.as_ref().as_token().variance().into()
-    }
+    /// Gets the kind of files that the glob can match.
+    ///
+    /// This function examines the end of the glob expression. If every sub-glob of the expression
+    /// terminates with a separator, then the glob only matches directories and this function
+    /// returns [`TerminalKind::Directory`]. Otherwise, the glob may match any kind of file,
+    /// including directories. Note that tree wildcards absorb any adjacent separators, so `**/`
+    /// may match any kind of file.
+    ///
+    /// This can be used to avoid querying the file system for metadata when filtering files.
+    ///
+    /// **The terminal kind describes the text that the glob matches and not the paths that it
+    /// matches when walking directory trees.** A glob that only matches directories also only
+    /// matches paths that terminate with a separator, so such a glob does not match `src/glob`
+    /// and walking such a glob yields no files. To match directory paths without a terminating
+    /// separator, use [`Glob::is_match_with`] with [trailing-slash-insensitive
+    /// matching][`MatchBehavior::trailing_slash_insensitive`] or walk the glob without its
+    /// terminating separator and filter entries by their file type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::query::TerminalKind;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/*/").unwrap();
+    /// assert_eq!(glob.terminal_kind(), TerminalKind::Directory);
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// assert_eq!(glob.terminal_kind(), TerminalKind::Any);
+    /// ```
+    ///
+    /// [`Glob::is_match_with`]: crate::Glob::is_match_with
+    /// [`MatchBehavior::trailing_slash_insensitive`]: crate::MatchBehavior::trailing_slash_insensitive
+    /// [`TerminalKind::Directory`]: crate::query::TerminalKind::Directory
+    pub fn terminal_kind(&self) -> TerminalKind {
+        if self
+            .tree
+            .as_ref()
+            .as_token()
+            .has_terminating_separator()
+            .is_always()
+        {
+            TerminalKind::Directory
+        }
+        else {
+            TerminalKind::Any
+        }
+    }
 
-    fn text(&self) -> TextVariance<'t> {
-        self.tree.as_ref().as_token().variance().into()
+    /// Returns `true` if a path matches the glob with the given [`MatchBehavior`].
+    ///
+    /// This function is the same as [`Program::is_match`], but the candidate path and glob are
+    /// normalized according to the given [`MatchBehavior`] before the path is matched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{Glob, MatchBehavior, Program};
+    ///
+    /// let glob = Glob::new("**/target").unwrap();
+    /// assert!(!glob.is_match("crate/target/"));
+    ///
+    /// let behavior = MatchBehavior {
+    ///     trailing_slash_insensitive: true,
+    /// };
+    /// assert!(glob.is_match_with("crate/target/", behavior));
+    ///
+    /// let glob = Glob::new("crate/*/").unwrap();
+    /// assert!(glob.is_match_with("crate/target", behavior));
+    /// ```
+    ///
+    /// [`MatchBehavior`]: crate::MatchBehavior
+    /// [`Program::is_match`]: crate::Program::is_match
+    pub fn is_match_with<'p>(
+        &self,
+        path: impl Into<CandidatePath<'p>>,
+        behavior: impl Into<MatchBehavior>,
+    ) -> bool {
+        let MatchBehavior {
+            trailing_slash_insensitive,
+        } = behavior.into();
+        let path = path.into();
+        if !trailing_slash_insensitive {
+            return self.is_match(path);
+        }
+        let path = path.trim_trailing_separator();
+        // Rather than compiling a program without the terminating separator of the glob, a
+        // separator is appended to the trimmed path. The glob matches this path if and only if
+        // the glob without its terminating separator matches the trimmed path.
+        let separated = || CandidatePath {
+            text: format!("{}/", path.text).into(),
+        };
+        match self.tree.as_ref().as_token().has_terminating_separator() {
+            // Neither the empty path nor a lone separator (a root path) is trimmed.
+            _ if path.text.chars().nth(1).is_none()
+                && path.text.chars().all(|x| x == '/' || path::is_separator(x)) =>
+            {
+                self.is_match(path)
+            },
+            When::Never => self.is_match(path),
+            When::Always => self.is_match(separated()),
+            When::Sometimes => self.is_match(separated()) || self.is_match(path),
+        }
     }
 
-    fn has_root(&self) -> When {
-        self.tree.as_ref().as_token().has_root()
+    /// Returns `true` if the path with the given components matches the glob.
+    ///
+    /// The components are joined with separators into a single [`CandidatePath`] without
+    /// constructing an intermediate [`PathBuf`], which is useful when integrating with a directory
+    /// walker or in-memory tree that provides the components of paths. This function is
+    /// consistent with [`Program::is_match`] on the equivalent joined path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/**/*.rs").unwrap();
+    /// assert!(glob.is_match_components(["src", "glob", "token.rs"]));
+    /// assert!(!glob.is_match_components(["tests", "walk.rs"]));
+    /// ```
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`PathBuf`]: std::path::PathBuf
+    /// [`Program::is_match`]: crate::Program::is_match
+    pub fn is_match_components<I>(&self, components: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: AsRef<OsStr>,
+    {
+        self.is_match(CandidatePath::from_components(components))
     }
 
-    fn is_exhaustive(&self) -> When {
-        self.tree.as_ref().as_token().is_exhaustive()
+    /// Returns `true` if a borrowed [`CandidatePath`] matches the glob.
+    ///
+    /// This function is the same as [`Program::is_match`], but borrows the [`CandidatePath`]
+    /// rather than consuming it, so a candidate can be matched against many globs or reused
+    /// without cloning its text. To get [matched text][`MatchedText`] from a borrowed
+    /// [`CandidatePath`], use [`Program::matched`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{CandidatePath, Glob};
+    ///
+    /// let candidate = CandidatePath::from("src/lib.rs");
+    /// for expression in ["**/*.rs", "src/**"] {
+    ///     assert!(Glob::new(expression).unwrap().is_match_candidate(&candidate));
+    /// }
+    /// ```
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`MatchedText`]: crate::MatchedText
+    /// [`Program::is_match`]: crate::Program::is_match
+    /// [`Program::matched`]: crate::Program::matched
+    pub fn is_match_candidate(&self, candidate: &CandidatePath<'_>) -> bool {
+        self.program.is_match(candidate.as_ref())
+    }
+
+    /// Gets the [matched text][`MatchedText`] of each way that a [`CandidatePath`] matches the
+    /// glob.
+    ///
+    /// A path may match a glob in more than one way when the glob has ambiguous repetitions,
+    /// alternatives, or wildcards. For example, `*-*` matches `a-b-c` by capturing either `a` and
+    /// `b-c` or `a-b` and `c`. However, the underlying regular expression engine resolves these
+    /// ambiguities with leftmost-first semantics and only reports the captures of a single match,
+    /// so **this iterator yields at most one item**: the same [`MatchedText`] as
+    /// [`Program::matched`]. Other decompositions of a match are not yielded. This function
+    /// provides a stable interface for code that extracts text via ambiguous globs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{CandidatePath, Glob};
+    ///
+    /// let glob = Glob::new("*-*").unwrap();
+    /// let path = CandidatePath::from("a-b-c");
+    /// let matches: Vec<_> = glob.all_captures(&path).collect();
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(matches[0].get(1), Some("a-b"));
+    /// assert_eq!(matches[0].get(2), Some("c"));
+    /// ```
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`MatchedText`]: crate::MatchedText
+    /// [`Program::matched`]: crate::Program::matched
+    pub fn all_captures<'p>(
+        &self,
+        path: &'p CandidatePath<'_>,
+    ) -> impl Iterator<Item = MatchedText<'p>> {
+        self.matched(path).into_iter()
+    }
+
+    /// Gets the end of the longest prefix of a path that matches the glob.
+    ///
+    /// Returns the byte index in the path up to which the glob matches or `None` if the glob does
+    /// not match any prefix of the path. **Prefixes always end at component boundaries**: either
+    /// at the end of the path or just before a separator. The remainder of the path (if any)
+    /// therefore begins with a separator. For example, `api/*` matches the prefix `api/users` of
+    /// the path `api/users/42`, but `api` does not match any prefix of the path `apiary`.
+    ///
+    /// This function is useful for routing, where a glob matches a path prefix and the remainder
+    /// of the path is a parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("api/*").unwrap();
+    /// let path = "api/users/42";
+    /// let end = glob.match_end(path).unwrap();
+    ///
+    /// assert_eq!(&path[..end], "api/users");
+    /// assert_eq!(&path[end..], "/42");
+    /// ```
+    pub fn match_end(&self, path: &str) -> Option<usize> {
+        let separator = self.tree.as_ref().separator();
+        iter::once(path.len())
+            .chain(
+                path.char_indices()
+                    .rev()
+                    .filter(|&(_, x)| separator.is_separator(x))
+                    .map(|(index, _)| index),
+            )
+            .find(|&end| self.program.is_match(&path[..end]))
+    }
+
+    /// Pairs the components of the glob with the text that they match in a [`CandidatePath`].
+    ///
+    /// Returns `None` if the path does not match the glob. Otherwise, returns the sub-expression
+    /// of each component of the glob and the text of the path components that it matches, in
+    /// order. A tree wildcard `**` is paired with all of the path components that it matches as
+    /// one entry, which has empty text if it matches no components. The root of a rooted glob is
+    /// not a component. Components like `{a/b,c}` that can match more than one path component
+    /// are paired with all of the path components that they match.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{CandidatePath, Glob};
+    ///
+    /// let glob = Glob::new("src/**/*.rs").unwrap();
+    /// let candidate = CandidatePath::from("src/walk/glob/mod.rs");
+    /// assert_eq!(
+    ///     glob.split_components(&candidate).unwrap(),
+    ///     [("src", "src"), ("**", "walk/glob"), ("*.rs", "mod.rs")],
+    /// );
+    /// ```
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    pub fn split_components<'a>(
+        &'a self,
+        candidate: &'a CandidatePath<'_>,
+    ) -> Option<Vec<(&'a str, &'a str)>> {
+        if !self.is_match_candidate(candidate) {
+            return None;
+        }
+        // An empty glob has no components.
+        if self.is_empty() {
+            return Some(vec![]);
+        }
+        let tree = self.tree.as_ref();
+        let components: Vec<_> = tree.as_token().components().collect();
+        let programs = self.component_programs.get_or_init(|| {
+            components
+                .iter()
+                .map(|component| {
+                    Glob::compile(component.clone()).expect("failed to compile component program")
+                })
+                .collect()
+        });
+        let text = candidate.as_ref();
+        let separator = tree.separator();
+        let mut ranges = vec![];
+        if !text.is_empty() {
+            let mut start = 0;
+            for (index, x) in text.char_indices() {
+                if separator.is_separator(x) {
+                    ranges.push(start..index);
+                    start = index + x.len_utf8();
+                }
+            }
+            ranges.push(start..text.len());
+        }
+        // The root of a rooted glob is not a component, so the empty text that precedes a leading
+        // separator is not matched by any component program.
+        let is_rooted =
+            !tree.as_token().has_root().is_never() && ranges.first().is_some_and(Range::is_empty);
+        let spans = is_rooted
+            .then(|| self::split_components(&components, programs, text, &ranges[1..]))
+            .flatten()
+            .or_else(|| self::split_components(&components, programs, text, &ranges))?;
+        let expression = tree.expression();
+        Some(
+            components
+                .iter()
+                .zip(spans)
+                .map(|(component, span)| {
+                    let tokens = component.tokens();
+                    let (start, _) = *tokens.first().unwrap().annotation().span();
+                    let (last, n) = *tokens.last().unwrap().annotation().span();
+                    let sub = &expression[start..last + n];
+                    // Tree wildcards include the separators that they absorb.
+                    let sub = if component.is_tree_wildcard() {
+                        match separator.custom() {
+                            Some(separator) => sub.trim_matches(separator),
+                            _ => sub.trim_matches(|x| x == '/' || x == '\\'),
+                        }
+                    }
+                    else {
+                        sub
+                    };
+                    (sub, &text[span])
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns `true` if text matches the glob when only forward slash `/` is a separator.
+    ///
+    /// This function matches text that is always delimited by forward slashes regardless of the
+    /// target platform, such as URL paths and the names of members in zip and tar archives. Unlike
+    /// [`Program::is_match`], **back slashes `\` are never interpreted as separators**, even on
+    /// Windows: they are ordinary characters in the text and can be matched by wildcards like `*`.
+    /// On platforms where `/` is the only separator, such as Unix, this function is equivalent to
+    /// [`Program::is_match`].
+    ///
+    /// On other platforms, this function compiles a separate program the first time that it is
+    /// called. This program is shared by clones of the glob.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("docs/*.html").unwrap();
+    /// assert!(glob.is_match_slash("docs/index.html"));
+    /// assert!(glob.is_match_slash(r"docs/a\b.html"));
+    /// assert!(!glob.is_match_slash("docs/api/index.html"));
+    /// ```
+    ///
+    /// [`Program::is_match`]: crate::Program::is_match
+    pub fn is_match_slash(&self, text: &str) -> bool {
+        #[cfg(unix)]
+        {
+            self.program.is_match(text)
+        }
+        #[cfg(not(unix))]
+        {
+            self.slash_program
+                .get_or_init(|| {
+                    encode::compile_with_separators::<Tokenized<_>>(
+                        self.tree.as_ref(),
+                        Separators::Slash,
+                    )
+                    .expect("failed to compile glob with slash separators")
+                })
+                .is_match(text)
+        }
+    }
+
+    /// Returns `true` if bytes that may not be UTF-8 match the glob.
+    ///
+    /// Paths on Unix are arbitrary bytes and need not be UTF-8. Such paths cannot be matched via
+    /// [`CandidatePath`] without a lossy conversion that replaces invalid UTF-8 with `U+FFFD`,
+    /// which can cause false positives. This function matches the bytes directly: **each byte of
+    /// invalid UTF-8 is matched like a single character** by wildcards and negated character
+    /// classes, but is never matched by literals nor other character classes. Valid UTF-8 is
+    /// matched exactly as [`Program::is_match`] matches text.
+    ///
+    /// This function compiles a separate program the first time that it is called. This program
+    /// (or the error that occurs when compiling it) is shared by clones of the glob.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program that matches bytes is too large. This program matches
+    /// invalid UTF-8 in addition to text and is larger than the program of the glob, so this can
+    /// occur even though the glob has been built. See [`BuildError`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/*.rs").unwrap();
+    /// assert!(glob.is_match_bytes(b"src/\xFFlib.rs").unwrap());
+    /// assert!(!glob.is_match_bytes(b"src/\xFF/lib.rs").unwrap());
+    /// ```
+    ///
+    /// [`BuildError`]: crate::BuildError
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`Program::is_match`]: crate::Program::is_match
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+    pub fn is_match_bytes(&self, bytes: &[u8]) -> Result<bool, BuildError> {
+        self.bytes_program
+            .get_or_init(|| encode::compile_bytes::<Tokenized<_>>(self.tree.as_ref()))
+            .as_ref()
+            .map(|program| program.is_match(&encode::escape_invalid_utf8(bytes)))
+            .map_err(|error| error.clone().into())
+    }
+
+    /// Compiles the glob into a [`DfaGlob`] that detects matches with a dense DFA.
+    ///
+    /// A [`DfaGlob`] only detects matches and cannot capture [matched text][`MatchedText`], but
+    /// typically matches [candidate paths][`CandidatePath`] with higher throughput than a `Glob`.
+    /// This is useful when matching a very large number of paths against a fixed glob. However,
+    /// constructing a DFA is expensive and its size can grow exponentially with the complexity of
+    /// the glob, so this should be preferred only when many paths are matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the size of the DFA exceeds a limit. See [`BuildError`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/**/*.{rs,toml}").unwrap().compile_dfa().unwrap();
+    /// assert!(glob.is_match("src/glob/mod.rs"));
+    /// assert!(!glob.is_match("tests/glob.rs"));
+    /// ```
+    ///
+    /// [`BuildError`]: crate::BuildError
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`DfaGlob`]: crate::DfaGlob
+    /// [`MatchedText`]: crate::MatchedText
+    #[cfg(feature = "dfa")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dfa")))]
+    pub fn compile_dfa(self) -> Result<DfaGlob, BuildError> {
+        let dfa = encode::compile_dfa::<Tokenized<_>>(self.tree.as_ref())?;
+        Ok(DfaGlob { dfa: Arc::new(dfa) })
+    }
+
+    /// Returns `true` if the glob has literals that have non-nominal semantics on the target
+    /// platform.
+    ///
+    /// The most notable semantic literals are the relative path components `.` and `..`, which
+    /// refer to a current and parent directory on Unix and Windows operating systems,
+    /// respectively. These are interpreted as literals in glob expressions, and so only logically
+    /// match paths that contain these exact nominal components (semantic meaning is lost).
+    ///
+    /// See [`Glob::partition`].
+    ///
+    /// [`Glob::partition`]: crate::Glob::partition
+    pub fn has_semantic_literals(&self) -> bool {
+        self.tree
+            .as_ref()
+            .as_token()
+            .literals()
+            .any(|(_, literal)| literal.is_semantic_literal())
+    }
+
+    /// Returns `true` if the glob is known to match components of bounded size.
+    ///
+    /// The breadth of a glob is the maximum size of the text that it matches within any one path
+    /// component. Wildcards like `*` and `**` have unbounded breadth and can match any file in a
+    /// directory, so globs with bounded breadth typically fan out much less when walking a
+    /// directory tree. Together with [`Program::depth`], this can be used to estimate the cost of
+    /// a walk before it is performed.
+    ///
+    /// This analysis is conservative: if this function returns `true`, then the breadth of the
+    /// glob is bounded, but it may return `false` for some globs with bounded breadth, such as
+    /// unbounded repetitions with alternatives that end in separators like `<{a/,b/}:0,>`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::Glob;
+    ///
+    /// assert!(Glob::new("src/{lib,glob}.rs").unwrap().is_bounded_breadth());
+    /// assert!(Glob::new("<[a-z]/:0,>main.rs").unwrap().is_bounded_breadth());
+    /// assert!(!Glob::new("src/*.rs").unwrap().is_bounded_breadth());
+    /// ```
+    ///
+    /// [`Program::depth`]: crate::Program::depth
+    pub fn is_bounded_breadth(&self) -> bool {
+        self.tree.as_ref().as_token().has_bounded_breadth()
+    }
+
+    /// Returns `true` if the glob is empty.
+    ///
+    /// An empty glob matches only the empty path. See [`Glob::empty`].
+    ///
+    /// [`Glob::empty`]: crate::Glob::empty
+    pub fn is_empty(&self) -> bool {
+        self.tree.as_ref().as_token().is_empty()
+    }
+
+    pub(crate) fn from_tree_and_program(
+        tree: Checked<Tokenized<'t, ExpressionMetadata>>,
+        program: Regex,
+    ) -> Self {
+        Glob {
+            tree,
+            program: Arc::new(program),
+            #[cfg(not(unix))]
+            slash_program: Arc::default(),
+            branch_program: Arc::default(),
+            component_programs: Arc::default(),
+            #[cfg(feature = "bytes")]
+            bytes_program: Arc::default(),
+        }
+    }
+
+    fn compile<T>(tree: impl Borrow<T>) -> Result<Regex, CompileError>
+    where
+        T: ConcatenationTree<'t>,
+    {
+        encode::compile(tree)
     }
 }
 
-// TODO: It may be useful to use dynamic dispatch via trait objects instead. This would allow for a
-//       variety of types to be composed in an `any` call and would be especially useful if
-//       additional combinators are introduced.
-/// Constructs a combinator that matches if any of its input [`Pattern`]s match.
-///
-/// This function accepts an [`IntoIterator`] with items that implement [`Pattern`], such as
-/// [`Glob`] and `&str`. The output [`Any`] implements [`Program`] by matching its component
-/// [`Program`]s. [`Any`] is often more ergonomic and efficient than matching individually against
-/// multiple [`Program`]s.
-///
-/// [`Any`] groups all captures and therefore only exposes the complete text of a match. It is not
-/// possible to index a particular capturing token in the component patterns. Combinators only
-/// support logical matching and cannot be used to semantically match (walk) a directory tree.
-///
-/// # Examples
-///
-/// To match a path against multiple patterns, the patterns can first be combined into an [`Any`].
-///
-/// ```rust
-/// use wax::{Glob, Program};
-///
-/// let any = wax::any([
-///     "src/**/*.rs",
-///     "tests/**/*.rs",
-///     "doc/**/*.md",
-///     "pkg/**/PKGBUILD",
-/// ])
-/// .unwrap();
-/// assert!(any.is_match("src/lib.rs"));
-/// ```
-///
-/// [`Glob`]s and other compiled [`Program`]s can also be composed into an [`Any`].
-///
-/// ```rust
-/// use wax::{Glob, Program};
-///
-/// let red = Glob::new("**/red/**/*.txt").unwrap();
-/// let blue = Glob::new("**/*blue*.txt").unwrap();
-/// assert!(wax::any([red, blue]).unwrap().is_match("red/potion.txt"));
-/// ```
-///
-/// This function can only combine patterns of the same type, but intermediate combinators can be
-/// used to combine different types into a single combinator.
-///
-/// ```rust
-/// use wax::{Glob, Program};
-///
-/// # fn fallible() -> Result<(), wax::BuildError> {
-/// let glob = Glob::new("**/*.txt")?;
-///
-/// // ...
-///
-/// #[rustfmt::skip]
-/// let any = wax::any([
-///     wax::any([glob]),
-///     wax::any([
-///         "**/*.pdf",
-///         "**/*.tex",
-///     ]),
-/// ])?;
-/// assert!(any.is_match("doc/lattice.tex"));
-/// # Ok(())
-/// # }
+impl Glob<'static> {
+    /// Constructs a [`Glob`] from a `'static` glob expression.
+    ///
+    /// This function is the same as [`Glob::new`], but accepts only `'static` glob expressions
+    /// and so never borrows from a caller's data. The resulting [`Glob`] can be stored in a global
+    /// via [`OnceLock`] and similar types, so that the expression is parsed and compiled only
+    /// once. See also [`LazyGlob`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the glob expression fails to build. See [`BuildError`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::OnceLock;
+    /// use wax::{Glob, Program};
+    ///
+    /// fn is_source(path: &str) -> bool {
+    ///     static GLOB: OnceLock<Glob<'static>> = OnceLock::new();
+    ///     GLOB.get_or_init(|| Glob::new_static("**/*.rs").unwrap())
+    ///         .is_match(path)
+    /// }
+    ///
+    /// assert!(is_source("src/lib.rs"));
+    /// ```
+    ///
+    /// [`BuildError`]: crate::BuildError
+    /// [`Glob`]: crate::Glob
+    /// [`Glob::new`]: crate::Glob::new
+    /// [`LazyGlob`]: crate::LazyGlob
+    /// [`OnceLock`]: std::sync::OnceLock
+    pub fn new_static(expression: &'static str) -> Result<Self, BuildError> {
+        Glob::new(expression)
+    }
+}
+
+impl Display for Glob<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.tree.as_ref().expression())
+    }
+}
+
+impl Eq for Glob<'_> {}
+
+impl FromStr for Glob<'static> {
+    type Err = BuildError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        Glob::new(expression).map(Glob::into_owned)
+    }
+}
+
+impl Hash for Glob<'_> {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.tree.as_ref().as_token().normalized().hash(state);
+    }
+}
+
+impl PartialEq for Glob<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.tree.as_ref().as_token().normalized() == other.tree.as_ref().as_token().normalized()
+    }
+}
+
+impl<'t> Pattern<'t> for Glob<'t> {
+    type Tokens = Tokenized<'t, ExpressionMetadata>;
+    type Error = Infallible;
+}
+
+impl<'t> Program<'t> for Glob<'t> {
+    fn is_match<'p>(&self, path: impl Into<CandidatePath<'p>>) -> bool {
+        let path = path.into();
+        self.program.is_match(path.as_ref())
+    }
+
+    fn matched<'p>(&self, path: &'p CandidatePath<'_>) -> Option<MatchedText<'p>> {
+        self.program.captures(path.as_ref()).map(|captures| {
+            let program = self.branch_program.get_or_init(|| {
+                encode::compile_branches::<Tokenized<_>>(self.tree.as_ref()).and_then(Result::ok)
+            });
+            MatchedText::from(captures).with_branch(self::matched_branch(program.as_ref(), path))
+        })
+    }
+
+    fn depth(&self) -> DepthVariance {
+        self.tree.as_ref().as_token().variance().into()
+    }
+
+    fn text(&self) -> TextVariance<'t> {
+        self.tree.as_ref().as_token().variance().into()
+    }
+
+    fn has_root(&self) -> When {
+        self.tree.as_ref().as_token().has_root()
+    }
+
+    fn is_exhaustive(&self) -> When {
+        self.tree.as_ref().as_token().is_exhaustive()
+    }
+}
+
+impl<'t> TryFrom<&'t str> for Glob<'t> {
+    type Error = BuildError;
+
+    fn try_from(expression: &'t str) -> Result<Self, Self::Error> {
+        Glob::new(expression)
+    }
+}
+
+/// Builder for [`Glob`]s with configurable limits and syntax.
+///
+/// [`Glob::new`] builds globs with default limits and syntax. `GlobBuilder` can be used to adjust
+/// these limits, such as when building globs from untrusted expressions, and to accept
+/// alternative syntax, such as back slash separators.
+///
+/// # Examples
+///
+/// Services that build globs from user input can set a size limit to reject expressions that are
+/// expensive to compile.
+///
+/// ```rust
+/// use wax::GlobBuilder;
+///
+/// let builder = GlobBuilder::new().size_limit(1024);
+/// assert!(builder.build("**/*.{md,txt}").is_ok());
+/// assert!(builder.build("<[a-z]:0,10000>").is_err());
 /// ```
 ///
-/// # Errors
+/// [`Glob`]: crate::Glob
+/// [`Glob::new`]: crate::Glob::new
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GlobBuilder {
+    size_limit: Option<usize>,
+    accepts_backslash_separators: bool,
+    tree_matches_hidden: bool,
+    requires_portable: bool,
+    allows_parent_dir: bool,
+    allows_alternation: bool,
+    allows_class: bool,
+    allows_repetition: bool,
+    separator: Option<char>,
+}
+
+impl GlobBuilder {
+    /// A limit on the estimated size of a compiled glob that is suitable for untrusted glob
+    /// expressions.
+    ///
+    /// See [`GlobBuilder::size_limit`].
+    ///
+    /// [`GlobBuilder::size_limit`]: crate::GlobBuilder::size_limit
+    pub const RECOMMENDED_SIZE_LIMIT: usize = 0x40000;
+
+    /// Constructs a `GlobBuilder` with default limits.
+    pub fn new() -> Self {
+        GlobBuilder {
+            size_limit: None,
+            accepts_backslash_separators: false,
+            tree_matches_hidden: true,
+            requires_portable: false,
+            allows_parent_dir: true,
+            allows_alternation: true,
+            allows_class: true,
+            allows_repetition: true,
+            separator: None,
+        }
+    }
+
+    /// Sets the limit on the estimated size of a compiled glob.
+    ///
+    /// The size of a glob is estimated before it is compiled by counting its tokens (and the
+    /// characters of its literals) with repetitions expanded by their upper bounds. For example,
+    /// `<ab:0,100>` has an estimated size of 200. Globs with an estimated size that exceeds this
+    /// limit fail to build (see [`BuildError::is_too_complex`]). By default, there is no limit,
+    /// as with [`Glob::new`]. [`RECOMMENDED_SIZE_LIMIT`] is suitable for untrusted glob
+    /// expressions.
+    ///
+    /// This limit is independent of and in addition to other limits on the size of compiled
+    /// programs and invariant text.
+    ///
+    /// [`BuildError::is_too_complex`]: crate::BuildError::is_too_complex
+    /// [`Glob::new`]: crate::Glob::new
+    /// [`RECOMMENDED_SIZE_LIMIT`]: crate::GlobBuilder::RECOMMENDED_SIZE_LIMIT
+    pub fn size_limit(self, limit: usize) -> Self {
+        GlobBuilder {
+            size_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Sets whether or not back slashes `\` may be interpreted as separators.
+    ///
+    /// Back slashes are escapes in glob expressions, so by default an expression like
+    /// `src\lib.rs` fails to build. When enabled, a back slash that is followed by a
+    /// meta-character is still interpreted as an escape, but is otherwise interpreted as a
+    /// separator. This is useful for expressions written by Windows users, but is ambiguous: for
+    /// example, `src\*.rs` escapes the zero-or-more wildcard and matches the literal file name
+    /// `src*.rs`. Use forward slashes `/` to separate components that begin with a
+    /// meta-character. Unicode property classes like `\p{L}` are not supported when enabled.
+    /// This is disabled by default.
+    ///
+    /// The expression of a built glob is retained as is, so the [`Display`] representation of
+    /// such a glob may contain back slash separators and cannot be built again without enabling
+    /// this option. Functions that construct a new expression, such as [`Glob::join`], replace
+    /// back slash separators with forward slashes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{GlobBuilder, Program};
+    ///
+    /// let builder = GlobBuilder::new().accept_backslash_separators(true);
+    /// let glob = builder.build(r"src\lib.rs").unwrap();
+    /// assert!(glob.is_match("src/lib.rs"));
+    ///
+    /// let glob = builder.build(r"src\{lib\}.rs").unwrap();
+    /// assert!(glob.is_match("src{lib}.rs"));
+    /// ```
+    ///
+    /// [`Display`]: std::fmt::Display
+    /// [`Glob::join`]: crate::Glob::join
+    pub fn accept_backslash_separators(self, accept: bool) -> Self {
+        GlobBuilder {
+            accepts_backslash_separators: accept,
+            ..self
+        }
+    }
+
+    /// Sets whether or not tree wildcards `**` match hidden components.
+    ///
+    /// Hidden components are those that begin with a dot `.`, such as `.git`. By default, tree
+    /// wildcards match any components, including hidden components. When disabled, tree wildcards
+    /// do not match any path with a hidden component in the text that they match. Other tokens
+    /// are unaffected, so hidden components can still be matched explicitly, as in `**/.git/**`
+    /// or `**/.*`.
+    ///
+    /// This affects only tree wildcards in the built glob's expression and is preserved when the
+    /// glob is combined with other patterns, such as via [`any`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{GlobBuilder, Program};
+    ///
+    /// let glob = GlobBuilder::new()
+    ///     .tree_matches_hidden(false)
+    ///     .build("**/*.md")
+    ///     .unwrap();
+    /// assert!(glob.is_match("doc/guide.md"));
+    /// assert!(!glob.is_match(".github/README.md"));
+    /// ```
+    ///
+    /// [`any`]: crate::any
+    pub fn tree_matches_hidden(self, matches: bool) -> Self {
+        GlobBuilder {
+            tree_matches_hidden: matches,
+            ..self
+        }
+    }
+
+    /// Sets whether or not globs must match the same paths on all supported platforms.
+    ///
+    /// When enabled, glob expressions fail to build if they can only match paths on some
+    /// platforms. Such expressions have literals with characters that cannot appear in file names
+    /// on Windows, namely `<`, `>`, `:`, `"`, `\`, `|`, `?`, `*`, and control characters, or have
+    /// Windows path prefixes like `C:`. Note that back slashes are separators on Windows. Other
+    /// platform differences, such as reserved file names, are not detected. This is disabled by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::GlobBuilder;
+    ///
+    /// let builder = GlobBuilder::new().require_portable(true);
+    /// assert!(builder.build("**/*.{md,txt}").is_ok());
+    /// assert!(builder.build("a|b.txt").is_err());
+    /// ```
+    pub fn require_portable(self, require: bool) -> Self {
+        GlobBuilder {
+            requires_portable: require,
+            ..self
+        }
+    }
+
+    /// Sets whether or not globs may have parent directory components `..`.
+    ///
+    /// When disabled, glob expressions with a `..` component fail to build, including within
+    /// alternatives and repetitions like `{..,src}/*` and components that can only match finite
+    /// texts like `[.][.]` and `<.:2>`. This prevents globs from user input from escaping a
+    /// directory tree. Components with other variant text are not detected: `..` can still be
+    /// matched by tokens like `*`, though `*` does not match `..` when walking directory trees.
+    /// This is enabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::GlobBuilder;
+    ///
+    /// let builder = GlobBuilder::new().allow_parent_dir(false);
+    /// assert!(builder.build("src/**/*.rs").is_ok());
+    /// assert!(builder.build("../secrets/*").is_err());
+    /// ```
+    pub fn allow_parent_dir(self, allow: bool) -> Self {
+        GlobBuilder {
+            allows_parent_dir: allow,
+            ..self
+        }
+    }
+
+    /// Sets whether or not globs may have alternations, such as `{a,b}`.
+    ///
+    /// When disabled, glob expressions with an alternation fail to build. Together with
+    /// [`allow_class`] and [`allow_repetition`], this can restrict globs from untrusted input to
+    /// simple wildcards. This is enabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::GlobBuilder;
+    ///
+    /// let builder = GlobBuilder::new()
+    ///     .allow_alternation(false)
+    ///     .allow_class(false)
+    ///     .allow_repetition(false);
+    /// assert!(builder.build("src/**/*.rs").is_ok());
+    /// assert!(builder.build("src/**/*.{rs,toml}").is_err());
+    /// assert!(builder.build("<[a-z]:1,>.txt").is_err());
+    /// ```
+    ///
+    /// [`allow_class`]: crate::GlobBuilder::allow_class
+    /// [`allow_repetition`]: crate::GlobBuilder::allow_repetition
+    pub fn allow_alternation(self, allow: bool) -> Self {
+        GlobBuilder {
+            allows_alternation: allow,
+            ..self
+        }
+    }
+
+    /// Sets whether or not globs may have character classes, such as `[a-z]` or `\p{L}`.
+    ///
+    /// When disabled, glob expressions with a character class (including a Unicode property
+    /// outside of a class) fail to build. See [`allow_alternation`]. This is enabled by default.
+    ///
+    /// [`allow_alternation`]: crate::GlobBuilder::allow_alternation
+    pub fn allow_class(self, allow: bool) -> Self {
+        GlobBuilder {
+            allows_class: allow,
+            ..self
+        }
+    }
+
+    /// Sets whether or not globs may have repetitions, such as `<a:1,>`.
+    ///
+    /// When disabled, glob expressions with a repetition fail to build. See
+    /// [`allow_alternation`]. This is enabled by default.
+    ///
+    /// [`allow_alternation`]: crate::GlobBuilder::allow_alternation
+    pub fn allow_repetition(self, allow: bool) -> Self {
+        GlobBuilder {
+            allows_repetition: allow,
+            ..self
+        }
+    }
+
+    /// Sets the character that is interpreted as a separator instead of forward slash `/`.
+    ///
+    /// This allows glob syntax to be used to match hierarchical text other than paths, such as
+    /// `.`-separated keys or `:`-separated module paths. The separator delimits components and
+    /// tree wildcards `**`, and forward slash `/` is then an ordinary literal character. The
+    /// separator can be escaped with a back slash `\` to match it literally. Back slash
+    /// separators and path prefixes are not recognized.
+    ///
+    /// The separator is part of the glob: it is preserved by functions that derive globs, such as
+    /// [`Glob::join`] and [`Glob::partition`], and by combinators like [`Any`]. Globs with
+    /// different separators are never equal. Globs built with a custom separator are intended for
+    /// matching text via [`Program`] APIs and should not be used to walk directory trees.
+    ///
+    /// # Errors
+    ///
+    /// If the separator is a meta-character like `*` or `{`, then building a glob fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{GlobBuilder, Program};
+    ///
+    /// let glob = GlobBuilder::new().separator('.').build("server.**.port").unwrap();
+    /// assert!(glob.is_match("server.http.port"));
+    /// assert!(glob.is_match("server.port"));
+    /// assert!(!glob.is_match("server/port"));
+    /// ```
+    ///
+    /// [`Any`]: crate::Any
+    /// [`Glob::join`]: crate::Glob::join
+    /// [`Glob::partition`]: crate::Glob::partition
+    /// [`Program`]: crate::Program
+    pub fn separator(self, separator: char) -> Self {
+        GlobBuilder {
+            separator: Some(separator),
+            ..self
+        }
+    }
+
+    /// Constructs a [`Glob`] from a glob expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the glob expression fails to build, including when the estimated size
+    /// of the glob exceeds the configured limit. See [`BuildError`].
+    ///
+    /// [`BuildError`]: crate::BuildError
+    /// [`Glob`]: crate::Glob
+    pub fn build<'t>(&self, expression: &'t str) -> Result<Glob<'t>, BuildError> {
+        if let Some(separator) = self.separator.filter(|&x| is_meta_character(x)) {
+            return Err(BuildErrorKind::UnsupportedSeparator(separator).into());
+        }
+        let tree = rule::check(token::parse_with_options(
+            expression,
+            ParseOptions {
+                accepts_backslash_separators: self.accepts_backslash_separators,
+                tree_excludes_hidden: !self.tree_matches_hidden,
+                separator: self.separator,
+                ..Default::default()
+            },
+        )?)?;
+        if self.requires_portable {
+            rule::check_portable(tree.as_ref())?;
+        }
+        if !self.allows_parent_dir {
+            rule::check_parent_directory(tree.as_ref())?;
+        }
+        rule::check_syntax(
+            tree.as_ref(),
+            self.allows_alternation,
+            self.allows_class,
+            self.allows_repetition,
+        )?;
+        let program = match self.size_limit {
+            Some(limit) => encode::compile_with_size_limit::<Tokenized<_>>(tree.as_ref(), limit)?,
+            None => encode::compile::<Tokenized<_>>(tree.as_ref())?,
+        };
+        Ok(Glob::from_tree_and_program(tree, program))
+    }
+}
+
+impl Default for GlobBuilder {
+    fn default() -> Self {
+        GlobBuilder::new()
+    }
+}
+
+/// A [`Glob`] that is built lazily from a `'static` glob expression.
 ///
-/// Returns an error if any of the inputs fail to build. If the inputs are a compiled [`Program`]
-/// type such as [`Glob`], then this only occurs if the compiled program is too large.
+/// `LazyGlob` can be constructed in `const` contexts and so can be used in `static` items. The
+/// glob expression is parsed and compiled at most once, when the `LazyGlob` is first accessed.
+/// This avoids repeatedly building the same [`Glob`] in functions that are called often.
+///
+/// `LazyGlob` dereferences to [`Glob`]. **Dereferencing panics if the glob expression fails to
+/// build.** Use [`LazyGlob::try_get`] to handle such errors.
+///
+/// # Examples
+///
+/// ```rust
+/// use wax::{LazyGlob, Program};
+///
+/// static SOURCE: LazyGlob = LazyGlob::new("**/*.{rs,toml}");
+///
+/// assert!(SOURCE.is_match("src/lib.rs"));
+/// assert!(!SOURCE.is_match("README.md"));
+/// ```
 ///
-/// [`Any`]: crate::Any
 /// [`Glob`]: crate::Glob
-/// [`IntoIterator`]: std::iter::IntoIterator
-/// [`Pattern`]: crate::Pattern
-/// [`Program`]: crate::Program
-pub fn any<'t, I>(patterns: I) -> Result<Any<'t>, BuildError>
-where
-    I: IntoIterator,
-    I::Item: Pattern<'t>,
-{
-    let tree = Checked::any(
-        patterns
-            .into_iter()
-            .map(TryInto::try_into)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(Into::into)?,
-    );
-    let program = Any::compile(tree.as_ref())?;
-    Ok(Any { tree, program })
+/// [`LazyGlob::try_get`]: crate::LazyGlob::try_get
+#[derive(Debug)]
+pub struct LazyGlob {
+    expression: &'static str,
+    glob: OnceLock<Result<Glob<'static>, BuildError>>,
 }
 
-// TODO: This function blindly escapes meta-characters, even if they are already escaped. Ignore
-//       escaped meta-characters in the input.
-/// Escapes text as a literal glob expression.
-///
-/// This function escapes any and all meta-characters in the given string, such that all text is
-/// interpreted as a literal or separator when read as a glob expression.
-///
-/// # Examples
-///
-/// This function can be used to escape opaque strings, such as a string obtained from a user that
-/// must be interpreted literally.
-///
-/// ```rust
-/// use wax::Glob;
-///
-/// // An opaque file name that this code does not construct.
-/// let name: String = {
-///     /* ... */
-///     # String::from("file.txt")
-/// };
-///
-/// // Do not allow patterns in `name`.
-/// let expression = format!("{}{}", "**/", wax::escape(&name));
-/// if let Ok(glob) = Glob::new(&expression) { /* ... */ }
-/// ```
-///
-/// Sometimes part of a path contains numerous meta-characters. This function can be used to
-/// reliably escape them while making the unescaped part of the expression a bit easier to read.
-///
-/// ```rust
-/// use wax::Glob;
-///
-/// let expression = format!("{}{}", "logs/**/", wax::escape("ingest[01](L).txt"));
-/// let glob = Glob::new(&expression).unwrap();
-/// ```
-// It is possible to call this function using a mutable reference, which may appear to mutate the
-// parameter in place.
-#[must_use]
-pub fn escape(unescaped: &str) -> Cow<str> {
-    const ESCAPE: char = '\\';
+impl LazyGlob {
+    /// Constructs a `LazyGlob` from a `'static` glob expression.
+    ///
+    /// The glob expression is **not** built by this function.
+    pub const fn new(expression: &'static str) -> Self {
+        LazyGlob {
+            expression,
+            glob: OnceLock::new(),
+        }
+    }
+
+    /// Gets the [`Glob`], building it if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the glob expression fails to build. See [`BuildError`].
+    ///
+    /// [`BuildError`]: crate::BuildError
+    /// [`Glob`]: crate::Glob
+    pub fn try_get(&self) -> Result<&Glob<'static>, &BuildError> {
+        self.glob
+            .get_or_init(|| Glob::new_static(self.expression))
+            .as_ref()
+    }
+
+    /// Gets the glob expression.
+    pub fn expression(&self) -> &'static str {
+        self.expression
+    }
+}
+
+impl Deref for LazyGlob {
+    type Target = Glob<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        self.try_get().unwrap_or_else(|error| {
+            panic!(
+                "failed to build lazy glob `{}`: {}",
+                self.expression, error,
+            )
+        })
+    }
+}
+
+/// Combinator that matches any of its component [`Program`]s.
+///
+/// An instance of `Any` is constructed using the [`any`] function, which combines multiple
+/// [`Program`]s for more ergonomic and efficient matching.
+///
+/// [`any`]: crate::any
+/// [`Program`]: crate::Program
+#[derive(Clone, Debug)]
+pub struct Any<'t> {
+    tree: Checked<Token<'t, ()>>,
+    program: Regex,
+    // Each component program is a branch. See `MatchedText::matched_branch`.
+    branch_program: OnceLock<Option<Regex>>,
+}
+
+impl<'t> Any<'t> {
+    fn compile(token: &Token<'t, ()>) -> Result<Regex, CompileError> {
+        encode::compile::<Token<_>>(token)
+    }
+}
+
+impl<'t> Pattern<'t> for Any<'t> {
+    type Tokens = Token<'t, ()>;
+    type Error = Infallible;
+}
+
+impl<'t> Program<'t> for Any<'t> {
+    fn is_match<'p>(&self, path: impl Into<CandidatePath<'p>>) -> bool {
+        let path = path.into();
+        self.program.is_match(path.as_ref())
+    }
+
+    fn matched<'p>(&self, path: &'p CandidatePath<'_>) -> Option<MatchedText<'p>> {
+        self.program.captures(path.as_ref()).map(|captures| {
+            let program = self.branch_program.get_or_init(|| {
+                encode::compile_branches::<Token<_>>(self.tree.as_ref()).and_then(Result::ok)
+            });
+            MatchedText::from(captures).with_branch(self::matched_branch(program.as_ref(), path))
+        })
+    }
+
+    fn depth(&self) -> DepthVariance {
+        self.tree.as_ref().as_token().variance().into()
+    }
+
+    fn text(&self) -> TextVariance<'t> {
+        self.tree.as_ref().as_token().variance().into()
+    }
+
+    fn has_root(&self) -> When {
+        self.tree.as_ref().as_token().has_root()
+    }
+
+    fn is_exhaustive(&self) -> When {
+        self.tree.as_ref().as_token().is_exhaustive()
+    }
+}
+
+// TODO: It may be useful to use dynamic dispatch via trait objects instead. This would allow for a
+//       variety of types to be composed in an `any` call and would be especially useful if
+//       additional combinators are introduced.
+/// Constructs a combinator that matches if any of its input [`Pattern`]s match.
+///
+/// This function accepts an [`IntoIterator`] with items that implement [`Pattern`], such as
+/// [`Glob`] and `&str`. The output [`Any`] implements [`Program`] by matching its component
+/// [`Program`]s. [`Any`] is often more ergonomic and efficient than matching individually against
+/// multiple [`Program`]s.
+///
+/// [`Any`] groups all captures and therefore only exposes the complete text of a match. It is not
+/// possible to index a particular capturing token in the component patterns. [`Any`] only
+/// supports logical matching and cannot be used to semantically match (walk) a directory tree.
+///
+/// # Examples
+///
+/// To match a path against multiple patterns, the patterns can first be combined into an [`Any`].
+///
+/// ```rust
+/// use wax::{Glob, Program};
+///
+/// let any = wax::any([
+///     "src/**/*.rs",
+///     "tests/**/*.rs",
+///     "doc/**/*.md",
+///     "pkg/**/PKGBUILD",
+/// ])
+/// .unwrap();
+/// assert!(any.is_match("src/lib.rs"));
+/// ```
+///
+/// [`Glob`]s and other compiled [`Program`]s can also be composed into an [`Any`].
+///
+/// ```rust
+/// use wax::{Glob, Program};
+///
+/// let red = Glob::new("**/red/**/*.txt").unwrap();
+/// let blue = Glob::new("**/*blue*.txt").unwrap();
+/// assert!(wax::any([red, blue]).unwrap().is_match("red/potion.txt"));
+/// ```
+///
+/// This function can only combine patterns of the same type, but intermediate combinators can be
+/// used to combine different types into a single combinator.
+///
+/// ```rust
+/// use wax::{Glob, Program};
+///
+/// # fn fallible() -> Result<(), wax::BuildError> {
+/// let glob = Glob::new("**/*.txt")?;
+///
+/// // ...
+///
+/// #[rustfmt::skip]
+/// let any = wax::any([
+///     wax::any([glob]),
+///     wax::any([
+///         "**/*.pdf",
+///         "**/*.tex",
+///     ]),
+/// ])?;
+/// assert!(any.is_match("doc/lattice.tex"));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if any of the inputs fail to build. If the inputs are a compiled [`Program`]
+/// type such as [`Glob`], then this only occurs if the compiled program is too large.
+///
+/// [`Any`]: crate::Any
+/// [`Glob`]: crate::Glob
+/// [`IntoIterator`]: std::iter::IntoIterator
+/// [`Pattern`]: crate::Pattern
+/// [`Program`]: crate::Program
+pub fn any<'t, I>(patterns: I) -> Result<Any<'t>, BuildError>
+where
+    I: IntoIterator,
+    I::Item: Pattern<'t>,
+{
+    let tree = Checked::any(
+        patterns
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)?,
+    );
+    let program = Any::compile(tree.as_ref())?;
+    Ok(Any {
+        tree,
+        program,
+        branch_program: OnceLock::new(),
+    })
+}
+
+/// Combinator that matches all of its component [`Program`]s.
+///
+/// An instance of `All` is constructed using the [`all`] function, which combines multiple
+/// [`Program`]s such that a path matches only if it matches every component [`Program`].
+///
+/// A conjunction cannot be expressed by a single glob expression, so `All` is not a [`Pattern`]
+/// and does not implement [`Program`]. Instead, it provides the same matching and query functions.
+///
+/// [`all`]: crate::all
+/// [`Pattern`]: crate::Pattern
+/// [`Program`]: crate::Program
+#[derive(Clone, Debug)]
+pub struct All<'t> {
+    trees: Vec<Checked<Token<'t, ()>>>,
+    programs: Vec<Regex>,
+}
+
+impl<'t> All<'t> {
+    fn compile(token: &Token<'t, ()>) -> Result<Regex, CompileError> {
+        encode::compile::<Token<_>>(token)
+    }
+
+    // Matched paths are in the intersection of the depths of the component programs. If these
+    // depths are disjoint, then no path can match all of the component programs.
+    fn intersect_depths(trees: &[Checked<Token<'t, ()>>]) -> Option<NaturalRange> {
+        trees
+            .iter()
+            .map(|tree| {
+                tree.as_ref()
+                    .as_token()
+                    .variance::<Depth>()
+                    .map_invariant(usize::from)
+            })
+            .try_fold(
+                NaturalRange::from_closed_and_open(0, None),
+                NaturalRange::intersection,
+            )
+    }
+
+    /// Returns `true` if the [candidate path][`CandidatePath`] matches all component programs.
+    ///
+    /// See [`Program::is_match`].
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`Program::is_match`]: crate::Program::is_match
+    pub fn is_match<'p>(&self, path: impl Into<CandidatePath<'p>>) -> bool {
+        let path = path.into();
+        self.programs
+            .iter()
+            .all(|program| program.is_match(path.as_ref()))
+    }
+
+    /// Gets the [matched text][`MatchedText`] of the first component program in a
+    /// [`CandidatePath`], if all component programs match.
+    ///
+    /// See [`Program::matched`].
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`MatchedText`]: crate::MatchedText
+    /// [`Program::matched`]: crate::Program::matched
+    pub fn matched<'p>(&self, path: &'p CandidatePath<'_>) -> Option<MatchedText<'p>> {
+        let (first, rest) = self.programs.split_first()?;
+        if rest.iter().all(|program| program.is_match(path.as_ref())) {
+            first.captures(path.as_ref()).map(From::from)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Gets the depth variance of the paths that match all component programs.
+    ///
+    /// See [`Program::depth`].
+    ///
+    /// [`Program::depth`]: crate::Program::depth
+    pub fn depth(&self) -> DepthVariance {
+        All::intersect_depths(&self.trees)
+            .expect("disjoint depths of component programs in `All`")
+            .map_invariant(Depth::from)
+            .into()
+    }
+
+    /// Gets the text variance of the paths that match all component programs.
+    ///
+    /// See [`Program::text`].
+    ///
+    /// [`Program::text`]: crate::Program::text
+    pub fn text(&self) -> TextVariance<'t> {
+        // If any component program has invariant text, then it is the only text that can match.
+        self.trees
+            .iter()
+            .map(|tree| TextVariance::from(tree.as_ref().as_token().variance::<Text>()))
+            .find(TextVariance::is_invariant)
+            .unwrap_or(TextVariance::Variant(()))
+    }
+
+    /// Describes when all component programs match candidate paths with a root.
+    ///
+    /// See [`Program::has_root`].
+    ///
+    /// [`Program::has_root`]: crate::Program::has_root
+    pub fn has_root(&self) -> When {
+        // Matched paths are a subset of the paths matched by each component program, so any
+        // certainty in a component program applies to all matched paths.
+        self.trees
+            .iter()
+            .map(|tree| tree.as_ref().as_token().has_root())
+            .find(|has_root| !has_root.is_sometimes())
+            .unwrap_or(When::Sometimes)
+    }
+
+    /// Describes when all component programs match candidate paths exhaustively.
+    ///
+    /// See [`Program::is_exhaustive`].
+    ///
+    /// [`Program::is_exhaustive`]: crate::Program::is_exhaustive
+    pub fn is_exhaustive(&self) -> When {
+        self.trees
+            .iter()
+            .map(|tree| tree.as_ref().as_token().is_exhaustive())
+            .reduce(When::and)
+            .expect("no component programs in `All`")
+    }
+}
+
+/// Constructs a combinator that matches if all of its input [`Pattern`]s match.
+///
+/// This function accepts an [`IntoIterator`] with items that implement [`Pattern`], such as
+/// [`Glob`] and `&str`. The output [`All`] matches its component [`Program`]s and can be used to
+/// layer constraints on paths. If there are no input [`Pattern`]s, then the output [`All`]
+/// behaves like the tree wildcard `**`.
+///
+/// Unlike [`Any`], the component [`Program`]s of an [`All`] are not compiled into a single
+/// [`Program`], and so an [`All`] is not a [`Pattern`] and cannot be composed into other
+/// combinators. The [matched text][`All::matched`] of an [`All`] is that of its first component
+/// [`Program`], so captures are indexed as they are in the first input [`Pattern`].
+///
+/// Query functions like [`All::depth`] describe the paths that match all component [`Program`]s.
+/// For example, if any component [`Program`] has invariant text, then so does the [`All`].
+///
+/// # Examples
+///
+/// To match a path against multiple constraints, the patterns can be combined into an [`All`].
+///
+/// ```rust
+/// let all = wax::all(["src/**", "**/*.rs"]).unwrap();
+/// assert!(all.is_match("src/lib.rs"));
+/// assert!(!all.is_match("src/lib.c"));
+/// assert!(!all.is_match("tests/walk.rs"));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if any of the inputs fail to build. If the inputs are a compiled [`Program`]
+/// type such as [`Glob`], then this only occurs if a compiled program is too large.
+///
+/// Returns an error if the inputs match paths with disjoint depths, such as `*` and `*/*`, as no
+/// path can match all of them (see [`BuildError::is_unsatisfiable`]).
+///
+/// [`All`]: crate::All
+/// [`Any`]: crate::Any
+/// [`BuildError::is_unsatisfiable`]: crate::BuildError::is_unsatisfiable
+/// [`Glob`]: crate::Glob
+/// [`IntoIterator`]: std::iter::IntoIterator
+/// [`Pattern`]: crate::Pattern
+/// [`All::depth`]: crate::All::depth
+/// [`All::matched`]: crate::All::matched
+/// [`Program`]: crate::Program
+pub fn all<'t, I>(patterns: I) -> Result<All<'t>, BuildError>
+where
+    I: IntoIterator,
+    I::Item: Pattern<'t>,
+{
+    let mut trees = patterns
+        .into_iter()
+        .map(|pattern| pattern.try_into().map(Checked::into_unannotated))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Into::into)?;
+    if trees.is_empty() {
+        trees.push(Checked::from(Glob::tree()).into_unannotated());
+    }
+    if All::intersect_depths(&trees).is_none() {
+        return Err(BuildErrorKind::UnsatisfiableConjunction.into());
+    }
+    let programs = trees
+        .iter()
+        .map(|tree| All::compile(tree.as_ref()))
+        .collect::<Result<_, _>>()?;
+    Ok(All { trees, programs })
+}
+
+/// Combinator that matches a [`Glob`] but not a negated [`Glob`].
+///
+/// An instance of `AndNot` is constructed using [`Glob::and_not`]. Paths match if they match the
+/// first [`Glob`] and do **not** match the negated [`Glob`]. The [matched
+/// text][`AndNot::matched`] of an `AndNot` is that of the first [`Glob`].
+///
+/// Query functions like [`AndNot::depth`] describe the first [`Glob`], as the paths that match an
+/// `AndNot` are a subset of the paths that match it. Negations cannot be expressed by glob
+/// expressions, so `AndNot` is not a [`Pattern`], does not implement [`Program`], and cannot be
+/// composed into other combinators. Instead, it provides the same matching and query functions.
+///
+/// [`AndNot::depth`]: crate::AndNot::depth
+/// [`AndNot::matched`]: crate::AndNot::matched
+/// [`Glob`]: crate::Glob
+/// [`Glob::and_not`]: crate::Glob::and_not
+/// [`Pattern`]: crate::Pattern
+/// [`Program`]: crate::Program
+#[derive(Clone, Debug)]
+pub struct AndNot<'t> {
+    glob: Glob<'t>,
+    negation: Glob<'t>,
+}
+
+impl<'t> AndNot<'t> {
+    /// Clones any borrowed data into an owning instance.
+    pub fn into_owned(self) -> AndNot<'static> {
+        let AndNot { glob, negation } = self;
+        AndNot {
+            glob: glob.into_owned(),
+            negation: negation.into_owned(),
+        }
+    }
+
+    /// Returns `true` if the [candidate path][`CandidatePath`] matches the [`Glob`] and does not
+    /// match the negated [`Glob`].
+    ///
+    /// See [`Program::is_match`].
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`Glob`]: crate::Glob
+    /// [`Program::is_match`]: crate::Program::is_match
+    pub fn is_match<'p>(&self, path: impl Into<CandidatePath<'p>>) -> bool {
+        let path = path.into();
+        self.glob.program.is_match(path.as_ref()) && !self.negation.program.is_match(path.as_ref())
+    }
+
+    /// Gets the [matched text][`MatchedText`] of the [`Glob`] in a [`CandidatePath`], if the
+    /// path does not match the negated [`Glob`].
+    ///
+    /// See [`Program::matched`].
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`Glob`]: crate::Glob
+    /// [`MatchedText`]: crate::MatchedText
+    /// [`Program::matched`]: crate::Program::matched
+    pub fn matched<'p>(&self, path: &'p CandidatePath<'_>) -> Option<MatchedText<'p>> {
+        if self.negation.program.is_match(path.as_ref()) {
+            None
+        }
+        else {
+            self.glob.matched(path)
+        }
+    }
+
+    /// Gets the depth variance of the [`Glob`].
+    ///
+    /// See [`Program::depth`].
+    ///
+    /// [`Glob`]: crate::Glob
+    /// [`Program::depth`]: crate::Program::depth
+    pub fn depth(&self) -> DepthVariance {
+        self.glob.depth()
+    }
+
+    /// Gets the text variance of the [`Glob`].
+    ///
+    /// See [`Program::text`].
+    ///
+    /// [`Glob`]: crate::Glob
+    /// [`Program::text`]: crate::Program::text
+    pub fn text(&self) -> TextVariance<'t> {
+        self.glob.text()
+    }
+
+    /// Describes when the [`Glob`] matches candidate paths with a root.
+    ///
+    /// See [`Program::has_root`].
+    ///
+    /// [`Glob`]: crate::Glob
+    /// [`Program::has_root`]: crate::Program::has_root
+    pub fn has_root(&self) -> When {
+        self.glob.has_root()
+    }
+
+    /// Describes when paths beneath a matched path also match.
+    ///
+    /// This is never [`Always`], because the negated [`Glob`] may discard paths beneath a match.
+    /// See [`Program::is_exhaustive`].
+    ///
+    /// [`Always`]: crate::query::When::Always
+    /// [`Glob`]: crate::Glob
+    /// [`Program::is_exhaustive`]: crate::Program::is_exhaustive
+    pub fn is_exhaustive(&self) -> When {
+        // Even if the glob is exhaustive, the negation may discard paths beneath a match.
+        self.glob.is_exhaustive().and(When::Sometimes)
+    }
+}
+
+/// Set of [`Pattern`]s that are matched against paths in a single pass.
+///
+/// `GlobSet` compiles many [`Pattern`]s into one program and reports **which** of them match a
+/// path. This is much more efficient than matching each [`Program`] individually when classifying
+/// paths against many patterns. Unlike [`Any`], `GlobSet` does not expose [matched
+/// text][`MatchedText`].
+///
+/// # Examples
+///
+/// ```rust
+/// use wax::GlobSet;
+///
+/// let set = GlobSet::new(["**/*.rs", "src/**", "**/*.md"]).unwrap();
+/// assert_eq!(set.matches("src/lib.rs"), vec![0, 1]);
+/// assert!(set.matches("LICENSE").is_empty());
+/// ```
+///
+/// [`Any`]: crate::Any
+/// [`MatchedText`]: crate::MatchedText
+/// [`Pattern`]: crate::Pattern
+/// [`Program`]: crate::Program
+#[derive(Clone, Debug)]
+pub struct GlobSet {
+    program: RegexSet,
+}
+
+impl GlobSet {
+    /// Constructs a `GlobSet` from [`Pattern`]s.
+    ///
+    /// This function accepts an [`IntoIterator`] with items that implement [`Pattern`], such as
+    /// [`Glob`] and `&str`. The index of each [`Pattern`] in the input is its index in the set.
+    ///
+    /// As with [`Glob::new`], there is no limit on the estimated size of the set. Use
+    /// [`GlobSet::with_size_limit`] when building sets from untrusted expressions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the inputs fail to build or if the compiled set is too large.
+    ///
+    /// [`Glob`]: crate::Glob
+    /// [`Glob::new`]: crate::Glob::new
+    /// [`GlobSet::with_size_limit`]: crate::GlobSet::with_size_limit
+    /// [`IntoIterator`]: std::iter::IntoIterator
+    /// [`Pattern`]: crate::Pattern
+    pub fn new<'t, I>(patterns: I) -> Result<Self, BuildError>
+    where
+        I: IntoIterator,
+        I::Item: Pattern<'t>,
+    {
+        let trees = GlobSet::trees(patterns)?;
+        let program = encode::compile_set::<<I::Item as Pattern<'t>>::Tokens, _>(
+            trees.iter().map(Checked::as_ref),
+        )?;
+        Ok(GlobSet { program })
+    }
+
+    /// Constructs a `GlobSet` from [`Pattern`]s with a limit on its estimated size.
+    ///
+    /// This function is the same as [`GlobSet::new`], but fails if the sum of the estimated sizes
+    /// of the [`Pattern`]s exceeds the given limit. Sizes are estimated as with
+    /// [`GlobBuilder::size_limit`] and [`GlobBuilder::RECOMMENDED_SIZE_LIMIT`] is also suitable
+    /// for sets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the inputs fail to build or if the compiled set is too large.
+    /// If the estimated size of the set exceeds the limit, then
+    /// [`BuildError::is_too_complex`] returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use wax::{GlobBuilder, GlobSet};
+    ///
+    /// let limit = GlobBuilder::RECOMMENDED_SIZE_LIMIT;
+    /// assert!(GlobSet::with_size_limit(["**/*.rs", "src/**"], limit).is_ok());
+    /// assert!(
+    ///     GlobSet::with_size_limit(["**/*.rs", "<[a-z]:0,1000000>"], limit)
+    ///         .unwrap_err()
+    ///         .is_too_complex()
+    /// );
+    /// ```
+    ///
+    /// [`BuildError::is_too_complex`]: crate::BuildError::is_too_complex
+    /// [`GlobBuilder::RECOMMENDED_SIZE_LIMIT`]: crate::GlobBuilder::RECOMMENDED_SIZE_LIMIT
+    /// [`GlobBuilder::size_limit`]: crate::GlobBuilder::size_limit
+    /// [`GlobSet::new`]: crate::GlobSet::new
+    /// [`Pattern`]: crate::Pattern
+    pub fn with_size_limit<'t, I>(patterns: I, limit: usize) -> Result<Self, BuildError>
+    where
+        I: IntoIterator,
+        I::Item: Pattern<'t>,
+    {
+        let trees = GlobSet::trees(patterns)?;
+        let program = encode::compile_set_with_size_limit::<<I::Item as Pattern<'t>>::Tokens, _>(
+            trees.iter().map(Checked::as_ref),
+            limit,
+        )?;
+        Ok(GlobSet { program })
+    }
+
+    fn trees<'t, I>(
+        patterns: I,
+    ) -> Result<Vec<Checked<<I::Item as Pattern<'t>>::Tokens>>, BuildError>
+    where
+        I: IntoIterator,
+        I::Item: Pattern<'t>,
+    {
+        patterns
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Gets the indices of the [`Pattern`]s in the set that match the [candidate
+    /// path][`CandidatePath`].
+    ///
+    /// Indices are yielded in ascending order.
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`Pattern`]: crate::Pattern
+    pub fn matches<'p>(&self, path: impl Into<CandidatePath<'p>>) -> Vec<usize> {
+        let path = path.into();
+        self.program.matches(path.as_ref()).into_iter().collect()
+    }
+
+    /// Returns `true` if any [`Pattern`] in the set matches the [candidate path][`CandidatePath`].
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`Pattern`]: crate::Pattern
+    pub fn is_match<'p>(&self, path: impl Into<CandidatePath<'p>>) -> bool {
+        let path = path.into();
+        self.program.is_match(path.as_ref())
+    }
+
+    /// Gets the number of [`Pattern`]s in the set.
+    ///
+    /// [`Pattern`]: crate::Pattern
+    pub fn len(&self) -> usize {
+        self.program.len()
+    }
+
+    /// Returns `true` if there are no [`Pattern`]s in the set.
+    ///
+    /// [`Pattern`]: crate::Pattern
+    pub fn is_empty(&self) -> bool {
+        self.program.is_empty()
+    }
+}
+
+/// A glob compiled into a dense DFA that detects matches.
+///
+/// `DfaGlob` is constructed via [`Glob::compile_dfa`] and only detects whether or not a
+/// [candidate path][`CandidatePath`] matches: it does not implement [`Program`] and cannot
+/// capture [matched text][`MatchedText`]. The DFA is shared, so cloning a `DfaGlob` does not
+/// clone it.
+///
+/// [`CandidatePath`]: crate::CandidatePath
+/// [`Glob::compile_dfa`]: crate::Glob::compile_dfa
+/// [`MatchedText`]: crate::MatchedText
+/// [`Program`]: crate::Program
+#[cfg(feature = "dfa")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dfa")))]
+#[derive(Clone, Debug)]
+pub struct DfaGlob {
+    dfa: Arc<DFA<Vec<u32>>>,
+}
+
+#[cfg(feature = "dfa")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dfa")))]
+impl DfaGlob {
+    /// Returns `true` if the [candidate path][`CandidatePath`] matches the glob.
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    pub fn is_match<'p>(&self, path: impl Into<CandidatePath<'p>>) -> bool {
+        let path = path.into();
+        let input = Input::new(path.as_ref())
+            .anchored(Anchored::Yes)
+            .earliest(true);
+        self.dfa
+            .try_search_fwd(&input)
+            .expect("failed to search DFA")
+            .is_some()
+    }
+
+    /// Gets the number of bytes of heap memory used by the DFA.
+    pub fn memory_usage(&self) -> usize {
+        self.dfa.memory_usage()
+    }
+}
+
+/// Matches a glob expression against a single file name, like `fnmatch`.
+///
+/// The glob expression must describe a single component: separators `/` and tree wildcards `**`
+/// are rejected. Wildcards like `*` and `?` never match separators, so the expression can only
+/// match a name with no separators. This function builds the glob expression for each call, so
+/// [`Glob`] should be preferred when matching the same expression against many names.
+///
+/// # Errors
+///
+/// Returns an error if the glob expression fails to build or has a component boundary, such as a
+/// separator `/` or tree wildcard `**`. See [`BuildError`].
+///
+/// # Examples
+///
+/// ```rust
+/// assert!(wax::fnmatch("*.{md,txt}", "README.md").unwrap());
+/// assert!(!wax::fnmatch("*.md", "doc/guide.md").unwrap());
+/// assert!(wax::fnmatch("**/*.md", "README.md").is_err());
+/// ```
+///
+/// [`BuildError`]: crate::BuildError
+/// [`Glob`]: crate::Glob
+pub fn fnmatch(expression: &str, name: &str) -> Result<bool, BuildError> {
+    let tree = parse_and_check(expression)?;
+    rule::check_component(tree.as_ref())?;
+    let program = encode::compile::<Tokenized<_>>(tree.as_ref())?;
+    Ok(program.is_match(CandidatePath::from(name).as_ref()))
+}
+
+// TODO: This function blindly escapes meta-characters, even if they are already escaped. Ignore
+//       escaped meta-characters in the input.
+/// Escapes text as a literal glob expression.
+///
+/// This function escapes any and all meta-characters in the given string (including back
+/// slashes `\`), such that all text is interpreted as a literal or separator when read as a glob
+/// expression. Separators `/` are not escaped, so the components of an escaped path are preserved
+/// and a glob built from the escaped text matches exactly that text.
+///
+/// # Examples
+///
+/// This function can be used to escape opaque strings, such as a string obtained from a user that
+/// must be interpreted literally.
+///
+/// ```rust
+/// use wax::Glob;
+///
+/// // An opaque file name that this code does not construct.
+/// let name: String = {
+///     /* ... */
+///     # String::from("file.txt")
+/// };
+///
+/// // Do not allow patterns in `name`.
+/// let expression = format!("{}{}", "**/", wax::escape(&name));
+/// if let Ok(glob) = Glob::new(&expression) { /* ... */ }
+/// ```
+///
+/// Sometimes part of a path contains numerous meta-characters. This function can be used to
+/// reliably escape them while making the unescaped part of the expression a bit easier to read.
+///
+/// ```rust
+/// use wax::Glob;
+///
+/// let expression = format!("{}{}", "logs/**/", wax::escape("ingest[01](L).txt"));
+/// let glob = Glob::new(&expression).unwrap();
+/// ```
+// It is possible to call this function using a mutable reference, which may appear to mutate the
+// parameter in place.
+#[must_use]
+pub fn escape(unescaped: &str) -> Cow<'_, str> {
+    const ESCAPE: char = '\\';
+
+    if unescaped.chars().any(is_meta_character) {
+        let mut escaped = String::new();
+        for x in unescaped.chars() {
+            if is_meta_character(x) {
+                escaped.push(ESCAPE);
+            }
+            escaped.push(x);
+        }
+        escaped.into()
+    }
+    else {
+        unescaped.into()
+    }
+}
+
+// TODO: Is it possible for `:` and `,` to be contextual meta-characters?
+/// Returns `true` if the given character is a meta-character.
+///
+/// This function does **not** return `true` for contextual meta-characters that may only be
+/// escaped in particular contexts, such as hyphens `-` in character class expressions. To detect
+/// these characters, use [`is_contextual_meta_character`].
+///
+/// [`is_contextual_meta_character`]: crate::is_contextual_meta_character
+pub const fn is_meta_character(x: char) -> bool {
+    matches!(
+        x,
+        '?' | '*' | '$' | ':' | '<' | '>' | '(' | ')' | '[' | ']' | '{' | '}' | ',' | '\\'
+    )
+}
+
+/// Returns `true` if the given character is a contextual meta-character.
+///
+/// Contextual meta-characters may only be escaped in particular contexts, such as hyphens `-` in
+/// character class expressions. Elsewhere, they are interpreted as literals. To detect
+/// non-contextual meta-characters, use [`is_meta_character`].
+///
+/// [`is_meta_character`]: crate::is_meta_character
+pub const fn is_contextual_meta_character(x: char) -> bool {
+    matches!(x, '-')
+}
+
+fn parse_and_check(
+    expression: &str,
+) -> Result<Checked<Tokenized<'_, ExpressionMetadata>>, BuildError> {
+    let tokenized = token::parse(expression)?;
+    let checked = rule::check(tokenized)?;
+    Ok(checked)
+}
+
+/// Splits the given ranges of path components among the given glob components.
+///
+/// Returns the range of text matched by each glob component or `None` if the path components
+/// cannot be split among the glob components. Tree wildcards may match no path components, but
+/// all other components must match at least one.
+fn split_components<A>(
+    components: &[token::Component<'_, '_, A>],
+    programs: &[Regex],
+    text: &str,
+    ranges: &[Range<usize>],
+) -> Option<Vec<Range<usize>>> {
+    let (component, program) = match (components.first(), programs.first()) {
+        (Some(component), Some(program)) => (component, program),
+        _ => return ranges.is_empty().then(Vec::new),
+    };
+    let start = ranges.first().map_or(text.len(), |range| range.start);
+    let min = if component.is_tree_wildcard() { 0 } else { 1 };
+    // Tree wildcards are greedy, so longer spans are attempted first.
+    for n in (min..=ranges.len()).rev() {
+        let span = match n {
+            0 => start..start,
+            _ => start..ranges[n - 1].end,
+        };
+        if program.is_match(&text[span.clone()]) {
+            if let Some(mut spans) =
+                self::split_components(&components[1..], &programs[1..], text, &ranges[n..])
+            {
+                spans.insert(0, span);
+                return Some(spans);
+            }
+        }
+    }
+    None
+}
+
+/// Gets the index of the branch matched by a program compiled by `encode::compile_branches`.
+fn matched_branch(program: Option<&Regex>, path: &CandidatePath<'_>) -> Option<usize> {
+    program?
+        .captures(path.as_ref())?
+        .iter()
+        .skip(1)
+        .position(|capture| capture.is_some())
+}
+
+fn minmax<T>(lhs: T, rhs: T) -> [T; 2]
+where
+    T: Ord,
+{
+    use Ordering::{Equal, Greater, Less};
+
+    match lhs.cmp(&rhs) {
+        Equal | Less => [lhs, rhs],
+        Greater => [rhs, lhs],
+    }
+}
+
+#[cfg(test)]
+pub mod harness {
+    use expect_macro::expect;
+    use itertools::Itertools;
+    use std::collections::hash_map::DefaultHasher;
+    use std::fmt::Debug;
+    use std::hash::{Hash, Hasher};
+    use std::path::{Path, PathBuf};
+
+    use crate::{
+        All, Any, BuildError, CandidatePath, Glob, GlobBuilder, GlobSet, MatchedText, Pattern,
+        Program,
+    };
+
+    pub trait PartitionNonEmpty<'t>: Sized {
+        fn assert_partition_non_empty(self) -> (PathBuf, Glob<'t>);
+    }
+
+    impl<'t> PartitionNonEmpty<'t> for Glob<'t> {
+        fn assert_partition_non_empty(self) -> (PathBuf, Glob<'t>) {
+            let (prefix, glob) = self.partition();
+            (
+                prefix,
+                glob.expect("`Glob::partition` is `None`, but expected `Some`"),
+            )
+        }
+    }
+
+    pub fn hash<T>(value: &T) -> u64
+    where
+        T: Hash,
+    {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn assert_escaped_text_eq(unescaped: &str, escaped: &str, expected: &str) {
+        assert!(
+            escaped == expected,
+            "unexpected output from `escape`:\
+            \n\tunescaped: `{}`\n\tescaped: `{}`\n\texpected: `{}`",
+            unescaped,
+            escaped,
+            expected,
+        );
+    }
+
+    pub fn assert_new_glob_is_ok(expression: &str) -> Glob<'_> {
+        let result = Glob::new(expression);
+        let error = result.as_ref().err().cloned();
+        expect!(
+            result,
+            "`Glob::new` is `Err`, but expected `Ok`: in expression: `{}`: error: \"{}\"",
+            expression,
+            error.unwrap(),
+        )
+    }
+
+    pub fn assert_new_glob_is_err(expression: &str) -> BuildError {
+        expect!(
+            Glob::new(expression).err(),
+            "`Glob::new` is `Ok`, but expected `Err`: in expression: `{}`",
+            expression,
+        )
+    }
+
+    pub fn assert_glob_builder_is_ok(builder: GlobBuilder, expression: &str) -> Glob<'_> {
+        let result = builder.build(expression);
+        let error = result.as_ref().err().cloned();
+        expect!(
+            result,
+            "`GlobBuilder::build` is `Err`, but expected `Ok`: in expression: `{}`: error: \"{}\"",
+            expression,
+            error.unwrap(),
+        )
+    }
+
+    pub fn assert_glob_builder_is_err(builder: GlobBuilder, expression: &str) -> BuildError {
+        expect!(
+            builder.build(expression).err(),
+            "`GlobBuilder::build` is `Ok`, but expected `Err`: in expression: `{}`",
+            expression,
+        )
+    }
+
+    pub fn assert_match_program_with<'t, T, F>(
+        program: impl Program<'t>,
+        candidate: impl Into<CandidatePath<'t>>,
+        f: F,
+    ) -> T
+    where
+        F: FnOnce(Option<MatchedText<'static>>) -> T,
+    {
+        let candidate = candidate.into();
+        f(program.matched(&candidate).map(MatchedText::into_owned))
+    }
+
+    pub fn assert_matched_is_some(matched: Option<MatchedText<'_>>) -> MatchedText<'_> {
+        matched.expect("matched text is `None`, but expected `Some`")
+    }
+
+    pub fn assert_matched_is_none(matched: Option<MatchedText<'_>>) {
+        assert!(
+            matched.is_none(),
+            "matched text is `Some`, but expected `None`"
+        );
+    }
+
+    pub fn assert_matched_has_text(
+        expected: impl IntoIterator<Item = (usize, &'static str)>,
+    ) -> impl FnOnce(Option<MatchedText<'_>>) {
+        move |matched| {
+            let mut has_matched_text = true;
+            let matched = assert_matched_is_some(matched);
+            let message = expected
+                .into_iter()
+                .filter_map(|(index, text)| match matched.get(index) {
+                    Some(matched) if matched == text => None,
+                    matched => Some(format!(
+                        "\tmatched text at capture {} is `{:?}`, but expected `{}`",
+                        index, matched, text,
+                    )),
+                })
+                .inspect(|_| {
+                    has_matched_text = false;
+                })
+                .join("\n");
+            assert!(has_matched_text, "unexpected matched text:\n{}", message);
+        }
+    }
+
+    pub fn assert_all_is_ok<'t, I>(patterns: I) -> All<'t>
+    where
+        I: Clone + IntoIterator,
+        I::Item: Debug + Pattern<'t>,
+    {
+        match crate::all(patterns.clone()) {
+            Ok(all) => all,
+            Err(error) => {
+                panic!(
+                    "`all` is `Err`, but expected `Ok`: error: \"{}\":{}",
+                    error,
+                    patterns
+                        .into_iter()
+                        .map(|pattern| format!("\n\tpattern: `{:?}`", pattern))
+                        .join(""),
+                )
+            },
+        }
+    }
+
+    pub fn assert_glob_set_is_ok<'t, I>(patterns: I) -> GlobSet
+    where
+        I: Clone + IntoIterator,
+        I::Item: Debug + Pattern<'t>,
+    {
+        match GlobSet::new(patterns.clone()) {
+            Ok(set) => set,
+            Err(error) => {
+                panic!(
+                    "`GlobSet::new` is `Err`, but expected `Ok`: error: \"{}\":{}",
+                    error,
+                    patterns
+                        .into_iter()
+                        .map(|pattern| format!("\n\tpattern: `{:?}`", pattern))
+                        .join(""),
+                )
+            },
+        }
+    }
+
+    pub fn assert_any_is_ok<'t, I>(patterns: I) -> Any<'t>
+    where
+        I: Clone + IntoIterator,
+        I::Item: Debug + Pattern<'t>,
+    {
+        match crate::any(patterns.clone()) {
+            Ok(any) => any,
+            Err(error) => {
+                panic!(
+                    "`any` is `Err`, but expected `Ok`: error: \"{}\":{}",
+                    error,
+                    patterns
+                        .into_iter()
+                        .map(|pattern| format!("\n\tpattern: `{:?}`", pattern))
+                        .join(""),
+                )
+            },
+        }
+    }
+
+    pub fn assert_any_is_err<'t, I>(patterns: I) -> BuildError
+    where
+        I: Clone + IntoIterator,
+        I::Item: Debug + Pattern<'t>,
+    {
+        match crate::any(patterns.clone()) {
+            Ok(_) => {
+                panic!(
+                    "`any` is `Ok`, but expected `Err`:{}",
+                    patterns
+                        .into_iter()
+                        .map(|pattern| format!("\n\tpattern: `{:?}`", pattern))
+                        .join(""),
+                )
+            },
+            Err(error) => error,
+        }
+    }
+
+    pub fn assert_partitioned_has_prefix_and_is_match<'p>(
+        partitioned: (PathBuf, Glob<'_>),
+        expected: (impl AsRef<Path>, impl Into<CandidatePath<'p>>),
+    ) -> MatchedText<'static> {
+        let expected = (expected.0.as_ref(), expected.1.into());
+        let (prefix, glob) = partitioned;
+        assert!(
+            prefix == expected.0,
+            "partitioned prefix is `{}`, but expected `{}`: in `Glob`: `{}`",
+            prefix.display(),
+            expected.0.display(),
+            glob,
+        );
+        assert_match_program_with(glob, expected.1, assert_matched_is_some)
+    }
+
+    pub fn assert_partitioned_has_prefix_and_expression<'t>(
+        partitioned: (PathBuf, Glob<'t>),
+        expected: (impl AsRef<Path>, &str),
+    ) -> (PathBuf, Glob<'t>) {
+        let (prefix, glob) = partitioned;
+        let expected = (expected.0.as_ref(), expected.1);
+        assert!(
+            prefix == expected.0,
+            "partitioned prefix is `{}`, but expected `{}`",
+            prefix.display(),
+            expected.0.display(),
+        );
+        let expression = glob.to_string();
+        assert!(
+            expression == expected.1,
+            "partitioned glob has expression `{}`, but expected `{}`",
+            expression,
+            expected.1,
+        );
+        (prefix, glob)
+    }
+}
+
+// TODO: Many of these tests need not be separated into distinct test functions, such as
+//       `new_glob_{}_is_ok`. Consider consolidating such functions using case labels for clarity.
+//       Finding the right balance may be tricky.
+// TODO: Most of these tests operate against public APIs like `Glob::new`, `Glob::partition`, and
+//       `crate::any`. Consider moving these into a `tests` directory as integration tests. Perhaps
+//       more importantly, some of these tests ought to have more local counterparts that directly
+//       test the parser, token tree, and encoder.
+// TODO: Construct paths from components in tests. In practice, using string literals works, but is
+//       technically specific to platforms that support `/` as a separator.
+#[cfg(test)]
+mod tests {
+    use expect_macro::expect;
+    use rstest::rstest;
+    use std::fmt::Debug;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    use crate::diagnostics::Span;
+    use crate::harness::{self, PartitionNonEmpty};
+    use crate::query::{SyntaxKind, TerminalKind, TokenKind, When};
+    use crate::{
+        BuildError, BuildErrorKind, CandidatePath, Glob, GlobBuilder, GlobSet, LazyGlob,
+        MatchBehavior, MatchedText, Pattern, Program,
+    };
+
+    #[rstest]
+    #[case::empty("", "")]
+    #[case::all(
+        "?*$:<>()[]{},\\",
+        "\\?\\*\\$\\:\\<\\>\\(\\)\\[\\]\\{\\}\\,\\\\",
+    )]
+    #[case("record[D00,00].txt", "record\\[D00\\,00\\].txt")]
+    #[case::whitespace("Do You Remember Love?.mp4", "Do You Remember Love\\?.mp4")]
+    #[case::cjk("左{}右", "左\\{\\}右")]
+    #[case::cjk("*中*", "\\*中\\*")]
+    fn escape_with_unescaped_text_is_escaped(#[case] unescaped: &str, #[case] expected: &str) {
+        let escaped = crate::escape(unescaped);
+        harness::assert_escaped_text_eq(unescaped, escaped.as_ref(), expected);
+    }
+
+    #[rstest]
+    #[case("a{b}.txt")]
+    #[case("record[D00,00].txt")]
+    #[case("src/<lib:1,>.rs")]
+    #[case("(?i)README.md")]
+    #[case("$HOME/*")]
+    #[case("a\\b")]
+    #[case("a\\?")]
+    fn match_glob_with_escaped_text_matches_only_text(#[case] unescaped: &str) {
+        let escaped = crate::escape(unescaped);
+        let glob = harness::assert_new_glob_is_ok(escaped.as_ref());
+        assert!(
+            glob.tokens_with_spans().all(|(kind, _)| matches!(
+                kind,
+                TokenKind::Concatenation | TokenKind::Literal | TokenKind::Separator,
+            )),
+            "escaped glob has patterns: escaped: `{}`",
+            escaped,
+        );
+        assert!(glob.is_match(unescaped));
+        assert!(!glob.is_match(format!("{}x", unescaped).as_str()));
+    }
+
+    // TODO: See `escaped`.
+    //#[rstest]
+    //#[case("\\?", "\\?")]
+    //fn escape_with_escaped_text_is_not_escaped_again(
+    //    #[case] unescaped: &str,
+    //    #[case] expected: &str,
+    //) {
+    //    let escaped = crate::escape(unescaped);
+    //    harness::assert_escaped_text_eq(unescaped, escaped.as_ref(), expected);
+    //}
+
+    #[rstest]
+    #[case("a/[xy]")]
+    #[case("a/[x-z]")]
+    #[case("a/[xyi-k]")]
+    #[case("a/[i-kxy]")]
+    #[case("a/[!xy]")]
+    #[case("a/[!x-z]")]
+    #[case("a/[xy]b/c")]
+    #[case("a/[x[yz]]")]
+    #[case("a/[a-z&&[!aeiou]]")]
+    #[case("a/[a-z&&[^aeiou]]")]
+    #[case("a/[!a-z&&[!aeiou]]")]
+    #[case("a/[[a-c]&&[b-d]&&[!c]]")]
+    #[case("a/[a&b]")]
+    #[case("a/[a\\&\\&b]")]
+    fn new_glob_with_class_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("a/\\[a-z\\]/c")]
+    #[case("a/[\\[]/c")]
+    #[case("a/[\\]]/c")]
+    #[case("a/[a\\-z]/c")]
+    #[case("a/[a\\\\]/c")]
+    fn new_glob_with_literal_escaped_class_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("a")]
+    #[case("a/b/c")]
+    #[case("abc")]
+    fn new_glob_with_literal_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("?")]
+    #[case("a/?")]
+    #[case("?a")]
+    #[case("a?")]
+    #[case("a?b")]
+    #[case("??a??b??")]
+    #[case("/?")]
+    fn new_glob_with_exactly_one_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("?*")]
+    #[case("*?")]
+    #[case("*/?")]
+    #[case("?*?")]
+    #[case("/?*")]
+    #[case("?$")]
+    fn new_glob_with_exactly_one_and_zom_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("*")]
+    #[case("a/*")]
+    #[case("*a")]
+    #[case("a*")]
+    #[case("a*b")]
+    #[case("/*")]
+    fn new_glob_with_eager_zom_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("$")]
+    #[case("a/$")]
+    #[case("$a")]
+    #[case("a$")]
+    #[case("a$b")]
+    #[case("/$")]
+    fn new_glob_with_lazy_zom_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("**")]
+    #[case("**/")]
+    #[case("/**")]
+    #[case("**/a")]
+    #[case("a/**")]
+    #[case("**/a/**/b/**")]
+    #[case("{**/a,b/c}")]
+    #[case("{a/b,c/**}")]
+    #[case("<**/a>")]
+    #[case("<a/**>")]
+    fn new_glob_with_tree_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("a/b\\?/c")]
+    #[case("a/b\\$/c")]
+    #[case("a/b\\*/c")]
+    #[case("a/b\\*\\*/c")]
+    fn new_glob_with_literal_escaped_wildcard_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("a/b[?]/c")]
+    #[case("a/b[$]/c")]
+    #[case("a/b[*]/c")]
+    #[case("a/b[*][*]/c")]
+    fn new_glob_with_class_escaped_wildcard_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("a/{x?z,y$}b*")]
+    #[case("a/{???,x$y,frob}b*")]
+    #[case("a/{???,x$y,frob}b*")]
+    #[case("a/{???,{x*z,y$}}b*")]
+    #[case("a{/**/b/,/b/**/}ca{t,b/**}")]
+    #[case("a{,b}c")]
+    #[case("file{,.bak}")]
+    #[case("{a,,b}")]
+    #[case("{,}")]
+    #[case("{**/,}file")]
+    #[case("{**/,a/}b/**")]
+    fn new_glob_with_alternation_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("a/\\{\\}/c")]
+    #[case("a/{x,y\\,,z}/c")]
+    fn new_glob_with_literal_escaped_alternation_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("a/[{][}]/c")]
+    #[case("a/{x,y[,],z}/c")]
+    fn new_glob_with_class_escaped_alternation_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("<a:0,1>")]
+    #[case("<a:0,>")]
+    #[case("<a:2>")]
+    #[case("<a:>")]
+    #[case("<a>")]
+    #[case("<a<b:0,>:0,>")]
+    // Rooted repetitions are accepted if the lower bound is one or greater.
+    #[case("</root:1,>")]
+    #[case("<[!.]*/:0,>[!.]*")]
+    fn new_glob_with_repetition_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("<a:1,3;->")]
+    #[case("<a:1,3; ->")]
+    #[case("<[0-9]:1,3; .>")]
+    #[case("<a:0,;{-,_}>")]
+    #[case("<a:2;b<c:1,;d>>")]
+    #[case("<*:1,;->")]
+    #[case("<a;b>")]
+    fn new_glob_with_separated_repetition_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[test]
+    fn new_lazy_glob_with_invalid_expression_is_err() {
+        static GLOB: LazyGlob = LazyGlob::new("**/**");
+
+        assert!(GLOB.try_get().is_err());
+        assert!(GLOB.try_get().is_err());
+    }
+
+    #[rstest]
+    #[case("{}")]
+    #[case("a{}b")]
+    #[case("{,/a}")]
+    #[case("a{,/}/b")]
+    fn new_glob_with_empty_alternative_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("{**}")]
+    #[case("{**,a}/b")]
+    #[case("{**/,}")]
+    #[case("a/{**/,}b")]
+    #[case("{{**/,}a,b}")]
+    #[case("<**:1,>")]
+    fn new_glob_with_singular_tree_alternative_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("<a:1,3;>")]
+    #[case("<a/:1,3;/b>")]
+    #[case("<a:1,3;/**/>")]
+    fn new_glob_with_separated_repetition_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("(?i)a/b/c")]
+    #[case("(?-i)a/b/c")]
+    #[case("a/(?-i)b/c")]
+    #[case("a/b/(?-i)c")]
+    #[case("(?i)a/(?-i)b/(?i)c")]
+    fn new_glob_with_flag_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case([
+        "src/**/*.rs",
+        "doc/**/*.md",
+        "pkg/**/PKGBUILD",
+    ])]
+    #[case([
+        Glob::new("src/**/*.rs"),
+        Glob::new("doc/**/*.md"),
+        Glob::new("pkg/**/PKGBUILD"),
+    ])]
+    #[case([
+        crate::any(["a/b", "c/d"]),
+        crate::any(["{e,f,g}", "{h,i}"]),
+    ])]
+    #[case::overlapping_trees(["/root", "relative"])]
+    fn any_is_ok<'t, I>(#[case] patterns: I)
+    where
+        I: Clone + IntoIterator,
+        I::Item: Debug + Pattern<'t>,
+    {
+        let _ = harness::assert_any_is_ok(patterns);
+    }
+
+    #[rstest]
+    #[case("//a")]
+    #[case("a//b")]
+    #[case("a/b//")]
+    #[case("a//**")]
+    #[case("{//}a")]
+    #[case("{**//}")]
+    fn new_glob_with_adjacent_separator_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("***")]
+    #[case("****")]
+    #[case("**/**")]
+    #[case("**/**/**")]
+    #[case("a/**/**/b")]
+    #[case("a{**/**,/b}")]
+    #[case("**/*/***")]
+    #[case("**$")]
+    #[case("**/$**")]
+    #[case("{*$}")]
+    #[case("<*$:1,>")]
+    fn new_glob_with_adjacent_tree_and_zom_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("**a")]
+    #[case("a**")]
+    #[case("a**b")]
+    #[case("a*b**")]
+    #[case("**/**a/**")]
+    fn new_glob_with_adjacent_tree_and_literal_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("**?")]
+    #[case("?**")]
+    #[case("?**?")]
+    #[case("?*?**")]
+    #[case("**/**?/**")]
+    fn new_glob_with_adjacent_tree_and_exactly_one_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("a/[a-z-]/c")]
+    #[case("a/[-a-z]/c")]
+    #[case("a/[-]/c")]
+    // Without special attention to escaping and character parsing, this could be mistakenly
+    // interpreted as an empty range over the character `-`. This should be rejected.
+    #[case("a/[---]/c")]
+    #[case("a/[[]/c")]
+    #[case("a/[]]/c")]
+    fn new_glob_with_unescaped_meta_characters_in_class_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("a/[a&&]/c")]
+    #[case("a/[&&a]/c")]
+    #[case("a/[a&&&&b]/c")]
+    #[case("a/[a&&[]]/c")]
+    #[case("a/[a&&[b]/c")]
+    #[case("a/[[!]]/c")]
+    fn new_glob_with_malformed_class_set_expression_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("a/\\p{NotAProperty}/c")]
+    #[case("a/[\\p{NotAProperty}]/c")]
+    #[case("a/\\p{}/c")]
+    #[case("a/\\p{L/c")]
+    #[case("a/\\pL/c")]
+    fn new_glob_with_invalid_unicode_property_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("*{okay,*}")]
+    #[case("{okay,*}*")]
+    #[case("${okay,*error}")]
+    #[case("{okay,error*}$")]
+    #[case("{*,okay}{okay,*}")]
+    #[case("{okay,error*}{okay,*error}")]
+    fn new_glob_with_adjacent_zom_over_alternation_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("/slash/{okay,/error}")]
+    #[case("{okay,error/}/slash")]
+    #[case("slash/{okay,/error/,okay}/slash")]
+    #[case("{okay,error/}{okay,/error}")]
+    // TODO: This is not really an adjacent boundary like the other test cases. Is there a better
+    //       place for this?
+    #[case("{**}")]
+    #[case("slash/{**/error}")]
+    #[case("{error/**}/slash")]
+    #[case("slash/{okay/**,**/error}")]
+    #[case("{**/okay,error/**}/slash")]
+    #[case("{**/okay,prefix{error/**}}/slash")]
+    #[case("{**/okay,slash/{**/error}}postfix")]
+    #[case("{error/**}{okay,**/error")]
+    fn new_glob_with_adjacent_boundary_over_alternation_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("{okay,/}")]
+    #[case("{okay,/**}")]
+    #[case("{okay,/error}")]
+    #[case("{okay,/**/error}")]
+    fn new_glob_with_rooted_alternation_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    // TODO: These are not really misordered bounds like the other test cases. Is there a better
+    //       place for this?
+    #[case("<a/:0,0>")]
+    #[case("<a/:,1>")]
+    #[case("<a/:1,0>")]
+    #[case("<a/:10,1>")]
+    #[case("<a/:10,9>")]
+    fn new_glob_with_misordered_repetition_bounds_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("<*:0,>")]
+    #[case("<a/*:0,>*")]
+    #[case("*<*a:0,>")]
+    fn new_glob_with_adjacent_zom_over_repetition_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("</:0,>")]
+    #[case("</a/:0,>")]
+    #[case("<a/:0,>/")]
+    #[case("<**:0,>")]
+    #[case("</**/a/**:0,>")]
+    #[case("<a/**:0,>/")]
+    #[case("/**</a:0,>")]
+    fn new_glob_with_adjacent_boundary_over_repetition_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    // Rooted repetitions are rejected if their lower bound is zero; any other lower bound is
+    // accepted.
+    #[rstest]
+    #[case("</root:0,>maybe")]
+    #[case("</root>")]
+    fn new_glob_with_sometimes_rooted_repetition_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
+    }
+
+    #[rstest]
+    #[case("<a:65536>")]
+    #[case("<long:16500>")]
+    #[case("a<long:16500>b")]
+    #[case("{<a:65536>,<long:16500>}")]
+    fn new_glob_with_oversized_invariant_repetition_is_rule_err(#[case] expression: &str) {
+        let error = harness::assert_new_glob_is_err(expression);
+        assert!(
+            matches!(
+                error,
+                BuildError {
+                    kind: BuildErrorKind::Rule(_),
+                    ..
+                },
+            ),
+            "`Glob::new` is {:?}, but expected `RuleError`",
+            error,
+        );
+    }
+
+    #[rstest]
+    #[case("[a&&b]")]
+    #[case("a/[a-z&&0-9]/c")]
+    #[case("[[a]&&[!a]]")]
+    #[case("[z-a]")]
+    #[case("{a,[a-c&&d-f]}")]
+    #[case("<[[![!a]]&&b]:1,2>")]
+    #[case("[a[b&&c]&&d]")]
+    fn new_glob_with_unsatisfiable_class_is_rule_err(#[case] expression: &str) {
+        let error = harness::assert_new_glob_is_err(expression);
+        assert!(
+            matches!(
+                error,
+                BuildError {
+                    kind: BuildErrorKind::Rule(_),
+                    ..
+                },
+            ),
+            "`Glob::new` is {:?}, but expected `RuleError`",
+            error,
+        );
+        assert!(error.is_unsatisfiable());
+    }
+
+    #[rstest]
+    #[case("{a,b")]
+    #[case("**/**")]
+    #[case("[a-z]*$")]
+    fn new_glob_with_other_err_is_not_unsatisfiable(#[case] expression: &str) {
+        assert!(!harness::assert_new_glob_is_err(expression).is_unsatisfiable());
+    }
+
+    #[rstest]
+    #[case("[a-a]")]
+    #[case("[a&&a-z]")]
+    #[case("[![a]]")]
+    #[case("[a[b&&c]]")]
+    #[case("[\\p{L}&&0-9]")]
+    #[case("a[/]b")]
+    fn new_glob_with_satisfiable_class_is_ok(#[case] expression: &str) {
+        harness::assert_new_glob_is_ok(expression);
+    }
+
+    #[rstest]
+    #[case("<a*:1000000>")]
+    fn new_glob_with_oversized_program_is_compile_err(#[case] expression: &str) {
+        let error = harness::assert_new_glob_is_err(expression);
+        assert!(
+            matches!(
+                error,
+                BuildError {
+                    kind: BuildErrorKind::Compile(_),
+                    ..
+                },
+            ),
+            "`Glob::new` is {:?}, but expected `CompileError`",
+            error,
+        );
+    }
+
+    #[rstest]
+    #[case("<ab:0,100>", 200)]
+    #[case("{a,bc}<?:2,>", 6)]
+    #[case("**/*.rs", 5)]
+    fn build_glob_with_size_limit_at_estimated_size_is_ok(
+        #[case] expression: &str,
+        #[case] limit: usize,
+    ) {
+        harness::assert_glob_builder_is_ok(GlobBuilder::new().size_limit(limit), expression);
+    }
+
+    #[rstest]
+    #[case("<ab:0,100>", 199)]
+    #[case("{a,bc}<?:2,>", 5)]
+    #[case("**/*.rs", 4)]
+    fn build_glob_with_size_limit_below_estimated_size_is_compile_err(
+        #[case] expression: &str,
+        #[case] limit: usize,
+    ) {
+        let error =
+            harness::assert_glob_builder_is_err(GlobBuilder::new().size_limit(limit), expression);
+        assert!(
+            matches!(
+                error,
+                BuildError {
+                    kind: BuildErrorKind::Compile(_),
+                    ..
+                },
+            ),
+            "`GlobBuilder::build` is {:?}, but expected `CompileError`",
+            error,
+        );
+    }
+
+    #[rstest]
+    #[case("<a:0,1000000>")]
+    #[case("<[a-z]:0,1000000>")]
+    #[case("<<[a-z]:0,1000>:0,1000>")]
+    fn build_glob_with_recommended_size_limit_is_too_complex(#[case] expression: &str) {
+        let error = harness::assert_glob_builder_is_err(
+            GlobBuilder::new().size_limit(GlobBuilder::RECOMMENDED_SIZE_LIMIT),
+            expression,
+        );
+        assert!(
+            error.is_too_complex(),
+            "`GlobBuilder::build` is {:?}, but expected too complex",
+            error,
+        );
+    }
+
+    #[rstest]
+    #[case("{a,b")]
+    #[case("**/**")]
+    fn build_glob_with_recommended_size_limit_is_not_too_complex(#[case] expression: &str) {
+        let error = harness::assert_glob_builder_is_err(
+            GlobBuilder::new().size_limit(GlobBuilder::RECOMMENDED_SIZE_LIMIT),
+            expression,
+        );
+        assert!(
+            !error.is_too_complex(),
+            "`GlobBuilder::build` is {:?}, but expected not too complex",
+            error,
+        );
+    }
 
-    if unescaped.chars().any(is_meta_character) {
-        let mut escaped = String::new();
-        for x in unescaped.chars() {
-            if is_meta_character(x) {
-                escaped.push(ESCAPE);
-            }
-            escaped.push(x);
-        }
-        escaped.into()
+    #[rstest]
+    #[case(r"src\lib.rs", "src/lib.rs")]
+    #[case(r"a\s", "a/s")]
+    #[case(r"a\b/c", "a/b/c")]
+    #[case(r"**\b.rs", "**/b.rs")]
+    #[case(r"{a\b,c}", "{a/b,c}")]
+    #[case(r"<a\b:1,>", "<a/b:1,>")]
+    #[case(r"src\{lib\}.rs", r"src\{lib\}.rs")]
+    #[case(r"src\*.rs", r"src\*.rs")]
+    #[case(r"a\\b", r"a\\b")]
+    fn build_glob_with_backslash_separators_eq(
+        #[case] expression: &str,
+        #[case] expected: &str,
+    ) {
+        let glob = harness::assert_glob_builder_is_ok(
+            GlobBuilder::new().accept_backslash_separators(true),
+            expression,
+        );
+        let expected = harness::assert_new_glob_is_ok(expected);
+        assert_eq!(glob, expected);
     }
-    else {
-        unescaped.into()
+
+    #[rstest]
+    #[case(r"src\lib.rs")]
+    #[case(r"a\s")]
+    fn build_glob_with_backslash_separators_disabled_is_err(#[case] expression: &str) {
+        harness::assert_glob_builder_is_err(
+            GlobBuilder::new().accept_backslash_separators(false),
+            expression,
+        );
     }
-}
 
-// TODO: Is it possible for `:` and `,` to be contextual meta-characters?
-/// Returns `true` if the given character is a meta-character.
-///
-/// This function does **not** return `true` for contextual meta-characters that may only be
-/// escaped in particular contexts, such as hyphens `-` in character class expressions. To detect
-/// these characters, use [`is_contextual_meta_character`].
-///
-/// [`is_contextual_meta_character`]: crate::is_contextual_meta_character
-pub const fn is_meta_character(x: char) -> bool {
-    matches!(
-        x,
-        '?' | '*' | '$' | ':' | '<' | '>' | '(' | ')' | '[' | ']' | '{' | '}' | ','
-    )
-}
+    #[rstest]
+    #[case("")]
+    #[case("**/*.rs")]
+    #[case("src/{lib,main}.rs")]
+    #[case("<[a-z]:1,3>?.txt")]
+    #[case("[!:]")]
+    #[case("(?i)README.md")]
+    fn build_portable_glob_is_ok(#[case] expression: &str) {
+        harness::assert_glob_builder_is_ok(GlobBuilder::new().require_portable(true), expression);
+    }
 
-/// Returns `true` if the given character is a contextual meta-character.
-///
-/// Contextual meta-characters may only be escaped in particular contexts, such as hyphens `-` in
-/// character class expressions. Elsewhere, they are interpreted as literals. To detect
-/// non-contextual meta-characters, use [`is_meta_character`].
-///
-/// [`is_meta_character`]: crate::is_meta_character
-pub const fn is_contextual_meta_character(x: char) -> bool {
-    matches!(x, '-')
-}
+    #[rstest]
+    #[case(r"a\:b")]
+    #[case(r"a\?b")]
+    #[case(r"a\*b")]
+    #[case(r"a\<b")]
+    #[case(r"a\\b")]
+    #[case("a|b")]
+    #[case("a\"b")]
+    #[case("{a,b\u{1}}")]
+    #[case(r"<a\:b:1,>")]
+    fn build_portable_glob_with_non_portable_literal_is_rule_err(#[case] expression: &str) {
+        let error = harness::assert_glob_builder_is_err(
+            GlobBuilder::new().require_portable(true),
+            expression,
+        );
+        assert!(
+            matches!(
+                error,
+                BuildError {
+                    kind: BuildErrorKind::Rule(_),
+                    ..
+                },
+            ),
+            "`GlobBuilder::build` is {:?}, but expected `RuleError`",
+            error,
+        );
+        assert_eq!(error.locations().count(), 1);
+    }
 
-fn parse_and_check(
-    expression: &str,
-) -> Result<Checked<Tokenized<'_, ExpressionMetadata>>, BuildError> {
-    let tokenized = token::parse(expression)?;
-    let checked = rule::check(tokenized)?;
-    Ok(checked)
-}
+    #[rstest]
+    #[case('.', "a.*.c", "a.b.c", true)]
+    #[case('.', "a.*.c", "a.b.x.c", false)]
+    #[case('.', "a.**.c", "a.b.x.c", true)]
+    #[case('.', "a.**", "a", true)]
+    #[case('.', "**.port", "port", true)]
+    #[case('.', "**.port", "server.http.port", true)]
+    #[case('.', "a/b.c", "a/b.c", true)]
+    #[case('.', "*", "a/b", true)]
+    #[case('.', "*", "a.b", false)]
+    #[case('.', "?", ".", false)]
+    #[case('.', "[!a]", ".", false)]
+    #[case('.', r"a\.*", "a.b", true)]
+    #[case('.', r"a\.*", "a.b.c", false)]
+    #[case('.', "{a,b.c}.d", "b.c.d", true)]
+    #[case('|', "a|*", "a|b", true)]
+    #[case('|', "a|*", "a|b|c", false)]
+    #[case('^', "a^**^c", "a^b^c", true)]
+    #[case('-', "[a-c]-*", "b-d", true)]
+    #[case('-', "[a-c]-*", "b-d-e", false)]
+    fn build_glob_with_separator_is_match_eq(
+        #[case] separator: char,
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob =
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().separator(separator), expression);
+        let is_match = glob.is_match(path);
+        assert!(
+            is_match == expected,
+            "`Program::is_match` is `{}`, but expected `{}`: in `Glob`: `{}`: with path: `{}`",
+            is_match,
+            expected,
+            glob,
+            path,
+        );
+    }
 
-fn minmax<T>(lhs: T, rhs: T) -> [T; 2]
-where
-    T: Ord,
-{
-    use Ordering::{Equal, Greater, Less};
+    #[rstest]
+    #[case('.', "a..b")]
+    #[case('.', "**.**")]
+    #[case('.', "a**")]
+    #[case('*', "a")]
+    #[case('{', "a")]
+    #[case(':', "a")]
+    #[case('\\', "a")]
+    fn build_glob_with_separator_is_err(#[case] separator: char, #[case] expression: &str) {
+        harness::assert_glob_builder_is_err(GlobBuilder::new().separator(separator), expression);
+    }
 
-    match lhs.cmp(&rhs) {
-        Equal | Less => [lhs, rhs],
-        Greater => [rhs, lhs],
+    #[rstest]
+    #[case("{a}.{b,{c,d}}.*", "a.{b,c,d}.*", "a.c.x", true)]
+    #[case("{a}.{b,{c,d}}.*", "a.{b,c,d}.*", "a.c.x.y", false)]
+    #[case("{a}.{b,{c,d}}.*", "a.{b,c,d}.*", "a/c/x", false)]
+    fn simplify_glob_with_separator_eq_and_is_match(
+        #[case] expression: &str,
+        #[case] expected: &str,
+        #[case] path: &str,
+        #[case] is_match: bool,
+    ) {
+        let glob =
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), expression)
+                .simplify();
+        assert_eq!(glob.to_string(), expected);
+        assert_eq!(glob.is_match(path), is_match);
     }
-}
 
-#[cfg(test)]
-pub mod harness {
-    use expect_macro::expect;
-    use itertools::Itertools;
-    use std::fmt::Debug;
-    use std::path::{Path, PathBuf};
+    #[rstest]
+    #[case("server.*", "port", "server.*.port", "server.http.port", true)]
+    #[case("server.*", "port", "server.*.port", "server/http/port", false)]
+    #[case("server.**", "*.port", "server.**.*.port", "server.a.b.port", true)]
+    #[case("server", "**.port", "server.**.port", "server.a.b.port", true)]
+    #[case("server", "**.port", "server.**.port", "server/a/b/port", false)]
+    fn join_glob_with_separator_eq_and_is_match(
+        #[case] left: &str,
+        #[case] right: &str,
+        #[case] expected: &str,
+        #[case] path: &str,
+        #[case] is_match: bool,
+    ) {
+        let builder = GlobBuilder::new().separator('.');
+        let left = harness::assert_glob_builder_is_ok(builder, left);
+        let right = harness::assert_glob_builder_is_ok(builder, right);
+        let glob = left.join(&right).expect("failed to join globs");
+        assert_eq!(glob.to_string(), expected);
+        assert_eq!(glob.is_match(path), is_match);
+    }
 
-    use crate::{Any, BuildError, CandidatePath, Glob, MatchedText, Pattern, Program};
+    #[test]
+    fn join_glob_with_different_separators_is_err() {
+        let left = harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), "a");
+        let right = harness::assert_new_glob_is_ok("b");
+        assert!(left.join(&right).is_err());
+        assert!(right.join(&left).is_err());
+    }
 
-    pub trait PartitionNonEmpty<'t>: Sized {
-        fn assert_partition_non_empty(self) -> (PathBuf, Glob<'t>);
+    #[rstest]
+    #[case("server.http.*", "server.http", "port")]
+    #[case("server.**.port", "server", "http.port")]
+    fn partition_glob_with_separator_has_prefix_and_is_match(
+        #[case] expression: &str,
+        #[case] prefix: &str,
+        #[case] path: &str,
+    ) {
+        harness::assert_partitioned_has_prefix_and_is_match(
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), expression)
+                .partition_or_empty(),
+            (prefix, path),
+        );
     }
 
-    impl<'t> PartitionNonEmpty<'t> for Glob<'t> {
-        fn assert_partition_non_empty(self) -> (PathBuf, Glob<'t>) {
-            let (prefix, glob) = self.partition();
-            (
-                prefix,
-                glob.expect("`Glob::partition` is `None`, but expected `Some`"),
-            )
-        }
+    #[rstest]
+    #[case(
+        "server.**.port",
+        "server.a.b.port",
+        [("server", "server"), ("**", "a.b"), ("port", "port")],
+    )]
+    #[case("a/b.*", "a/b.c", [("a/b", "a/b"), ("*", "c")])]
+    #[case("a/b", "a/b", [("a/b", "a/b")])]
+    fn split_components_of_glob_with_separator_eq<const N: usize>(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: [(&str, &str); N],
+    ) {
+        let glob =
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), expression);
+        let candidate = CandidatePath::from(path);
+        let components = expect!(
+            glob.split_components(&candidate),
+            "`Glob::split_components` is `None`: in `Glob`: `{}`: with path: `{}`",
+            glob,
+            path,
+        );
+        assert_eq!(components, expected);
     }
 
-    pub fn assert_escaped_text_eq(unescaped: &str, escaped: &str, expected: &str) {
-        assert!(
-            escaped == expected,
-            "unexpected output from `escape`:\
-            \n\tunescaped: `{}`\n\tescaped: `{}`\n\texpected: `{}`",
-            unescaped,
-            escaped,
+    #[test]
+    fn match_end_of_glob_with_separator_eq() {
+        let glob = harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), "api.*");
+        assert_eq!(glob.match_end("api.users.42"), Some("api.users".len()));
+        assert_eq!(glob.match_end("api/users"), None);
+    }
+
+    #[rstest]
+    #[case("{a.*,b}", "a.x", Some(0))]
+    #[case("{a.*,b}", "b", Some(1))]
+    fn match_glob_with_separator_matched_branch_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: Option<usize>,
+    ) {
+        let glob =
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), expression);
+        let candidate = CandidatePath::from(path);
+        let matched = harness::assert_matched_is_some(glob.matched(&candidate));
+        assert_eq!(matched.matched_branch(), expected);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[rstest]
+    #[case("a.*", b"a.\xFF", true)]
+    #[case("a.*", b"a.\xFF.b", false)]
+    #[case("a.*", b"a.\xFF/b", true)]
+    fn match_glob_with_separator_and_bytes_eq(
+        #[case] expression: &str,
+        #[case] bytes: &[u8],
+        #[case] expected: bool,
+    ) {
+        let glob =
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), expression);
+        assert_eq!(
+            glob.is_match_bytes(bytes).expect("failed to compile glob for bytes"),
             expected,
         );
     }
 
-    pub fn assert_new_glob_is_ok(expression: &str) -> Glob<'_> {
-        let result = Glob::new(expression);
-        let error = result.as_ref().err().cloned();
-        expect!(
-            result,
-            "`Glob::new` is `Err`, but expected `Ok`: in expression: `{}`: error: \"{}\"",
+    #[cfg(feature = "dfa")]
+    #[rstest]
+    #[case("a.*", "a.b")]
+    #[case("a.*", "a.b.c")]
+    #[case("a.*", "a/b")]
+    #[case("a.**.c", "a.b.c")]
+    #[case("a.**.c", "a/b/c")]
+    fn match_dfa_glob_with_separator_eq_match_glob(#[case] expression: &str, #[case] path: &str) {
+        let glob =
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), expression);
+        let expected = glob.is_match(path);
+        let dfa = glob.compile_dfa().expect("failed to compile DFA");
+        assert_eq!(dfa.is_match(path), expected);
+    }
+
+    #[rstest]
+    #[case("a.x", true)]
+    #[case("b/x", true)]
+    #[case("a.x.y", false)]
+    #[case("a/x", false)]
+    #[case("b.x", false)]
+    fn match_any_of_globs_with_separators_eq(#[case] path: &str, #[case] expected: bool) {
+        let any = harness::assert_any_is_ok([
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().separator('.'), "a.*"),
+            harness::assert_new_glob_is_ok("b/*"),
+        ]);
+        assert_eq!(any.is_match(path), expected);
+    }
+
+    #[rstest]
+    #[case("a.b", true)]
+    #[case("a.x.b", false)]
+    fn match_all_of_globs_with_separator_eq(#[case] path: &str, #[case] expected: bool) {
+        let builder = GlobBuilder::new().separator('.');
+        let all = harness::assert_all_is_ok([
+            harness::assert_glob_builder_is_ok(builder, "a.*"),
+            harness::assert_glob_builder_is_ok(builder, "*.b"),
+        ]);
+        assert_eq!(all.is_match(path), expected);
+    }
+
+    #[test]
+    fn glob_with_separator_ne_glob_with_native_separator() {
+        let builder = GlobBuilder::new().separator('.');
+        let left = harness::assert_glob_builder_is_ok(builder, "server.*.port");
+        assert_ne!(left, harness::assert_new_glob_is_ok("server/*/port"));
+
+        let right = harness::assert_glob_builder_is_ok(builder, "server.*.port");
+        assert_eq!(left, right);
+        assert_eq!(harness::hash(&left), harness::hash(&right));
+    }
+
+    #[rstest]
+    #[case("src/**/*.rs")]
+    #[case("a../b")]
+    #[case("..a/b")]
+    #[case("./a")]
+    #[case("[.][.][.]/a")]
+    #[case("<.:3>/a")]
+    fn build_glob_with_parent_dir_disabled_is_ok(#[case] expression: &str) {
+        harness::assert_glob_builder_is_ok(GlobBuilder::new().allow_parent_dir(false), expression);
+    }
+
+    #[rstest]
+    #[case("..")]
+    #[case("../a")]
+    #[case("a/../b")]
+    #[case("/..")]
+    #[case("{..,a}/b")]
+    #[case("<../:1,>a")]
+    #[case("[.][.]/*.rs")]
+    #[case(".[.]/*.rs")]
+    #[case("<.:2>/*.rs")]
+    #[case("<.:1,2>/*.rs")]
+    #[case("{a,.[.]}/*.rs")]
+    #[case("a/<[.]:2>")]
+    #[case("<a/[.][.]:1>/b")]
+    fn build_glob_with_parent_dir_disabled_is_rule_err(#[case] expression: &str) {
+        harness::assert_glob_builder_is_ok(GlobBuilder::new(), expression);
+        let error = harness::assert_glob_builder_is_err(
+            GlobBuilder::new().allow_parent_dir(false),
             expression,
-            error.unwrap(),
-        )
+        );
+        assert!(
+            matches!(
+                error,
+                BuildError {
+                    kind: BuildErrorKind::Rule(_),
+                    ..
+                },
+            ),
+            "`GlobBuilder::build` is {:?}, but expected `RuleError`",
+            error,
+        );
+        assert_eq!(error.locations().count(), 1);
     }
 
-    pub fn assert_new_glob_is_err(expression: &str) -> BuildError {
-        expect!(
-            Glob::new(expression).err(),
-            "`Glob::new` is `Ok`, but expected `Err`: in expression: `{}`",
+    #[rstest]
+    #[case("src/**/*.rs")]
+    #[case("a?c/$")]
+    #[case("(?i)readme.md")]
+    fn build_glob_with_syntax_disabled_is_ok(#[case] expression: &str) {
+        harness::assert_glob_builder_is_ok(
+            GlobBuilder::new()
+                .allow_alternation(false)
+                .allow_class(false)
+                .allow_repetition(false),
             expression,
-        )
+        );
+    }
+
+    #[rstest]
+    #[case(GlobBuilder::new().allow_alternation(false), "*.{rs,toml}", SyntaxKind::Alternation)]
+    #[case(GlobBuilder::new().allow_alternation(false), "<{a,b}:2>", SyntaxKind::Alternation)]
+    #[case(GlobBuilder::new().allow_class(false), "[a-z].txt", SyntaxKind::Class)]
+    #[case(GlobBuilder::new().allow_class(false), "\\p{L}.txt", SyntaxKind::Class)]
+    #[case(GlobBuilder::new().allow_class(false), "{a,[bc]}", SyntaxKind::Class)]
+    #[case(GlobBuilder::new().allow_repetition(false), "<a:1,>", SyntaxKind::Repetition)]
+    #[case(GlobBuilder::new().allow_repetition(false), "{a,<b:2>}", SyntaxKind::Repetition)]
+    fn build_glob_with_syntax_disabled_is_rule_err(
+        #[case] builder: GlobBuilder,
+        #[case] expression: &str,
+        #[case] expected: SyntaxKind,
+    ) {
+        harness::assert_glob_builder_is_ok(GlobBuilder::new(), expression);
+        let error = harness::assert_glob_builder_is_err(builder, expression);
+        assert!(
+            matches!(
+                error,
+                BuildError {
+                    kind: BuildErrorKind::Rule(_),
+                    ..
+                },
+            ),
+            "`GlobBuilder::build` is {:?}, but expected `RuleError`",
+            error,
+        );
+        assert_eq!(error.locations().count(), 1);
+        assert_eq!(error.disallowed_syntax(), Some(expected));
     }
 
-    pub fn assert_match_program_with<'t, T, F>(
-        program: impl Program<'t>,
-        candidate: impl Into<CandidatePath<'t>>,
-        f: F,
-    ) -> T
-    where
-        F: FnOnce(Option<MatchedText<'static>>) -> T,
-    {
-        let candidate = candidate.into();
-        f(program.matched(&candidate).map(MatchedText::into_owned))
+    #[rstest]
+    #[case("*.md", "README.md")]
+    #[case("?", "a")]
+    #[case("[a-z]*.{md,txt}", "guide.txt")]
+    #[case("<[0-9]:1,3>", "123")]
+    #[case("", "")]
+    fn fnmatch_with_matching_name_is_true(#[case] expression: &str, #[case] name: &str) {
+        assert!(expect!(crate::fnmatch(expression, name), "`fnmatch` is `Err`"));
     }
 
-    pub fn assert_matched_is_some(matched: Option<MatchedText<'_>>) -> MatchedText<'_> {
-        matched.expect("matched text is `None`, but expected `Some`")
+    #[rstest]
+    #[case("*.md", "doc/guide.md")]
+    #[case("*", "a/b")]
+    #[case("?", "ab")]
+    fn fnmatch_with_nonmatching_name_is_false(#[case] expression: &str, #[case] name: &str) {
+        assert!(!expect!(crate::fnmatch(expression, name), "`fnmatch` is `Err`"));
     }
 
-    pub fn assert_matched_is_none(matched: Option<MatchedText<'_>>) {
+    #[rstest]
+    #[case("**")]
+    #[case("a/b")]
+    #[case("/a")]
+    #[case("**/*.md")]
+    #[case("{a,b/c}")]
+    #[case("<a/:1,2>")]
+    fn fnmatch_with_component_boundary_is_rule_err(#[case] expression: &str) {
+        let error = crate::fnmatch(expression, "a").expect_err("`fnmatch` is `Ok`");
         assert!(
-            matched.is_none(),
-            "matched text is `Some`, but expected `None`"
+            matches!(
+                error,
+                BuildError {
+                    kind: BuildErrorKind::Rule(_),
+                    ..
+                },
+            ),
+            "`fnmatch` is {:?}, but expected `RuleError`",
+            error,
         );
     }
 
-    pub fn assert_matched_has_text(
-        expected: impl IntoIterator<Item = (usize, &'static str)>,
-    ) -> impl FnOnce(Option<MatchedText<'_>>) {
-        move |matched| {
-            let mut has_matched_text = true;
-            let matched = assert_matched_is_some(matched);
-            let message = expected
-                .into_iter()
-                .filter_map(|(index, text)| match matched.get(index) {
-                    Some(matched) if matched == text => None,
-                    matched => Some(format!(
-                        "\tmatched text at capture {} is `{:?}`, but expected `{}`",
-                        index, matched, text,
-                    )),
-                })
-                .inspect(|_| {
-                    has_matched_text = false;
-                })
-                .join("\n");
-            assert!(has_matched_text, "unexpected matched text:\n{}", message);
-        }
+    #[rstest]
+    #[case("(?)a")]
+    #[case("(?-)a")]
+    #[case("()a")]
+    fn new_glob_with_incomplete_flag_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
     }
 
-    pub fn assert_any_is_ok<'t, I>(patterns: I) -> Any<'t>
-    where
-        I: Clone + IntoIterator,
-        I::Item: Debug + Pattern<'t>,
-    {
-        match crate::any(patterns.clone()) {
-            Ok(any) => any,
-            Err(error) => {
-                panic!(
-                    "`any` is `Err`, but expected `Ok`: error: \"{}\":{}",
-                    error,
-                    patterns
-                        .into_iter()
-                        .map(|pattern| format!("\n\tpattern: `{:?}`", pattern))
-                        .join(""),
-                )
-            },
-        }
+    #[rstest]
+    #[case("/(?i)/")]
+    #[case("$(?i)$")]
+    #[case("*(?i)*")]
+    #[case("**(?i)?")]
+    #[case("a(?i)**")]
+    #[case("**(?i)a")]
+    fn new_glob_with_adjacent_separator_and_zom_over_flag_is_err(#[case] expression: &str) {
+        harness::assert_new_glob_is_err(expression);
     }
 
-    pub fn assert_any_is_err<'t, I>(patterns: I) -> BuildError
+    #[rstest]
+    #[case([
+        "{a,b,c}",
+        "{d,e}",
+        "f/{g,/error,h}",
+    ])]
+    fn any_with_adjacent_boundary_is_err<'t, I>(#[case] patterns: I)
     where
         I: Clone + IntoIterator,
         I::Item: Debug + Pattern<'t>,
     {
-        match crate::any(patterns.clone()) {
-            Ok(_) => {
-                panic!(
-                    "`any` is `Ok`, but expected `Err`:{}",
-                    patterns
-                        .into_iter()
-                        .map(|pattern| format!("\n\tpattern: `{:?}`", pattern))
-                        .join(""),
-                )
-            },
-            Err(error) => error,
-        }
+        harness::assert_any_is_err(patterns);
     }
 
-    pub fn assert_partitioned_has_prefix_and_is_match<'p>(
-        partitioned: (PathBuf, Glob<'_>),
-        expected: (impl AsRef<Path>, impl Into<CandidatePath<'p>>),
-    ) -> MatchedText<'static> {
-        let expected = (expected.0.as_ref(), expected.1.into());
-        let (prefix, glob) = partitioned;
-        assert!(
-            prefix == expected.0,
-            "partitioned prefix is `{}`, but expected `{}`: in `Glob`: `{}`",
-            prefix.display(),
-            expected.0.display(),
-            glob,
-        );
-        assert_match_program_with(glob, expected.1, assert_matched_is_some)
+    #[rstest]
+    #[case("src", "*.rs", "src/*.rs")]
+    #[case("src/**", "*.rs", "src/**/*.rs")]
+    #[case("src", "**/*.rs", "src/**/*.rs")]
+    #[case("src/", "*.rs", "src/*.rs")]
+    #[case("{a,b}", "<c/:1,2>d", "{a,b}/<c/:1,2>d")]
+    #[case("", "*.rs", "*.rs")]
+    #[case("src", "", "src")]
+    #[case("/root", "*.rs", "/root/*.rs")]
+    #[case("a", "/b", "a/b")]
+    #[case("a", "/**", "a/**")]
+    #[case("a/", "/b", "a/b")]
+    #[case("a/", "/**", "a/**")]
+    #[case("src/", "**/*.rs", "src/**/*.rs")]
+    #[case("src/**", "/b", "src/**/b")]
+    #[case("**", "*.rs", "**/*.rs")]
+    fn join_glob_eq(#[case] left: &str, #[case] right: &str, #[case] expected: &str) {
+        let left = harness::assert_new_glob_is_ok(left);
+        let right = harness::assert_new_glob_is_ok(right);
+        let glob = expect!(left.join(&right), "`Glob::join` is `Err`");
+        assert_eq!(glob.to_string(), expected);
+        assert_eq!(glob, harness::assert_new_glob_is_ok(expected));
     }
 
-    pub fn assert_partitioned_has_prefix_and_expression<'t>(
-        partitioned: (PathBuf, Glob<'t>),
-        expected: (impl AsRef<Path>, &str),
-    ) -> (PathBuf, Glob<'t>) {
-        let (prefix, glob) = partitioned;
-        let expected = (expected.0.as_ref(), expected.1);
-        assert!(
-            prefix == expected.0,
-            "partitioned prefix is `{}`, but expected `{}`",
-            prefix.display(),
-            expected.0.display(),
-        );
-        let expression = glob.to_string();
-        assert!(
-            expression == expected.1,
-            "partitioned glob has expression `{}`, but expected `{}`",
-            expression,
-            expected.1,
-        );
-        (prefix, glob)
+    #[rstest]
+    #[case(r"src\lib.rs", "x", "src/lib.rs/x")]
+    #[case(r"src\a", "**/*.rs", "src/a/**/*.rs")]
+    #[case(r"a\b/**\c", r"d\e", "a/b/**/c/d/e")]
+    #[case(r"src\{lib\}.rs", "x", r"src\{lib\}.rs/x")]
+    fn join_glob_with_backslash_separators_eq(
+        #[case] left: &str,
+        #[case] right: &str,
+        #[case] expected: &str,
+    ) {
+        let builder = GlobBuilder::new().accept_backslash_separators(true);
+        let left = harness::assert_glob_builder_is_ok(builder, left);
+        let right = harness::assert_glob_builder_is_ok(builder, right);
+        let glob = expect!(left.join(&right), "`Glob::join` is `Err`");
+        assert_eq!(glob.to_string(), expected);
+        assert_eq!(glob, harness::assert_new_glob_is_ok(expected));
     }
-}
-
-// TODO: Many of these tests need not be separated into distinct test functions, such as
-//       `new_glob_{}_is_ok`. Consider consolidating such functions using case labels for clarity.
-//       Finding the right balance may be tricky.
-// TODO: Most of these tests operate against public APIs like `Glob::new`, `Glob::partition`, and
-//       `crate::any`. Consider moving these into a `tests` directory as integration tests. Perhaps
-//       more importantly, some of these tests ought to have more local counterparts that directly
-//       test the parser, token tree, and encoder.
-// TODO: Construct paths from components in tests. In practice, using string literals works, but is
-//       technically specific to platforms that support `/` as a separator.
-#[cfg(test)]
-mod tests {
-    use rstest::rstest;
-    use std::fmt::Debug;
-
-    use crate::diagnostics::Span;
-    use crate::harness::{self, PartitionNonEmpty};
-    use crate::{BuildError, BuildErrorKind, Glob, MatchedText, Pattern, Program};
 
+    // Case sensitivity is the default on Unix, so the default flags are `(?-i)`.
+    #[cfg(not(windows))]
     #[rstest]
-    #[case::empty("", "")]
-    #[case::all("?*$:<>()[]{},", "\\?\\*\\$\\:\\<\\>\\(\\)\\[\\]\\{\\}\\,")]
-    #[case("record[D00,00].txt", "record\\[D00\\,00\\].txt")]
-    #[case::whitespace("Do You Remember Love?.mp4", "Do You Remember Love\\?.mp4")]
-    #[case::cjk("左{}右", "左\\{\\}右")]
-    #[case::cjk("*中*", "\\*中\\*")]
-    fn escape_with_unescaped_text_is_escaped(#[case] unescaped: &str, #[case] expected: &str) {
-        let escaped = crate::escape(unescaped);
-        harness::assert_escaped_text_eq(unescaped, escaped.as_ref(), expected);
+    #[case("(?i)a", "b", "(?i)a(?-i)/b")]
+    #[case("(?i)a/", "b", "(?i)a/(?-i)b")]
+    #[case("(?i)a/", "**/b", "(?i)a(?-i)/**/b")]
+    #[case("(?i)a/**", "b", "(?i)a/**(?-i)/b")]
+    #[case("(?i)a(?-i)b", "c", "(?i)a(?-i)b(?-i)/c")]
+    #[case("a", "(?i)b", "a/(?i)b")]
+    fn join_glob_with_flags_eq(#[case] left: &str, #[case] right: &str, #[case] expected: &str) {
+        let left = harness::assert_new_glob_is_ok(left);
+        let right = harness::assert_new_glob_is_ok(right);
+        let glob = expect!(left.join(&right), "`Glob::join` is `Err`");
+        assert_eq!(glob.to_string(), expected);
+        assert_eq!(glob, harness::assert_new_glob_is_ok(expected));
     }
 
-    // TODO: See `escaped`.
-    //#[rstest]
-    //#[case("\\?", "\\?")]
-    //fn escape_with_escaped_text_is_not_escaped_again(
-    //    #[case] unescaped: &str,
-    //    #[case] expected: &str,
-    //) {
-    //    let escaped = crate::escape(unescaped);
-    //    harness::assert_escaped_text_eq(unescaped, escaped.as_ref(), expected);
-    //}
+    #[cfg(not(windows))]
+    #[rstest]
+    #[case("(?i)a", "b", "A/b", true)]
+    #[case("(?i)a", "b", "A/B", false)]
+    #[case("(?i)a/", "**/b", "A/x/b", true)]
+    #[case("(?i)a/", "**/b", "A/x/B", false)]
+    fn join_glob_with_flags_is_match_eq(
+        #[case] left: &str,
+        #[case] right: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = expect!(
+            harness::assert_new_glob_is_ok(left).join(&harness::assert_new_glob_is_ok(right)),
+            "`Glob::join` is `Err`",
+        );
+        assert_eq!(glob.is_match(path), expected);
+        // The displayed expression is parsed into an equivalent glob.
+        assert_eq!(
+            harness::assert_new_glob_is_ok(glob.to_string().as_str()).is_match(path),
+            expected,
+        );
+    }
 
     #[rstest]
-    #[case("a/[xy]")]
-    #[case("a/[x-z]")]
-    #[case("a/[xyi-k]")]
-    #[case("a/[i-kxy]")]
-    #[case("a/[!xy]")]
-    #[case("a/[!x-z]")]
-    #[case("a/[xy]b/c")]
-    fn new_glob_with_class_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("src/**", "*.rs", "src/a/b.rs", true)]
+    #[case("src/**", "*.rs", "src/b.rs", true)]
+    #[case("src/**", "*.rs", "lib.rs", false)]
+    #[case("src", "*.rs", "src/lib.rs", true)]
+    #[case("src", "*.rs", "src/a/lib.rs", false)]
+    fn join_glob_is_match_eq(
+        #[case] left: &str,
+        #[case] right: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = expect!(
+            harness::assert_new_glob_is_ok(left).join(&harness::assert_new_glob_is_ok(right)),
+            "`Glob::join` is `Err`",
+        );
+        assert_eq!(glob.is_match(path), expected);
     }
 
     #[rstest]
-    #[case("a/\\[a-z\\]/c")]
-    #[case("a/[\\[]/c")]
-    #[case("a/[\\]]/c")]
-    #[case("a/[a\\-z]/c")]
-    fn new_glob_with_literal_escaped_class_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("**", "**")]
+    #[case("a/**", "**/b")]
+    fn join_glob_is_err(#[case] left: &str, #[case] right: &str) {
+        let left = harness::assert_new_glob_is_ok(left);
+        let right = harness::assert_new_glob_is_ok(right);
+        assert!(left.join(&right).is_err());
     }
 
     #[rstest]
-    #[case("")]
-    #[case("a")]
-    #[case("a/b/c")]
-    #[case("abc")]
-    fn new_glob_with_literal_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("", "")]
+    #[case("src/**/*.rs", "src/**/*.rs")]
+    #[case("{a}", "a")]
+    #[case("{{a}}", "a")]
+    #[case("a{b}c", "abc")]
+    #[case("{src}/{a,{b,c}}.{rs}", "src/{a,b,c}.rs")]
+    #[case("{{a,b},{c,{d,e}}}", "{a,b,c,d,e}")]
+    #[case("{a,}{b}", "{a,}b")]
+    #[case("<{a}:1,3;{-}>", "<a:1,3;->")]
+    #[case("{**/*.rs}", "**/*.rs")]
+    #[case("{(?i)a}b", "(?i)ab")]
+    #[case("{a\\,b}", "{a\\,b}")]
+    #[case("{**/,}target", "{**/,}target")]
+    #[case("<{*}:1,2>", "<{*}:1,2>")]
+    fn simplify_glob_eq(#[case] expression: &str, #[case] expected: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression).simplify();
+        assert_eq!(glob.to_string(), expected);
+        assert_eq!(glob, harness::assert_new_glob_is_ok(expected));
+    }
+
+    #[test]
+    fn simplify_glob_captures_span_eq() {
+        let glob = harness::assert_new_glob_is_ok("{src}/*.{rs,{md,txt}}").simplify();
+        let expression = glob.to_string();
+        assert_eq!(expression, "src/*.{rs,md,txt}");
+        let captures: Vec<_> = glob
+            .captures()
+            .map(|token| {
+                let (start, n) = token.span();
+                &expression[start..start + n]
+            })
+            .collect();
+        assert_eq!(captures, ["*", "{rs,md,txt}"]);
+    }
+
+    #[test]
+    fn simplify_glob_preserves_matches() {
+        const EXPRESSIONS: &[&str] = &[
+            "{a}",
+            "a{b}c",
+            "{a{b}}",
+            "{a,{b,{c}}}",
+            "{a,}{b}",
+            "<{a}:1,3>",
+            "<{a}:1,3;{-}>",
+            "{src}{/**/*.{rs}}",
+            "{src/,}{lib,main}.{rs}",
+            "{**/,}{target}/**",
+            "{(?i)a}b",
+            "(?i){a}(?-i){b}",
+            "{[ab]}{?}",
+            "/{a}/{b,{c,d}}",
+            "<{*}:1,2>",
+        ];
+        const PATHS: &[&str] = &[
+            "",
+            "a",
+            "A",
+            "ab",
+            "aB",
+            "Ab",
+            "abc",
+            "b",
+            "c",
+            "d",
+            "a-a",
+            "a-a-a",
+            "aaa",
+            "src/lib.rs",
+            "src/a/main.rs",
+            "lib.rs",
+            "main.rs",
+            "target",
+            "target/debug",
+            "a/target/debug",
+            "/a/b",
+            "/a/d",
+            "/a/e",
+        ];
+
+        for expression in EXPRESSIONS {
+            let glob = harness::assert_new_glob_is_ok(expression);
+            let simplified = glob.clone().simplify();
+            let reparsed = harness::assert_new_glob_is_ok(&simplified.to_string()).into_owned();
+            for path in PATHS {
+                let expected = glob.is_match(*path);
+                assert_eq!(
+                    simplified.is_match(*path),
+                    expected,
+                    "simplified glob does not match like original: expression: `{}`: simplified: \
+                     `{}`: path: `{}`",
+                    expression,
+                    simplified,
+                    path,
+                );
+                assert_eq!(
+                    reparsed.is_match(*path),
+                    expected,
+                    "reparsed glob does not match like original: expression: `{}`: simplified: \
+                     `{}`: path: `{}`",
+                    expression,
+                    simplified,
+                    path,
+                );
+            }
+        }
     }
 
     #[rstest]
-    #[case("?")]
-    #[case("a/?")]
-    #[case("?a")]
-    #[case("a?")]
-    #[case("a?b")]
-    #[case("??a??b??")]
-    #[case("/?")]
-    fn new_glob_with_exactly_one_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("a/b", "a/b", [("a", "a"), ("b", "b")])]
+    #[case("src/**/*.rs", "src/lib.rs", [("src", "src"), ("**", ""), ("*.rs", "lib.rs")])]
+    #[case("src/**/*.rs", "src/a/b/c.rs", [("src", "src"), ("**", "a/b"), ("*.rs", "c.rs")])]
+    #[case("**/*.md", "doc/guide.md", [("**", "doc"), ("*.md", "guide.md")])]
+    #[case("a/**", "a", [("a", "a"), ("**", "")])]
+    #[case("a/**", "a/b/c", [("a", "a"), ("**", "b/c")])]
+    #[case("/a/*", "/a/b", [("a", "a"), ("*", "b")])]
+    #[case("/**/b", "/a/b", [("**", "a"), ("b", "b")])]
+    #[case("{a/b,c}/d", "a/b/d", [("{a/b,c}", "a/b"), ("d", "d")])]
+    #[case("<*/:2>*.rs", "a/b/c.rs", [("<*/:2>*.rs", "a/b/c.rs")])]
+    #[case("**/b/**", "a/b/b/c", [("**", "a/b"), ("b", "b"), ("**", "c")])]
+    #[case("", "", [])]
+    fn split_components_of_glob_eq<const N: usize>(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: [(&str, &str); N],
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let candidate = CandidatePath::from(path);
+        let components = expect!(
+            glob.split_components(&candidate),
+            "`Glob::split_components` is `None`: in `Glob`: `{}`: with path: `{}`",
+            glob,
+            path,
+        );
+        assert_eq!(components, expected);
     }
 
     #[rstest]
-    #[case("?*")]
-    #[case("*?")]
-    #[case("*/?")]
-    #[case("?*?")]
-    #[case("/?*")]
-    #[case("?$")]
-    fn new_glob_with_exactly_one_and_zom_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("a/b", "a/c")]
+    #[case("**/*.md", "lib.rs")]
+    fn split_components_of_glob_with_nonmatching_path_is_none(
+        #[case] expression: &str,
+        #[case] path: &str,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert!(glob.split_components(&CandidatePath::from(path)).is_none());
+    }
+
+    #[test]
+    fn empty_glob_is_empty() {
+        let glob = Glob::empty();
+        assert!(glob.is_empty());
+        assert_eq!(glob, harness::assert_new_glob_is_ok(""));
+        assert!(!harness::assert_new_glob_is_ok("a").is_empty());
     }
 
     #[rstest]
-    #[case("*")]
-    #[case("a/*")]
-    #[case("*a")]
-    #[case("a*")]
-    #[case("a*b")]
-    #[case("/*")]
-    fn new_glob_with_eager_zom_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("", harness::assert_matched_has_text([(0, "")]))]
+    #[case("a", harness::assert_matched_is_none)]
+    #[case("abc", harness::assert_matched_is_none)]
+    #[case(".", harness::assert_matched_is_none)]
+    #[case("/", harness::assert_matched_is_none)]
+    #[case("a/b", harness::assert_matched_is_none)]
+    fn match_empty_glob<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok(""), path, f);
     }
 
     #[rstest]
-    #[case("$")]
-    #[case("a/$")]
-    #[case("$a")]
-    #[case("a$")]
-    #[case("a$b")]
-    #[case("/$")]
-    fn new_glob_with_lazy_zom_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("a/b", harness::assert_matched_has_text([(0, "a/b")]))]
+    #[case("aa/b", harness::assert_matched_is_none)]
+    #[case("a/bb", harness::assert_matched_is_none)]
+    #[case("a/b/c", harness::assert_matched_is_none)]
+    fn match_invariant_glob<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a/b"), path, f);
     }
 
     #[rstest]
-    #[case("**")]
-    #[case("**/")]
-    #[case("/**")]
-    #[case("**/a")]
-    #[case("a/**")]
-    #[case("**/a/**/b/**")]
-    #[case("{**/a,b/c}")]
-    #[case("{a/b,c/**}")]
-    #[case("<**/a>")]
-    #[case("<a/**>")]
-    fn new_glob_with_tree_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("a/b", harness::assert_matched_has_text([(0, "a/b")]))]
+    #[case("a/x/b", harness::assert_matched_has_text([(0, "a/x/b")]))]
+    #[case("a/x/y/z/b", harness::assert_matched_has_text([(0, "a/x/y/z/b"), (1, "x/y/z/")]))]
+    #[case("a", harness::assert_matched_is_none)]
+    #[case("b/a", harness::assert_matched_is_none)]
+    #[case("x/a/b", harness::assert_matched_is_none)]
+    #[case("x/a/x/b", harness::assert_matched_is_none)]
+    fn match_glob_with_tree<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a/**/b"), path, f);
     }
 
     #[rstest]
-    #[case("a/b\\?/c")]
-    #[case("a/b\\$/c")]
-    #[case("a/b\\*/c")]
-    #[case("a/b\\*\\*/c")]
-    fn new_glob_with_literal_escaped_wildcard_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case(".ext", harness::assert_matched_has_text([(0, ".ext"), (2, "")]))]
+    #[case("file.ext", harness::assert_matched_has_text([(0, "file.ext"), (2, "file")]))]
+    #[case("a/b/file.ext", harness::assert_matched_has_text([
+        (0, "a/b/file.ext"),
+        (1, "a/b/"),
+        (2, "file"),
+    ]))]
+    #[case("file", harness::assert_matched_is_none)]
+    #[case("file.txt", harness::assert_matched_is_none)]
+    fn match_glob_with_tree_and_zom<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("**/*.ext"), path, f);
     }
 
     #[rstest]
-    #[case("a/b[?]/c")]
-    #[case("a/b[$]/c")]
-    #[case("a/b[*]/c")]
-    #[case("a/b[*][*]/c")]
-    fn new_glob_with_class_escaped_wildcard_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("prefix-file.ext", harness::assert_matched_has_text([
+        (0, "prefix-file.ext"),
+        (1, "prefix"),
+        (2, "file"),
+        (3, "ext"),
+    ]))]
+    #[case("a-b-c.ext", harness::assert_matched_has_text([
+        (0, "a-b-c.ext"),
+        (1, "a"),
+        (2, "b-c"),
+        (3, "ext"),
+    ]))]
+    #[case("file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_eager_and_lazy_zom<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("$-*.*"), path, f);
     }
 
     #[rstest]
-    #[case("a/{x?z,y$}b*")]
-    #[case("a/{???,x$y,frob}b*")]
-    #[case("a/{???,x$y,frob}b*")]
-    #[case("a/{???,{x*z,y$}}b*")]
-    #[case("a{/**/b/,/b/**/}ca{t,b/**}")]
-    fn new_glob_with_alternation_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("*.*", "a.tar.gz", "a.tar")]
+    #[case("$.*", "a.tar.gz", "a")]
+    #[case("*-x", "a-x-x", "a-x")]
+    #[case("$-x", "a-x-x", "a-x")]
+    #[case("*a*", "banana", "banan")]
+    #[case("$a*", "banana", "b")]
+    #[case("src/*/**", "src/a/b/c", "a")]
+    #[case("src/$/**", "src/a/b/c", "a")]
+    fn match_glob_with_zom_captures_longest_or_shortest_text(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: &str,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let candidate = CandidatePath::from(path);
+        let matched = harness::assert_matched_is_some(glob.matched(&candidate));
+        assert_eq!(
+            matched.get(1),
+            Some(expected),
+            "unexpected capture: expression: `{}`: path: `{}`",
+            expression,
+            path,
+        );
     }
 
     #[rstest]
-    #[case("a/\\{\\}/c")]
-    #[case("a/{x,y\\,,z}/c")]
-    fn new_glob_with_literal_escaped_alternation_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("a/x/file.ext", harness::assert_matched_has_text([
+        (0, "a/x/file.ext"),
+        (1, "x"),
+        (2, "file.ext"),
+    ]))]
+    #[case("a/y/file.ext", harness::assert_matched_has_text([(1, "y")]))]
+    #[case("a/i/file.ext", harness::assert_matched_has_text([(1, "i")]))]
+    #[case("a/k/file.ext", harness::assert_matched_has_text([(1, "k")]))]
+    #[case("a/j/file.ext", harness::assert_matched_has_text([(1, "j")]))]
+    #[case("a/z/file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_ascii_class<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a/[xyi-k]/**"), path, f);
     }
 
     #[rstest]
-    #[case("a/[{][}]/c")]
-    #[case("a/{x,y[,],z}/c")]
-    fn new_glob_with_class_escaped_alternation_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("a/金/file.ext", harness::assert_matched_has_text([
+        (0, "a/金/file.ext"),
+        (1, "金"),
+        (2, "file.ext"),
+    ]))]
+    #[case("a/銀/file.ext", harness::assert_matched_has_text([(1, "銀")]))]
+    #[case("a/銅/file.ext", harness::assert_matched_is_none)]
+    #[case("a/b/file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_cjk_class<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a/[金銀]/**"), path, f);
     }
 
     #[rstest]
-    #[case("<a:0,1>")]
-    #[case("<a:0,>")]
-    #[case("<a:2>")]
-    #[case("<a:>")]
-    #[case("<a>")]
-    #[case("<a<b:0,>:0,>")]
-    // Rooted repetitions are accepted if the lower bound is one or greater.
-    #[case("</root:1,>")]
-    #[case("<[!.]*/:0,>[!.]*")]
-    fn new_glob_with_repetition_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("a/[/file.ext", harness::assert_matched_has_text([
+        (0, "a/[/file.ext"),
+        (1, "["),
+        (2, "file.ext"),
+    ]))]
+    #[case("a/]/file.ext", harness::assert_matched_has_text([(1, "]")]))]
+    #[case("a/-/file.ext", harness::assert_matched_has_text([(1, "-")]))]
+    #[case("a/b/file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_literal_escaped_class<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("a/[\\[\\]\\-]/**"),
+            path,
+            f,
+        );
     }
 
+    #[cfg(any(unix, windows))]
     #[rstest]
-    #[case("(?i)a/b/c")]
-    #[case("(?-i)a/b/c")]
-    #[case("a/(?-i)b/c")]
-    #[case("a/b/(?-i)c")]
-    #[case("(?i)a/(?-i)b/(?i)c")]
-    fn new_glob_with_flag_is_ok(#[case] expression: &str) {
-        harness::assert_new_glob_is_ok(expression);
+    #[case("", harness::assert_matched_is_none)]
+    #[case("a", harness::assert_matched_is_none)]
+    #[case("b", harness::assert_matched_is_none)]
+    #[case("ab", harness::assert_matched_is_none)]
+    #[case("a/b", harness::assert_matched_is_none)]
+    fn match_glob_with_empty_class<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        // A class is empty if it only matches separators on the target platform. Such a class
+        // never matches anything.
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a[/]b"), path, f);
     }
 
     #[rstest]
-    #[case([
-        "src/**/*.rs",
-        "doc/**/*.md",
-        "pkg/**/PKGBUILD",
-    ])]
-    #[case([
-        Glob::new("src/**/*.rs"),
-        Glob::new("doc/**/*.md"),
-        Glob::new("pkg/**/PKGBUILD"),
-    ])]
-    #[case([
-        crate::any(["a/b", "c/d"]),
-        crate::any(["{e,f,g}", "{h,i}"]),
-    ])]
-    #[case::overlapping_trees(["/root", "relative"])]
-    fn any_is_ok<'t, I>(#[case] patterns: I)
+    #[case("a-c", harness::assert_matched_has_text([(0, "a-c"), (1, "-")]))]
+    #[case("axc", harness::assert_matched_has_text([(0, "axc"), (1, "x")]))]
+    #[case("a9c", harness::assert_matched_has_text([(0, "a9c"), (1, "9")]))]
+    #[case("abc", harness::assert_matched_is_none)]
+    #[case("a0c", harness::assert_matched_is_none)]
+    #[case("a4c", harness::assert_matched_is_none)]
+    #[case("a/c", harness::assert_matched_is_none)]
+    fn match_glob_with_negated_class<T, F>(#[case] path: &str, #[case] f: F)
     where
-        I: Clone + IntoIterator,
-        I::Item: Debug + Pattern<'t>,
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        let _ = harness::assert_any_is_ok(patterns);
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a[!b0-4]c"), path, f);
     }
 
     #[rstest]
-    #[case("//a")]
-    #[case("a//b")]
-    #[case("a/b//")]
-    #[case("a//**")]
-    #[case("{//}a")]
-    #[case("{**//}")]
-    fn new_glob_with_adjacent_separator_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("abc", harness::assert_matched_has_text([(0, "abc"), (1, "b")]))]
+    #[case("azc", harness::assert_matched_has_text([(0, "azc"), (1, "z")]))]
+    #[case("aec", harness::assert_matched_is_none)]
+    #[case("aBc", harness::assert_matched_is_none)]
+    #[case("a/c", harness::assert_matched_is_none)]
+    fn match_glob_with_class_intersection<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("a[a-z&&[!aeiou]]c"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("***")]
-    #[case("****")]
-    #[case("**/**")]
-    #[case("a{**/**,/b}")]
-    #[case("**/*/***")]
-    #[case("**$")]
-    #[case("**/$**")]
-    #[case("{*$}")]
-    #[case("<*$:1,>")]
-    fn new_glob_with_adjacent_tree_and_zom_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("[a-z&&[^aeiou]]", "b", true)]
+    #[case("[a-z&&[^aeiou]]", "a", false)]
+    #[case("[!a-z&&[!aeiou]]", "a", true)]
+    #[case("[!a-z&&[!aeiou]]", "B", true)]
+    #[case("[!a-z&&[!aeiou]]", "b", false)]
+    #[case("[ab[cd]]", "d", true)]
+    #[case("[ab[!cd]]", "c", false)]
+    #[case("[[a-c]&&[b-d]&&[!c]]", "b", true)]
+    #[case("[[a-c]&&[b-d]&&[!c]]", "c", false)]
+    #[case("[ab&&b]", "b", true)]
+    #[case("[ab&&b]", "a", false)]
+    #[case("[a&b]", "&", true)]
+    #[case("[a\\&\\&b]", "&", true)]
+    #[case("[\\&&]", "&", true)]
+    #[case("[\\&&]", "a", false)]
+    #[case("[a\\&&b]", "&", true)]
+    #[case("[a\\&&b]", "b", true)]
+    #[case("[/&&[!a]]", "/", false)]
+    fn match_glob_with_class_set_expression_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.is_match(path),
+            expected,
+            "unexpected match: expression: `{}`: path: `{}`",
+            expression,
+            path,
+        );
+    }
+
+    #[rstest]
+    #[case("abc.txt", harness::assert_matched_has_text([(0, "abc.txt"), (1, "abc")]))]
+    #[case("αβγ.txt", harness::assert_matched_has_text([(0, "αβγ.txt"), (1, "αβγ")]))]
+    #[case("a_1.txt", harness::assert_matched_has_text([(0, "a_1.txt"), (1, "a_1")]))]
+    #[case("a-1.txt", harness::assert_matched_is_none)]
+    #[case("a/1.txt", harness::assert_matched_is_none)]
+    fn match_glob_with_unicode_property_class<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("<[\\p{L}\\p{N}_]:1,>.txt"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("**a")]
-    #[case("a**")]
-    #[case("a**b")]
-    #[case("a*b**")]
-    #[case("**/**a/**")]
-    fn new_glob_with_adjacent_tree_and_literal_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("λ1", harness::assert_matched_has_text([(0, "λ1"), (1, "λ"), (2, "1")]))]
+    #[case("a1", harness::assert_matched_is_none)]
+    #[case("λλ", harness::assert_matched_is_none)]
+    #[case("λ/", harness::assert_matched_is_none)]
+    fn match_glob_with_standalone_unicode_property<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("\\p{Greek}\\P{L}"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("**?")]
-    #[case("?**")]
-    #[case("?**?")]
-    #[case("?*?**")]
-    #[case("**/**?/**")]
-    fn new_glob_with_adjacent_tree_and_exactly_one_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("a/xyzb/file.ext", harness::assert_matched_has_text([
+        (0, "a/xyzb/file.ext"),
+        (1, "xyz"),
+        (2, "file.ext"),
+    ]))]
+    #[case("a/yb/file.ext", harness::assert_matched_has_text([
+        (0, "a/yb/file.ext"),
+        (1, "y"),
+        (2, "file.ext"),
+    ]))]
+    #[case("a/xyz/file.ext", harness::assert_matched_is_none)]
+    #[case("a/y/file.ext", harness::assert_matched_is_none)]
+    #[case("a/xyzub/file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_alternation<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("a/{x?z,y$}b/*"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("a/[a-z-]/c")]
-    #[case("a/[-a-z]/c")]
-    #[case("a/[-]/c")]
-    // Without special attention to escaping and character parsing, this could be mistakenly
-    // interpreted as an empty range over the character `-`. This should be rejected.
-    #[case("a/[---]/c")]
-    #[case("a/[[]/c")]
-    #[case("a/[]]/c")]
-    fn new_glob_with_unescaped_meta_characters_in_class_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("a/xyzb/file.ext", harness::assert_matched_has_text([
+        (0, "a/xyzb/file.ext"),
+        (1, "xyz"),
+        (2, "file.ext"),
+    ]))]
+    #[case("a/xyz/file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_nested_alternation<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("a/{y$,{x?z,?z}}b/*"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("*{okay,*}")]
-    #[case("{okay,*}*")]
-    #[case("${okay,*error}")]
-    #[case("{okay,error*}$")]
-    #[case("{*,okay}{okay,*}")]
-    #[case("{okay,error*}{okay,*error}")]
-    fn new_glob_with_adjacent_zom_over_alternation_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("ac", harness::assert_matched_has_text([(0, "ac"), (1, "")]))]
+    #[case("abc", harness::assert_matched_has_text([(0, "abc"), (1, "b")]))]
+    #[case("abbc", harness::assert_matched_is_none)]
+    #[case("adc", harness::assert_matched_is_none)]
+    fn match_glob_with_empty_alternative<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a{,b}c"), path, f);
     }
 
     #[rstest]
-    #[case("/slash/{okay,/error}")]
-    #[case("{okay,error/}/slash")]
-    #[case("slash/{okay,/error/,okay}/slash")]
-    #[case("{okay,error/}{okay,/error}")]
-    // TODO: This is not really an adjacent boundary like the other test cases. Is there a better
-    //       place for this?
-    #[case("{**}")]
-    #[case("slash/{**/error}")]
-    #[case("{error/**}/slash")]
-    #[case("slash/{okay/**,**/error}")]
-    #[case("{**/okay,error/**}/slash")]
-    #[case("{**/okay,prefix{error/**}}/slash")]
-    #[case("{**/okay,slash/{**/error}}postfix")]
-    #[case("{error/**}{okay,**/error")]
-    fn new_glob_with_adjacent_boundary_over_alternation_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("file", true)]
+    #[case("a/file", true)]
+    #[case("a/b/file", true)]
+    #[case("afile", false)]
+    #[case("a/file/b", false)]
+    fn match_glob_with_leading_tree_alternative_eq(#[case] path: &str, #[case] expected: bool) {
+        let glob = harness::assert_new_glob_is_ok("{**/,}file");
+        assert_eq!(
+            glob.is_match(path),
+            expected,
+            "unexpected match: path: `{}`",
+            path,
+        );
     }
 
     #[rstest]
-    #[case("{okay,/}")]
-    #[case("{okay,/**}")]
-    #[case("{okay,/error}")]
-    #[case("{okay,/**/error}")]
-    fn new_glob_with_rooted_alternation_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("prefix/a/b/c/postfix", harness::assert_matched_has_text([
+        (0, "prefix/a/b/c/postfix"),
+        (1, "/a/b/c"),
+    ]))]
+    #[case("prefix/a/c/postfix", harness::assert_matched_has_text([(1, "/a/c")]))]
+    #[case("prefix/a/postfix", harness::assert_matched_has_text([(1, "/a")]))]
+    #[case("prefix/a/b/postfix", harness::assert_matched_is_none)]
+    fn match_glob_with_tree_in_alternation<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("prefix{/a,/b,/**/c}/postfix"),
+            path,
+            f,
+        );
     }
 
+    #[cfg(unix)]
     #[rstest]
-    // TODO: These are not really misordered bounds like the other test cases. Is there a better
-    //       place for this?
-    #[case("<a/:0,0>")]
-    #[case("<a/:,1>")]
-    #[case("<a/:1,0>")]
-    #[case("<a/:10,1>")]
-    #[case("<a/:10,9>")]
-    fn new_glob_with_misordered_repetition_bounds_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case(b"a/\xFF.txt", harness::assert_matched_has_text([
+        (0, "a/\u{FFFD}.txt"),
+        (1, "\u{FFFD}"),
+    ]))]
+    #[case(b"a/b\xFFc.txt", harness::assert_matched_has_text([
+        (0, "a/b\u{FFFD}c.txt"),
+        (1, "b\u{FFFD}c"),
+    ]))]
+    #[case(b"\xFF/b.txt", harness::assert_matched_is_none)]
+    fn match_glob_with_non_unicode_candidate<T, F>(#[case] path: &[u8], #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt as _;
+
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("a/*.txt"),
+            OsStr::from_bytes(path),
+            f,
+        );
     }
 
     #[rstest]
-    #[case("<*:0,>")]
-    #[case("<a/*:0,>*")]
-    #[case("*<*a:0,>")]
-    fn new_glob_with_adjacent_zom_over_repetition_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("log-000.txt", harness::assert_matched_has_text([(0, "log-000.txt"), (1, "000")]))]
+    #[case("log-1970-01-01.txt", harness::assert_matched_has_text([
+        (0, "log-1970-01-01.txt"),
+        (1, "1970-01-01"),
+    ]))]
+    #[case("log-abc.txt", harness::assert_matched_is_none)]
+    #[case("log-nope-no-no.txt", harness::assert_matched_is_none)]
+    fn match_glob_with_repetition_in_alternation<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("log-{<[0-9]:3>,<[0-9]:4>-<[0-9]:2>-<[0-9]:2>}.txt"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("</:0,>")]
-    #[case("</a/:0,>")]
-    #[case("<a/:0,>/")]
-    #[case("<**:0,>")]
-    #[case("</**/a/**:0,>")]
-    #[case("<a/**:0,>/")]
-    #[case("/**</a:0,>")]
-    fn new_glob_with_adjacent_boundary_over_repetition_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("a/000000/file.ext", harness::assert_matched_has_text([
+        (0, "a/000000/file.ext"),
+        (1, "000000"),
+        (2, "file.ext"),
+    ]))]
+    #[case("a/123456/file.ext", harness::assert_matched_has_text([
+        (0, "a/123456/file.ext"),
+        (1, "123456"),
+        (2, "file.ext"),
+    ]))]
+    #[case("a/00000/file.ext", harness::assert_matched_is_none)]
+    #[case("a/0000000/file.ext", harness::assert_matched_is_none)]
+    #[case("a/bbbbbb/file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_repetition<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("a/<[0-9]:6>/*"),
+            path,
+            f,
+        );
     }
 
-    // Rooted repetitions are rejected if their lower bound is zero; any other lower bound is
-    // accepted.
     #[rstest]
-    #[case("</root:0,>maybe")]
-    #[case("</root>")]
-    fn new_glob_with_sometimes_rooted_repetition_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("a/x.rs", harness::assert_matched_has_text([(0, "a/x.rs"), (1, "a/"), (2, "x")]))]
+    #[case("a/b/c/x.rs", harness::assert_matched_has_text([
+        (0, "a/b/c/x.rs"),
+        (1, "a/b/c/"),
+        (2, "x"),
+    ]))]
+    #[case("x.rs", harness::assert_matched_is_none)]
+    #[case("a/x.txt", harness::assert_matched_is_none)]
+    #[case("a/b/c/d/x.rs", harness::assert_matched_is_none)]
+    fn match_glob_with_component_repetition<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("<*/:1,3>*.rs"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("<a:65536>")]
-    #[case("<long:16500>")]
-    #[case("a<long:16500>b")]
-    #[case("{<a:65536>,<long:16500>}")]
-    fn new_glob_with_oversized_invariant_repetition_is_rule_err(#[case] expression: &str) {
-        let error = harness::assert_new_glob_is_err(expression);
-        assert!(
-            matches!(
-                error,
-                BuildError {
-                    kind: BuildErrorKind::Rule(_),
-                    ..
-                },
-            ),
-            "`Glob::new` is {:?}, but expected `RuleError`",
-            error,
+    #[case("a-b", harness::assert_matched_has_text([(0, "a-b"), (1, "a-b")]))]
+    #[case("a-b-c", harness::assert_matched_has_text([(0, "a-b-c"), (1, "a-b-c")]))]
+    #[case("a-b-c-d", harness::assert_matched_has_text([(0, "a-b-c-d"), (1, "a-b-c-d")]))]
+    #[case("a", harness::assert_matched_is_none)]
+    #[case("a-", harness::assert_matched_is_none)]
+    #[case("a-b-", harness::assert_matched_is_none)]
+    #[case("-a-b", harness::assert_matched_is_none)]
+    #[case("a--b", harness::assert_matched_is_none)]
+    #[case("a-b-c-d-e", harness::assert_matched_is_none)]
+    fn match_glob_with_separated_repetition<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("<[a-z]:2,4; ->"),
+            path,
+            f,
         );
     }
 
     #[rstest]
-    #[case("<a*:1000000>")]
-    fn new_glob_with_oversized_program_is_compile_err(#[case] expression: &str) {
-        let error = harness::assert_new_glob_is_err(expression);
-        assert!(
-            matches!(
-                error,
-                BuildError {
-                    kind: BuildErrorKind::Compile(_),
-                    ..
-                },
-            ),
-            "`Glob::new` is {:?}, but expected `CompileError`",
-            error,
+    #[case("v.txt", harness::assert_matched_has_text([(0, "v.txt"), (1, "")]))]
+    #[case("v1.txt", harness::assert_matched_has_text([(0, "v1.txt"), (1, "1")]))]
+    #[case("v1.22.txt", harness::assert_matched_has_text([(0, "v1.22.txt"), (1, "1.22")]))]
+    #[case("v1..2.txt", harness::assert_matched_is_none)]
+    #[case("v.1.txt", harness::assert_matched_is_none)]
+    fn match_glob_with_zero_lower_bound_separated_repetition<T, F>(
+        #[case] path: &str,
+        #[case] f: F,
+    ) where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("v<<[0-9]:1,>:0,;.>.txt"),
+            path,
+            f,
         );
     }
 
     #[rstest]
-    #[case("(?)a")]
-    #[case("(?-)a")]
-    #[case("()a")]
-    fn new_glob_with_incomplete_flag_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("src/lib.rs", harness::assert_matched_has_text([(1, "src/"), (2, "lib")]))]
+    #[case("Cargo.toml", harness::assert_matched_has_text([(2, "Cargo"), (3, "toml")]))]
+    #[case("README.md", harness::assert_matched_is_none)]
+    fn match_lazy_glob<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        static GLOB: LazyGlob = LazyGlob::new("**/*.{rs,toml}");
+
+        harness::assert_match_program_with(GLOB.clone(), path, f);
     }
 
     #[rstest]
-    #[case("/(?i)/")]
-    #[case("$(?i)$")]
-    #[case("*(?i)*")]
-    #[case("**(?i)?")]
-    #[case("a(?i)**")]
-    #[case("**(?i)a")]
-    fn new_glob_with_adjacent_separator_and_zom_over_flag_is_err(#[case] expression: &str) {
-        harness::assert_new_glob_is_err(expression);
+    #[case("a/b/file.ext", harness::assert_matched_has_text([
+        (0, "a/b/file.ext"),
+        (1, "a/b/"),
+        (2, "file.ext"),
+    ]))]
+    #[case(".a/b/file.ext", harness::assert_matched_is_none)]
+    #[case("a/.b/file.ext", harness::assert_matched_is_none)]
+    #[case("a/b/.file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_negated_class_in_repetition<T, F>(#[case] path: &str, #[case] f: F)
+    where
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
+    {
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("<[!.]*/><[!.]*:0,1>"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case([
-        "{a,b,c}",
-        "{d,e}",
-        "f/{g,/error,h}",
-    ])]
-    fn any_with_adjacent_boundary_is_err<'t, I>(#[case] patterns: I)
+    #[case("log-000.txt", harness::assert_matched_has_text([(0, "log-000.txt"), (1, "-000")]))]
+    #[case("log-123-456.txt", harness::assert_matched_has_text([
+        (0, "log-123-456.txt"),
+        (1, "-123-456"),
+    ]))]
+    #[case("log-abc.txt", harness::assert_matched_is_none)]
+    #[case("log-123-456-789.txt", harness::assert_matched_is_none)]
+    fn match_glob_with_nested_repetition<T, F>(#[case] path: &str, #[case] f: F)
     where
-        I: Clone + IntoIterator,
-        I::Item: Debug + Pattern<'t>,
+        F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_any_is_err(patterns);
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("log<-<[0-9]:3>:1,2>.txt"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("", harness::assert_matched_has_text([(0, "")]))]
-    #[case("abc", harness::assert_matched_is_none)]
-    fn match_empty_glob<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("a/file.ext", harness::assert_matched_has_text([
+        (0, "a/file.ext"),
+        (1, "a"),
+        (2, "file.ext"),
+    ]))]
+    #[case("aaa/file.ext", harness::assert_matched_has_text([
+        (0, "aaa/file.ext"),
+        (1, "aaa"),
+        (2, "file.ext"),
+    ]))]
+    #[case("b/file.ext", harness::assert_matched_has_text([(1, "b")]))]
+    #[case("bbb/file.ext", harness::assert_matched_has_text([(1, "bbb")]))]
+    #[case("file.ext", harness::assert_matched_is_none)]
+    #[case("c/file.ext", harness::assert_matched_is_none)]
+    fn match_glob_with_alternation_in_repetition<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok(""), path, f);
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("<{a,b}:1,>/**"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("a/b", harness::assert_matched_has_text([(0, "a/b")]))]
-    #[case("aa/b", harness::assert_matched_is_none)]
-    #[case("a/bb", harness::assert_matched_is_none)]
-    #[case("a/b/c", harness::assert_matched_is_none)]
-    fn match_invariant_glob<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("/var/log/network.log", harness::assert_matched_has_text([
+        (0, "/var/log/network.log"),
+        (2, "var"),
+        (3, "log/"),
+        (4, "network"),
+    ]))]
+    #[case("/home/nobody/.var/network.log", harness::assert_matched_has_text([
+        (0, "/home/nobody/.var/network.log"),
+        // TODO: The match and capture behavior of `**` here seems not only to cross boundaries,
+        //       but match only part of a component! Greedy or not, tree wildcards ought to operate
+        //       exclusively on some number of **complete** components: 
+        (1, "/home/nobody/."),
+        (2, "var"),
+        (4, "network"),
+    ]))]
+    #[case("./var/cron.log", harness::assert_matched_is_none)]
+    #[case("mnt/var/log/cron.log", harness::assert_matched_is_none)]
+    fn match_glob_with_rooted_tree<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a/b"), path, f);
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("/**/{var,.var}/**/*.log"),
+            path,
+            f,
+        );
     }
 
+    #[cfg(windows)]
     #[rstest]
-    #[case("a/b", harness::assert_matched_has_text([(0, "a/b")]))]
-    #[case("a/x/b", harness::assert_matched_has_text([(0, "a/x/b")]))]
-    #[case("a/x/y/z/b", harness::assert_matched_has_text([(0, "a/x/y/z/b"), (1, "x/y/z/")]))]
-    #[case("a", harness::assert_matched_is_none)]
-    #[case("b/a", harness::assert_matched_is_none)]
-    fn match_glob_with_tree<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("C:/src/lib.rs", harness::assert_matched_has_text([(0, "C:/src/lib.rs"), (2, "lib")]))]
+    #[case("c:/src/a/lib.rs", harness::assert_matched_has_text([(1, "a/"), (2, "lib")]))]
+    #[case("D:/src/lib.rs", harness::assert_matched_is_none)]
+    #[case("src/lib.rs", harness::assert_matched_is_none)]
+    fn match_glob_with_drive_prefix<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a/**/b"), path, f);
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("C:/src/**/*.rs"),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case(".ext", harness::assert_matched_has_text([(0, ".ext"), (2, "")]))]
-    #[case("file.ext", harness::assert_matched_has_text([(0, "file.ext"), (2, "file")]))]
-    #[case("a/b/file.ext", harness::assert_matched_has_text([
-        (0, "a/b/file.ext"),
+    #[case("x", harness::assert_matched_has_text([(0, "x")]))]
+    #[case("a/b/x/c/d", harness::assert_matched_has_text([
+        (0, "a/b/x/c/d"),
         (1, "a/b/"),
-        (2, "file"),
+        (2, "c/d"),
     ]))]
-    #[case("file", harness::assert_matched_is_none)]
-    #[case("file.txt", harness::assert_matched_is_none)]
-    fn match_glob_with_tree_and_zom<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("a.b/x/c.d", harness::assert_matched_has_text([(1, "a.b/"), (2, "c.d")]))]
+    #[case(".a/x", harness::assert_matched_is_none)]
+    #[case("a/.b/x", harness::assert_matched_is_none)]
+    #[case("x/.c", harness::assert_matched_is_none)]
+    #[case("x/c/.d/e", harness::assert_matched_is_none)]
+    fn match_glob_with_tree_excluding_hidden<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("**/*.ext"), path, f);
+        harness::assert_match_program_with(
+            harness::assert_glob_builder_is_ok(
+                GlobBuilder::new().tree_matches_hidden(false),
+                "**/x/**",
+            ),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("prefix-file.ext", harness::assert_matched_has_text([
-        (0, "prefix-file.ext"),
-        (1, "prefix"),
-        (2, "file"),
-        (3, "ext"),
-    ]))]
-    #[case("a-b-c.ext", harness::assert_matched_has_text([
-        (0, "a-b-c.ext"),
-        (1, "a"),
-        (2, "b-c"),
-        (3, "ext"),
-    ]))]
-    #[case("file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_eager_and_lazy_zom<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("/var/log", harness::assert_matched_has_text([(0, "/var/log"), (1, "/var/")]))]
+    #[case("/log", harness::assert_matched_has_text([(0, "/log")]))]
+    #[case("/.var/log", harness::assert_matched_is_none)]
+    fn match_glob_with_rooted_tree_excluding_hidden<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("$-*.*"), path, f);
+        harness::assert_match_program_with(
+            harness::assert_glob_builder_is_ok(
+                GlobBuilder::new().tree_matches_hidden(false),
+                "/**/log",
+            ),
+            path,
+            f,
+        );
     }
 
     #[rstest]
-    #[case("a/x/file.ext", harness::assert_matched_has_text([
-        (0, "a/x/file.ext"),
-        (1, "x"),
-        (2, "file.ext"),
+    #[case("photos/flower.jpg", harness::assert_matched_has_text([
+        (0, "photos/flower.jpg"),
+        (2, "flower"),
+        (3, "jpg"),
     ]))]
-    #[case("a/y/file.ext", harness::assert_matched_has_text([(1, "y")]))]
-    #[case("a/i/file.ext", harness::assert_matched_has_text([(1, "i")]))]
-    #[case("a/k/file.ext", harness::assert_matched_has_text([(1, "k")]))]
-    #[case("a/j/file.ext", harness::assert_matched_has_text([(1, "j")]))]
-    #[case("a/z/file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_ascii_class<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("photos/flower.JPEG", harness::assert_matched_has_text([(3, "JPEG")]))]
+    #[case("Photos/flower.jpeg", harness::assert_matched_is_none)]
+    fn match_glob_with_case_sensitivity_flag<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a/[xyi-k]/**"), path, f);
+        harness::assert_match_program_with(
+            harness::assert_new_glob_is_ok("(?-i)photos/**/*.(?i){jpg,jpeg}"),
+            path,
+            f,
+        );
     }
 
+    // Case-sensitive literals must be encoded as such regardless of the case sensitivity of the
+    // target platform's file system APIs.
     #[rstest]
-    #[case("a/金/file.ext", harness::assert_matched_has_text([
-        (0, "a/金/file.ext"),
-        (1, "金"),
-        (2, "file.ext"),
-    ]))]
-    #[case("a/銀/file.ext", harness::assert_matched_has_text([(1, "銀")]))]
-    #[case("a/銅/file.ext", harness::assert_matched_is_none)]
-    #[case("a/b/file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_cjk_class<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("(?-i)README", "README", true)]
+    #[case("(?-i)README", "readme", false)]
+    #[case("(?-i)README", "ReadMe", false)]
+    #[case("(?-i)README.(?i)md", "README.MD", true)]
+    #[case("(?-i)README.(?i)md", "readme.md", false)]
+    #[case("(?i)doc/(?-i)README", "DOC/README", true)]
+    #[case("(?i)doc/(?-i)README", "DOC/readme", false)]
+    #[cfg_attr(unix, case("README", "readme", false))]
+    #[cfg_attr(windows, case("README", "readme", true))]
+    fn match_glob_with_case_sensitive_flag_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let is_match = glob.is_match(path);
+        assert!(
+            is_match == expected,
+            "`Program::is_match` is `{}`, but expected `{}`: in `Glob`: `{}`: with path: `{}`",
+            is_match,
+            expected,
+            glob,
+            path,
+        );
+    }
+
+    #[cfg(feature = "unicode-case")]
+    #[rstest]
+    #[case("(?i)straße", "STRASSE", true)]
+    #[case("(?i)straße", "Strasse", true)]
+    #[case("(?i)straße", "STRAẞE", true)]
+    #[case("(?i)strasse", "straße", true)]
+    #[case("(?i)STRASSE", "straße", true)]
+    #[case("(?-i)straße", "strasse", false)]
+    #[case("(?i)straße", "strase", false)]
+    #[case("(?i)ΣΟΦΟΣ", "σοφος", true)]
+    #[case("(?i)σοφος", "ΣΟΦΟΣ", true)]
+    #[case("(?i)σοφος", "σοφοσ", true)]
+    #[case("(?-i)σοφος", "σοφοσ", false)]
+    fn match_glob_with_full_case_folding_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let is_match = glob.is_match(path);
+        assert!(
+            is_match == expected,
+            "`Glob::is_match` is `{}`, but expected `{}`: in `Glob`: `{}`: path: `{}`",
+            is_match,
+            expected,
+            glob,
+            path,
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[rstest]
+    #[case("{a,b", r#"{"expression":"{a,b","start":0,"ends":[1,1,1,1]}"#)]
+    #[case("**/**", r#"{"expression":"**/**","start":3,"ends":[4]}"#)]
+    #[case("a/\"\t{", r#"{"expression":"a/\"\t{","start":4,"ends":[5]}"#)]
+    fn new_glob_parse_error_to_json_eq(#[case] expression: &str, #[case] expected: &str) {
+        let error = harness::assert_new_glob_is_err(expression);
+        assert_eq!(error.to_json().as_deref(), Some(expected));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn new_glob_rule_error_to_json_is_none() {
+        let error = harness::assert_new_glob_is_err("{foo,**}");
+        assert!(error.to_json().is_none());
+    }
+
+    #[cfg(feature = "bytes")]
+    #[rstest]
+    #[case("*.rs", b"\xFF.rs", true)]
+    #[case("*.rs", b"a\xC3\xA9\xFF.rs", true)]
+    #[case("?.rs", b"\xFF.rs", true)]
+    #[case("?.rs", b"\xC3.rs", true)]
+    #[case("?.rs", b"\xFF\xFE.rs", false)]
+    #[case("??.rs", "\u{E9}.rs".as_bytes(), false)]
+    #[case("[!a].rs", b"\xFF.rs", true)]
+    #[case("[a-z].rs", b"\xFF.rs", false)]
+    #[case("\u{FFFD}.rs", b"\xFF.rs", false)]
+    #[case("**/*.rs", b"a/\xFF/b.rs", true)]
+    #[case("**/*.rs", b".\xFF/b.rs", true)]
+    #[case("a/*", b"a/\xFF/b", false)]
+    #[case("a/*", b"a/\xFF", true)]
+    #[case("src/lib.rs", b"src/lib.rs", true)]
+    fn match_glob_with_bytes_eq(
+        #[case] expression: &str,
+        #[case] bytes: &[u8],
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let is_match = glob
+            .is_match_bytes(bytes)
+            .expect("failed to compile glob for bytes");
+        assert!(
+            is_match == expected,
+            "`Glob::is_match_bytes` is `{}`, but expected `{}`: in `Glob`: `{}`: bytes: `{:?}`",
+            is_match,
+            expected,
+            glob,
+            bytes,
+        );
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn match_glob_with_bytes_and_oversized_program_is_err() {
+        let glob = harness::assert_new_glob_is_ok("<?:0,9000>");
+        assert!(glob.is_match_bytes(b"a").is_err());
+        assert!(glob.clone().is_match_bytes(b"a").is_err());
+    }
+
+    #[cfg(feature = "dfa")]
+    #[rstest]
+    #[case("src/lib.rs", "src/lib.rs")]
+    #[case("src/lib.rs", "src/lib.rs/")]
+    #[case("**/*.rs", "src/glob/mod.rs")]
+    #[case("**/*.rs", "README.md")]
+    #[case("src/**/*.{rs,toml}", "src/Cargo.toml")]
+    #[case("src/*.rs", "src/glob/mod.rs")]
+    #[case("(?i)README.md", "readme.MD")]
+    #[case("<[a-z]/:1,3>*.md", "a/b/c/guide.md")]
+    #[case("<[a-z]/:1,3>*.md", "a/b/c/d/guide.md")]
+    #[case("[!a]/?", "b/\u{E9}")]
+    #[case("a/*", "a/")]
+    fn match_dfa_glob_eq_match_glob(#[case] expression: &str, #[case] path: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let expected = glob.is_match(path);
+        let dfa = glob.compile_dfa().expect("failed to compile DFA");
+        assert!(
+            dfa.is_match(path) == expected,
+            "`DfaGlob::is_match` is `{}`, but expected `{}`: in `Glob`: `{}`: path: `{}`",
+            !expected,
+            expected,
+            expression,
+            path,
+        );
+    }
+
+    #[rstest]
+    #[case("**", "", false)]
+    #[case("**", "a", true)]
+    #[case("**", "a/b", true)]
+    #[case("**", ".a/b", true)]
+    #[case("a/**", "a", true)]
+    #[case("a/**", "a/b", true)]
+    #[case("", "", true)]
+    fn match_non_empty_glob_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression).non_empty();
+        let is_match = glob.is_match(path);
+        assert!(
+            is_match == expected,
+            "`Glob::is_match` is `{}`, but expected `{}`: in `Glob`: `{}`: path: `{}`",
+            is_match,
+            expected,
+            glob,
+            path,
+        );
+    }
+
+    #[rstest]
+    #[case("**", "*/**", When::Always)]
+    #[case("a/**", "a/**", When::Always)]
+    #[case("**/*.rs", "**/*.rs", When::Never)]
+    fn query_non_empty_glob_depth_and_is_exhaustive_eq(
+        #[case] expression: &str,
+        #[case] expected_depth: &str,
+        #[case] expected_is_exhaustive: When,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression).non_empty();
+        assert_eq!(glob.depth(), harness::assert_new_glob_is_ok(expected_depth).depth());
+        assert_eq!(glob.is_exhaustive(), expected_is_exhaustive);
+        assert_eq!(glob.to_string(), expression);
+    }
+
+    #[rstest]
+    #[case("", false)]
+    #[case("a", true)]
+    #[case("a/b", true)]
+    #[case("/a", true)]
+    #[case(".a", false)]
+    #[case("a/.b", false)]
+    fn match_non_empty_glob_with_tree_excluding_hidden_eq(
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob =
+            harness::assert_glob_builder_is_ok(GlobBuilder::new().tree_matches_hidden(false), "**")
+                .non_empty();
+        assert_eq!(glob.is_match(path), expected, "path: `{}`", path);
+    }
+
+    #[rstest]
+    #[case("api/*", "api/users/42", Some(9))]
+    #[case("api/*", "api/users", Some(9))]
+    #[case("api/*", "api/users/", Some(9))]
+    #[case("a/**", "a/b/c", Some(5))]
+    #[case("**", "a/b", Some(3))]
+    #[case("{a,a/b}", "a/b/c", Some(3))]
+    #[case("/a", "/a/b", Some(2))]
+    #[case("", "a/b", None)]
+    #[case("api", "apiary", None)]
+    #[case("api", "apiary/x", None)]
+    #[case("*.rs", "src/lib.rs", None)]
+    fn match_end_of_glob_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: Option<usize>,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.match_end(path),
+            expected,
+            "unexpected match end: in `Glob`: `{}`: path: `{}`",
+            glob,
+            path,
+        );
+    }
+
+    #[rstest]
+    #[case("a(b)", harness::assert_matched_has_text([(0, "a(b)")]))]
+    fn match_glob_with_literal_escaped_flag<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a/[金銀]/**"), path, f);
+        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a\\(b\\)"), path, f);
     }
 
     #[rstest]
-    #[case("a/[/file.ext", harness::assert_matched_has_text([
-        (0, "a/[/file.ext"),
-        (1, "["),
-        (2, "file.ext"),
-    ]))]
-    #[case("a/]/file.ext", harness::assert_matched_has_text([(1, "]")]))]
-    #[case("a/-/file.ext", harness::assert_matched_has_text([(1, "-")]))]
-    #[case("a/b/file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_literal_escaped_class<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("src/lib.rs", harness::assert_matched_has_text([(0, "src/lib.rs")]))]
+    #[case("doc/api.md", harness::assert_matched_has_text([(0, "doc/api.md")]))]
+    #[case(
+        "pkg/arch/lib-git/PKGBUILD",
+        harness::assert_matched_has_text([(0, "pkg/arch/lib-git/PKGBUILD")]),
+    )]
+    fn match_any<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
         harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("a/[\\[\\]\\-]/**"),
+            harness::assert_any_is_ok(["src/**/*.rs", "doc/**/*.md", "pkg/**/PKGBUILD"]),
             path,
             f,
         );
     }
 
-    #[cfg(any(unix, windows))]
     #[rstest]
-    #[case("", harness::assert_matched_is_none)]
-    #[case("a", harness::assert_matched_is_none)]
-    #[case("b", harness::assert_matched_is_none)]
-    #[case("ab", harness::assert_matched_is_none)]
-    #[case("a/b", harness::assert_matched_is_none)]
-    fn match_glob_with_empty_class<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("src/lib.rs", harness::assert_matched_has_text([(0, "src/lib.rs")]))]
+    #[case("src/walk/mod.rs", harness::assert_matched_has_text([(0, "src/walk/mod.rs")]))]
+    #[case("src/lib.c", harness::assert_matched_is_none)]
+    #[case("tests/walk.rs", harness::assert_matched_is_none)]
+    fn match_all<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        // A class is empty if it only matches separators on the target platform. Such a class
-        // never matches anything.
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a[/]b"), path, f);
+        let all = harness::assert_all_is_ok(["src/**", "**/*.rs"]);
+        f(all.matched(&CandidatePath::from(path)));
     }
 
     #[rstest]
-    #[case("a-c", harness::assert_matched_has_text([(0, "a-c"), (1, "-")]))]
-    #[case("axc", harness::assert_matched_has_text([(0, "axc"), (1, "x")]))]
-    #[case("a9c", harness::assert_matched_has_text([(0, "a9c"), (1, "9")]))]
-    #[case("abc", harness::assert_matched_is_none)]
-    #[case("a0c", harness::assert_matched_is_none)]
-    #[case("a4c", harness::assert_matched_is_none)]
-    #[case("a/c", harness::assert_matched_is_none)]
-    fn match_glob_with_negated_class<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("src/lib.rs", harness::assert_matched_has_text([(0, "src/lib.rs"), (2, "lib")]))]
+    #[case("src/walk/mod.rs", harness::assert_matched_has_text([(1, "walk/"), (2, "mod")]))]
+    #[case("src/tests/walk.rs", harness::assert_matched_is_none)]
+    #[case("src/lib.c", harness::assert_matched_is_none)]
+    fn match_glob_and_not<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a[!b0-4]c"), path, f);
+        let and_not = harness::assert_new_glob_is_ok("src/**/*.rs")
+            .and_not(harness::assert_new_glob_is_ok("**/tests/**"));
+        f(and_not.matched(&CandidatePath::from(path)));
     }
 
     #[rstest]
-    #[case("a/xyzb/file.ext", harness::assert_matched_has_text([
-        (0, "a/xyzb/file.ext"),
-        (1, "xyz"),
-        (2, "file.ext"),
-    ]))]
-    #[case("a/yb/file.ext", harness::assert_matched_has_text([
-        (0, "a/yb/file.ext"),
-        (1, "y"),
-        (2, "file.ext"),
-    ]))]
-    #[case("a/xyz/file.ext", harness::assert_matched_is_none)]
-    #[case("a/y/file.ext", harness::assert_matched_is_none)]
-    #[case("a/xyzub/file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_alternation<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("a/{x?z,y$}b/*"),
-            path,
-            f,
+    #[case("", &[], true)]
+    #[case("lib.rs", &["lib.rs"], true)]
+    #[case("src/**/*.rs", &["src", "glob", "token.rs"], true)]
+    #[case("src/**/*.rs", &["src", "token.rs"], true)]
+    #[case("src/**/*.rs", &["tests", "walk.rs"], false)]
+    #[case("{a/b,c}", &["a", "b"], true)]
+    #[case("*", &["a", "b"], false)]
+    #[case("src/{lib,main}.rs", &["src", "main.rs"], true)]
+    #[cfg_attr(unix, case("/root/**", &["/", "root", "a"], true))]
+    fn match_glob_components_eq(
+        #[case] expression: &str,
+        #[case] components: &[&str],
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let path: PathBuf = components.iter().collect();
+        assert_eq!(
+            glob.is_match_components(components),
+            expected,
+            "unexpected match: expression: `{}`: components: {:?}",
+            expression,
+            components,
         );
+        assert_eq!(glob.is_match_components(components), glob.is_match(path.as_path()));
     }
 
     #[rstest]
-    #[case("a/xyzb/file.ext", harness::assert_matched_has_text([
-        (0, "a/xyzb/file.ext"),
-        (1, "xyz"),
-        (2, "file.ext"),
-    ]))]
-    #[case("a/xyz/file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_nested_alternation<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("a/{y$,{x?z,?z}}b/*"),
-            path,
-            f,
-        );
+    #[case("*-*", "a-b-c")]
+    #[case("<*/:0,>*.rs", "src/glob/token.rs")]
+    #[case("{a*,*b}", "ab")]
+    #[case("src/*.rs", "src/lib.rs")]
+    #[case("src/*.rs", "tests/walk.rs")]
+    fn match_glob_all_captures_eq_matched(#[case] expression: &str, #[case] path: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let path = CandidatePath::from(path);
+        let all: Vec<_> = glob.all_captures(&path).collect();
+        let matched: Vec<_> = glob.matched(&path).into_iter().collect();
+        assert_eq!(all.len(), matched.len());
+        for (all, matched) in all.iter().zip(matched.iter()) {
+            for index in 0..=glob.capture_count() {
+                assert_eq!(all.get(index), matched.get(index));
+            }
+        }
     }
 
     #[rstest]
-    #[case("prefix/a/b/c/postfix", harness::assert_matched_has_text([
-        (0, "prefix/a/b/c/postfix"),
-        (1, "/a/b/c"),
-    ]))]
-    #[case("prefix/a/c/postfix", harness::assert_matched_has_text([(1, "/a/c")]))]
-    #[case("prefix/a/postfix", harness::assert_matched_has_text([(1, "/a")]))]
-    #[case("prefix/a/b/postfix", harness::assert_matched_is_none)]
-    fn match_glob_with_tree_in_alternation<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("prefix{/a,/b,/**/c}/postfix"),
-            path,
-            f,
+    #[case("src/*", "src/glob", true, true)]
+    #[case("src/*", "src/glob/", false, true)]
+    #[case("src/*", "src/glob//", false, false)]
+    #[case("src/*/", "src/glob", false, true)]
+    #[case("src/*/", "src/glob/", true, true)]
+    #[case("src/*/", "src/glob//", false, false)]
+    #[case("{src/*/,*.md}", "src/glob", false, true)]
+    #[case("{src/*/,*.md}", "README.md", true, true)]
+    #[case("{src/*/,*.md}", "README.md/", false, true)]
+    #[case("a/", "a", false, true)]
+    #[case("a", "a/", false, true)]
+    #[case("/", "/", true, true)]
+    #[case("", "/", false, false)]
+    fn match_glob_with_trailing_slash_insensitive_behavior(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] is_sensitive_match: bool,
+        #[case] is_insensitive_match: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.is_match_with(path, MatchBehavior::default()),
+            is_sensitive_match,
+        );
+        assert_eq!(
+            glob.is_match_with(
+                path,
+                MatchBehavior {
+                    trailing_slash_insensitive: true,
+                },
+            ),
+            is_insensitive_match,
         );
     }
 
     #[rstest]
-    #[case("log-000.txt", harness::assert_matched_has_text([(0, "log-000.txt"), (1, "000")]))]
-    #[case("log-1970-01-01.txt", harness::assert_matched_has_text([
-        (0, "log-1970-01-01.txt"),
-        (1, "1970-01-01"),
-    ]))]
-    #[case("log-abc.txt", harness::assert_matched_is_none)]
-    #[case("log-nope-no-no.txt", harness::assert_matched_is_none)]
-    fn match_glob_with_repetition_in_alternation<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("log-{<[0-9]:3>,<[0-9]:4>-<[0-9]:2>-<[0-9]:2>}.txt"),
-            path,
-            f,
+    #[case("", 0)]
+    #[case("a/b", 0)]
+    #[case("(?i)a/b", 0)]
+    #[case("*", 1)]
+    #[case("a/**/b", 1)]
+    #[case("**/*.rs", 2)]
+    #[case("?[a-z]$", 3)]
+    #[case("{a,b/**,<c*:1,>}", 1)]
+    #[case("<a{*,?}:1,; ->/*", 2)]
+    #[case("(?i){ss,ß}*", 2)]
+    #[case("/**/a", 1)]
+    fn query_glob_capture_count_eq_program_captures(
+        #[case] expression: &str,
+        #[case] expected: usize,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(glob.capture_count(), expected);
+        // The encoder must not introduce capturing groups beyond those of capturing tokens and
+        // the implicit capture of the complete match. Otherwise, capture indices are unstable.
+        assert_eq!(
+            glob.program.captures_len(),
+            expected + 1,
+            "compiled program has unexpected captures: expression: `{}`",
+            expression,
         );
     }
 
     #[rstest]
-    #[case("a/000000/file.ext", harness::assert_matched_has_text([
-        (0, "a/000000/file.ext"),
-        (1, "000000"),
-        (2, "file.ext"),
-    ]))]
-    #[case("a/123456/file.ext", harness::assert_matched_has_text([
-        (0, "a/123456/file.ext"),
-        (1, "123456"),
-        (2, "file.ext"),
-    ]))]
-    #[case("a/00000/file.ext", harness::assert_matched_is_none)]
-    #[case("a/0000000/file.ext", harness::assert_matched_is_none)]
-    #[case("a/bbbbbb/file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_repetition<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("a/<[0-9]:6>/*"),
-            path,
-            f,
+    #[case("docs/*.html", "docs/index.html", true)]
+    #[case("**/*.rs", "src/glob/mod.rs", true)]
+    #[case("*", "a\\b", true)]
+    #[case("**/*.rs", "src\\lib.rs", true)]
+    #[case("a/?", "a/\\", true)]
+    #[case("docs/*.html", "docs\\index.html", false)]
+    #[case("docs/*.html", "docs/api/index.html", false)]
+    #[case("a/*/c", "a/b\\c", false)]
+    fn match_glob_with_slash_separators(
+        #[case] expression: &str,
+        #[case] text: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.is_match_slash(text),
+            expected,
+            "unexpected match with slash separators: expression: `{}`, text: `{}`",
+            expression,
+            text,
         );
     }
 
     #[rstest]
-    #[case("a/b/file.ext", harness::assert_matched_has_text([
-        (0, "a/b/file.ext"),
-        (1, "a/b/"),
-        (2, "file.ext"),
-    ]))]
-    #[case(".a/b/file.ext", harness::assert_matched_is_none)]
-    #[case("a/.b/file.ext", harness::assert_matched_is_none)]
-    #[case("a/b/.file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_negated_class_in_repetition<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("<[!.]*/><[!.]*:0,1>"),
-            path,
-            f,
+    #[case("", true)]
+    #[case("a/b", true)]
+    #[case("a?/[bc]", true)]
+    #[case("{a,bb/c}", true)]
+    #[case("<a:0,3>", true)]
+    #[case("<a/:0,>", true)]
+    #[case("<a/b:1,>c", true)]
+    #[case("<a:1,;/>", true)]
+    #[case("*", false)]
+    #[case("a/**", false)]
+    #[case("a/$", false)]
+    #[case("{a,b*}", false)]
+    #[case("<a:0,>", false)]
+    #[case("<{a/,b}:1,>", false)]
+    fn query_glob_is_bounded_breadth_eq(#[case] expression: &str, #[case] expected: bool) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.is_bounded_breadth(),
+            expected,
+            "unexpected breadth boundedness: expression: `{}`",
+            expression,
         );
     }
 
     #[rstest]
-    #[case("log-000.txt", harness::assert_matched_has_text([(0, "log-000.txt"), (1, "-000")]))]
-    #[case("log-123-456.txt", harness::assert_matched_has_text([
-        (0, "log-123-456.txt"),
-        (1, "-123-456"),
-    ]))]
-    #[case("log-abc.txt", harness::assert_matched_is_none)]
-    #[case("log-123-456-789.txt", harness::assert_matched_is_none)]
-    fn match_glob_with_nested_repetition<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("log<-<[0-9]:3>:1,2>.txt"),
-            path,
-            f,
+    #[case("**/*.rs")]
+    #[case("src/{lib,main}.rs")]
+    fn clone_glob_shares_program(#[case] expression: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let clone = glob.clone();
+        assert!(Arc::ptr_eq(&glob.program, &clone.program));
+        assert!(Arc::ptr_eq(&glob.program, &clone.into_owned().program));
+    }
+
+    #[rstest]
+    #[case("src/*.rs", "src/lib.rs", true)]
+    #[case("src/**", "src/token/mod.rs", true)]
+    #[case("src/*.rs", "src/token/mod.rs", false)]
+    #[case("", "", true)]
+    fn match_glob_candidate_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let candidate = CandidatePath::from(Path::new(path));
+        let is_match = glob.is_match_candidate(&candidate);
+        assert!(
+            is_match == expected,
+            "`Glob::is_match_candidate` is `{}`, but expected `{}`: in `Glob`: `{}`: candidate: \
+             `{}`",
+            is_match,
+            expected,
+            glob,
+            candidate,
         );
+        assert_eq!(is_match, glob.is_match(candidate));
     }
 
     #[rstest]
-    #[case("a/file.ext", harness::assert_matched_has_text([
-        (0, "a/file.ext"),
-        (1, "a"),
-        (2, "file.ext"),
-    ]))]
-    #[case("aaa/file.ext", harness::assert_matched_has_text([
-        (0, "aaa/file.ext"),
-        (1, "aaa"),
-        (2, "file.ext"),
-    ]))]
-    #[case("b/file.ext", harness::assert_matched_has_text([(1, "b")]))]
-    #[case("bbb/file.ext", harness::assert_matched_has_text([(1, "bbb")]))]
-    #[case("file.ext", harness::assert_matched_is_none)]
-    #[case("c/file.ext", harness::assert_matched_is_none)]
-    fn match_glob_with_alternation_in_repetition<T, F>(#[case] path: &str, #[case] f: F)
+    #[case("", harness::assert_matched_has_text([(0, "")]))]
+    #[case("a/b", harness::assert_matched_has_text([(0, "a/b")]))]
+    fn match_empty_all<T, F>(#[case] path: &str, #[case] f: F)
     where
         F: FnOnce(Option<MatchedText<'_>>) -> T,
     {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("<{a,b}:1,>/**"),
-            path,
-            f,
+        let all = harness::assert_all_is_ok([""; 0]);
+        f(all.matched(&CandidatePath::from(path)));
+    }
+
+    #[rstest]
+    #[case(["a/b", "*/*"], "a/b")]
+    #[case(["*/*", "**"], "*/*")]
+    #[case(["<*/:0,3>*", "<*/:2,5>*"], "<*/:2,3>*")]
+    #[case(["**/*.rs", "src/**"], "src/**")]
+    #[case(["<*/:0,3>*", "*/*/*/*"], "*/*/*/*")]
+    fn query_all_depth_eq<const N: usize>(#[case] patterns: [&str; N], #[case] expected: &str) {
+        let all = harness::assert_all_is_ok(patterns);
+        let expected = harness::assert_new_glob_is_ok(expected).depth();
+        assert_eq!(all.depth(), expected);
+    }
+
+    #[rstest]
+    #[case(["*", "*/*"])]
+    #[case(["a/b", "**/*.rs", "*"])]
+    #[case(["<*/:0,2>*", "<*/:4,>*"])]
+    fn all_with_disjoint_depths_is_unsatisfiable<const N: usize>(#[case] patterns: [&str; N]) {
+        let error = match crate::all(patterns) {
+            Ok(_) => panic!("`all` is `Ok`, but expected `Err`: patterns: {:?}", patterns),
+            Err(error) => error,
+        };
+        assert!(error.is_unsatisfiable());
+    }
+
+    #[rstest]
+    #[case(["a/b", "*/*"], Some("a/b"))]
+    #[case(["*/*", "a/b"], Some("a/b"))]
+    #[case(["*/*", "**"], None)]
+    fn query_all_text_eq<const N: usize>(
+        #[case] patterns: [&str; N],
+        #[case] expected: Option<&str>,
+    ) {
+        let all = harness::assert_all_is_ok(patterns);
+        assert_eq!(all.text().invariant().as_deref(), expected);
+    }
+
+    #[rstest]
+    #[case([&["/a/**"] as &[_], &["**/*.rs"]], When::Always)]
+    #[case([&["/a/**", "b/**"] as &[_], &["/a/**", "**/*.rs"]], When::Sometimes)]
+    #[case([&["/a/**", "b/**"] as &[_], &["b/**"]], When::Never)]
+    fn query_all_has_root_eq<const N: usize>(
+        #[case] patterns: [&[&str]; N],
+        #[case] expected: When,
+    ) {
+        // Globs always or never have roots, so `Any` is used to construct component programs
+        // that sometimes have roots.
+        let all = harness::assert_all_is_ok(
+            patterns.map(|patterns| harness::assert_any_is_ok(patterns.iter().copied())),
         );
+        assert_eq!(all.has_root(), expected);
+    }
+
+    #[rstest]
+    #[case(["a/**", "**"], When::Always)]
+    #[case(["a/**", "**/*.rs"], When::Never)]
+    fn query_all_is_exhaustive_eq<const N: usize>(
+        #[case] patterns: [&str; N],
+        #[case] expected: When,
+    ) {
+        let all = harness::assert_all_is_ok(patterns);
+        assert_eq!(all.is_exhaustive(), expected);
+    }
+
+    #[rstest]
+    #[case("src/lib.rs", [0, 1, 3])]
+    #[case("src/walk/mod.rs", [0, 1])]
+    #[case("doc/guide.md", [2])]
+    #[case("README.md", [2, 3])]
+    #[case("LICENSE", [])]
+    fn match_glob_set<const N: usize>(#[case] path: &str, #[case] expected: [usize; N]) {
+        let set =
+            harness::assert_glob_set_is_ok(["**/*.rs", "src/**", "**/*.md", "{*/lib.rs,*.md}"]);
+        assert_eq!(set.matches(path), expected);
+        assert_eq!(set.is_match(path), N != 0);
+    }
+
+    #[test]
+    fn match_glob_set_of_globs() {
+        let set = harness::assert_glob_set_is_ok([
+            harness::assert_new_glob_is_ok("**/*.{go,rs}"),
+            harness::assert_new_glob_is_ok("{a}/b"),
+        ]);
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.matches("a/b"), [1]);
+        assert_eq!(set.matches("a/b.rs"), [0]);
+    }
+
+    #[test]
+    fn new_empty_glob_set_matches_nothing() {
+        let set = harness::assert_glob_set_is_ok([""; 0]);
+        assert!(set.is_empty());
+        assert!(!set.is_match(""));
+    }
+
+    #[test]
+    fn new_glob_set_with_invalid_expression_is_err() {
+        assert!(GlobSet::new(["**/*.rs", "a/**/**"]).is_err());
     }
 
     #[rstest]
-    #[case("/var/log/network.log", harness::assert_matched_has_text([
-        (0, "/var/log/network.log"),
-        (2, "var"),
-        (3, "log/"),
-        (4, "network"),
-    ]))]
-    #[case("/home/nobody/.var/network.log", harness::assert_matched_has_text([
-        (0, "/home/nobody/.var/network.log"),
-        // TODO: The match and capture behavior of `**` here seems not only to cross boundaries,
-        //       but match only part of a component! Greedy or not, tree wildcards ought to operate
-        //       exclusively on some number of **complete** components: 
-        (1, "/home/nobody/."),
-        (2, "var"),
-        (4, "network"),
-    ]))]
-    #[case("./var/cron.log", harness::assert_matched_is_none)]
-    #[case("mnt/var/log/cron.log", harness::assert_matched_is_none)]
-    fn match_glob_with_rooted_tree<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("/**/{var,.var}/**/*.log"),
-            path,
-            f,
-        );
+    #[case(["<ab:0,100>", "a"], 201)]
+    #[case(["{a,bc}<?:2,>", "**/*.rs"], 11)]
+    fn new_glob_set_with_size_limit_at_estimated_size_is_ok<const N: usize>(
+        #[case] expressions: [&str; N],
+        #[case] limit: usize,
+    ) {
+        let set = GlobSet::with_size_limit(expressions, limit).expect("`GlobSet` is `Err`");
+        assert_eq!(set.len(), N);
     }
 
     #[rstest]
-    #[case("photos/flower.jpg", harness::assert_matched_has_text([
-        (0, "photos/flower.jpg"),
-        (2, "flower"),
-        (3, "jpg"),
-    ]))]
-    #[case("photos/flower.JPEG", harness::assert_matched_has_text([(3, "JPEG")]))]
-    #[case("Photos/flower.jpeg", harness::assert_matched_is_none)]
-    fn match_glob_with_case_sensitivity_flag<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_new_glob_is_ok("(?-i)photos/**/*.(?i){jpg,jpeg}"),
-            path,
-            f,
-        );
+    #[case(["<ab:0,100>", "a"], 200)]
+    #[case(["{a,bc}<?:2,>", "**/*.rs"], 10)]
+    fn new_glob_set_with_size_limit_below_estimated_size_is_too_complex<const N: usize>(
+        #[case] expressions: [&str; N],
+        #[case] limit: usize,
+    ) {
+        let error = match GlobSet::with_size_limit(expressions, limit) {
+            Ok(_) => panic!("`GlobSet` is `Ok`, but expected `Err`: {:?}", expressions),
+            Err(error) => error,
+        };
+        assert!(error.is_too_complex());
+        assert!(GlobSet::new(expressions).is_ok());
     }
 
     #[rstest]
-    #[case("a(b)", harness::assert_matched_has_text([(0, "a(b)")]))]
-    fn match_glob_with_literal_escaped_flag<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(harness::assert_new_glob_is_ok("a\\(b\\)"), path, f);
+    #[case("{*.rs,*.md}", "lib.rs", Some(0))]
+    #[case("{*.rs,*.md}", "README.md", Some(1))]
+    #[case("{**/*.rs,src/**}", "src/lib.rs", Some(0))]
+    #[case("{a,b/**,c}", "b/c", Some(1))]
+    #[case("{a,{b,c}}", "c", Some(1))]
+    #[case("*.{rs,md}", "lib.rs", None)]
+    #[case("{a,b}/c", "a/c", None)]
+    #[case("**/*.rs", "lib.rs", None)]
+    fn match_glob_matched_branch_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: Option<usize>,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let candidate = CandidatePath::from(path);
+        let matched = harness::assert_matched_is_some(glob.matched(&candidate));
+        assert_eq!(matched.matched_branch(), expected);
+        assert_eq!(matched.into_owned().matched_branch(), expected);
     }
 
-    #[rstest]
-    #[case("src/lib.rs", harness::assert_matched_has_text([(0, "src/lib.rs")]))]
-    #[case("doc/api.md", harness::assert_matched_has_text([(0, "doc/api.md")]))]
-    #[case(
-        "pkg/arch/lib-git/PKGBUILD",
-        harness::assert_matched_has_text([(0, "pkg/arch/lib-git/PKGBUILD")]),
-    )]
-    fn match_any<T, F>(#[case] path: &str, #[case] f: F)
-    where
-        F: FnOnce(Option<MatchedText<'_>>) -> T,
-    {
-        harness::assert_match_program_with(
-            harness::assert_any_is_ok(["src/**/*.rs", "doc/**/*.md", "pkg/**/PKGBUILD"]),
-            path,
-            f,
+    #[test]
+    fn match_glob_into_owned_is_owned() {
+        let glob = harness::assert_new_glob_is_ok("src/**/*.rs");
+        let candidate = CandidatePath::from("src/glob/token.rs");
+        let matched = harness::assert_matched_is_some(glob.matched(&candidate));
+        assert!(!matched.is_owned());
+
+        let matched = matched.into_owned();
+        assert!(matched.is_owned());
+        assert!(matched.to_owned().is_owned());
+        let matched = matched.into_owned();
+        assert!(matched.is_owned());
+        assert_eq!(matched.complete(), "src/glob/token.rs");
+        assert_eq!(matched.get(2), Some("token"));
+    }
+
+    #[test]
+    fn match_glob_with_branches_does_not_change_captures() {
+        let glob = harness::assert_new_glob_is_ok("{*.rs,*.md}");
+        let candidate = CandidatePath::from("README.md");
+        harness::assert_matched_has_text([(0, "README.md"), (1, "README.md")])(
+            glob.matched(&candidate),
         );
+        assert!(glob.matched(&candidate).unwrap().get(2).is_none());
+    }
+
+    #[rstest]
+    #[case("src/lib.rs", Some(0))]
+    #[case("doc/guide.md", Some(1))]
+    #[case("tests/walk.rs", Some(0))]
+    fn match_any_matched_branch_eq(#[case] path: &str, #[case] expected: Option<usize>) {
+        let any = harness::assert_any_is_ok(["**/*.rs", "doc/**"]);
+        let candidate = CandidatePath::from(path);
+        let matched = harness::assert_matched_is_some(any.matched(&candidate));
+        assert_eq!(matched.matched_branch(), expected);
     }
 
     #[rstest]
@@ -2079,6 +6594,121 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case::prefixed_and_non_empty("a/b/x?z/*.ext", "xyz/file.ext", true)]
+    #[case::prefixed_and_non_empty("a/b/x?z/*.ext", "a/b/xyz/file.ext", false)]
+    #[case::only_variant_wildcard("x?z/*.ext", "xyz/file.ext", true)]
+    #[case::only_invariant_literal("a/b", "", true)]
+    #[case::only_invariant_literal("a/b", "a/b", false)]
+    #[case::rooted_tree("/**/*.ext", "a/file.ext", true)]
+    fn match_glob_without_invariant_prefix_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression).without_invariant_prefix();
+        assert_eq!(
+            glob.is_match(path),
+            expected,
+            "unexpected match: expression: `{}`: path: `{}`",
+            expression,
+            path,
+        );
+    }
+
+    #[rstest]
+    #[case::empty("", None)]
+    #[case::only_variant_wildcard("x?z/*.ext", None)]
+    #[case::only_tree("**/*.ext", None)]
+    #[case::prefixed_and_non_empty("a/b/x?z/*.ext", Some("a/b"))]
+    #[case::only_invariant_literal("a/b", Some("a/b"))]
+    #[case::invariant_alternation("{a/b}/c", Some("a/b/c"))]
+    #[case::rooted_literal("/root/**/*.ext", Some("/root"))]
+    #[case::rooted_tree("/**/*.ext", Some("/"))]
+    fn query_glob_invariant_prefix_eq(#[case] expression: &str, #[case] expected: Option<&str>) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.invariant_prefix().as_deref(),
+            expected.map(Path::new),
+            "unexpected invariant prefix: expression: `{}`",
+            expression,
+        );
+    }
+
+    #[rstest]
+    #[case::empty("", "")]
+    #[case::only_variant_wildcard("x?z/*.ext", "")]
+    #[case::only_tree("**/*.ext", "")]
+    #[case::prefixed_and_non_empty("a/b/x?z/*.ext", "a/b/")]
+    #[case::prefixed_and_partially_invariant("a/bc*/d", "a/")]
+    #[case::only_invariant_literal("a/b", "a/b")]
+    #[case::invariant_alternation("{a/b}/c/*", "{a/b}/c/")]
+    #[case::escaped_literal("a\\*b/?", "a\\*b/")]
+    #[case::flagged_literal("(?-i)a/b*", "(?-i)a/")]
+    #[case::rooted_literal("/root/**/*.ext", "/root")]
+    #[case::rooted_tree("/**/*.ext", "/")]
+    fn query_glob_invariant_prefix_len_eq(#[case] expression: &str, #[case] expected: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            &expression[..glob.invariant_prefix_len()],
+            expected,
+            "unexpected invariant prefix length: expression: `{}`",
+            expression,
+        );
+    }
+
+    #[rstest]
+    #[case::empty("", vec![("", true, Some(""), false)])]
+    #[case::literal(
+        "src/lib.rs",
+        vec![("src", true, Some("src"), false), ("lib.rs", true, Some("lib.rs"), false)],
+    )]
+    #[case::tree(
+        "src/**/*.rs",
+        vec![
+            ("src", true, Some("src"), false),
+            ("/**/", false, None, true),
+            ("*.rs", false, None, false),
+        ],
+    )]
+    #[case::invariant_alternation(
+        "{src}/lib.rs",
+        vec![("{src}", true, None, false), ("lib.rs", true, Some("lib.rs"), false)],
+    )]
+    #[case::variant_alternation(
+        "{a,b}/c",
+        vec![("{a,b}", false, None, false), ("c", true, Some("c"), false)],
+    )]
+    fn query_glob_components_eq(
+        #[case] expression: &str,
+        #[case] expected: Vec<(&str, bool, Option<&str>, bool)>,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let components: Vec<_> = glob
+            .components()
+            .map(|component| {
+                let (start, n) = component.span();
+                (
+                    &expression[start..][..n],
+                    component.is_invariant(),
+                    component.literal_text().map(String::from),
+                    component.is_tree_wildcard(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            components,
+            expected
+                .into_iter()
+                .map(|(text, is_invariant, literal, is_tree)| {
+                    (text, is_invariant, literal.map(String::from), is_tree)
+                })
+                .collect::<Vec<_>>(),
+            "unexpected components: expression: `{}`",
+            expression,
+        );
+    }
+
     #[rstest]
     #[case::empty("", "", "")]
     fn repartition_invariant_glob_has_empty_prefix_and_idempotent_expression(
@@ -2125,6 +6755,10 @@ mod tests {
     // This is not rooted, because character classes never match separators. This example compiles
     // an empty character class, which never matches anything (even nothing).
     #[cfg_attr(any(unix, windows), case("[/]root", false))]
+    #[cfg_attr(windows, case("C:/", true))]
+    #[cfg_attr(windows, case("c:/src/**", true))]
+    #[cfg_attr(windows, case("//server/share/src/**", true))]
+    #[cfg_attr(windows, case("a/C:/", false))]
     fn query_glob_has_root_eq(#[case] expression: &str, #[case] expected: bool) {
         let glob = harness::assert_new_glob_is_ok(expression);
         let has_root = glob.has_root().is_always();
@@ -2195,14 +6829,409 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case("a/*", [
+        (TokenKind::Concatenation, (0, 3)),
+        (TokenKind::Literal, (0, 1)),
+        (TokenKind::Separator, (1, 1)),
+        (TokenKind::ZeroOrMore, (2, 1)),
+    ])]
+    #[case("{a,?}/**", [
+        (TokenKind::Concatenation, (0, 8)),
+        (TokenKind::Alternation, (0, 5)),
+        (TokenKind::Concatenation, (1, 1)),
+        (TokenKind::Literal, (1, 1)),
+        (TokenKind::Concatenation, (3, 1)),
+        (TokenKind::ExactlyOne, (3, 1)),
+        (TokenKind::Tree, (5, 3)),
+    ])]
+    #[case("<[a-z]:1,; ->", [
+        (TokenKind::Concatenation, (0, 13)),
+        (TokenKind::Repetition, (0, 13)),
+        (TokenKind::Concatenation, (1, 5)),
+        (TokenKind::Class, (1, 5)),
+        (TokenKind::Concatenation, (11, 1)),
+        (TokenKind::Literal, (11, 1)),
+    ])]
+    fn query_glob_tokens_with_spans_eq(
+        #[case] expression: &str,
+        #[case] expected: impl AsRef<[(TokenKind, Span)]>,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let tokens: Vec<_> = glob.tokens_with_spans().collect();
+        let expected = expected.as_ref();
+        assert!(
+            tokens == expected,
+            "`Glob::tokens_with_spans` is `{:?}`, but expected `{:?}`: in `Glob`: `{}`",
+            tokens,
+            expected,
+            glob,
+        );
+    }
+
+    #[rstest]
+    #[case("src/lib.rs", ["src", "/", "lib.rs"])]
+    #[case("**/*.{md,txt}", ["**/", "*", ".", "{md,txt}"])]
+    #[case("<[a-z]:1,; ->", ["<[a-z]:1,; ->"])]
+    fn query_glob_tokens_eq(
+        #[case] expression: &str,
+        #[case] expected: impl AsRef<[&'static str]>,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let tokens: Vec<_> = glob
+            .tokens()
+            .map(|token| {
+                let (start, n) = token.span();
+                &expression[start..][..n]
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            expected.as_ref(),
+            "unexpected tokens: expression: `{}`",
+            expression,
+        );
+    }
+
+    #[rstest]
+    #[case("src/lib.rs", ["src", "lib.rs"], [])]
+    #[case("{a,<b:1,3>}/**/*.md", ["a", "b", ".md"], [(1, Some(3))])]
+    #[case("<{a,b}/:0,>[!c]?", ["a", "b"], [(0, None)])]
+    fn query_glob_accept_visitor_eq(
+        #[case] expression: &str,
+        #[case] expected_literals: impl AsRef<[&'static str]>,
+        #[case] expected_bounds: impl AsRef<[(usize, Option<usize>)]>,
+    ) {
+        use crate::query::{GlobToken, TokenVisitor};
+
+        #[derive(Default)]
+        struct Visitor {
+            literals: Vec<String>,
+            bounds: Vec<(usize, Option<usize>)>,
+            wildcards: usize,
+        }
+
+        impl<'i, 't> TokenVisitor<'i, 't> for Visitor {
+            fn visit_literal(&mut self, token: GlobToken<'i, 't>) {
+                self.literals.extend(token.literal_text().map(String::from));
+            }
+
+            fn visit_repetition(&mut self, token: GlobToken<'i, 't>) {
+                self.bounds.extend(token.repetition_bounds());
+                token.walk(self);
+            }
+
+            fn visit_wildcard(&mut self, _: GlobToken<'i, 't>) {
+                self.wildcards += 1;
+            }
+        }
+
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let mut visitor = Visitor::default();
+        glob.accept(&mut visitor);
+        assert_eq!(visitor.literals, expected_literals.as_ref());
+        assert_eq!(visitor.bounds, expected_bounds.as_ref());
+        assert_eq!(
+            visitor.wildcards,
+            glob.tokens_with_spans()
+                .filter(|(kind, _)| {
+                    matches!(
+                        kind,
+                        TokenKind::ExactlyOne | TokenKind::ZeroOrMore | TokenKind::Tree
+                    )
+                })
+                .count(),
+        );
+    }
+
+    #[rstest]
+    #[case("**/*.rs", [])]
+    #[case("<a>", [(0, None, "a")])]
+    #[case("<a*/:0,>*.rs", [(0, None, "a*/")])]
+    #[case("<[a-z]:1,; ->", [(1, None, "[a-z]")])]
+    #[case("{<a:2>,<b:1,3>}", [(2, Some(2), "a"), (1, Some(3), "b")])]
+    #[case("<<a:1,2>/:0,>", [(0, None, "<a:1,2>/"), (1, Some(2), "a")])]
+    fn query_glob_repetitions_eq(
+        #[case] expression: &str,
+        #[case] expected: impl AsRef<[(usize, Option<usize>, &'static str)]>,
+    ) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let repetitions: Vec<_> = glob.repetitions().collect();
+        let expected = expected.as_ref();
+        assert!(
+            repetitions == expected,
+            "`Glob::repetitions` is `{:?}`, but expected `{:?}`: in `Glob`: `{}`",
+            repetitions,
+            expected,
+            glob,
+        );
+    }
+
+    #[rstest]
+    #[case("**", When::Always)]
+    #[case("a/**", When::Always)]
+    #[case("a/**/", When::Always)]
+    #[case("{a/**,b/**}", When::Always)]
+    #[case("<*/:1,>", When::Always)]
+    #[case("a/<*/>*", When::Always)]
+    #[case("{a/**,**/b}", When::Sometimes)]
+    #[case("{**/,}target/**", When::Always)]
+    #[case("", When::Never)]
+    #[case("a", When::Never)]
+    #[case("**/*.rs", When::Never)]
+    #[case("a/**/b", When::Never)]
+    #[case("<*/:0,4>", When::Never)]
+    fn query_glob_is_exhaustive_eq(#[case] expression: &str, #[case] expected: When) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.is_exhaustive(),
+            expected,
+            "unexpected exhaustiveness: expression: `{}`",
+            expression,
+        );
+    }
+
+    #[rstest]
+    #[case("**", When::Never)]
+    #[case("a/**", When::Never)]
+    #[case("{a/**,b/**}", When::Never)]
+    fn query_glob_with_tree_excluding_hidden_is_exhaustive_eq(
+        #[case] expression: &str,
+        #[case] expected: When,
+    ) {
+        let glob = harness::assert_glob_builder_is_ok(
+            GlobBuilder::new().tree_matches_hidden(false),
+            expression,
+        );
+        assert_eq!(
+            glob.is_exhaustive(),
+            expected,
+            "unexpected exhaustiveness: expression: `{}`",
+            expression,
+        );
+    }
+
+    #[rstest]
+    #[case("a/", TerminalKind::Directory)]
+    #[case("src/*/", TerminalKind::Directory)]
+    #[case("{a/,b/}", TerminalKind::Directory)]
+    #[case("<a/:1,>", TerminalKind::Directory)]
+    #[case("", TerminalKind::Any)]
+    #[case("a", TerminalKind::Any)]
+    #[case("**", TerminalKind::Any)]
+    #[case("**/", TerminalKind::Any)]
+    #[case("a/**/", TerminalKind::Any)]
+    #[case("**/*.rs", TerminalKind::Any)]
+    #[case("{a/,b}", TerminalKind::Any)]
+    #[case("<a/:0,>", TerminalKind::Any)]
+    fn query_glob_terminal_kind_eq(#[case] expression: &str, #[case] expected: TerminalKind) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.terminal_kind(),
+            expected,
+            "unexpected terminal kind: expression: `{}`",
+            expression,
+        );
+    }
+
+    // Globs that only match directories only match paths that terminate with a separator.
+    #[rstest]
+    #[case("a/", "a")]
+    #[case("src/*/", "src/glob")]
+    #[case("{a/,b/}", "b")]
+    #[case("<a/:1,>", "a/a")]
+    fn match_glob_with_directory_terminal_kind_eq(#[case] expression: &str, #[case] path: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(glob.terminal_kind(), TerminalKind::Directory);
+        assert!(!glob.is_match(path));
+        assert!(glob.is_match(format!("{}/", path).as_str()));
+        assert!(glob.is_match_with(
+            path,
+            MatchBehavior {
+                trailing_slash_insensitive: true,
+            },
+        ));
+    }
+
+    #[rstest]
+    #[case("a/b", "a/b")]
+    #[case("{a}/b", "a/b")]
+    #[case("{{a}}/b", "a/b")]
+    #[case("{a,{b,c}}", "{a,b,c}")]
+    #[case("{{a,b},c}", "{a,b,c}")]
+    #[case("<a:1>", "a")]
+    #[case("<a:1,1>/b", "{a}/b")]
+    #[case("<a:0,>", "<a>")]
+    #[case("**/*.rs", "{**/*.rs}")]
+    fn glob_with_insignificant_syntax_eq(#[case] left: &str, #[case] right: &str) {
+        let left = harness::assert_new_glob_is_ok(left);
+        let right = harness::assert_new_glob_is_ok(right);
+        assert_eq!(left, right);
+        assert_eq!(harness::hash(&left), harness::hash(&right));
+    }
+
+    #[rstest]
+    #[case("a/b", "a/c")]
+    #[case("{a,b}", "{b,a}")]
+    #[case("{a}b", "ab")]
+    #[case("[ab]", "[a-b]")]
+    #[case("(?i)a", "a")]
+    #[case("<a:1,>", "a")]
+    #[case("*", "$")]
+    fn glob_with_significant_syntax_ne(#[case] left: &str, #[case] right: &str) {
+        let left = harness::assert_new_glob_is_ok(left);
+        let right = harness::assert_new_glob_is_ok(right);
+        assert_ne!(left, right);
+    }
+
+    #[rstest]
+    #[case("", "an empty path")]
+    #[case("**/*.rs", "any directories, then any characters, then `.rs`")]
+    #[case("/**/a", "the root, then any directories, then `a`")]
+    #[case(
+        "src/{lib,main}.rs",
+        "`src`, then a separator, then either `lib` or `main`, then `.rs`"
+    )]
+    #[case("<[a-z]:1,3>", "one of `a` through `z` repeated 1 to 3 times")]
+    #[case("<?:1,>", "any one character repeated at least 1 time")]
+    #[case(
+        "<a/:2;->",
+        "(`a`, then a separator) repeated exactly 2 times separated by `-`"
+    )]
+    #[case(
+        "[!a-cx]?$",
+        "any character except `a` through `c` or `x`, then any one character, then any \
+         characters (lazily)"
+    )]
+    #[case(
+        "[a-z&&[!aeiou]]",
+        "one of (one of `a` through `z`) and (any character except `a` or `e` or `i` or `o` or `u`)"
+    )]
+    #[case("{a/b,c}*", "either (`a`, then a separator, then `b`) or `c`, then any characters")]
+    #[case("<{a,b}c:0,>", "(either `a` or `b`, then `c`) repeated any number of times")]
+    fn describe_glob_eq(#[case] expression: &str, #[case] expected: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(glob.describe(), expected);
+    }
+
+    #[rstest]
+    #[case("{*.rs,lib.rs}", vec![1])]
+    #[case("{lib.rs,*.rs}", vec![0])]
+    #[case("{a,a,a}", vec![1, 2])]
+    #[case("{*.rs,*.md,*.rs}", vec![2])]
+    #[case("{a,[a]}", vec![1])]
+    #[case("{,*}", vec![0])]
+    #[case("{src/**,src/lib.rs,doc/*.md}", vec![1])]
+    #[case("{*.rs,*.md}", vec![])]
+    #[case("{a*,*b}", vec![])]
+    #[case("{a,b}/c", vec![])]
+    #[case("lib.rs", vec![])]
+    fn query_glob_redundant_branches_eq(#[case] expression: &str, #[case] expected: Vec<usize>) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.redundant_branches(),
+            expected,
+            "unexpected redundant branches: expression: `{}`",
+            expression,
+        );
+    }
+
+    #[rstest]
+    #[case("*.rs", &["rs"])]
+    #[case("src/**/*.{rs,toml}", &["rs", "toml"])]
+    #[case("a.tar.gz", &["gz"])]
+    #[case("*.{tar.gz,tgz}", &["gz", "tgz"])]
+    #[case("{a.md,b.txt}", &["md", "txt"])]
+    #[case("*{.rs,.rs}", &["rs"])]
+    #[case("*.r{s,lib}", &["rs", "rlib"])]
+    fn glob_possible_extensions_eq(#[case] expression: &str, #[case] expected: &[&str]) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let expected: Vec<_> = expected.iter().copied().map(String::from).collect();
+        assert_eq!(glob.possible_extensions(), Some(expected));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("*")]
+    #[case("*.*")]
+    #[case("**")]
+    #[case("src/")]
+    #[case("Makefile")]
+    #[case(".gitignore")]
+    #[case("*.[rt]s")]
+    #[case("{*.rs,Makefile}")]
+    #[case("(?i)*.rs")]
+    fn glob_possible_extensions_is_none(#[case] expression: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert!(glob.possible_extensions().is_none());
+    }
+
+    #[rstest]
+    #[case("src/lib.rs", &["src/lib.rs"])]
+    #[case("config.{toml,yaml,json}", &["config.toml", "config.yaml", "config.json"])]
+    #[case("{a,a}", &["a"])]
+    #[case("<a/:1,2>b", &["a/b", "a/a/b"])]
+    #[case("<{a,b}:2;->", &["a-a", "a-b", "b-a", "b-b"])]
+    #[case(
+        "{src,tests}/{lib,main}.rs",
+        &["src/lib.rs", "src/main.rs", "tests/lib.rs", "tests/main.rs"],
+    )]
+    fn query_glob_enumerate_eq(#[case] expression: &str, #[case] expected: &[&str]) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        let expected: Vec<_> = expected.iter().copied().map(PathBuf::from).collect();
+        assert_eq!(
+            glob.enumerate(),
+            Some(expected),
+            "unexpected enumeration: expression: `{}`",
+            expression,
+        );
+    }
+
+    #[rstest]
+    #[case("*.rs")]
+    #[case("**")]
+    #[case("[ab]")]
+    #[case("<a:1,>")]
+    #[case("(?i)a")]
+    #[case("<{a,b}:11>")]
+    fn query_glob_enumerate_is_none(#[case] expression: &str) {
+        let glob = harness::assert_new_glob_is_ok(expression);
+        assert!(glob.enumerate().is_none());
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("**/*.rs")]
+    #[case("src/{lib,main}.rs")]
+    fn parse_glob_from_owned_str_eq(#[case] expression: &str) {
+        // The expression is copied into a `String` to ensure that the parsed `Glob` does not
+        // borrow it.
+        let glob: Glob<'static> = expect!(
+            String::from(expression).parse(),
+            "`str::parse` is `Err`, but expected `Ok`: in expression: `{}`",
+            expression,
+        );
+        assert_eq!(glob, harness::assert_new_glob_is_ok(expression));
+    }
+
+    #[rstest]
+    #[case::parse("{a")]
+    #[case::rule("<a:65536>")]
+    #[case::compile("<a*:1000000>")]
+    fn parse_glob_from_str_with_invalid_expression_is_err(#[case] expression: &str) {
+        assert!(expression.parse::<Glob>().is_err());
+    }
+
     #[rstest]
     #[case("", true)]
     #[case("/a/file.ext", true)]
     #[case("/a/{file.ext}", true)]
     #[case("/a/b/file.ext", true)]
     #[case("{a,a}", true)]
+    #[case("a{,}b", true)]
     #[case("<a/b:2>", true)]
     #[case("/a/{b,c}", false)]
+    #[case("a{,b}c", false)]
     #[case("<a/b:1,>", false)]
     #[case("/[ab]/file.ext", false)]
     #[case("**", false)]
@@ -2212,8 +7241,10 @@ mod tests {
     #[cfg_attr(unix, case("/[a-a]/file.ext", true))]
     #[cfg_attr(unix, case("/[a-aaa-a]/file.ext", true))]
     #[cfg_attr(unix, case("/a/(?i)file.ext", false))]
+    #[cfg_attr(unix, case("(?-i)README", true))]
     #[cfg_attr(windows, case("{a,A}", true))]
     #[cfg_attr(windows, case("/a/(?-i)file.ext", false))]
+    #[cfg_attr(windows, case("(?-i)README", false))]
     fn query_glob_text_is_invariant_eq(#[case] expression: &str, #[case] expected: bool) {
         let glob = harness::assert_new_glob_is_ok(expression);
         let is_invariant = glob.text().is_invariant();
@@ -11,6 +11,7 @@ use std::borrow::Cow;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
+use crate::diagnostics::SpanExt;
 use crate::token::{self, Depth, Text, TokenVariance};
 
 pub use crate::diagnostics::{LocatedError, Span};
@@ -355,3 +356,379 @@ impl CapturingToken {
         self.span
     }
 }
+
+/// A component of a glob expression.
+///
+/// Components are the parts of a glob expression that are delimited by separators, such as `src`,
+/// `**`, and `*.rs` in `src/**/*.rs`. Tree wildcards always form their own component. Note that
+/// some tokens, such as alternations like `{a/b,c}`, may span more than one path component, and
+/// so such tokens form a single component.
+///
+/// See [`Glob::components`].
+///
+/// # Examples
+///
+/// ```rust
+/// use wax::Glob;
+///
+/// let glob = Glob::new("src/**/*.rs").unwrap();
+/// let texts: Vec<_> = glob
+///     .components()
+///     .map(|component| component.literal_text())
+///     .collect();
+/// assert_eq!(texts, [Some("src".into()), None, None]);
+/// ```
+///
+/// [`Glob::components`]: crate::Glob::components
+#[derive(Clone, Debug)]
+pub struct GlobComponent<'i, 't>(token::Component<'i, 't, token::ExpressionMetadata>);
+
+impl<'i, 't> GlobComponent<'i, 't> {
+    /// Returns `true` if the component matches only one text.
+    ///
+    /// Invariant components consist of literals and other patterns that match exactly one text,
+    /// such as `src` and `{src}`.
+    pub fn is_invariant(&self) -> bool {
+        self.0.is_invariant()
+    }
+
+    /// Returns `true` if the component is a tree wildcard `**`.
+    pub fn is_tree_wildcard(&self) -> bool {
+        self.0.is_tree_wildcard()
+    }
+
+    /// Gets the text of the component if it consists only of literals.
+    ///
+    /// Returns `None` if the component contains any other patterns, even if the component is
+    /// [invariant][`GlobComponent::is_invariant`].
+    ///
+    /// [`GlobComponent::is_invariant`]: crate::query::GlobComponent::is_invariant
+    pub fn literal_text(&self) -> Option<Cow<'t, str>> {
+        self.0.literal().map(|literal| literal.text())
+    }
+
+    /// Gets the span of the component's sub-expression.
+    pub fn span(&self) -> Span {
+        self.0
+            .tokens()
+            .iter()
+            .map(|token| *token.annotation())
+            .reduce(SpanExt::union)
+            .expect("no tokens in component")
+    }
+}
+
+impl<'i, 't> From<token::Component<'i, 't, token::ExpressionMetadata>> for GlobComponent<'i, 't> {
+    fn from(component: token::Component<'i, 't, token::ExpressionMetadata>) -> Self {
+        GlobComponent(component)
+    }
+}
+
+/// Kind of files that a glob can match.
+///
+/// See [`Glob::terminal_kind`].
+///
+/// [`Glob::terminal_kind`]: crate::Glob::terminal_kind
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TerminalKind {
+    /// The glob only matches directories.
+    ///
+    /// Globs that always terminate with a separator, such as `src/*/`, can only match
+    /// directories. **Such globs only match paths that also terminate with a separator**, such as
+    /// `src/glob/`, and never match paths like `src/glob` nor the paths of files yielded by walks,
+    /// which do not terminate with separators. See [`MatchBehavior::trailing_slash_insensitive`].
+    ///
+    /// [`MatchBehavior::trailing_slash_insensitive`]: crate::MatchBehavior::trailing_slash_insensitive
+    Directory,
+    /// The glob may match any kind of file, including directories.
+    ///
+    /// Note that the name of a directory is not distinguished from the name of any other file, so
+    /// no glob matches only non-directory files. For example, `**/*.rs` matches a directory named
+    /// `lib.rs`.
+    Any,
+}
+
+impl TerminalKind {
+    /// Returns `true` if the glob only matches directories.
+    pub fn is_directory(&self) -> bool {
+        matches!(self, TerminalKind::Directory)
+    }
+}
+
+/// Kind of glob syntax that can be disallowed when building a glob.
+///
+/// See [`BuildError::disallowed_syntax`].
+///
+/// [`BuildError::disallowed_syntax`]: crate::BuildError::disallowed_syntax
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SyntaxKind {
+    /// An alternation, such as `{a,b}`.
+    ///
+    /// See [`GlobBuilder::allow_alternation`].
+    ///
+    /// [`GlobBuilder::allow_alternation`]: crate::GlobBuilder::allow_alternation
+    Alternation,
+    /// A character class, such as `[a-z]` or `\p{L}`.
+    ///
+    /// See [`GlobBuilder::allow_class`].
+    ///
+    /// [`GlobBuilder::allow_class`]: crate::GlobBuilder::allow_class
+    Class,
+    /// A repetition, such as `<a:1,>`.
+    ///
+    /// See [`GlobBuilder::allow_repetition`].
+    ///
+    /// [`GlobBuilder::allow_repetition`]: crate::GlobBuilder::allow_repetition
+    Repetition,
+}
+
+impl SyntaxKind {
+    pub(crate) fn plural_name(&self) -> &'static str {
+        match self {
+            SyntaxKind::Alternation => "alternations",
+            SyntaxKind::Class => "character classes",
+            SyntaxKind::Repetition => "repetitions",
+        }
+    }
+}
+
+/// Kind of a token in a glob expression.
+///
+/// See [`Glob::tokens_with_spans`].
+///
+/// [`Glob::tokens_with_spans`]: crate::Glob::tokens_with_spans
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TokenKind {
+    /// An alternation, such as `{a,b}`.
+    Alternation,
+    /// A character class, such as `[a-z]`.
+    Class,
+    /// A concatenation of tokens.
+    ///
+    /// Glob expressions and the sub-globs of alternations and repetitions are concatenations.
+    Concatenation,
+    /// A literal, such as `a`.
+    Literal,
+    /// A Windows path prefix, such as the drive `C:` or the UNC share `//server/share`.
+    ///
+    /// Prefixes are only recognized on Windows.
+    Prefix,
+    /// A repetition, such as `<a:1,3>`.
+    Repetition,
+    /// A separator `/`.
+    Separator,
+    /// An exactly-one wildcard `?`.
+    ExactlyOne,
+    /// A zero-or-more wildcard `*` or `$`.
+    ZeroOrMore,
+    /// A tree wildcard `**`.
+    Tree,
+}
+
+impl<'t, A> From<&'_ token::TokenTopology<'t, A>> for TokenKind {
+    fn from(topology: &token::TokenTopology<'t, A>) -> Self {
+        use token::{BranchKind, LeafKind, Topology, Wildcard};
+
+        match topology {
+            Topology::Branch(BranchKind::Alternation(_)) => TokenKind::Alternation,
+            Topology::Branch(BranchKind::Concatenation(_)) => TokenKind::Concatenation,
+            Topology::Branch(BranchKind::Repetition(_)) => TokenKind::Repetition,
+            Topology::Leaf(LeafKind::Class(_)) => TokenKind::Class,
+            Topology::Leaf(LeafKind::Literal(_)) => TokenKind::Literal,
+            Topology::Leaf(LeafKind::Prefix(_)) => TokenKind::Prefix,
+            Topology::Leaf(LeafKind::Separator(_)) => TokenKind::Separator,
+            Topology::Leaf(LeafKind::Wildcard(Wildcard::One(_))) => TokenKind::ExactlyOne,
+            Topology::Leaf(LeafKind::Wildcard(Wildcard::ZeroOrMore(..))) => TokenKind::ZeroOrMore,
+            Topology::Leaf(LeafKind::Wildcard(Wildcard::Tree { .. })) => TokenKind::Tree,
+        }
+    }
+}
+
+/// A token in a glob expression.
+///
+/// `GlobToken`s form a tree: branch tokens like alternations and repetitions contain other
+/// tokens, which can be traversed via [`GlobToken::tokens`] or a [`TokenVisitor`]. This is a
+/// read-only view of the token tree of a [`Glob`] and does not expose its internal
+/// representation.
+///
+/// See [`Glob::tokens`].
+///
+/// [`Glob`]: crate::Glob
+/// [`Glob::tokens`]: crate::Glob::tokens
+/// [`GlobToken::tokens`]: crate::query::GlobToken::tokens
+/// [`TokenVisitor`]: crate::query::TokenVisitor
+#[derive(Clone, Copy, Debug)]
+pub struct GlobToken<'i, 't>(&'i token::Token<'t, token::ExpressionMetadata>);
+
+impl<'i, 't> GlobToken<'i, 't> {
+    /// Gets the kind of the token.
+    pub fn kind(&self) -> TokenKind {
+        TokenKind::from(self.0.topology())
+    }
+
+    /// Gets the span of the token's sub-expression.
+    pub fn span(&self) -> Span {
+        *self.0.annotation()
+    }
+
+    /// Gets the tokens that the token contains.
+    ///
+    /// The tokens of an alternation are its branches, each of which is typically a concatenation.
+    /// The tokens of a repetition are its sub-glob and, if any, the separator between its
+    /// repetitions. Leaf tokens like literals and wildcards contain no tokens.
+    pub fn tokens(&self) -> impl 'i + Clone + Iterator<Item = GlobToken<'i, 't>> {
+        self.0
+            .tokens()
+            .map_or(&[][..], token::Composition::into_inner)
+            .iter()
+            .map(GlobToken)
+    }
+
+    /// Gets the text of the token if it is a literal.
+    pub fn literal_text(&self) -> Option<&str> {
+        self.0.as_literal().map(token::Literal::text)
+    }
+
+    /// Gets the lower and upper bounds of the token if it is a repetition.
+    ///
+    /// The upper bound is `None` if the repetition is unbounded.
+    pub fn repetition_bounds(&self) -> Option<(usize, Option<usize>)> {
+        self.0
+            .as_repetition()
+            .map(token::Repetition::bound_specification)
+    }
+
+    /// Returns `true` if the token is case-insensitive.
+    ///
+    /// Only literals are case-insensitive, such as the literal `a` in `(?i)a`.
+    pub fn is_case_insensitive(&self) -> bool {
+        self.0
+            .as_literal()
+            .is_some_and(token::Literal::is_case_insensitive)
+    }
+
+    /// Visits the token with a [`TokenVisitor`].
+    ///
+    /// This calls the function of the visitor that corresponds to the [kind][`TokenKind`] of the
+    /// token. Tokens that the token contains are only visited if that function visits them, such
+    /// as via [`GlobToken::walk`].
+    ///
+    /// [`GlobToken::walk`]: crate::query::GlobToken::walk
+    /// [`TokenKind`]: crate::query::TokenKind
+    /// [`TokenVisitor`]: crate::query::TokenVisitor
+    pub fn accept<V>(&self, visitor: &mut V)
+    where
+        V: TokenVisitor<'i, 't> + ?Sized,
+    {
+        match self.kind() {
+            TokenKind::Alternation => visitor.visit_alternation(*self),
+            TokenKind::Class => visitor.visit_class(*self),
+            TokenKind::Concatenation => visitor.visit_concatenation(*self),
+            TokenKind::Literal => visitor.visit_literal(*self),
+            TokenKind::Prefix => visitor.visit_prefix(*self),
+            TokenKind::Repetition => visitor.visit_repetition(*self),
+            TokenKind::Separator => visitor.visit_separator(*self),
+            TokenKind::ExactlyOne | TokenKind::ZeroOrMore | TokenKind::Tree => {
+                visitor.visit_wildcard(*self)
+            },
+        }
+    }
+
+    /// Visits the tokens that the token contains with a [`TokenVisitor`].
+    ///
+    /// [`TokenVisitor`]: crate::query::TokenVisitor
+    pub fn walk<V>(&self, visitor: &mut V)
+    where
+        V: TokenVisitor<'i, 't> + ?Sized,
+    {
+        for token in self.tokens() {
+            token.accept(visitor);
+        }
+    }
+}
+
+impl<'i, 't> From<&'i token::Token<'t, token::ExpressionMetadata>> for GlobToken<'i, 't> {
+    fn from(token: &'i token::Token<'t, token::ExpressionMetadata>) -> Self {
+        GlobToken(token)
+    }
+}
+
+/// Traverses the token tree of a glob expression.
+///
+/// Each function visits a [`GlobToken`] of a particular [kind][`TokenKind`]. By default, branch
+/// tokens visit the tokens that they contain via [`GlobToken::walk`] and leaf tokens do nothing,
+/// so implementations need only define the functions for the tokens that they examine. Functions
+/// may be added to this trait for new kinds of tokens, but always with such a default, so
+/// implementations are not broken by these additions.
+///
+/// # Examples
+///
+/// Counting the literals in a glob expression, including those in alternations and repetitions:
+///
+/// ```rust
+/// use wax::query::{GlobToken, TokenVisitor};
+/// use wax::Glob;
+///
+/// #[derive(Default)]
+/// struct Literals(Vec<String>);
+///
+/// impl<'i, 't> TokenVisitor<'i, 't> for Literals {
+///     fn visit_literal(&mut self, token: GlobToken<'i, 't>) {
+///         self.0.extend(token.literal_text().map(String::from));
+///     }
+/// }
+///
+/// let glob = Glob::new("src/{lib,main}.rs").unwrap();
+/// let mut literals = Literals::default();
+/// glob.accept(&mut literals);
+/// assert_eq!(literals.0, ["src", "lib", "main", ".rs"]);
+/// ```
+///
+/// [`GlobToken`]: crate::query::GlobToken
+/// [`GlobToken::walk`]: crate::query::GlobToken::walk
+/// [`TokenKind`]: crate::query::TokenKind
+pub trait TokenVisitor<'i, 't> {
+    /// Visits an alternation, such as `{a,b}`.
+    fn visit_alternation(&mut self, token: GlobToken<'i, 't>) {
+        token.walk(self);
+    }
+
+    /// Visits a character class, such as `[a-z]`.
+    fn visit_class(&mut self, token: GlobToken<'i, 't>) {
+        let _ = token;
+    }
+
+    /// Visits a concatenation, such as a branch of an alternation.
+    fn visit_concatenation(&mut self, token: GlobToken<'i, 't>) {
+        token.walk(self);
+    }
+
+    /// Visits a literal, such as `a`.
+    fn visit_literal(&mut self, token: GlobToken<'i, 't>) {
+        let _ = token;
+    }
+
+    /// Visits a Windows path prefix, such as the drive `C:`.
+    fn visit_prefix(&mut self, token: GlobToken<'i, 't>) {
+        let _ = token;
+    }
+
+    /// Visits a repetition, such as `<a:1,3>`.
+    fn visit_repetition(&mut self, token: GlobToken<'i, 't>) {
+        token.walk(self);
+    }
+
+    /// Visits a separator `/`.
+    fn visit_separator(&mut self, token: GlobToken<'i, 't>) {
+        let _ = token;
+    }
+
+    /// Visits a wildcard `?`, `*`, `$`, or `**`.
+    ///
+    /// The kind of wildcard is given by [`GlobToken::kind`].
+    ///
+    /// [`GlobToken::kind`]: crate::query::GlobToken::kind
+    fn visit_wildcard(&mut self, token: GlobToken<'i, 't>) {
+        let _ = token;
+    }
+}
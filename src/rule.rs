@@ -18,15 +18,17 @@ use std::convert::Infallible;
 #[cfg(feature = "miette")]
 use std::fmt::Display;
 use std::iter::Fuse;
-use std::path::PathBuf;
+use std::path::{self, PathBuf};
+use std::ptr;
 use std::slice;
 use thiserror::Error;
 
 use crate::diagnostics::{CompositeSpan, CorrelatedSpan, SpanExt as _, Spanned};
+use crate::query::SyntaxKind;
 use crate::token::walk::{self, TokenEntry};
 use crate::token::{
-    self, BranchKind, ExpressionMetadata, NaturalRange, Repetition, Size, Token, TokenTree,
-    Tokenized,
+    self, Archetype, BranchKind, Class, ExpressionMetadata, LeafKind, Repetition, Size, Token,
+    TokenTree, Tokenized,
 };
 use crate::{Any, BuildError, Glob, Pattern};
 
@@ -216,6 +218,19 @@ impl<'t> RuleError<'t> {
     pub fn expression(&self) -> &str {
         self.expression.as_ref()
     }
+
+    /// Returns `true` if the glob expression has a character class that matches no characters.
+    pub fn is_unsatisfiable(&self) -> bool {
+        matches!(self.kind, RuleErrorKind::Unsatisfiable)
+    }
+
+    /// Gets the kind of syntax that is disallowed in the glob expression, if any.
+    pub fn disallowed_syntax(&self) -> Option<SyntaxKind> {
+        match self.kind {
+            RuleErrorKind::DisallowedSyntax(kind) => Some(kind),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(feature = "miette")]
@@ -230,6 +245,12 @@ impl Diagnostic for RuleError<'_> {
             RuleErrorKind::AdjacentZeroOrMore => "wax::glob::adjacent_zero_or_more",
             RuleErrorKind::OversizedInvariant => "wax::glob::oversized_invariant",
             RuleErrorKind::IncompatibleBounds => "wax::glob::incompatible_bounds",
+            RuleErrorKind::NonPortableCharacter(_) => "wax::glob::non_portable_character",
+            RuleErrorKind::NonPortablePrefix => "wax::glob::non_portable_prefix",
+            RuleErrorKind::ComponentBoundary => "wax::glob::component_boundary",
+            RuleErrorKind::ParentDirectory => "wax::glob::parent_directory",
+            RuleErrorKind::DisallowedSyntax(_) => "wax::glob::disallowed_syntax",
+            RuleErrorKind::Unsatisfiable => "wax::glob::unsatisfiable",
         })))
     }
 
@@ -239,6 +260,10 @@ impl Diagnostic for RuleError<'_> {
                 "this error typically occurs when a repetition has a convergent bound that is too \
                  large",
             ))),
+            RuleErrorKind::Unsatisfiable => Some(Box::new(String::from(
+                "this class matches no characters, such as an empty intersection `[a&&b]` or a \
+                 reversed range `[z-a]`",
+            ))),
             _ => None,
         }
     }
@@ -269,6 +294,18 @@ enum RuleErrorKind {
     OversizedInvariant,
     #[error("incompatible repetition bounds")]
     IncompatibleBounds,
+    #[error("character {0:?} in literal is not portable")]
+    NonPortableCharacter(char),
+    #[error("path prefix is not portable")]
+    NonPortablePrefix,
+    #[error("component boundary `/` or `**` in single component pattern")]
+    ComponentBoundary,
+    #[error("parent directory component `..` is not allowed")]
+    ParentDirectory,
+    #[error("{} are not allowed", .0.plural_name())]
+    DisallowedSyntax(SyntaxKind),
+    #[error("unsatisfiable character class")]
+    Unsatisfiable,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -295,6 +332,12 @@ where
             .map(|token| Checked { inner: token })
             .collect()
     }
+
+    pub fn into_unannotated(self) -> Checked<Token<'t, ()>> {
+        Checked {
+            inner: self.release().into_token().fold_map(|_| ()),
+        }
+    }
 }
 
 impl<'t> Checked<Token<'t, ()>> {
@@ -329,6 +372,11 @@ impl<'t, A> Checked<Tokenized<'t, A>> {
             inner: self.release().into_owned(),
         }
     }
+
+    pub fn exclude_empty(&mut self) -> bool {
+        // Excluding empty text from a tree wildcard does not violate rules.
+        self.inner.exclude_empty()
+    }
 }
 
 impl<'t, A> Checked<Tokenized<'t, A>>
@@ -344,6 +392,17 @@ where
             tokenized.map(|tokenized| Checked { inner: tokenized }),
         )
     }
+
+    pub fn simplify(self) -> Self
+    where
+        A: Clone,
+    {
+        // `Tokenized::simplify` may violate rules, such as when an alternation that contains only
+        // a zero-or-more wildcard is removed from a repetition: `<{*}:1,2>` is simplified to
+        // `<*:1,2>`. The simplified tree is checked again and discarded if it violates any rules.
+        let tokenized = self.release();
+        check(tokenized.clone().simplify()).unwrap_or(Checked { inner: tokenized })
+    }
 }
 
 impl<T> AsRef<T> for Checked<T> {
@@ -400,6 +459,7 @@ where
     boundary(&tree)?;
     bounds(&tree)?;
     branch(&tree)?;
+    classes(&tree)?;
     size(&tree)?;
     Ok(Checked { inner: tree })
 }
@@ -456,8 +516,7 @@ where
                     outer
                         .map(Token::annotation)
                         .map(A::span)
-                        .copied()
-                        .map(From::from),
+                        .copied(),
                     *inner.annotation().span(),
                 ),
             }
@@ -508,7 +567,7 @@ where
         I: Iterator<Item = TokenEntry<'i, 't, A>>,
         P: FnMut(&'i Token<'t, A>) -> bool,
     {
-        token.map_or(false, |token| {
+        token.is_some_and(|token| {
             traversal(token).any(move |entry| predicate(entry.into_token()))
         })
     }
@@ -518,7 +577,7 @@ where
     }
 
     fn is_zom<A>(token: &Token<'_, A>) -> bool {
-        matches!(token.as_leaf(), Some(Wildcard(ZeroOrMore(_))))
+        matches!(token.as_leaf(), Some(Wildcard(ZeroOrMore(..))))
     }
 
     fn has_starting_boundary<A>(token: Option<&Token<'_, A>>) -> bool {
@@ -560,6 +619,7 @@ where
     fn check_branch<'i, 't, A>(
         terminals: Terminals<&'i Token<'t, A>>,
         outer: Outer<'i, 't, A>,
+        is_leading_alternative: bool,
     ) -> Result<(), CorrelatedError>
     where
         A: Spanned,
@@ -590,6 +650,16 @@ where
                     inner,
                 ))
             },
+            // The branch is an alternative in an alternation that begins the expression and is
+            // followed by a component; allow singular tree tokens, which match any directories
+            // that precede the component.
+            //
+            // For example, `{**/,}foo`.
+            Only((_, Some(Wildcard(Tree { has_root: false, .. }))))
+                if is_leading_alternative && right.is_some() && !has_starting_boundary(right) =>
+            {
+                Ok(())
+            },
             // Disallow singular tree tokens.
             //
             // For example, `{foo,bar,**}`.
@@ -617,8 +687,8 @@ where
             // The branch is prefixed by a zero-or-more token; disallow leading zero-or-more tokens.
             //
             // For example, `foo*{bar,*,baz}`.
-            Only((inner, Some(Wildcard(ZeroOrMore(_)))))
-            | StartEnd((inner, Some(Wildcard(ZeroOrMore(_)))), _)
+            Only((inner, Some(Wildcard(ZeroOrMore(..)))))
+            | StartEnd((inner, Some(Wildcard(ZeroOrMore(..)))), _)
                 if has_ending_zom(left) =>
             {
                 Err(CorrelatedError::new(
@@ -631,8 +701,8 @@ where
             // tokens.
             //
             // For example, `{foo,*,bar}*baz`.
-            Only((inner, Some(Wildcard(ZeroOrMore(_)))))
-            | StartEnd(_, (inner, Some(Wildcard(ZeroOrMore(_)))))
+            Only((inner, Some(Wildcard(ZeroOrMore(..)))))
+            | StartEnd(_, (inner, Some(Wildcard(ZeroOrMore(..)))))
                 if has_starting_zom(right) =>
             {
                 Err(CorrelatedError::new(
@@ -669,10 +739,7 @@ where
             // The alternation is preceded by a termination; disallow rooted sub-globs.
             //
             // For example, `{/**/foo,bar}`.
-            Only((inner, Some(Wildcard(Tree { has_root: true }))))
-            | StartEnd((inner, Some(Wildcard(Tree { has_root: true }))), _)
-                if left.is_none() =>
-            {
+            StartEnd((inner, Some(Wildcard(Tree { has_root: true, .. }))), _) if left.is_none() => {
                 Err(CorrelatedError::new(
                     RuleErrorKind::RootedSubGlob,
                     left,
@@ -686,13 +753,15 @@ where
     fn check_repetition<'i, 't, A>(
         terminals: Terminals<&'i Token<'t, A>>,
         outer: Outer<'i, 't, A>,
-        variance: NaturalRange,
+        repetition: &Repetition<'t, A>,
     ) -> Result<(), CorrelatedError>
     where
         A: Spanned,
     {
         let Outer { left, .. } = outer;
-        let lower = variance.lower().into_bound();
+        let lower = repetition.variance().lower().into_bound();
+        // The repeated token is never adjacent to itself when separated.
+        let is_adjacent = !repetition.is_separated();
         match terminals.map(|token| (token, token.as_leaf())) {
             // The repetition is preceded by a termination; disallow rooted sub-globs with a zero
             // lower bound.
@@ -711,8 +780,8 @@ where
             // lower bound.
             //
             // For example, `</**/foo>`.
-            Only((inner, Some(Wildcard(Tree { has_root: true }))))
-            | StartEnd((inner, Some(Wildcard(Tree { has_root: true }))), _)
+            Only((inner, Some(Wildcard(Tree { has_root: true, .. }))))
+            | StartEnd((inner, Some(Wildcard(Tree { has_root: true, .. }))), _)
                 if left.is_none() && lower.is_unbounded() =>
             {
                 Err(CorrelatedError::new(
@@ -724,7 +793,9 @@ where
             // The repetition begins and ends with a separator.
             //
             // For example, `</foo/bar/:1,>`.
-            StartEnd((left, _), (right, _)) if left.boundary().and(right.boundary()).is_some() => {
+            StartEnd((left, _), (right, _))
+                if is_adjacent && left.boundary().and(right.boundary()).is_some() =>
+            {
                 Err(CorrelatedError::new(
                     RuleErrorKind::AdjacentBoundary,
                     Some(left),
@@ -734,7 +805,7 @@ where
             // The repetition is a singular separator.
             //
             // For example, `</:1,>`.
-            Only((token, Some(Separator(_)))) => Err(CorrelatedError::new(
+            Only((token, Some(Separator(_)))) if is_adjacent => Err(CorrelatedError::new(
                 RuleErrorKind::AdjacentBoundary,
                 None,
                 token,
@@ -742,11 +813,9 @@ where
             // The repetition is a singular zero-or-more wildcard.
             //
             // For example, `<*:1,>`.
-            Only((token, Some(Wildcard(ZeroOrMore(_))))) => Err(CorrelatedError::new(
-                RuleErrorKind::SingularZeroOrMore,
-                None,
-                token,
-            )),
+            Only((token, Some(Wildcard(ZeroOrMore(..))))) if is_adjacent => Err(
+                CorrelatedError::new(RuleErrorKind::SingularZeroOrMore, None, token),
+            ),
             _ => Ok(()),
         }
     }
@@ -756,6 +825,7 @@ where
     while let Some(token) = tokens.pop_front() {
         use BranchKind::{Alternation, Repetition};
 
+        let is_root = ptr::eq(token, tree.as_token());
         for (left, token, right) in token
             .concatenation()
             .iter()
@@ -769,7 +839,8 @@ where
                     for token in alternation.tokens() {
                         let concatenation = token.concatenation();
                         if let Some(terminals) = concatenation.terminals() {
-                            check_branch(terminals, outer).map_err(diagnose)?;
+                            check_branch(terminals, outer, is_root && left.is_none())
+                                .map_err(diagnose)?;
                             check_alternation(terminals, outer).map_err(diagnose)?;
                         }
                     }
@@ -781,11 +852,23 @@ where
                     let token = repetition.token();
                     let concatenation = token.concatenation();
                     if let Some(terminals) = concatenation.terminals() {
-                        check_branch(terminals, outer).map_err(diagnose)?;
-                        check_repetition(terminals, outer, repetition.variance())
-                            .map_err(diagnose)?;
+                        check_branch(terminals, outer, false).map_err(diagnose)?;
+                        check_repetition(terminals, outer, repetition).map_err(diagnose)?;
                     }
                     tokens.push_back(token);
+                    if let Some(separator) = repetition.separator() {
+                        // Separators are always preceded and followed by the repeated token.
+                        let concatenation = separator.concatenation();
+                        if let Some(terminals) = concatenation.terminals() {
+                            check_branch(
+                                terminals,
+                                Outer::default().or(Some(token), Some(token)),
+                                false,
+                            )
+                            .map_err(diagnose)?;
+                        }
+                        tokens.push_back(separator);
+                    }
                 },
                 _ => {},
             }
@@ -810,7 +893,7 @@ where
                 .as_repetition()
                 .map(Repetition::bound_specification)
                 .and_then(|(lower, upper)| upper.map(|upper| (lower, upper)))
-                .map_or(false, |(lower, upper)| {
+                .is_some_and(|(lower, upper)| {
                     (lower > upper) || (lower == 0 && lower == upper)
                 })
         })
@@ -826,6 +909,114 @@ where
     }
 }
 
+// Character classes that match no characters, such as `[a&&b]` and `[z-a]`, can never match and
+// are rejected here. Classes with Unicode properties are only rejected if they are empty
+// regardless of the characters in those properties. Classes that match only separators, such as
+// `[/]`, depend on the target platform and are not rejected.
+fn classes<'t, A>(tree: &Tokenized<'t, A>) -> Result<(), RuleError<'t>>
+where
+    A: Spanned,
+{
+    // A set of code points as sorted, disjoint, and non-adjacent inclusive ranges.
+    type CodePoints = Vec<(u32, u32)>;
+
+    const UNIVERSE: [(u32, u32); 2] = [(0, 0xD7FF), (0xE000, 0x0010_FFFF)];
+
+    fn union(mut ranges: CodePoints) -> CodePoints {
+        ranges.sort_unstable();
+        ranges.into_iter().fold(vec![], |mut union, (start, end)| {
+            match union.last_mut() {
+                Some((_, last)) if start <= last.saturating_add(1) => {
+                    *last = (*last).max(end);
+                },
+                _ => union.push((start, end)),
+            }
+            union
+        })
+    }
+
+    fn intersection(lhs: &[(u32, u32)], rhs: &[(u32, u32)]) -> CodePoints {
+        lhs.iter()
+            .cartesian_product(rhs)
+            .filter_map(|(&(a, b), &(c, d))| {
+                let (start, end) = (a.max(c), b.min(d));
+                (start <= end).then_some((start, end))
+            })
+            .collect()
+    }
+
+    fn complement(ranges: &[(u32, u32)]) -> CodePoints {
+        let mut complement = vec![];
+        let mut start = 0;
+        for &(a, b) in ranges {
+            if a > start {
+                complement.push((start, a - 1));
+            }
+            start = b + 1;
+        }
+        if start <= 0x0010_FFFF {
+            complement.push((start, 0x0010_FFFF));
+        }
+        intersection(&complement, &UNIVERSE)
+    }
+
+    // Returns `None` if the code points cannot be determined, such as for Unicode properties.
+    fn code_points(archetypes: &[Archetype]) -> Option<CodePoints> {
+        let mut ranges = vec![];
+        for archetype in archetypes {
+            match archetype {
+                Archetype::Character(x) => ranges.push((u32::from(*x), u32::from(*x))),
+                Archetype::Range(a, b) => {
+                    if a <= b {
+                        ranges.push((u32::from(*a), u32::from(*b)));
+                    }
+                },
+                Archetype::Property { .. } => return None,
+                Archetype::Set(class) => ranges.extend(class_code_points(class)?),
+                Archetype::Intersection(operands) => {
+                    ranges.extend(operands.iter().try_fold(
+                        UNIVERSE.to_vec(),
+                        |intersected, operand| {
+                            Some(intersection(&intersected, &code_points(operand)?))
+                        },
+                    )?);
+                },
+            }
+        }
+        Some(union(ranges))
+    }
+
+    fn class_code_points(class: &Class) -> Option<CodePoints> {
+        code_points(class.archetypes()).map(|ranges| {
+            if class.is_negated() {
+                complement(&ranges)
+            }
+            else {
+                ranges
+            }
+        })
+    }
+
+    if let Some(token) = walk::forward(tree)
+        .map(TokenEntry::into_token)
+        .find(|token| {
+            token
+                .as_class()
+                .and_then(class_code_points)
+                .is_some_and(|ranges| ranges.is_empty())
+        })
+    {
+        Err(RuleError::new(
+            tree.expression().clone(),
+            RuleErrorKind::Unsatisfiable,
+            CompositeSpan::spanned("here", *token.annotation().span()),
+        ))
+    }
+    else {
+        Ok(())
+    }
+}
+
 fn size<'t, A>(tree: &Tokenized<'t, A>) -> Result<(), RuleError<'t>>
 where
     A: Spanned,
@@ -839,7 +1030,7 @@ where
             token
                 .variance::<Size>()
                 .invariant()
-                .map_or(false, |size| size >= MAX_INVARIANT_SIZE)
+                .is_some_and(|size| size >= MAX_INVARIANT_SIZE)
         })
     {
         Err(RuleError::new(
@@ -853,6 +1044,154 @@ where
     }
 }
 
+/// Checks that a token tree can match the same paths on all supported platforms.
+///
+/// This is not one of the rules applied by `check` and must be requested explicitly. It rejects
+/// literals with characters that cannot appear in file names on Windows (including back slashes,
+/// which are separators on Windows) and Windows path prefixes, which are only recognized on
+/// Windows.
+pub fn check_portable<'t, A>(tree: &Tokenized<'t, A>) -> Result<(), RuleError<'t>>
+where
+    A: Spanned,
+{
+    fn is_non_portable(x: char) -> bool {
+        x.is_ascii_control() || matches!(x, '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*')
+    }
+
+    for token in walk::forward(tree).map(TokenEntry::into_token) {
+        let kind = match token.as_leaf() {
+            Some(LeafKind::Literal(ref literal)) => literal
+                .text()
+                .chars()
+                .find(|&x| is_non_portable(x))
+                .map(RuleErrorKind::NonPortableCharacter),
+            Some(LeafKind::Prefix(_)) => Some(RuleErrorKind::NonPortablePrefix),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            return Err(RuleError::new(
+                tree.expression().clone(),
+                kind,
+                CompositeSpan::spanned("here", *token.annotation().span()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a token tree matches only a single component, such as a file name.
+///
+/// This is not one of the rules applied by `check` and must be requested explicitly. It rejects
+/// separators, tree wildcards, and path prefixes, which are component boundaries or can only
+/// appear in paths.
+pub fn check_component<'t, A>(tree: &Tokenized<'t, A>) -> Result<(), RuleError<'t>>
+where
+    A: Spanned,
+{
+    if let Some(token) = walk::forward(tree)
+        .map(TokenEntry::into_token)
+        .find(|token| {
+            matches!(
+                token.as_leaf(),
+                Some(
+                    LeafKind::Separator(_)
+                        | LeafKind::Wildcard(token::Wildcard::Tree { .. })
+                        | LeafKind::Prefix(_)
+                ),
+            )
+        })
+    {
+        Err(RuleError::new(
+            tree.expression().clone(),
+            RuleErrorKind::ComponentBoundary,
+            CompositeSpan::spanned("here", *token.annotation().span()),
+        ))
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// Checks that a token tree has no parent directory components `..`.
+///
+/// This is not one of the rules applied by `check` and must be requested explicitly. Components
+/// with a literal `..` are detected, including in alternatives and repetitions. Components with
+/// finite texts are also detected if any of their texts has a `..` component, such as `[.][.]`,
+/// `.[.]`, and `<.:2>`. Components with other variant text, such as `*`, are not detected.
+pub fn check_parent_directory<'t, A>(tree: &Tokenized<'t, A>) -> Result<(), RuleError<'t>>
+where
+    A: Spanned,
+{
+    // The maximum number of texts of a component that are checked for parent directories.
+    const TEXT_LIMIT: usize = 1024;
+
+    fn is_parent_directory(text: &str) -> bool {
+        text.split(|x| x == '/' || path::is_separator(x))
+            .any(|component| component == "..")
+    }
+
+    let token = tree.as_token();
+    let component = token
+        .literals()
+        .find(|(_, literal)| literal.text() == "..")
+        .map(|(component, _)| component)
+        .or_else(|| {
+            token.components().find(|component| {
+                component
+                    .texts(TEXT_LIMIT)
+                    .is_some_and(|texts| texts.iter().any(|text| is_parent_directory(text)))
+            })
+        });
+    if let Some(component) = component {
+        Err(RuleError::new(
+            tree.expression().clone(),
+            RuleErrorKind::ParentDirectory,
+            CompositeSpan::spanned("here", *component.tokens()[0].annotation().span()),
+        ))
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// Checks that a token tree has no alternations, character classes, or repetitions when they are
+/// disallowed.
+///
+/// This is not one of the rules applied by `check` and must be requested explicitly. Unicode
+/// properties outside of a class, such as `\p{L}`, are character classes.
+pub fn check_syntax<'t, A>(
+    tree: &Tokenized<'t, A>,
+    allows_alternation: bool,
+    allows_class: bool,
+    allows_repetition: bool,
+) -> Result<(), RuleError<'t>>
+where
+    A: Spanned,
+{
+    for token in walk::forward(tree).map(TokenEntry::into_token) {
+        let syntax = if !allows_alternation && token.as_alternation().is_some() {
+            Some(SyntaxKind::Alternation)
+        }
+        else if !allows_class && token.as_class().is_some() {
+            Some(SyntaxKind::Class)
+        }
+        else if !allows_repetition && token.as_repetition().is_some() {
+            Some(SyntaxKind::Repetition)
+        }
+        else {
+            None
+        };
+        if let Some(syntax) = syntax {
+            return Err(RuleError::new(
+                tree.expression().clone(),
+                RuleErrorKind::DisallowedSyntax(syntax),
+                CompositeSpan::spanned("here", *token.annotation().span()),
+            ));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -8,7 +8,7 @@ use std::cmp;
 use std::collections::VecDeque;
 use std::iter;
 use std::mem;
-use std::path::{PathBuf, MAIN_SEPARATOR_STR};
+use std::path::{self, PathBuf, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
 use std::slice;
 use std::str;
 
@@ -19,10 +19,13 @@ use crate::token::variance::invariant::{
 };
 use crate::token::variance::ops;
 use crate::token::variance::{TreeExhaustiveness, TreeVariance, VarianceFold, VarianceTerm};
-use crate::token::walk::{BranchFold, Fold, FoldMap, Starting, TokenEntry};
+use crate::token::walk::{BranchFold, Ending, Fold, FoldMap, Forward, Starting, TokenEntry};
 use crate::{StrExt as _, PATHS_ARE_CASE_INSENSITIVE};
 
-pub use crate::token::parse::{parse, ParseError, ROOT_SEPARATOR_EXPRESSION};
+pub use crate::token::parse::{
+    default_flags_expression, parse, parse_with_options, ParseError, ParseOptions,
+    ROOT_SEPARATOR_EXPRESSION,
+};
 pub use crate::token::variance::invariant::{Breadth, Depth, Invariant, Size, Text};
 pub use crate::token::variance::natural::{BoundedVariantRange, NaturalRange, VariantRange};
 pub use crate::token::variance::{Boundedness, TokenVariance, Variance};
@@ -167,20 +170,31 @@ pub enum Boundary {
 pub struct Tokenized<'t, A> {
     expression: Cow<'t, str>,
     token: Token<'t, A>,
+    separator: Separator,
 }
 
 impl<'t, A> Tokenized<'t, A> {
     pub fn into_owned(self) -> Tokenized<'static, A> {
-        let Tokenized { expression, token } = self;
+        let Tokenized {
+            expression,
+            token,
+            separator,
+        } = self;
         Tokenized {
             expression: expression.into_owned().into(),
             token: token.into_owned(),
+            separator,
         }
     }
 
     pub fn expression(&self) -> &Cow<'t, str> {
         &self.expression
     }
+
+    /// Gets the separator that delimits the components of the expression.
+    pub fn separator(&self) -> Separator {
+        self.separator
+    }
 }
 
 impl<'t, A> Tokenized<'t, A>
@@ -198,7 +212,11 @@ where
                 .expect("span offset split UTF-8 byte sequence")
         }
 
-        let Tokenized { expression, token } = self;
+        let Tokenized {
+            expression,
+            token,
+            separator,
+        } = self;
 
         let (n, text) = token.invariant_text_prefix();
         let mut unrooted = 0;
@@ -211,6 +229,14 @@ where
             .sum::<usize>()
             + unrooted;
 
+        // Paths normalize a trailing native separator, but not a trailing custom separator.
+        let text = match separator.custom() {
+            Some(separator) => text
+                .strip_suffix(separator)
+                .map(String::from)
+                .unwrap_or(text),
+            None => text,
+        };
         (
             text.into(),
             token.map(|token| Tokenized {
@@ -223,11 +249,372 @@ where
                         String::from(pop_expression_bytes(&expression, offset)).into()
                     },
                 },
+                separator,
             }),
         )
     }
 }
 
+impl<'t, A> Tokenized<'t, A>
+where
+    A: Default + Spanned,
+{
+    /// Concatenates the token trees and expressions of two tokenized expressions.
+    ///
+    /// The token trees are joined much like paths. A separator is inserted between the token
+    /// trees unless either has a boundary where they meet, such as a tree wildcard. A leading
+    /// separator in the other token tree is discarded if this token tree ends with a boundary and
+    /// a leading tree wildcard in the other token tree absorbs any trailing separator in this
+    /// token tree. For example, `src` and `*.rs` are concatenated as `src/*.rs` and `src/` and
+    /// `**/*.rs` are concatenated as `src/**/*.rs`. If either token tree is empty, then the other
+    /// is returned as is. Any inserted separator is the separator of this token tree, so the token
+    /// trees should have the same separator. If this expression contains flags, then the default
+    /// flags are restored before the other expression, because flags in this expression do not
+    /// apply to the other token tree. The concatenated token tree is not checked.
+    pub fn concatenate(self, other: Self) -> Self {
+        fn is_separator<A>(token: Option<&Token<'_, A>>) -> bool {
+            matches!(token.and_then(Token::as_leaf), Some(LeafKind::Separator(_)))
+        }
+
+        fn tree_root<'a, A>(token: Option<&'a mut Token<'_, A>>) -> Option<&'a mut bool> {
+            match token?.topology {
+                TokenTopology::Leaf(LeafKind::Wildcard(Wildcard::Tree {
+                    ref mut has_root,
+                    ..
+                })) => Some(has_root),
+                _ => None,
+            }
+        }
+
+        if self.token.is_empty() {
+            return other;
+        }
+        if other.token.is_empty() {
+            return self;
+        }
+
+        let Tokenized {
+            expression: left,
+            token: lhs,
+            separator,
+        } = self;
+        let Tokenized {
+            expression: right,
+            token: rhs,
+            ..
+        } = other;
+        let mut left = left.into_owned();
+        let mut right = right.as_ref();
+        let mut tokens = lhs.into_concatenation();
+        let mut rhs = rhs.into_concatenation();
+
+        // Discard any leading separator in the right-hand token tree if the left-hand token tree
+        // ends with a boundary, so that `a/` and `/b` are concatenated as `a/b`.
+        let mut popped = 0;
+        if tokens.last().is_some_and(Token::is_boundary) && is_separator(rhs.first()) {
+            let (start, n) = *rhs.remove(0).annotation().span();
+            popped = start + n;
+            right = &right[popped..];
+        }
+        // A leading tree wildcard absorbs the separator between the token trees, just as when
+        // parsing an expression like `src/**`.
+        let (is_tree, is_absorbed) = match tree_root(rhs.first_mut()) {
+            Some(has_root) => {
+                if is_separator(tokens.last()) {
+                    let (start, _) = *tokens.pop().unwrap().annotation().span();
+                    left.truncate(start);
+                }
+                (true, !mem::replace(has_root, true))
+            },
+            _ => (false, false),
+        };
+        // Flags apply to the remainder of an expression, but the other token tree is parsed with
+        // the default flags. The default flags are restored if this expression contains any flags
+        // (even if they are already restored), so that the concatenated expression can be parsed
+        // into the same token tree. For example, `(?i)a` and `b` are concatenated as
+        // `(?i)a(?-i)/b`.
+        if left.contains("(?") {
+            left.push_str(default_flags_expression());
+        }
+        let infix = if is_absorbed {
+            separator.expression_text()
+        }
+        else if is_tree || is_separator(tokens.last()) || is_separator(rhs.first()) {
+            "".into()
+        }
+        else if tokens.last().is_some_and(Token::is_boundary) {
+            // Tree wildcards are boundaries, but a trailing tree wildcard does not include the
+            // separator in its expression.
+            separator.expression_text()
+        }
+        else {
+            tokens.push(Token::new(
+                LeafKind::from(separator),
+                A::default().map_span(|_| (left.len(), separator.expression_len())),
+            ));
+            separator.expression_text()
+        };
+        let n = tokens.len();
+        let offset = left.len() + infix.len();
+        tokens.extend(rhs.into_iter().map(|token| {
+            token.fold_map(|annotation: A| {
+                annotation.map_span(|(start, n)| (start - popped + offset, n))
+            })
+        }));
+        if is_absorbed {
+            let span = tokens[n].annotation.span_mut();
+            *span = (span.0 - infix.len(), span.1 + infix.len());
+        }
+        let expression = format!("{}{}{}", left, infix, right);
+        Tokenized {
+            token: Token::new(
+                BranchKind::from(Concatenation::from(tokens)),
+                A::default().map_span(|_| (0, expression.len())),
+            ),
+            expression: expression.into(),
+            separator,
+        }
+    }
+
+    /// Replaces back slashes that are interpreted as separators with forward slashes in the
+    /// expression.
+    ///
+    /// The text of separators, prefixes, and tree wildcards never contains escapes, so any back
+    /// slash in their text is a separator (see `GlobBuilder::accept_backslash_separators`). Both
+    /// slashes are one byte, so spans are unchanged. The token tree is not modified.
+    pub fn normalize_separators(self) -> Self {
+        let Tokenized {
+            expression,
+            token,
+            separator,
+        } = self;
+        let mut bytes = None;
+        for token in walk::forward(&token).map(TokenEntry::into_token) {
+            if !matches!(
+                token.as_leaf(),
+                Some(
+                    LeafKind::Separator(_)
+                        | LeafKind::Prefix(_)
+                        | LeafKind::Wildcard(Wildcard::Tree { .. })
+                ),
+            ) {
+                continue;
+            }
+            let (start, n) = *token.annotation().span();
+            if expression.as_bytes()[start..start + n].contains(&b'\\') {
+                let bytes = bytes.get_or_insert_with(|| expression.as_bytes().to_vec());
+                for byte in &mut bytes[start..start + n] {
+                    if *byte == b'\\' {
+                        *byte = b'/';
+                    }
+                }
+            }
+        }
+        Tokenized {
+            expression: match bytes {
+                Some(bytes) => String::from_utf8(bytes)
+                    .expect("separator replacement split UTF-8 byte sequence")
+                    .into(),
+                _ => expression,
+            },
+            token,
+            separator,
+        }
+    }
+
+    /// Simplifies the token tree and expression without changing the paths that they match.
+    ///
+    /// Alternations with a single branch are replaced by that branch, alternations that form an
+    /// entire branch of another alternation are merged into it, empty literals are discarded from
+    /// concatenations, and adjacent literals with the same case sensitivity are coalesced. For
+    /// example, `{a}/{b,{c,d}}{,x}` is simplified to `a/{b,c,d}{,x}`. Braces are removed from the
+    /// expression and spans are shifted accordingly. Flags are applied in the order in which they
+    /// appear in an expression regardless of alternations, so removing braces does not change
+    /// their effect.
+    ///
+    /// Alternations are capturing, so removing alternations may change the indices of captures.
+    /// The simplified token tree is not checked.
+    pub fn simplify(self) -> Self {
+        fn coalesce<'t, A>(tokens: Vec<Token<'t, A>>) -> Vec<Token<'t, A>>
+        where
+            A: Spanned,
+        {
+            let mut tokens: Vec<_> = tokens
+                .into_iter()
+                .coalesce(|left, right| match (left.as_literal(), right.as_literal()) {
+                    (Some(lhs), Some(rhs))
+                        if lhs.is_case_insensitive() == rhs.is_case_insensitive() =>
+                    {
+                        let literal = Literal {
+                            text: format!("{}{}", lhs.text(), rhs.text()).into(),
+                            is_case_insensitive: lhs.is_case_insensitive(),
+                        };
+                        let (start, _) = *left.annotation().span();
+                        let (end, n) = *right.annotation().span();
+                        Ok(Token::new(
+                            LeafKind::from(literal),
+                            left.annotation.map_span(|_| (start, end + n - start)),
+                        ))
+                    },
+                    _ => Err((left, right)),
+                })
+                .collect();
+            // Discard empty literals unless the concatenation consists only of empty literals.
+            if tokens.iter().any(|token| !token.is_empty_literal()) {
+                tokens.retain(|token| !token.is_empty_literal());
+            }
+            tokens
+        }
+
+        // Simplifies the token and pushes the byte offsets of any braces that are removed from
+        // the expression. An alternation with a single branch is replaced by that branch, which
+        // is spliced into the enclosing concatenation.
+        fn simplify<'t, A>(
+            token: Token<'t, A>,
+            expression: &str,
+            braces: &mut Vec<usize>,
+        ) -> Token<'t, A>
+        where
+            A: Spanned,
+        {
+            // Gets the offsets of the delimiting braces of an alternation. Spans of alternations
+            // may begin with flags, but end with the closing brace.
+            let delimiters = |span: &Span| {
+                let (start, n) = *span;
+                let text = &expression[start..start + n];
+                // Braces are not removed from alternations with escapes, because escapes within
+                // alternations (such as `\,`) may not be valid elsewhere.
+                text.find('{')
+                    .filter(|_| text.ends_with('}') && !text.contains('\\'))
+                    .map(|offset| (start + offset, start + n - 1))
+            };
+
+            let Token {
+                topology,
+                annotation,
+            } = token;
+            match topology {
+                TokenTopology::Leaf(leaf) => Token::new(leaf, annotation),
+                TokenTopology::Branch(BranchKind::Alternation(Alternation(tokens))) => {
+                    let mut branches = vec![];
+                    for token in tokens {
+                        let token = simplify(token, expression, braces);
+                        let delimiters = match token.concatenation() {
+                            [alternation] if alternation.is_alternation() => {
+                                delimiters(alternation.annotation().span())
+                            },
+                            _ => None,
+                        };
+                        match delimiters {
+                            Some((open, close)) => {
+                                braces.extend([open, close]);
+                                if let TokenTopology::Branch(BranchKind::Alternation(
+                                    Alternation(tokens),
+                                )) = token.into_concatenation().pop().unwrap().topology
+                                {
+                                    branches.extend(tokens);
+                                }
+                            },
+                            _ => branches.push(token),
+                        }
+                    }
+                    match (branches.len(), delimiters(annotation.span())) {
+                        (1, Some((open, close))) => {
+                            braces.extend([open, close]);
+                            let branch = branches.pop().unwrap();
+                            Token::new(
+                                BranchKind::from(Concatenation::from(branch.into_concatenation())),
+                                annotation,
+                            )
+                        },
+                        _ => Token::new(BranchKind::from(Alternation::from(branches)), annotation),
+                    }
+                },
+                TokenTopology::Branch(BranchKind::Concatenation(Concatenation(tokens))) => {
+                    let tokens = tokens
+                        .into_iter()
+                        .map(|token| simplify(token, expression, braces))
+                        .flat_map(Token::into_concatenation)
+                        .collect();
+                    Token::new(
+                        BranchKind::from(Concatenation::from(coalesce(tokens))),
+                        annotation,
+                    )
+                },
+                TokenTopology::Branch(BranchKind::Repetition(mut repetition)) => {
+                    repetition.tokens = repetition
+                        .tokens
+                        .into_iter()
+                        .map(|token| simplify(token, expression, braces))
+                        .collect();
+                    Token::new(BranchKind::from(repetition), annotation)
+                },
+            }
+        }
+
+        let Tokenized {
+            expression,
+            token,
+            separator,
+        } = self;
+        let mut braces = vec![];
+        let token = simplify(token, expression.as_ref(), &mut braces);
+        braces.sort_unstable();
+        let removed = |offset: usize| braces.partition_point(|&brace| brace < offset);
+        let token = token.fold_map(|annotation: A| {
+            annotation.map_span(|(start, n)| {
+                let shifted = removed(start);
+                (start - shifted, n - (removed(start + n) - shifted))
+            })
+        });
+        let expression = if braces.is_empty() {
+            expression
+        }
+        else {
+            expression
+                .char_indices()
+                .filter(|(offset, _)| braces.binary_search(offset).is_err())
+                .map(|(_, x)| x)
+                .collect::<String>()
+                .into()
+        };
+        Tokenized {
+            expression,
+            token,
+            separator,
+        }
+    }
+}
+
+impl<'t, A> Tokenized<'t, A> {
+    /// Requires that a tree wildcard that comprises the entire token tree, such as `**`, matches
+    /// at least one component.
+    ///
+    /// Returns `true` if the token tree consists of such a tree wildcard. Otherwise, the token
+    /// tree is unchanged.
+    pub fn exclude_empty(&mut self) -> bool {
+        let token = if self.token.as_concatenation().is_some() {
+            match self.token.as_concatenation_mut().unwrap().0.as_mut_slice() {
+                [token] => token,
+                _ => return false,
+            }
+        }
+        else {
+            &mut self.token
+        };
+        match token.topology {
+            TokenTopology::Leaf(LeafKind::Wildcard(Wildcard::Tree {
+                ref mut excludes_empty,
+                ..
+            })) => {
+                *excludes_empty = true;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
 impl<'t, A> TokenTree<'t> for Tokenized<'t, A> {
     type Annotation = A;
 
@@ -315,6 +702,13 @@ impl<'t, A> Token<'t, A> {
         alternatives
     }
 
+    fn into_concatenation(self) -> Vec<Self> {
+        match self.topology {
+            TokenTopology::Branch(BranchKind::Concatenation(Concatenation(tokens))) => tokens,
+            _ => vec![self],
+        }
+    }
+
     fn into_non_trivial(self) -> Self {
         use BranchKind::{
             Alternation as AlternationKind, Concatenation as ConcatenationKind,
@@ -334,7 +728,7 @@ impl<'t, A> Token<'t, A> {
                 ) if tokens.len() == 1 => tokens.drain(..).next().unwrap(),
                 // Repetitions that occur exactly once are trivial.
                 Branch(RepetitionKind(repetition)) if repetition.variance().is_one() => {
-                    *repetition.token
+                    repetition.tokens.into_iter().next().unwrap()
                 },
                 _ => {
                     return token;
@@ -474,12 +868,15 @@ impl<'t, A> Token<'t, A> {
         }
 
         let mut tokens = self.concatenation().iter().peekable();
-        if tokens.peek().map_or(false, |token| {
+        if let Some(token) = tokens.peek().filter(|token| {
             // This is a very general predicate, but at time of writing amounts to, "Is this a tree
             // wildcard?"
             token.has_root().is_always() && token.variance::<Text>().is_variant()
         }) {
-            return (0, String::from(Separator::INVARIANT_TEXT));
+            let separator = token
+                .as_wildcard()
+                .map_or_else(Separator::default, Wildcard::separator);
+            return (0, separator.invariant_text().into_owned());
         }
         let mut head = None;
         let mut checkpoint = None;
@@ -505,6 +902,118 @@ impl<'t, A> Token<'t, A> {
         head.map_or_else(empty, Prefix::into_count_text)
     }
 
+    // Gets the literal file extensions that the final component of the token can match, if any.
+    //
+    // The final component is read from its end until each possible suffix contains a `.` and
+    // therefore delimits an extension. Any variant text in this suffix (besides alternatives of
+    // invariant text) means that the extensions cannot be determined.
+    pub fn extensions(&self) -> Option<Vec<String>> {
+        fn is_delimited(suffix: &str) -> bool {
+            suffix.contains(['.', '/'])
+        }
+
+        let component = self.components().last()?;
+        let mut tokens = component.tokens().iter().rev().peekable();
+        let mut suffixes = vec![String::new()];
+        while !suffixes.iter().all(|suffix| is_delimited(suffix)) {
+            let token = tokens.next()?;
+            let texts = match token.variance::<Text>() {
+                Variance::Invariant(text) => vec![text.to_string().into_owned()],
+                _ => token
+                    .as_alternation()?
+                    .tokens()
+                    .iter()
+                    .map(|token| {
+                        token
+                            .variance::<Text>()
+                            .invariant()
+                            .map(|text| text.to_string().into_owned())
+                    })
+                    .collect::<Option<Vec<_>>>()?,
+            };
+            suffixes = suffixes
+                .into_iter()
+                .flat_map(|suffix| {
+                    if is_delimited(&suffix) {
+                        vec![suffix]
+                    }
+                    else {
+                        texts.iter().map(|text| format!("{}{}", text, suffix)).collect()
+                    }
+                })
+                .collect();
+        }
+        let is_exhausted = tokens.peek().is_none();
+        suffixes
+            .iter()
+            .map(|suffix| {
+                let name = suffix.rsplit('/').next().unwrap_or(suffix);
+                match name.rsplit_once('.') {
+                    // A leading `.` in an entire file name (as in `.gitignore`) does not delimit
+                    // an extension.
+                    Some(("", _)) if is_exhausted && name.len() == suffix.len() => None,
+                    Some((_, extension)) => Some(String::from(extension)),
+                    None => None,
+                }
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|extensions| extensions.into_iter().unique().collect())
+    }
+
+    // Gets the finite set of texts that the token can match, if any.
+    //
+    // Texts are expanded from alternations and bounded repetitions of invariant text. Returns
+    // `None` if the token has any other variant text (such as a wildcard) or if the number of
+    // texts exceeds the given limit at any point in the expansion. Texts are not deduplicated.
+    pub fn texts(&self, limit: usize) -> Option<Vec<String>> {
+        if let Variance::Invariant(text) = self.variance::<Text>() {
+            return Some(vec![text.to_string().into_owned()]);
+        }
+        match self.topology() {
+            TokenTopology::Leaf(_) => None,
+            TokenTopology::Branch(BranchKind::Alternation(ref alternation)) => {
+                let mut texts = vec![];
+                for token in alternation.tokens() {
+                    texts.extend(token.texts(limit)?);
+                    if texts.len() > limit {
+                        return None;
+                    }
+                }
+                Some(texts)
+            },
+            TokenTopology::Branch(BranchKind::Concatenation(ref concatenation)) => {
+                concatenated_texts(concatenation.tokens(), limit)
+            },
+            TokenTopology::Branch(BranchKind::Repetition(ref repetition)) => {
+                let (lower, upper) = repetition.bound_specification();
+                let upper = upper?;
+                let token = repetition.token().texts(limit)?;
+                // Separators only occur between repeated tokens, so every repetition but the
+                // first is preceded by a separator.
+                let separated = match repetition.separator() {
+                    Some(separator) => product(&separator.texts(limit)?, &token, limit)?,
+                    None => token.clone(),
+                };
+                let mut texts = vec![];
+                // The texts of exactly `n` repetitions.
+                let mut repeated = vec![String::new()];
+                for n in 0..=upper {
+                    if n >= lower {
+                        texts.extend(repeated.iter().cloned());
+                        if texts.len() > limit {
+                            return None;
+                        }
+                    }
+                    if n < upper {
+                        repeated =
+                            product(&repeated, if n == 0 { &token } else { &separated }, limit)?;
+                    }
+                }
+                Some(texts)
+            },
+        }
+    }
+
     pub fn has_root(&self) -> When {
         struct IsRooting;
 
@@ -545,6 +1054,283 @@ impl<'t, A> Token<'t, A> {
         self.fold(IsRooting).unwrap_or(When::Never)
     }
 
+    pub fn has_terminating_separator(&self) -> When {
+        struct IsTerminating;
+
+        impl<'t, A> Fold<'t, A> for IsTerminating {
+            type Sequencer = Ending;
+            type Term = When;
+
+            fn sequencer() -> Self::Sequencer {
+                Ending
+            }
+
+            fn fold(
+                &mut self,
+                branch: &BranchKind<'t, A>,
+                terms: Vec<Self::Term>,
+            ) -> Option<Self::Term> {
+                terms
+                    .into_iter()
+                    .reduce(match branch.composition() {
+                        Composition::Conjunctive(_) => When::or,
+                        Composition::Disjunctive(_) => When::certainty,
+                    })
+                    .map(|term| {
+                        // Repetitions that may match nothing do not necessarily terminate with
+                        // their repeated token.
+                        if let BranchKind::Repetition(ref repetition) = branch {
+                            if repetition.variance().lower().into_bound().is_unbounded() {
+                                return term.and(When::Sometimes);
+                            }
+                        }
+                        term
+                    })
+            }
+
+            fn term(&mut self, leaf: &LeafKind<'t>) -> Self::Term {
+                matches!(leaf.boundary(), Some(Boundary::Separator)).into()
+            }
+        }
+
+        self.fold(IsTerminating).unwrap_or(When::Never)
+    }
+
+    pub fn normalized(&self) -> NormalToken<'t> {
+        struct Normalize;
+
+        impl<'t, A> Fold<'t, A> for Normalize {
+            type Sequencer = Forward;
+            type Term = NormalToken<'t>;
+
+            fn sequencer() -> Self::Sequencer {
+                Forward
+            }
+
+            fn fold(
+                &mut self,
+                branch: &BranchKind<'t, A>,
+                terms: Vec<Self::Term>,
+            ) -> Option<Self::Term> {
+                // Flatten nested branches of the same kind and collapse branches with only one
+                // child. See `Token::into_non_trivial`.
+                fn flatten<'t>(
+                    terms: Vec<NormalToken<'t>>,
+                    f: impl Fn(NormalToken<'t>) -> Result<Vec<NormalToken<'t>>, NormalToken<'t>>,
+                    g: impl FnOnce(Vec<NormalToken<'t>>) -> NormalToken<'t>,
+                ) -> NormalToken<'t> {
+                    let mut terms: Vec<_> = terms
+                        .into_iter()
+                        .flat_map(|term| f(term).unwrap_or_else(|term| vec![term]))
+                        .collect();
+                    if terms.len() == 1 {
+                        terms.pop().unwrap()
+                    }
+                    else {
+                        g(terms)
+                    }
+                }
+
+                Some(match branch {
+                    BranchKind::Alternation(_) => flatten(
+                        terms,
+                        |term| match term {
+                            NormalToken::Alternation(terms) => Ok(terms),
+                            term => Err(term),
+                        },
+                        NormalToken::Alternation,
+                    ),
+                    BranchKind::Concatenation(_) => flatten(
+                        terms,
+                        |term| match term {
+                            NormalToken::Concatenation(terms) => Ok(terms),
+                            term => Err(term),
+                        },
+                        NormalToken::Concatenation,
+                    ),
+                    BranchKind::Repetition(ref repetition) => {
+                        let variance = repetition.variance();
+                        if variance.is_one() {
+                            terms.into_iter().next()?
+                        }
+                        else {
+                            NormalToken::Repetition(terms, variance)
+                        }
+                    },
+                })
+            }
+
+            fn term(&mut self, leaf: &LeafKind<'t>) -> Self::Term {
+                NormalToken::Leaf(leaf.clone())
+            }
+        }
+
+        self.fold(Normalize)
+            .unwrap_or_else(|| NormalToken::Concatenation(vec![]))
+    }
+
+    pub fn describe(&self) -> String {
+        struct Describe;
+
+        impl<'t, A> Fold<'t, A> for Describe {
+            type Sequencer = Forward;
+            type Term = String;
+
+            fn sequencer() -> Self::Sequencer {
+                Forward
+            }
+
+            fn fold(
+                &mut self,
+                branch: &BranchKind<'t, A>,
+                terms: Vec<Self::Term>,
+            ) -> Option<Self::Term> {
+                // Descriptions of sequences are delimited by commas, so such descriptions are
+                // parenthesized when nested in alternations and repetitions.
+                fn group(term: String) -> String {
+                    if term.contains(", ") {
+                        format!("({})", term)
+                    }
+                    else {
+                        term
+                    }
+                }
+
+                Some(match branch {
+                    BranchKind::Alternation(_) => {
+                        let mut terms = terms.into_iter().map(group);
+                        let first = terms.next()?;
+                        format!("either {}", terms.fold(first, |text, term| text + " or " + &term))
+                    },
+                    BranchKind::Concatenation(_) => {
+                        terms.into_iter().reduce(|text, term| text + ", then " + &term)?
+                    },
+                    BranchKind::Repetition(ref repetition) => {
+                        let mut terms = terms.into_iter().map(group);
+                        let token = terms.next()?;
+                        let bounds = match repetition.bound_specification() {
+                            (lower, Some(upper)) if lower == upper => {
+                                format!("exactly {} time{}", lower, plural(lower))
+                            },
+                            (lower, Some(upper)) => format!("{} to {} times", lower, upper),
+                            (0, None) => "any number of times".into(),
+                            (lower, None) => format!("at least {} time{}", lower, plural(lower)),
+                        };
+                        match terms.next() {
+                            Some(separator) => {
+                                format!("{} repeated {} separated by {}", token, bounds, separator)
+                            },
+                            None => format!("{} repeated {}", token, bounds),
+                        }
+                    },
+                })
+            }
+
+            fn term(&mut self, leaf: &LeafKind<'t>) -> Self::Term {
+                fn describe_class(archetypes: &[Archetype], is_negated: bool) -> String {
+                    let archetypes = archetypes.iter().map(describe_archetype).join(" or ");
+                    if is_negated {
+                        format!("any character except {}", archetypes)
+                    }
+                    else {
+                        format!("one of {}", archetypes)
+                    }
+                }
+
+                fn describe_archetype(archetype: &Archetype) -> String {
+                    match archetype {
+                        Archetype::Character(x) => format!("`{}`", x),
+                        Archetype::Range(a, b) => format!("`{}` through `{}`", a, b),
+                        Archetype::Property {
+                            ref name,
+                            is_negated,
+                        } => format!(
+                            "any character {} Unicode property `{}`",
+                            if *is_negated { "without" } else { "with" },
+                            name,
+                        ),
+                        Archetype::Set(ref set) => {
+                            format!("({})", describe_class(set.archetypes(), set.is_negated()))
+                        },
+                        Archetype::Intersection(ref operands) => operands
+                            .iter()
+                            .map(|operand| match operand.as_slice() {
+                                [Archetype::Set(_)] => describe_archetype(&operand[0]),
+                                _ => format!("({})", describe_class(operand, false)),
+                            })
+                            .join(" and "),
+                    }
+                }
+
+                match leaf {
+                    LeafKind::Class(ref class) => {
+                        describe_class(class.archetypes(), class.is_negated())
+                    },
+                    LeafKind::Literal(ref literal) => {
+                        if literal.has_variant_casing() {
+                            if literal.is_case_insensitive {
+                                format!("`{}` ignoring case", literal.text())
+                            }
+                            else {
+                                format!("`{}` matching case", literal.text())
+                            }
+                        }
+                        else {
+                            format!("`{}`", literal.text())
+                        }
+                    },
+                    LeafKind::Prefix(Prefix::Drive(letter)) => format!("the drive `{}:`", letter),
+                    LeafKind::Prefix(ref prefix @ Prefix::Unc { .. }) => {
+                        format!("the share `{}`", prefix.text())
+                    },
+                    LeafKind::Separator(_) => "a separator".into(),
+                    LeafKind::Wildcard(Wildcard::One(_)) => "any one character".into(),
+                    LeafKind::Wildcard(Wildcard::ZeroOrMore(Evaluation::Eager, _)) => {
+                        "any characters".into()
+                    },
+                    LeafKind::Wildcard(Wildcard::ZeroOrMore(Evaluation::Lazy, _)) => {
+                        "any characters (lazily)".into()
+                    },
+                    LeafKind::Wildcard(Wildcard::Tree {
+                        has_root,
+                        excludes_hidden,
+                        ..
+                    }) => {
+                        let directories = if *excludes_hidden {
+                            "any non-hidden directories"
+                        }
+                        else {
+                            "any directories"
+                        };
+                        if *has_root {
+                            format!("the root, then {}", directories)
+                        }
+                        else {
+                            directories.into()
+                        }
+                    },
+                }
+            }
+        }
+
+        fn plural(n: usize) -> &'static str {
+            if n == 1 {
+                ""
+            }
+            else {
+                "s"
+            }
+        }
+
+        if self.is_empty() {
+            String::from("an empty path")
+        }
+        else {
+            self.fold(Describe)
+                .unwrap_or_else(|| String::from("an empty path"))
+        }
+    }
+
     pub fn has_boundary(&self) -> bool {
         walk::forward(self)
             .map(TokenEntry::into_token)
@@ -569,6 +1355,42 @@ impl<'t, A> Token<'t, A> {
         }
     }
 
+    // Unlike `is_empty`, this also considers case-insensitive empty literals.
+    fn is_empty_literal(&self) -> bool {
+        self.as_literal().is_some_and(|literal| literal.text().is_empty())
+    }
+
+    // Breadth is not composed (see `Breadth`), so this query is conservative: it only considers
+    // the breadth of leaf tokens and whether unbounded repetitions are delimited by boundaries.
+    // Some expressions with bounded breadth, such as `<{a/,b/}:0,>`, are reported as unbounded.
+    pub fn has_bounded_breadth(&self) -> bool {
+        match self.topology {
+            TokenTopology::Leaf(ref leaf) => variance::term::<Breadth>(leaf).is_bounded(),
+            TokenTopology::Branch(ref branch) => {
+                if let BranchKind::Repetition(ref repetition) = branch {
+                    // An unbounded repetition can extend a component indefinitely unless either
+                    // the repeated token or the separator of the repetition ends the component.
+                    let (_, upper) = repetition.bound_specification();
+                    if upper.is_none()
+                        && !repetition.tokens().iter().any(|token| {
+                            token
+                                .concatenation()
+                                .iter()
+                                .any(|token| token.boundary().is_some())
+                        })
+                    {
+                        return false;
+                    }
+                }
+                branch
+                    .tokens()
+                    .into_inner()
+                    .iter()
+                    .all(Token::has_bounded_breadth)
+            },
+        }
+    }
+
     // TODO: There is a distinction between exhaustiveness of a glob and exhaustiveness of a match
     //       (this is also true of other properties). The latter can be important for performance
     //       optimization, but may also be useful in the public API (perhaps as part of
@@ -733,6 +1555,18 @@ impl<'t, A> From<LeafKind<'t>> for TokenTopology<'t, A> {
     }
 }
 
+/// A token tree without annotations and with trivial branches collapsed.
+///
+/// Normal tokens are structurally comparable: two token trees with the same normal form match the
+/// same paths, though the converse is not true. See `Token::normalized`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum NormalToken<'t> {
+    Alternation(Vec<NormalToken<'t>>),
+    Concatenation(Vec<NormalToken<'t>>),
+    Repetition(Vec<NormalToken<'t>>, NaturalRange),
+    Leaf(LeafKind<'t>),
+}
+
 // TODO: The use of this trait and `BranchFold` in `Token::fold_map` is a very unfortunate
 //       consequence of using an intrusive tree: the data and topology of a token tree cannot be
 //       cleanly separated. Remove these APIs if and when the tree is implemented via an
@@ -789,7 +1623,7 @@ impl<'t, A> BranchKind<'t, A> {
         match self {
             Alternation(ref alternation) => Disjunctive(alternation.tokens()),
             Concatenation(ref concatenation) => Conjunctive(concatenation.tokens()),
-            Repetition(ref repetition) => Conjunctive(slice::from_ref(repetition.token())),
+            Repetition(ref repetition) => Conjunctive(repetition.tokens()),
         }
     }
 
@@ -854,10 +1688,11 @@ trait Unroot<A> {
     fn unroot(&mut self, annotation: &mut A) -> Self::Output;
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum LeafKind<'t> {
     Class(Class),
     Literal(Literal<'t>),
+    Prefix(Prefix<'t>),
     Separator(Separator),
     Wildcard(Wildcard),
 }
@@ -867,11 +1702,24 @@ impl<'t> LeafKind<'t> {
         match self {
             LeafKind::Class(class) => LeafKind::Class(class),
             LeafKind::Literal(literal) => LeafKind::Literal(literal.into_owned()),
+            LeafKind::Prefix(prefix) => LeafKind::Prefix(prefix.into_owned()),
             LeafKind::Separator(separator) => LeafKind::Separator(separator),
             LeafKind::Wildcard(wildcard) => LeafKind::Wildcard(wildcard),
         }
     }
 
+    /// Gets the separator of the leaf, if any.
+    ///
+    /// Separators, wildcards, and classes carry the separator that they match or exclude.
+    pub fn separator(&self) -> Option<Separator> {
+        match self {
+            LeafKind::Class(class) => Some(class.separator()),
+            LeafKind::Separator(separator) => Some(*separator),
+            LeafKind::Wildcard(wildcard) => Some(wildcard.separator()),
+            _ => None,
+        }
+    }
+
     pub fn boundary(&self) -> Option<Boundary> {
         match self {
             LeafKind::Separator(_) => Some(Boundary::Separator),
@@ -883,7 +1731,9 @@ impl<'t> LeafKind<'t> {
     pub fn is_rooting(&self) -> bool {
         matches!(
             self,
-            LeafKind::Separator(_) | LeafKind::Wildcard(Wildcard::Tree { has_root: true })
+            LeafKind::Prefix(_)
+                | LeafKind::Separator(_)
+                | LeafKind::Wildcard(Wildcard::Tree { has_root: true, .. })
         )
     }
 
@@ -904,6 +1754,12 @@ impl<'t> From<Literal<'t>> for LeafKind<'t> {
     }
 }
 
+impl<'t> From<Prefix<'t>> for LeafKind<'t> {
+    fn from(prefix: Prefix<'t>) -> Self {
+        LeafKind::Prefix(prefix)
+    }
+}
+
 impl From<Separator> for LeafKind<'static> {
     fn from(separator: Separator) -> Self {
         LeafKind::Separator(separator)
@@ -935,16 +1791,18 @@ impl<'t, T> VarianceTerm<T> for LeafKind<'t>
 where
     Class: VarianceTerm<T>,
     Literal<'t>: VarianceTerm<T>,
+    Prefix<'t>: VarianceTerm<T>,
     Separator: VarianceTerm<T>,
     Wildcard: VarianceTerm<T>,
     T: Invariant,
 {
     fn term(&self) -> T::Term {
-        use LeafKind::{Class, Literal, Separator, Wildcard};
+        use LeafKind::{Class, Literal, Prefix, Separator, Wildcard};
 
         match self {
             Class(ref class) => class.term(),
             Literal(ref literal) => literal.term(),
+            Prefix(ref prefix) => prefix.term(),
             Separator(ref separator) => separator.term(),
             Wildcard(ref wildcard) => wildcard.term(),
         }
@@ -997,7 +1855,7 @@ impl<'t, A> VarianceFold<Text<'t>> for Alternation<'t, A> {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Archetype {
     Character(char),
     // TODO: A range archetype spans Unicode code points. This should be clearly documented and
@@ -1010,6 +1868,17 @@ pub enum Archetype {
     //       it isn't yet possible to select classes like `{greek}` or `{flag}`. Such support may
     //       relieve the limitations of a code point range.
     Range(char, char),
+    /// A Unicode property, such as a general category or script, as in `\p{L}` or `\P{Greek}`.
+    ///
+    /// The name of the property is validated when parsed and is encoded as is.
+    Property { name: String, is_negated: bool },
+    /// A nested and possibly negated set of archetypes, as in `[a-z[!aeiou]]`.
+    Set(Class),
+    /// An intersection of unions of archetypes, as in `[a-z&&[!aeiou]]`.
+    ///
+    /// Intersection has the lowest precedence in a class, so each operand is the union of its
+    /// archetypes.
+    Intersection(Vec<Vec<Archetype>>),
 }
 
 impl From<char> for Archetype {
@@ -1055,15 +1924,19 @@ impl<'t> VarianceTerm<Text<'t>> for Archetype {
                     Variance::Invariant(*a)
                 }
             },
+            Archetype::Property { .. } | Archetype::Set(_) | Archetype::Intersection(_) => {
+                Variance::Variant(Boundedness::BOUNDED)
+            },
         }
         .map_invariant(|invariant| invariant.to_string().into_nominal_text())
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Class {
     is_negated: bool,
     archetypes: Vec<Archetype>,
+    separator: Separator,
 }
 
 impl Class {
@@ -1075,6 +1948,11 @@ impl Class {
         self.is_negated
     }
 
+    /// Gets the separator that the class never matches.
+    pub fn separator(&self) -> Separator {
+        self.separator
+    }
+
     fn fold<T, F>(&self, f: F) -> TokenVariance<T>
     where
         Archetype: VarianceTerm<T>,
@@ -1170,13 +2048,73 @@ impl<'t, A> VarianceFold<Text<'t>> for Concatenation<'t, A> {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Evaluation {
     Eager,
     Lazy,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+// Prefixes are only parsed at the beginning of an expression and are always followed by a
+// separator, so a prefix and its separator form the root of a glob.
+/// A Windows path prefix, such as the drive `C:` or the UNC share `//server/share`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Prefix<'t> {
+    /// A drive prefix. The letter is always uppercase.
+    Drive(char),
+    /// A UNC share prefix.
+    Unc {
+        server: Cow<'t, str>,
+        share: Cow<'t, str>,
+    },
+}
+
+impl<'t> Prefix<'t> {
+    pub fn into_owned(self) -> Prefix<'static> {
+        match self {
+            Prefix::Drive(letter) => Prefix::Drive(letter),
+            Prefix::Unc { server, share } => Prefix::Unc {
+                server: server.into_owned().into(),
+                share: share.into_owned().into(),
+            },
+        }
+    }
+
+    /// Gets the text of the prefix using the platform's main separator.
+    pub fn text(&self) -> String {
+        match self {
+            Prefix::Drive(letter) => format!("{}:", letter),
+            Prefix::Unc { server, share } => {
+                format!("{0}{0}{1}{0}{2}", MAIN_SEPARATOR, server, share)
+            },
+        }
+    }
+}
+
+impl<'t> VarianceTerm<Breadth> for Prefix<'t> {
+    fn term(&self) -> InvariantTerm<Breadth> {
+        Variance::zero()
+    }
+}
+
+impl<'t> VarianceTerm<Depth> for Prefix<'t> {
+    fn term(&self) -> InvariantTerm<Depth> {
+        Zero::zero()
+    }
+}
+
+impl<'t> VarianceTerm<Size> for Prefix<'t> {
+    fn term(&self) -> InvariantTerm<Size> {
+        Variance::Invariant(self.text().len().into())
+    }
+}
+
+impl<'t> VarianceTerm<Text<'t>> for Prefix<'t> {
+    fn term(&self) -> InvariantTerm<Text<'t>> {
+        Variance::Invariant(self.text().into_structural_text())
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Literal<'t> {
     text: Cow<'t, str>,
     is_case_insensitive: bool,
@@ -1241,7 +2179,7 @@ impl<'t> VarianceTerm<Size> for Literal<'t> {
     fn term(&self) -> InvariantTerm<Size> {
         // TODO: This assumes that the size of graphemes in a casing set are the same. Is that
         //       correct?
-        Variance::Invariant(self.text.as_bytes().len().into())
+        Variance::Invariant(self.text.len().into())
     }
 }
 
@@ -1253,12 +2191,14 @@ impl<'t> VarianceTerm<Text<'t>> for Literal<'t> {
 }
 
 // TODO: The `VarianceFold` implementations for `Repetition` reimplement concatenation in `fold`.
-//       Moreover, `Repetition`s have only one token, so `fold` can simply forward its term. Can
-//       `VarianceFold` be decomposed in a way that avoids this redundancy? Note too that no other
-//       token (at time of writing) has an interesting `finalize` implementation.
+//       Moreover, unseparated `Repetition`s have only one token, so `fold` can simply forward its
+//       term. Can `VarianceFold` be decomposed in a way that avoids this redundancy? Note too that
+//       no other token (at time of writing) has an interesting `finalize` implementation.
 #[derive(Clone, Debug)]
 pub struct Repetition<'t, A> {
-    token: Box<Token<'t, A>>,
+    // The repeated token is always first and is followed by the separator token, if any. These
+    // tokens are stored contiguously so that they can be presented as a composition.
+    tokens: Vec<Token<'t, A>>,
     lower: usize,
     // This representation is not ideal, as it does not statically enforce the invariant that the
     // upper bound is greater than or equal to the lower bound. For example, this field could
@@ -1269,8 +2209,33 @@ pub struct Repetition<'t, A> {
 }
 
 impl<'t, A> Repetition<'t, A> {
+    pub(crate) fn new(
+        token: Token<'t, A>,
+        separator: Option<Token<'t, A>>,
+        lower: usize,
+        upper: Option<usize>,
+    ) -> Self {
+        Repetition {
+            tokens: Some(token).into_iter().chain(separator).collect(),
+            lower,
+            upper,
+        }
+    }
+
     pub fn token(&self) -> &Token<'t, A> {
-        &self.token
+        &self.tokens[0]
+    }
+
+    pub fn separator(&self) -> Option<&Token<'t, A>> {
+        self.tokens.get(1)
+    }
+
+    pub fn tokens(&self) -> &[Token<'t, A>] {
+        &self.tokens
+    }
+
+    pub fn is_separated(&self) -> bool {
+        self.separator().is_some()
     }
 
     pub(crate) fn bound_specification(&self) -> (usize, Option<usize>) {
@@ -1280,6 +2245,61 @@ impl<'t, A> Repetition<'t, A> {
     pub fn variance(&self) -> NaturalRange {
         self.bound_specification().into()
     }
+
+    // Separators only occur between repeated tokens, so there is always one fewer separator than
+    // repetitions (except when there are no repetitions at all).
+    pub fn separator_variance(&self) -> NaturalRange {
+        let variance = self.variance();
+        NaturalRange::from_closed_and_open(
+            variance.lower().into_usize().saturating_sub(1),
+            variance
+                .upper()
+                .into_usize()
+                .map(|upper| upper.saturating_sub(1)),
+        )
+    }
+
+    // When separated, the terms of the repeated token and separator are folded together here and
+    // `finalize` does nothing. Note that terms must be complete and in order: if the separator term
+    // is missing, then no term is produced.
+    fn fold_terms<T>(&self, terms: Vec<T>) -> Option<T>
+    where
+        T: Clone + ops::Conjunction<Output = T> + ops::Product<NaturalRange, Output = T>,
+    {
+        if self.is_separated() {
+            // The separated repetition `<g:m,n;s>` is folded like the concatenation `<gs:m-1,n-1>g`
+            // or, if the lower bound is zero, like the repetition `<<gs:0,n-1>g:0,1>`.
+            let [token, separator]: [T; 2] = terms.try_into().ok()?;
+            let term = ops::conjunction(
+                ops::product(
+                    ops::conjunction(token.clone(), separator),
+                    self.separator_variance(),
+                ),
+                token,
+            );
+            Some(if self.variance().lower().into_usize() == 0 {
+                ops::product(term, NaturalRange::from((0, 1)))
+            }
+            else {
+                term
+            })
+        }
+        else {
+            terms.into_iter().reduce(ops::conjunction)
+        }
+    }
+
+    fn finalize_term<T>(&self, term: T) -> T
+    where
+        T: ops::Product<NaturalRange, Output = T>,
+    {
+        if self.is_separated() {
+            term
+        }
+        else {
+            ops::product(term, self.variance())
+        }
+    }
 }
 
 impl<'t, A> BranchComposition<'t> for Repetition<'t, A> {
@@ -1290,54 +2310,101 @@ impl<'t, A> BranchComposition<'t> for Repetition<'t, A> {
         variance: Self::BranchData,
         tokens: Vec<Token<'t, Self::Annotation>>,
     ) -> Result<Self, ()> {
-        tokens.into_iter().next().ok_or(()).map(|token| Repetition {
-            token: Box::new(token),
-            lower: variance.lower().into_usize(),
-            upper: variance.upper().into_usize(),
-        })
+        if (1..=2).contains(&tokens.len()) {
+            Ok(Repetition {
+                tokens,
+                lower: variance.lower().into_usize(),
+                upper: variance.upper().into_usize(),
+            })
+        }
+        else {
+            Err(())
+        }
     }
 
     fn decompose(self) -> (Self::BranchData, Vec<Token<'t, Self::Annotation>>) {
         let variance = self.variance();
-        (variance, vec![*self.token])
+        (variance, self.tokens)
     }
 }
 
 impl<'t, A> VarianceFold<Depth> for Repetition<'t, A> {
     fn fold(&self, terms: Vec<InvariantTerm<Depth>>) -> Option<InvariantTerm<Depth>> {
-        terms.into_iter().reduce(ops::conjunction)
+        self.fold_terms(terms)
     }
 
     fn finalize(&self, term: InvariantTerm<Depth>) -> InvariantTerm<Depth> {
-        ops::product(term, self.variance())
+        self.finalize_term(term)
     }
 }
 
 impl<'t, A> VarianceFold<Size> for Repetition<'t, A> {
     fn fold(&self, terms: Vec<InvariantTerm<Size>>) -> Option<InvariantTerm<Size>> {
-        terms.into_iter().reduce(ops::conjunction)
+        self.fold_terms(terms)
     }
 
     fn finalize(&self, term: InvariantTerm<Size>) -> InvariantTerm<Size> {
-        ops::product(term, self.variance())
+        self.finalize_term(term)
     }
 }
 
 impl<'t, A> VarianceFold<Text<'t>> for Repetition<'t, A> {
     fn fold(&self, terms: Vec<InvariantTerm<Text<'t>>>) -> Option<InvariantTerm<Text<'t>>> {
-        terms.into_iter().reduce(ops::conjunction)
+        self.fold_terms(terms)
     }
 
     fn finalize(&self, term: InvariantTerm<Text<'t>>) -> InvariantTerm<Text<'t>> {
-        ops::product(term, self.variance())
+        self.finalize_term(term)
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Separator;
+/// A separator that delimits components.
+///
+/// Tokens that match or exclude separators, such as wildcards and classes, also carry a
+/// `Separator`, so that the separator of a token is preserved when it is composed with tokens from
+/// other token trees.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Separator {
+    /// The character that delimits components or `None` if components are delimited by the
+    /// separators of the target platform. See `ParseOptions::separator`.
+    custom: Option<char>,
+}
 
 impl Separator {
     const INVARIANT_TEXT: &'static str = MAIN_SEPARATOR_STR;
+
+    pub fn custom(&self) -> Option<char> {
+        self.custom
+    }
+
+    /// Returns `true` if the given character is a separator.
+    pub fn is_separator(&self, x: char) -> bool {
+        match self.custom {
+            Some(separator) => x == separator,
+            _ => x == '/' || path::is_separator(x),
+        }
+    }
+
+    /// Gets the length in bytes of the separator in an expression.
+    pub fn expression_len(&self) -> usize {
+        self.custom
+            .map_or(ROOT_SEPARATOR_EXPRESSION.len(), char::len_utf8)
+    }
+
+    /// Gets the text of the separator in an expression.
+    pub fn expression_text(&self) -> Cow<'static, str> {
+        match self.custom {
+            Some(separator) => separator.to_string().into(),
+            _ => ROOT_SEPARATOR_EXPRESSION.into(),
+        }
+    }
+
+    fn invariant_text(&self) -> Cow<'static, str> {
+        match self.custom {
+            Some(separator) => separator.to_string().into(),
+            _ => Separator::INVARIANT_TEXT.into(),
+        }
+    }
 }
 
 impl VarianceTerm<Breadth> for Separator {
@@ -1356,27 +2423,44 @@ impl VarianceTerm<Size> for Separator {
     fn term(&self) -> InvariantTerm<Size> {
         // TODO: This is incorrect. The compiled regular expression may ignore a terminating
         //       separator, in which case the size is a bounded range.
-        Variance::Invariant(Separator::INVARIANT_TEXT.as_bytes().len().into())
+        Variance::Invariant(self.invariant_text().len().into())
     }
 }
 
 impl<'t> VarianceTerm<Text<'t>> for Separator {
     fn term(&self) -> InvariantTerm<Text<'t>> {
-        Variance::Invariant(Separator::INVARIANT_TEXT.into_structural_text())
+        Variance::Invariant(self.invariant_text().into_structural_text())
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Wildcard {
-    One,
-    ZeroOrMore(Evaluation),
-    Tree { has_root: bool },
+    One(Separator),
+    ZeroOrMore(Evaluation, Separator),
+    Tree {
+        has_root: bool,
+        excludes_hidden: bool,
+        /// Whether or not the tree wildcard must match at least one component.
+        excludes_empty: bool,
+        separator: Separator,
+    },
 }
 
 impl Wildcard {
+    /// Gets the separator that the wildcard matches or does not match.
+    pub fn separator(&self) -> Separator {
+        match self {
+            Wildcard::One(separator)
+            | Wildcard::ZeroOrMore(_, separator)
+            | Wildcard::Tree { separator, .. } => *separator,
+        }
+    }
+
     fn unroot(&mut self) -> bool {
         match self {
-            Wildcard::Tree { ref mut has_root } => mem::replace(has_root, false),
+            Wildcard::Tree {
+                ref mut has_root, ..
+            } => mem::replace(has_root, false),
             _ => false,
         }
     }
@@ -1392,7 +2476,7 @@ where
         if Wildcard::unroot(self) {
             // Move the beginning of the span in the annotation forward to dissociate the token
             // from any separator in the expression.
-            let n = ROOT_SEPARATOR_EXPRESSION.len();
+            let n = self.separator().expression_len();
             let span = annotation.span_mut();
             span.0 = span.0.saturating_add(n);
             span.1 = span.1.saturating_sub(n);
@@ -1407,7 +2491,7 @@ where
 impl VarianceTerm<Breadth> for Wildcard {
     fn term(&self) -> InvariantTerm<Breadth> {
         match self {
-            Wildcard::One => Variance::zero(),
+            Wildcard::One(_) => Variance::zero(),
             _ => Variance::unbounded(),
         }
     }
@@ -1416,6 +2500,11 @@ impl VarianceTerm<Breadth> for Wildcard {
 impl VarianceTerm<Depth> for Wildcard {
     fn term(&self) -> InvariantTerm<Depth> {
         match self {
+            // Tree wildcards that exclude empty paths match at least one component.
+            Wildcard::Tree {
+                excludes_empty: true,
+                ..
+            } => BoundaryTerm::unbounded_from(Depth::from(1)),
             Wildcard::Tree { .. } => BoundaryTerm::unbounded(),
             _ => Zero::zero(),
         }
@@ -1430,7 +2519,7 @@ impl VarianceTerm<Size> for Wildcard {
             //       then size can vary wildly (though there is probably an upper bound that
             //       depends on the version of Unicode). Use an appropriate range here.
             //Wildcard::One => Variance::Variant(Size::from(1).into_lower_bound()),
-            Wildcard::One => Variance::Invariant(Size::from(4)),
+            Wildcard::One(_) => Variance::Invariant(Size::from(4)),
             _ => Variance::unbounded(),
         }
     }
@@ -1492,6 +2581,24 @@ impl<'i, 't, A> Component<'i, 't, A> {
             })
         }
     }
+
+    pub fn is_invariant(&self) -> bool {
+        self.tokens()
+            .iter()
+            .all(|token| token.variance::<Text>().is_invariant())
+    }
+
+    pub fn is_tree_wildcard(&self) -> bool {
+        matches!(
+            self.tokens(),
+            [token] if matches!(token.as_wildcard(), Some(Wildcard::Tree { .. })),
+        )
+    }
+
+    // Gets the finite set of texts that the component can match, if any. See `Token::texts`.
+    pub fn texts(&self, limit: usize) -> Option<Vec<String>> {
+        concatenated_texts(self.tokens(), limit)
+    }
 }
 
 impl<'i, 't, A> Clone for Component<'i, 't, A> {
@@ -1508,6 +2615,28 @@ impl<'i, 't, A> ConcatenationTree<'t> for Component<'i, 't, A> {
     }
 }
 
+// Gets the cartesian product of texts, if the number of texts does not exceed the given limit.
+fn product(lhs: &[String], rhs: &[String], limit: usize) -> Option<Vec<String>> {
+    if lhs.len().checked_mul(rhs.len())? > limit {
+        None
+    }
+    else {
+        Some(
+            lhs.iter()
+                .cartesian_product(rhs)
+                .map(|(lhs, rhs)| format!("{}{}", lhs, rhs))
+                .collect(),
+        )
+    }
+}
+
+// Gets the finite set of texts that a concatenation of tokens can match, if any.
+fn concatenated_texts<A>(tokens: &[Token<'_, A>], limit: usize) -> Option<Vec<String>> {
+    tokens.iter().try_fold(vec![String::new()], |texts, token| {
+        product(&texts, &token.texts(limit)?, limit)
+    })
+}
+
 pub fn any<'t, I>(trees: I) -> Token<'t, ()>
 where
     I: IntoIterator,
@@ -1583,7 +2712,10 @@ mod tests {
     use rstest::{fixture, rstest};
     use std::path::Path;
 
-    use crate::token::{harness, parse, ExpressionMetadata, Token, TokenTree, Tokenized};
+    use crate::token::{
+        harness, parse, ExpressionMetadata, LeafKind, ParseOptions, Prefix, Token, TokenTree,
+        Tokenized,
+    };
 
     // This fixture constructs a `Tokenized` with the following tokens in its (filtered)
     // concatenation:
@@ -1656,4 +2788,72 @@ mod tests {
             expected,
         );
     }
+
+    #[rstest]
+    #[case("C:/", Prefix::Drive('C'), "C:/")]
+    #[case("c:/a/*", Prefix::Drive('C'), "C:/a/")]
+    #[case(
+        "//server/share/a",
+        Prefix::Unc { server: "server".into(), share: "share".into() },
+        "//server/share/a",
+    )]
+    fn parse_expression_with_prefix_has_root_and_invariant_path_prefix_eq(
+        #[case] expression: &str,
+        #[case] expected: Prefix<'static>,
+        #[case] prefix: impl AsRef<Path>,
+    ) {
+        let tokenized = harness::assert_tokenized_invariant_path_prefix_eq(
+            parse::harness::assert_parse_expression_with_options_is_ok(
+                expression,
+                ParseOptions {
+                    accepts_prefixes: true,
+                    ..Default::default()
+                },
+            ),
+            prefix,
+        );
+        let token = tokenized.as_token();
+        assert!(
+            token.has_root().is_always(),
+            "`Token::has_root` is `{:?}`, but expected `Always`: in `Tokenized`: `{}`",
+            token.has_root(),
+            tokenized.expression(),
+        );
+        let first = token.concatenation().first().and_then(Token::as_leaf);
+        assert!(
+            matches!(first, Some(LeafKind::Prefix(prefix)) if *prefix == expected),
+            "first token is `{:?}`, but expected prefix `{:?}`: in `Tokenized`: `{}`",
+            first,
+            expected,
+            tokenized.expression(),
+        );
+    }
+
+    #[rstest]
+    #[case("C:")]
+    #[case("C:a")]
+    #[case("a/C:/")]
+    #[case("{C:/}")]
+    #[case("//server")]
+    #[case("//server/share")]
+    fn parse_expression_with_misplaced_or_unterminated_prefix_has_no_prefix(
+        #[case] expression: &str,
+    ) {
+        let result = parse::parse_with_options(
+            expression,
+            ParseOptions {
+                accepts_prefixes: true,
+                ..Default::default()
+            },
+        );
+        if let Ok(tokenized) = result {
+            let first = tokenized.as_token().concatenation().first().and_then(Token::as_leaf);
+            assert!(
+                !matches!(first, Some(LeafKind::Prefix(_))),
+                "first token is `{:?}`, but expected no prefix: in `Tokenized`: `{}`",
+                first,
+                tokenized.expression(),
+            );
+        }
+    }
 }
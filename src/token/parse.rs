@@ -2,7 +2,10 @@
 use miette::{self, Diagnostic, LabeledSpan, SourceCode};
 use nom::error::{VerboseError as NomError, VerboseErrorKind as NomErrorKind};
 use pori::{Located, Location, Stateful};
+use regex::Regex;
 use std::borrow::Cow;
+#[cfg(feature = "json")]
+use std::fmt::Write as _;
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 use thiserror::Error;
@@ -10,9 +13,9 @@ use thiserror::Error;
 use crate::diagnostics::{LocatedError, Span};
 use crate::token::{
     Alternation, Archetype, BranchKind, Class, Concatenation, Evaluation, ExpressionMetadata,
-    LeafKind, Literal, Repetition, Separator, Token, TokenTopology, Tokenized, Wildcard,
+    LeafKind, Literal, Prefix, Repetition, Separator, Token, TokenTopology, Tokenized, Wildcard,
 };
-use crate::PATHS_ARE_CASE_INSENSITIVE;
+use crate::{default_case_sensitivity, PATHS_HAVE_PREFIXES};
 
 type Expression<'i> = Located<'i, str>;
 type Input<'i> = Stateful<Expression<'i>, ParserState>;
@@ -130,6 +133,40 @@ impl<'t> ParseError<'t> {
     pub fn expression(&self) -> &str {
         self.expression.as_ref()
     }
+
+    /// Formats the error as a JSON object for machine-readable diagnostics.
+    ///
+    /// The object has the form `{"expression":...,"start":...,"ends":[...]}`, where `start` is the
+    /// least byte offset of the error locations in the expression and `ends` are the byte offsets
+    /// at which each error location ends, in the same order as [`locations`]. Editors can use
+    /// these offsets to highlight the erroneous parts of an expression.
+    ///
+    /// [`locations`]: Self::locations
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn to_json(&self) -> String {
+        let start = self
+            .locations
+            .iter()
+            .map(|location| location.span().0)
+            .min()
+            .unwrap_or(0);
+        let ends = self
+            .locations
+            .iter()
+            .map(|location| {
+                let (start, n) = location.span();
+                (start + n).to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"{{"expression":"{}","start":{},"ends":[{}]}}"#,
+            escape_json(self.expression()),
+            start,
+            ends,
+        )
+    }
 }
 
 #[cfg(feature = "miette")]
@@ -148,10 +185,30 @@ impl Diagnostic for ParseError<'_> {
     }
 }
 
+#[cfg(feature = "json")]
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for x in text.chars() {
+        match x {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            x if x.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", x as u32);
+            },
+            x => escaped.push(x),
+        }
+    }
+    escaped
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 struct ParserState {
     flags: FlagState,
     subexpression: usize,
+    options: ParseOptions,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -159,10 +216,21 @@ struct FlagState {
     is_case_insensitive: bool,
 }
 
+impl FlagState {
+    fn expression(&self) -> &'static str {
+        if self.is_case_insensitive {
+            "(?i)"
+        }
+        else {
+            "(?-i)"
+        }
+    }
+}
+
 impl Default for FlagState {
     fn default() -> Self {
         FlagState {
-            is_case_insensitive: PATHS_ARE_CASE_INSENSITIVE,
+            is_case_insensitive: default_case_sensitivity().is_case_insensitive(),
         }
     }
 }
@@ -172,7 +240,61 @@ enum FlagToggle {
     CaseInsensitive(bool),
 }
 
-pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, ParseError> {
+/// Options that determine how glob expressions are parsed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParseOptions {
+    /// Whether or not back slashes `\` may be interpreted as separators.
+    ///
+    /// A back slash is interpreted as an escape when it is followed by a meta-character and is
+    /// otherwise interpreted as a separator. For example, `src\{a}` escapes the opening brace of
+    /// an alternation, but `src\lib.rs` is equivalent to `src/lib.rs`.
+    pub accepts_backslash_separators: bool,
+    /// Whether or not tree wildcards `**` exclude hidden components (those that begin with `.`).
+    pub tree_excludes_hidden: bool,
+    /// Whether or not Windows path prefixes may begin an expression.
+    ///
+    /// A prefix is either a drive like `C:` or a UNC share like `//server/share` and must be
+    /// followed by a separator. This is `true` by default only on Windows.
+    pub accepts_prefixes: bool,
+    /// A character that is interpreted as the separator instead of forward slash `/`.
+    ///
+    /// When set, forward slash `/` is interpreted as a literal, the separator may be escaped with
+    /// a back slash `\`, and neither back slash separators nor prefixes are recognized.
+    pub separator: Option<char>,
+}
+
+impl ParseOptions {
+    fn separator(&self) -> Separator {
+        Separator {
+            custom: self.separator,
+        }
+    }
+}
+
+/// Gets an expression of flags that restores the default flags, such as `(?-i)`.
+pub fn default_flags_expression() -> &'static str {
+    FlagState::default().expression()
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            accepts_backslash_separators: false,
+            tree_excludes_hidden: false,
+            accepts_prefixes: PATHS_HAVE_PREFIXES,
+            separator: None,
+        }
+    }
+}
+
+pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, ParseError<'_>> {
+    parse_with_options(expression, ParseOptions::default())
+}
+
+pub fn parse_with_options(
+    expression: &str,
+    options: ParseOptions,
+) -> Result<Tokenized<'_, ExpressionMetadata>, ParseError<'_>> {
     use nom::bytes::complete as bytes;
     use nom::character::complete as character;
     use nom::error;
@@ -193,6 +315,11 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
         }
     }
 
+    fn parse_options(input: Input) -> ParseResult<ParseOptions> {
+        let options = input.state.options;
+        Ok((input, options))
+    }
+
     fn flags<'i, F>(
         mut toggle: impl FnMut(FlagToggle) -> F,
     ) -> impl FnMut(Input<'i>) -> ParseResult<'i, ()>
@@ -244,20 +371,79 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
         })
     }
 
+    // Unicode properties are not recognized when back slashes may be interpreted as separators,
+    // because an expression like `a\p{b}` is then ambiguous.
+    fn property(input: Input) -> ParseResult<Archetype> {
+        fn is_property(name: &str) -> bool {
+            Regex::new(&format!("\\p{{{}}}", name)).is_ok()
+        }
+
+        if input.state.options.accepts_backslash_separators {
+            return Err(ErrorMode::Error(ErrorStack {
+                errors: vec![(input, NomErrorKind::Context("unicode property"))],
+            }));
+        }
+        combinator::map(
+            sequence::tuple((
+                branch::alt((
+                    combinator::value(false, bytes::tag("\\p{")),
+                    combinator::value(true, bytes::tag("\\P{")),
+                )),
+                error::context(
+                    "unicode property name",
+                    combinator::verify(bytes::is_not("}"), |name: &Input| {
+                        is_property(name.as_ref())
+                    }),
+                ),
+                bytes::tag("}"),
+            )),
+            |(is_negated, name, _): (_, Input, _)| Archetype::Property {
+                name: name.into_data().into(),
+                is_negated,
+            },
+        )(input)
+    }
+
     fn class(input: Input) -> ParseResult<TokenTopology<ExpressionMetadata>> {
+        // Intersections have the lowest precedence, so the archetypes of a class are the
+        // intersection of unions of archetypes. For example, `[a-z&&[!aeiou]]` is the
+        // intersection of `a-z` and the nested set `[!aeiou]`.
         fn archetypes(input: Input) -> ParseResult<Vec<Archetype>> {
+            combinator::map(
+                multi::separated_list1(bytes::tag("&&"), union),
+                |mut operands| {
+                    if operands.len() == 1 {
+                        operands.pop().unwrap()
+                    }
+                    else {
+                        vec![Archetype::Intersection(operands)]
+                    }
+                },
+            )(input)
+        }
+
+        fn union(input: Input) -> ParseResult<Vec<Archetype>> {
             let escaped_character = |input| {
                 branch::alt((
-                    character::none_of("[]-\\"),
+                    character::none_of("[]-&\\"),
+                    // An ampersand is a literal unless it begins an intersection `&&`.
+                    sequence::terminated(
+                        character::char('&'),
+                        combinator::not(character::char('&')),
+                    ),
                     branch::alt((
                         combinator::value('[', bytes::tag("\\[")),
                         combinator::value(']', bytes::tag("\\]")),
                         combinator::value('-', bytes::tag("\\-")),
+                        combinator::value('&', bytes::tag("\\&")),
+                        combinator::value('\\', bytes::tag("\\\\")),
                     )),
                 ))(input)
             };
 
             multi::many1(branch::alt((
+                property,
+                set,
                 combinator::map(
                     sequence::separated_pair(escaped_character, bytes::tag("-"), escaped_character),
                     Archetype::from,
@@ -266,46 +452,109 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
             )))(input)
         }
 
-        combinator::map(
-            sequence::delimited(
-                bytes::tag("["),
-                sequence::tuple((combinator::opt(bytes::tag("!")), archetypes)),
-                bytes::tag("]"),
+        // Nested sets may be negated with either `!` or `^`, as in `[a-z&&[^aeiou]]`.
+        fn set(input: Input) -> ParseResult<Archetype> {
+            let separator = input.state.options.separator();
+            combinator::map(
+                sequence::delimited(
+                    bytes::tag("["),
+                    sequence::tuple((
+                        combinator::opt(branch::alt((bytes::tag("!"), bytes::tag("^")))),
+                        archetypes,
+                    )),
+                    bytes::tag("]"),
+                ),
+                move |(negation, archetypes)| {
+                    Archetype::Set(Class {
+                        is_negated: negation.is_some(),
+                        archetypes,
+                        separator,
+                    })
+                },
+            )(input)
+        }
+
+        let separator = input.state.options.separator();
+        branch::alt((
+            combinator::map(
+                sequence::delimited(
+                    bytes::tag("["),
+                    sequence::tuple((combinator::opt(bytes::tag("!")), archetypes)),
+                    bytes::tag("]"),
+                ),
+                move |(negation, archetypes)| {
+                    LeafKind::from(Class {
+                        is_negated: negation.is_some(),
+                        archetypes,
+                        separator,
+                    })
+                    .into()
+                },
             ),
-            |(negation, archetypes)| {
+            // A Unicode property outside of a class is a class with only that property.
+            combinator::map(property, move |archetype| {
                 LeafKind::from(Class {
-                    is_negated: negation.is_some(),
-                    archetypes,
+                    is_negated: false,
+                    archetypes: vec![archetype],
+                    separator,
                 })
                 .into()
+            }),
+        ))(input)
+    }
+
+    fn escaped<'i>(input: Input<'i>) -> ParseResult<'i, &'i str> {
+        // A custom separator is a meta-character and so may also be escaped.
+        if let Some(separator) = input.state.options.separator {
+            if let Ok((input, text)) =
+                combinator::recognize(character::char::<_, ErrorStack<'i>>(separator))(input)
+            {
+                return Ok((input, text.into_data()));
+            }
+        }
+        branch::alt((
+            combinator::value("?", bytes::tag("?")),
+            combinator::value("*", bytes::tag("*")),
+            combinator::value("$", bytes::tag("$")),
+            combinator::value(":", bytes::tag(":")),
+            combinator::value("<", bytes::tag("<")),
+            combinator::value(">", bytes::tag(">")),
+            combinator::value("(", bytes::tag("(")),
+            combinator::value(")", bytes::tag(")")),
+            combinator::value("[", bytes::tag("[")),
+            combinator::value("]", bytes::tag("]")),
+            combinator::value("{", bytes::tag("{")),
+            combinator::value("}", bytes::tag("}")),
+            combinator::value(",", bytes::tag(",")),
+            combinator::value("\\", bytes::tag("\\")),
+        ))(input)
+    }
+
+    // A back slash that is not followed by a meta-character terminates the literal rather than
+    // failing the parse, because it may begin a separator (when back slashes may be interpreted as
+    // separators) or a Unicode property like `\p{L}`. `escaped_transform` rejects unrecognized
+    // escapes, so it is not used here.
+    fn literal_text(input: Input) -> ParseResult<String> {
+        let separator = input.state.options.separator.unwrap_or('/');
+        multi::fold_many1(
+            branch::alt((
+                combinator::map(
+                    bytes::take_till1(move |x| x == separator || crate::is_meta_character(x)),
+                    |text: Input| text.into_data(),
+                ),
+                sequence::preceded(bytes::tag("\\"), escaped),
+            )),
+            String::new,
+            |mut text, chunk| {
+                text.push_str(chunk);
+                text
             },
         )(input)
     }
 
     fn literal(input: Input) -> ParseResult<TokenTopology<ExpressionMetadata>> {
         combinator::map(
-            combinator::verify(
-                bytes::escaped_transform(
-                    bytes::is_not("/?*$:<>()[]{},\\"),
-                    '\\',
-                    branch::alt((
-                        combinator::value("?", bytes::tag("?")),
-                        combinator::value("*", bytes::tag("*")),
-                        combinator::value("$", bytes::tag("$")),
-                        combinator::value(":", bytes::tag(":")),
-                        combinator::value("<", bytes::tag("<")),
-                        combinator::value(">", bytes::tag(">")),
-                        combinator::value("(", bytes::tag("(")),
-                        combinator::value(")", bytes::tag(")")),
-                        combinator::value("[", bytes::tag("[")),
-                        combinator::value("]", bytes::tag("]")),
-                        combinator::value("{", bytes::tag("{")),
-                        combinator::value("}", bytes::tag("}")),
-                        combinator::value(",", bytes::tag(",")),
-                    )),
-                ),
-                |text: &str| !text.is_empty(),
-            ),
+            combinator::verify(literal_text, |text: &str| !text.is_empty()),
             move |text| {
                 LeafKind::from(Literal {
                     text: text.into(),
@@ -316,8 +565,76 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
         )(input)
     }
 
+    // Back slashes are only interpreted as separators when enabled and when not followed by a
+    // meta-character, in which case the back slash is an escape (see `literal`).
+    fn separator_text(input: Input) -> ParseResult<Input> {
+        if let Some(separator) = input.state.options.separator {
+            combinator::recognize(character::char(separator))(input)
+        }
+        else if input.state.options.accepts_backslash_separators {
+            branch::alt((
+                bytes::tag("/"),
+                sequence::terminated(bytes::tag("\\"), combinator::not(escaped)),
+            ))(input)
+        }
+        else {
+            bytes::tag("/")(input)
+        }
+    }
+
     fn separator(input: Input) -> ParseResult<TokenTopology<ExpressionMetadata>> {
-        combinator::value(LeafKind::from(Separator).into(), bytes::tag("/"))(input)
+        let separator = input.state.options.separator();
+        combinator::value(LeafKind::from(separator).into(), separator_text)(input)
+    }
+
+    // Prefixes are only recognized at the very beginning of an expression (not merely the
+    // beginning of a sub-expression) and must be followed by a separator, which is parsed as a
+    // distinct token. Drive letters are normalized to uppercase.
+    fn prefix(input: Input) -> ParseResult<TokenTopology<ExpressionMetadata>> {
+        fn component(input: Input) -> ParseResult<Input> {
+            bytes::is_not("/?*$:<>()[]{},\\")(input)
+        }
+
+        // A leading `\\` would otherwise be interpreted as an escaped back slash.
+        fn unc_root(input: Input) -> ParseResult<Input> {
+            if input.state.options.accepts_backslash_separators {
+                branch::alt((bytes::tag("//"), bytes::tag("\\\\")))(input)
+            }
+            else {
+                bytes::tag("//")(input)
+            }
+        }
+
+        if !input.state.options.accepts_prefixes
+            || input.state.options.separator.is_some()
+            || input.location() != 0
+        {
+            return Err(ErrorMode::Error(ErrorStack {
+                errors: vec![(input, NomErrorKind::Context("prefix"))],
+            }));
+        }
+        combinator::map(
+            sequence::terminated(
+                branch::alt((
+                    combinator::map(
+                        sequence::terminated(
+                            combinator::verify(character::anychar, char::is_ascii_alphabetic),
+                            bytes::tag(":"),
+                        ),
+                        |letter| Prefix::Drive(letter.to_ascii_uppercase()),
+                    ),
+                    combinator::map(
+                        sequence::tuple((unc_root, component, separator_text, component)),
+                        |(_, server, _, share): (_, Input, _, Input)| Prefix::Unc {
+                            server: server.into_data().into(),
+                            share: share.into_data().into(),
+                        },
+                    ),
+                )),
+                combinator::peek(separator_text),
+            ),
+            |prefix| LeafKind::from(prefix).into(),
+        )(input)
     }
 
     fn wildcard<'i>(
@@ -326,18 +643,22 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
         branch::alt((
             error::context(
                 "exactly-one",
-                combinator::map(bytes::tag("?"), |_| LeafKind::from(Wildcard::One).into()),
+                combinator::map(
+                    sequence::preceded(bytes::tag("?"), parse_options),
+                    |options| LeafKind::from(Wildcard::One(options.separator())).into(),
+                ),
             ),
             error::context(
                 "tree",
                 combinator::map(
                     sequence::tuple((
+                        parse_options,
                         error::context(
                             "prefix",
                             combinator::map(
                                 branch::alt((
                                     sequence::tuple((
-                                        combinator::value(true, bytes::tag("/")),
+                                        combinator::value(true, separator_text),
                                         flags_with_state,
                                     )),
                                     sequence::tuple((
@@ -354,7 +675,7 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
                                 "postfix",
                                 branch::alt((
                                     combinator::map(
-                                        sequence::tuple((flags_with_state, bytes::tag("/"))),
+                                        sequence::tuple((flags_with_state, separator_text)),
                                         |(_, postfix)| postfix,
                                     ),
                                     terminator.clone(),
@@ -362,62 +683,109 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
                             ),
                         ),
                     )),
-                    |(has_root, _)| LeafKind::from(Wildcard::Tree { has_root }).into(),
+                    |(options, has_root, _)| {
+                        LeafKind::from(Wildcard::Tree {
+                            has_root,
+                            excludes_hidden: options.tree_excludes_hidden,
+                            excludes_empty: false,
+                            separator: options.separator(),
+                        })
+                        .into()
+                    },
                 ),
             ),
             error::context(
                 "zero-or-more",
                 combinator::map(
-                    sequence::terminated(
-                        bytes::tag("*"),
-                        branch::alt((
-                            combinator::map(
-                                combinator::peek(sequence::tuple((
-                                    flags_without_state,
-                                    error::context("no terminating wildcard", bytes::is_not("*$")),
-                                ))),
-                                |(_, right)| right,
-                            ),
-                            terminator.clone(),
-                        )),
+                    sequence::preceded(
+                        sequence::terminated(
+                            bytes::tag("*"),
+                            branch::alt((
+                                combinator::map(
+                                    combinator::peek(sequence::tuple((
+                                        flags_without_state,
+                                        error::context(
+                                            "no terminating wildcard",
+                                            bytes::is_not("*$"),
+                                        ),
+                                    ))),
+                                    |(_, right)| right,
+                                ),
+                                terminator.clone(),
+                            )),
+                        ),
+                        parse_options,
                     ),
-                    |_| LeafKind::from(Wildcard::ZeroOrMore(Evaluation::Eager)).into(),
+                    |options| {
+                        LeafKind::from(Wildcard::ZeroOrMore(Evaluation::Eager, options.separator()))
+                            .into()
+                    },
                 ),
             ),
             error::context(
                 "zero-or-more",
                 combinator::map(
-                    sequence::terminated(
-                        bytes::tag("$"),
-                        branch::alt((
-                            combinator::map(
-                                combinator::peek(sequence::tuple((
-                                    flags_without_state,
-                                    error::context("no terminating wildcard", bytes::is_not("*$")),
-                                ))),
-                                |(_, right)| right,
-                            ),
-                            terminator,
-                        )),
+                    sequence::preceded(
+                        sequence::terminated(
+                            bytes::tag("$"),
+                            branch::alt((
+                                combinator::map(
+                                    combinator::peek(sequence::tuple((
+                                        flags_without_state,
+                                        error::context(
+                                            "no terminating wildcard",
+                                            bytes::is_not("*$"),
+                                        ),
+                                    ))),
+                                    |(_, right)| right,
+                                ),
+                                terminator,
+                            )),
+                        ),
+                        parse_options,
                     ),
-                    |_| LeafKind::from(Wildcard::ZeroOrMore(Evaluation::Lazy)).into(),
+                    |options| {
+                        LeafKind::from(Wildcard::ZeroOrMore(Evaluation::Lazy, options.separator()))
+                            .into()
+                    },
                 ),
             ),
         ))
     }
 
+    // Branches may be empty, as in `file{,.bak}`, in which case the branch is a concatenation of
+    // an empty literal (like any other sub-glob).
+    // An alternation with only an empty branch `{}` is rejected.
     fn alternation(input: Input) -> ParseResult<TokenTopology<ExpressionMetadata>> {
+        fn terminator(input: Input) -> ParseResult<Input> {
+            combinator::peek(branch::alt((bytes::tag(","), bytes::tag("}"))))(input)
+        }
+
         sequence::delimited(
             bytes::tag("{"),
             combinator::map(
-                multi::separated_list1(
-                    bytes::tag(","),
-                    error::context(
-                        "sub-glob",
-                        glob(move |input| {
-                            combinator::peek(branch::alt((bytes::tag(","), bytes::tag("}"))))(input)
-                        }),
+                combinator::verify(
+                    multi::separated_list1(
+                        bytes::tag(","),
+                        branch::alt((
+                            error::context("sub-glob", glob(terminator)),
+                            error::context(
+                                "empty sub-glob",
+                                combinator::map(pori::span(terminator), |(span, _)| {
+                                    Token::new(
+                                        BranchKind::from(Concatenation::from(vec![Token::empty(
+                                            span,
+                                        )])),
+                                        span,
+                                    )
+                                }),
+                            ),
+                        )),
                     ),
+                    |branches: &[Token<'_, _>]| match branches {
+                        [branch] => !branch.concatenation().iter().all(Token::is_empty),
+                        _ => true,
+                    },
                 ),
                 |branches: Vec<Token<'_, _>>| BranchKind::from(Alternation::from(branches)).into(),
             ),
@@ -433,6 +801,7 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
             combinator::map(
                 sequence::terminated(
                     multi::many1(branch::alt((
+                        annotate(error::context("prefix", prefix)),
                         annotate(error::context(
                             "literal",
                             sequence::preceded(flags_with_state, literal),
@@ -504,6 +873,19 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
             ))(input)
         }
 
+        // Separators are only recognized after a bounds specification, so `;` is otherwise
+        // interpreted as a literal within the repeated sub-glob (as in `<a;b>`). Whitespace
+        // between `;` and the separator sub-glob is ignored.
+        fn separator(input: Input) -> ParseResult<Token<ExpressionMetadata>> {
+            sequence::preceded(
+                sequence::pair(bytes::tag(";"), character::space0),
+                error::context(
+                    "separator",
+                    glob(move |input| combinator::peek(bytes::tag(">"))(input)),
+                ),
+            )(input)
+        }
+
         combinator::map(
             sequence::delimited(
                 bytes::tag("<"),
@@ -515,16 +897,12 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
                         }),
                     ),
                     error::context("bounds", bounds),
+                    combinator::opt(separator),
                 )),
                 bytes::tag(">"),
             ),
-            |(token, (lower, upper))| {
-                BranchKind::from(Repetition {
-                    token: Box::new(token),
-                    lower,
-                    upper,
-                })
-                .into()
+            |(token, (lower, upper), separator)| {
+                BranchKind::from(Repetition::new(token, separator, lower, upper)).into()
             },
         )(input)
     }
@@ -539,16 +917,24 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
         Ok(Tokenized {
             expression: expression.into(),
             token: Token::empty(Default::default()),
+            separator: options.separator(),
         })
     }
     else {
-        let input = Input::new(Expression::from(expression), ParserState::default());
+        let input = Input::new(
+            Expression::from(expression),
+            ParserState {
+                options,
+                ..Default::default()
+            },
+        );
         let token = combinator::all_consuming(glob(combinator::eof))(input)
             .map(|(_, token)| token)
             .map_err(|error| ParseError::new(expression, error))?;
         Ok(Tokenized {
             expression: expression.into(),
             token,
+            separator: options.separator(),
         })
     }
 }
@@ -557,7 +943,7 @@ pub fn parse(expression: &str) -> Result<Tokenized<'_, ExpressionMetadata>, Pars
 pub mod harness {
     use expect_macro::expect;
 
-    use crate::token::{parse, ExpressionMetadata, Tokenized};
+    use crate::token::{parse, ExpressionMetadata, ParseOptions, Tokenized};
 
     pub fn assert_parse_expression_is_ok(expression: &str) -> Tokenized<'_, ExpressionMetadata> {
         expect!(
@@ -566,4 +952,17 @@ pub mod harness {
             expression,
         )
     }
+
+    pub fn assert_parse_expression_with_options_is_ok(
+        expression: &str,
+        options: ParseOptions,
+    ) -> Tokenized<'_, ExpressionMetadata> {
+        expect!(
+            parse::parse_with_options(expression, options),
+            "`parse::parse_with_options` is `Err`, but expected `Ok`: in expression: `{}`: \
+             options: `{:?}`",
+            expression,
+            options,
+        )
+    }
 }
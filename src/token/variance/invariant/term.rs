@@ -21,6 +21,13 @@ where
         ))
     }
 
+    pub fn unbounded_from(lower: T) -> Self {
+        BoundaryTerm::Conjunctive(SeparatedTerm(
+            Termination::Coalescent,
+            Variance::Variant(lower.into_lower_bound()),
+        ))
+    }
+
     pub fn as_variance(&self) -> Variance<&T, Option<&Boundedness<T::Bound>>> {
         match self {
             BoundaryTerm::Conjunctive(ref term) => term.1.as_ref().map_variant(Some),
@@ -253,12 +253,11 @@ impl<'t> PartialEq for Fragment<'t> {
         match (self, other) {
             (Nominal(ref left), Nominal(ref right)) => {
                 if PATHS_ARE_CASE_INSENSITIVE {
-                    // This comparison uses Unicode simple case folding. It would be better to use
-                    // full case folding (and better still to use case folding appropriate for the
-                    // language of the text), but this approach is used to have consistent results
-                    // with the regular expression encoding of compiled globs. A more comprehensive
-                    // alternative would be to use something like the `focaccia` crate. See also
-                    // `CharExt::has_casing`.
+                    // This comparison uses Unicode simple case folding or, with the `unicode-case`
+                    // feature, full case folding. It would be better still to use case folding
+                    // appropriate for the language of the text, but this approach is used to have
+                    // consistent results with the regular expression encoding of compiled globs.
+                    // See also `CharExt::has_casing`.
                     encode::case_folded_eq(left.as_ref(), right.as_ref())
                 }
                 else {
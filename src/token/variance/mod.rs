@@ -15,7 +15,7 @@ use crate::token::variance::natural::{
 };
 use crate::token::variance::ops::{Conjunction, Disjunction, Product};
 use crate::token::walk::{ChildToken, Fold, Forward, ParentToken, Sequencer};
-use crate::token::{Boundary, BranchKind, LeafKind};
+use crate::token::{Boundary, BranchKind, LeafKind, Wildcard};
 
 pub use Boundedness::{Bounded, Unbounded};
 
@@ -132,7 +132,7 @@ impl<T, B> Variance<T, Boundedness<B>> {
     pub fn is_unbounded(&self) -> bool {
         self.as_ref()
             .variant()
-            .map_or(false, Boundedness::is_unbounded)
+            .is_some_and(Boundedness::is_unbounded)
     }
 }
 
@@ -315,17 +315,34 @@ impl Sequencer for TreeExhaustiveness {
         &mut self,
         parent: ParentToken<'i, 't, A>,
     ) -> impl Iterator<Item = ChildToken<'i, 't, A>> {
-        parent.into_tokens().rev().take_while(|token| {
-            token.as_ref().as_leaf().map_or(true, |leaf| {
-                if let Some(Boundary::Separator) = leaf.boundary() {
-                    true
-                }
-                else {
-                    let breadth = self::term::<Breadth>(leaf);
-                    let text = self::term::<Text>(leaf);
-                    breadth.is_unbounded() && text.is_unbounded()
-                }
-            })
+        // Separated repetitions are never considered exhaustive. None of their tokens are
+        // enqueued, because the reversed terms of the repeated token and separator cannot be
+        // distinguished when folded.
+        let is_separated = matches!(
+            parent.as_ref(),
+            BranchKind::Repetition(ref repetition) if repetition.is_separated(),
+        );
+        parent.into_tokens().rev().take_while(move |token| {
+            !is_separated
+                && token.as_ref().as_leaf().map_or(true, |leaf| {
+                    if let Some(Boundary::Separator) = leaf.boundary() {
+                        true
+                    }
+                    // Tree wildcards that exclude hidden components do not match every path
+                    // beneath a directory, so they are never exhaustive.
+                    else if let LeafKind::Wildcard(Wildcard::Tree {
+                        excludes_hidden: true,
+                        ..
+                    }) = leaf
+                    {
+                        false
+                    }
+                    else {
+                        let breadth = self::term::<Breadth>(leaf);
+                        let text = self::term::<Text>(leaf);
+                        breadth.is_unbounded() && text.is_unbounded()
+                    }
+                })
         })
     }
 }
@@ -352,7 +369,7 @@ impl<'t, A> Fold<'t, A> for TreeExhaustiveness {
                 .as_ref()
                 .map(BoundaryTerm::is_exhaustive)
                 .as_ref()
-                .map_or(false, When::is_maybe_true)
+                .is_some_and(When::is_maybe_true)
             {
                 sum
             }
@@ -481,9 +498,9 @@ mod tests {
     use rstest::rstest;
 
     use crate::query::When;
-    use crate::token::parse;
     use crate::token::variance::invariant::{Depth, Size, Text};
     use crate::token::variance::{harness, TokenVariance, Variance};
+    use crate::token::{parse, ParseOptions};
 
     use When::{Always, Never, Sometimes};
 
@@ -504,6 +521,9 @@ mod tests {
     #[case("<a/:0,3>", harness::range(0, 3))]
     #[case("x<a/:3>", harness::invariant(3))]
     #[case("<a/:3>x", harness::invariant(4))]
+    #[case("<a:3;/>", harness::invariant(3))]
+    #[case("<a:1,3;/>", harness::range(1, 3))]
+    #[case("<a/:2;b/>", harness::invariant(3))]
     // TODO: Open components must not be empty. This means that `*` must match something if it
     //       comprises a component, for example. This is perhaps more obvious in patterns like
     //       `a/*/b`. However, this is not yet tested and is not consistently emitted by the
@@ -547,7 +567,10 @@ mod tests {
     #[case("a", harness::invariant(1))]
     #[case("a/b", harness::invariant(3))]
     #[case("a/**", harness::range(1, None))]
+    #[case("<a:3;->", harness::invariant(5))]
+    #[case("<ab:1,3;->", harness::range(2, 8))]
     #[case("<a*/:1,>*", harness::range(2, None))]
+    #[case("a<b:0,2>", harness::range(1, 3))]
     #[case("**", Variance::unbounded())]
     #[case("<*/>*", Variance::unbounded())]
     #[case("<<?>/>*", Variance::unbounded())]
@@ -565,6 +588,7 @@ mod tests {
     #[rstest]
     #[case("a", harness::invariant(Text::from_components(["a"]).unwrap()))]
     #[case("a/b", harness::invariant(Text::from_components(["a", "b"]).unwrap()))]
+    #[case("<a:3;/>", harness::invariant(Text::from_components(["a", "a", "a"]).unwrap()))]
     #[case("a/**", harness::bounded())]
     #[case("<a*/:1,>*", harness::bounded())]
     #[case("**", Variance::unbounded())]
@@ -638,6 +662,8 @@ mod tests {
     #[case("a</**/b>", Never)]
     #[case("<?>", Never)]
     #[case("<?/>", Never)]
+    // Separated repetitions are never considered exhaustive.
+    #[case("<*:1,;/>", Never)]
     #[case("{**/a,**/b}", Never)]
     fn parse_expression_is_exhaustive_eq(#[case] expression: &str, #[case] expected: When) {
         harness::assert_tokenized_exhaustiveness_eq(
@@ -645,4 +671,25 @@ mod tests {
             expected,
         );
     }
+
+    #[rstest]
+    #[case("**", Never)]
+    #[case("a/**", Never)]
+    #[case("{a/**,b/**}", Never)]
+    #[case("<a/**:1,>", Never)]
+    fn parse_expression_with_tree_excluding_hidden_is_exhaustive_eq(
+        #[case] expression: &str,
+        #[case] expected: When,
+    ) {
+        harness::assert_tokenized_exhaustiveness_eq(
+            parse::harness::assert_parse_expression_with_options_is_ok(
+                expression,
+                ParseOptions {
+                    tree_excludes_hidden: true,
+                    ..Default::default()
+                },
+            ),
+            expected,
+        );
+    }
 }
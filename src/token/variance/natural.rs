@@ -311,6 +311,19 @@ impl NaturalRange {
         Self::from_closed_and_open(lower.into_usize(), upper.into_usize())
     }
 
+    /// Gets the intersection of the ranges or `None` if the ranges are disjoint.
+    pub fn intersection(self, other: impl Into<NaturalRange>) -> Option<Self> {
+        let other = other.into();
+        let lower = cmp::max(self.lower(), other.lower()).into_usize();
+        let upper = cmp::min(self.upper(), other.upper()).into_usize();
+        if upper.is_some_and(|upper| upper < lower) {
+            None
+        }
+        else {
+            Some(Self::from_closed_and_open(lower, upper))
+        }
+    }
+
     pub fn lower(&self) -> NaturalLower {
         match self {
             Variance::Invariant(ref n) => NaturalBound::from(*n).into_lower(),
@@ -326,11 +339,11 @@ impl NaturalRange {
     }
 
     pub fn is_one(&self) -> bool {
-        self.as_ref().invariant().map_or(false, |&n| n == 1)
+        self.as_ref().invariant().is_some_and(|&n| n == 1)
     }
 
     pub fn is_zero(&self) -> bool {
-        self.as_ref().invariant().map_or(false, |&n| n == 0)
+        self.as_ref().invariant().is_some_and(|&n| n == 0)
     }
 }
 
@@ -680,7 +693,11 @@ impl BoundedVariantRange {
                 extent,
             },
             Lower(lower) => Lower(expect_add(lower)),
-            Upper(upper) => Upper(expect_add(upper)),
+            // Translating a range with an open lower bound closes the lower bound.
+            Upper(upper) => NonZeroUsize::new(vector).map_or(Upper(upper), |lower| Both {
+                lower,
+                extent: upper,
+            }),
         }
     }
 
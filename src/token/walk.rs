@@ -48,7 +48,7 @@ pub trait Fold<'t, A> {
     fn term(&mut self, leaf: &LeafKind<'t>) -> Self::Term;
 }
 
-impl<'f, 't, A, F> Fold<'t, A> for &'f mut F
+impl<'t, A, F> Fold<'t, A> for &mut F
 where
     F: Fold<'t, A>,
 {
@@ -457,12 +457,16 @@ impl Sequencer for Ending {
         parent: ParentToken<'i, 't, A>,
     ) -> impl Iterator<Item = Child<&'i Token<'t, A>>> {
         let composition = parent.as_ref().composition();
+        let is_repetition = matches!(parent.as_ref(), BranchKind::Repetition(_));
         let tokens = parent.into_tokens();
-        let n = match composition {
-            Composition::Conjunctive(_) => tokens.len().saturating_sub(1),
-            Composition::Disjunctive(_) => 0,
+        let n = tokens.len();
+        let (skip, take) = match composition {
+            // Repetitions end with their repeated token, which always precedes any separator.
+            Composition::Conjunctive(_) if is_repetition => (0, 1),
+            Composition::Conjunctive(_) => (n.saturating_sub(1), 1),
+            Composition::Disjunctive(_) => (0, n),
         };
-        tokens.skip(n)
+        tokens.skip(skip).take(take)
     }
 }
 
@@ -1,4 +1,8 @@
+use std::cmp;
+use std::env;
+use std::fs;
 use std::num::NonZeroUsize;
+use std::path::{Component, Path, PathBuf};
 
 use crate::query::DepthVariance;
 
@@ -36,7 +40,7 @@ impl DepthMin {
     pub fn from_min_or_unbounded(min: usize) -> DepthBehavior {
         use DepthBehavior::{Min, Unbounded};
 
-        DepthMin::try_from(min).map(Min).unwrap_or(Unbounded)
+        DepthMin::try_from(min).map_or(Unbounded, Min)
     }
 
     pub(crate) fn min_at_pivot(self, pivot: usize) -> usize {
@@ -226,6 +230,32 @@ impl DepthBehavior {
         };
         DepthBehavior::bounded(translation(min.into()).ok()?, translation(max.into()).ok()?)
     }
+
+    /// Bounds the maximum depth to at most the given depth.
+    ///
+    /// Returns the behavior unchanged if the given depth is less than the minimum depth.
+    pub(crate) fn bounded_by_max(self, max: usize) -> Self {
+        let (min, upper) = match self {
+            DepthBehavior::Max(upper) => (None, Some(upper.0)),
+            DepthBehavior::Min(min) => (Some(min.0.get()), None),
+            DepthBehavior::MinMax(minmax) => (Some(minmax.min.get()), Some(minmax.max().get())),
+            DepthBehavior::Unbounded => (None, None),
+        };
+        DepthBehavior::bounded(min, upper.map_or(max, |upper| cmp::min(upper, max)))
+            .unwrap_or(self)
+    }
+
+    pub(crate) fn min_max_at_pivot(self, pivot: usize) -> (usize, Option<usize>) {
+        match self {
+            DepthBehavior::Max(max) => (0, Some(max.max_at_pivot(pivot))),
+            DepthBehavior::Min(min) => (min.min_at_pivot(pivot), None),
+            DepthBehavior::MinMax(minmax) => {
+                let (min, max) = minmax.min_max_at_pivot(pivot);
+                (min, Some(max))
+            },
+            DepthBehavior::Unbounded => (0, None),
+        }
+    }
 }
 
 impl From<DepthMax> for DepthBehavior {
@@ -276,6 +306,97 @@ pub enum LinkBehavior {
     /// If a link is re-entrant and forms a cycle, then an error will be emitted instead of an
     /// entry and traversal does not follow the link.
     ReadTarget,
+    /// Read the target of the symbolic link, but do not descend into it.
+    ///
+    /// This behavior reads the target of the symbolic link like [`ReadTarget`], but traversal
+    /// never follows the link like [`ReadFile`]. The corresponding entry uses the path of the link
+    /// file and its metadata describes the target, so links to directories are yielded as
+    /// directories, but their contents are not read. Because links are never followed, cycles
+    /// cannot occur. If the target cannot be read, such as when the link is broken, then the
+    /// entry describes the link file itself.
+    ///
+    /// [`ReadFile`]: crate::walk::LinkBehavior::ReadFile
+    /// [`ReadTarget`]: crate::walk::LinkBehavior::ReadTarget
+    ReadTargetNoDescend,
+}
+
+impl LinkBehavior {
+    /// Returns `true` if traversal follows links into their targets.
+    pub(crate) fn is_following(&self) -> bool {
+        matches!(self, LinkBehavior::ReadTarget)
+    }
+}
+
+/// Configuration for normalizing the paths of files yielded by a walk.
+///
+/// Determines how the root path of a walk is presented in the paths of files yielded by functions
+/// like [`Glob::walk_with_behavior`]. Only the [root path segment][`Entry::root_relative_paths`]
+/// is normalized: the relative path segment is read from the directory tree and never contains
+/// `.` nor `..` components. Normalization is purely presentational and does not affect matching,
+/// which is always performed against the relative path segment.
+///
+/// Normalization only applies to walks of the native file system. Paths read from an [`Fs`] via
+/// functions like [`Glob::walk_in`] and paths in [`WalkError`]s are never normalized.
+///
+/// # Defaults
+///
+/// The default path normalization is [`Verbatim`] (the root path is used as given).
+///
+/// [`Entry::root_relative_paths`]: crate::walk::Entry::root_relative_paths
+/// [`Fs`]: crate::walk::Fs
+/// [`Glob::walk_in`]: crate::Glob::walk_in
+/// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+/// [`Verbatim`]: crate::walk::PathNormalization::Verbatim
+/// [`WalkError`]: crate::walk::WalkError
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PathNormalization {
+    /// Use the root path as given.
+    ///
+    /// Paths are joined to the root path verbatim, so walking `./project` yields paths like
+    /// `./project/src/lib.rs`.
+    #[default]
+    Verbatim,
+    /// Lexically clean the root path.
+    ///
+    /// Current directory components `.` are removed and parent directory components `..` are
+    /// collapsed where it is safe to do so: that is, when the preceding component is a directory
+    /// that is not a symbolic link (collapsing `link/..` may refer to a different directory than
+    /// the parent of the link target). If the root path is cleaned away entirely, such as when
+    /// walking `.`, then the root itself is yielded as `.` and other paths are relative. For
+    /// example, walking `./project` yields paths like `project/src/lib.rs` and walking `.` yields
+    /// paths like `src/lib.rs`.
+    Clean,
+    /// Lexically clean the root path and make it relative to the current working directory.
+    ///
+    /// This is the same as [`Clean`], but additionally strips the current working directory from
+    /// an absolute root path that is beneath it. Root paths that are relative, outside of the
+    /// current working directory, or for which the current working directory cannot be read are
+    /// only cleaned.
+    ///
+    /// [`Clean`]: crate::walk::PathNormalization::Clean
+    RelativeToCurrentDir,
+}
+
+impl PathNormalization {
+    /// Normalizes the given root path of a walk.
+    ///
+    /// Returns `None` if the root path is used verbatim.
+    pub(crate) fn normalize_root(self, root: &Path) -> Option<PathBuf> {
+        match self {
+            PathNormalization::Verbatim => None,
+            PathNormalization::Clean => Some(clean(root)),
+            PathNormalization::RelativeToCurrentDir => {
+                let root = clean(root);
+                Some(
+                    env::current_dir()
+                        .ok()
+                        .filter(|_| root.is_absolute())
+                        .and_then(|cwd| root.strip_prefix(clean(&cwd)).ok().map(PathBuf::from))
+                        .unwrap_or(root),
+                )
+            },
+        }
+    }
 }
 
 /// Configuration for walking directory trees.
@@ -288,14 +409,18 @@ pub enum LinkBehavior {
 ///
 /// # Defaults
 ///
-/// By default, walk behavior has [unbounded depth][`DepthBehavior::Unbounded`] and reads links as
-/// [regular files][`LinkBehavior::ReadFile`] (ignoring their targets). Fields have the following
-/// values:
+/// By default, walk behavior has [unbounded depth][`DepthBehavior::Unbounded`], reads links as
+/// [regular files][`LinkBehavior::ReadFile`] (ignoring their targets), and yields paths joined to
+/// the [verbatim][`PathNormalization::Verbatim`] root path. Fields have the following values:
 ///
-/// | Field     | Description                       | Value                        |
-/// |-----------|-----------------------------------|------------------------------|
-/// | [`depth`] | Bounds on depth.                  | [`DepthBehavior::Unbounded`] |
-/// | [`link`]  | Interpretation of symbolic links. | [`LinkBehavior::ReadFile`]   |
+/// | Field                       | Description                       | Value                           |
+/// |-----------------------------|-----------------------------------|---------------------------------|
+/// | [`depth`]                   | Bounds on depth.                  | [`DepthBehavior::Unbounded`]    |
+/// | [`link`]                    | Interpretation of symbolic links. | [`LinkBehavior::ReadFile`]      |
+/// | [`include_root`]            | Whether to yield the root path.   | `true`                          |
+/// | [`normalize`]               | Normalization of yielded paths.   | [`PathNormalization::Verbatim`] |
+/// | [`yield_intermediate_dirs`] | Whether to yield unmatched paths. | `false`                         |
+/// | [`strict_components`]       | Whether to reject odd components. | `false`                         |
 ///
 /// # Examples
 ///
@@ -315,10 +440,30 @@ pub enum LinkBehavior {
 /// }
 /// ```
 ///
+/// To walk only the contents of a directory, exclude the root path.
+///
+/// ```rust,no_run
+/// use wax::walk::WalkBehavior;
+/// use wax::Glob;
+///
+/// let behavior = WalkBehavior {
+///     include_root: false,
+///     ..Default::default()
+/// };
+/// for entry in Glob::new("**").unwrap().walk_with_behavior(".", behavior) {
+///     let entry = entry.unwrap();
+///     // ...
+/// }
+/// ```
+///
 /// [`depth`]: crate::walk::WalkBehavior::depth
 /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+/// [`include_root`]: crate::walk::WalkBehavior::include_root
 /// [`link`]: crate::walk::WalkBehavior::link
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+/// [`normalize`]: crate::walk::WalkBehavior::normalize
+/// [`strict_components`]: crate::walk::WalkBehavior::strict_components
+/// [`yield_intermediate_dirs`]: crate::walk::WalkBehavior::yield_intermediate_dirs
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct WalkBehavior {
     /// Bounds on the depth of the walk and matched files.
     ///
@@ -336,6 +481,74 @@ pub struct WalkBehavior {
     ///
     /// [`LinkBehavior::ReadFile`]: crate::walk::LinkBehavior::ReadFile
     pub link: LinkBehavior,
+    /// Whether or not to yield the root path of the walk.
+    ///
+    /// Determines if the file at the given path of a walk is yielded, such as the directory `.`
+    /// when matching `**` via `Glob::new("**").unwrap().walk(".")`. This is the only file with an
+    /// empty [relative path][`Entry::root_relative_paths`]. When `false`, this file is discarded,
+    /// but its directory tree is still read. Files in any invariant prefix of a glob are never
+    /// yielded as the root path, because that path is not given to the walk. The default value is
+    /// `true`.
+    ///
+    /// [`Entry::root_relative_paths`]: crate::walk::Entry::root_relative_paths
+    pub include_root: bool,
+    /// Normalization of the paths of yielded files.
+    ///
+    /// Determines how the root path of a walk is presented in the paths of yielded files, such as
+    /// whether walking `./project` yields `./project/src/lib.rs` or `project/src/lib.rs`. The
+    /// default value is [`PathNormalization::Verbatim`].
+    ///
+    /// [`PathNormalization::Verbatim`]: crate::walk::PathNormalization::Verbatim
+    pub normalize: PathNormalization,
+    /// Whether or not to yield directories that are read but do not match.
+    ///
+    /// Determines if directories that do not match a glob but may contain matching files are
+    /// yielded, such as `src` and `src/walk` when walking `**/*.rs`. These directories are the
+    /// path to each match and are yielded as [intermediate entries][`GlobEntry::is_intermediate`]
+    /// before any of their contents. Directories that cannot contain matching files, such as `doc`
+    /// when walking `src/**/*.rs`, are never read nor yielded. This is useful for presenting
+    /// matches in a tree. The default value is `false`.
+    ///
+    /// [`GlobEntry::is_intermediate`]: crate::walk::GlobEntry::is_intermediate
+    pub yield_intermediate_dirs: bool,
+    /// Whether or not to yield errors for paths with components that are not normal.
+    ///
+    /// Root-relative paths of files are matched against a glob component by component, and any
+    /// components that are not normal, such as `.`, `..`, or Windows prefixes, are skipped. When
+    /// `true`, such a component in the root-relative path of a file instead yields an error (see
+    /// [`WalkError::is_unexpected_component`]) and the file is not matched. This can help diagnose
+    /// unusual paths, such as those of some symbolic links and junctions on Windows. The default
+    /// value is `false`.
+    ///
+    /// [`WalkError::is_unexpected_component`]: crate::walk::WalkError::is_unexpected_component
+    pub strict_components: bool,
+}
+
+impl WalkBehavior {
+    pub(crate) fn min_max_at_pivot(&self, pivot: usize) -> (usize, Option<usize>) {
+        let (min, max) = self.depth.min_max_at_pivot(pivot);
+        // The root path is only at depth zero if there is no pivot (that is, there is no
+        // invariant prefix between the root path and the walk).
+        if !self.include_root && pivot == 0 {
+            (cmp::max(min, 1), max)
+        }
+        else {
+            (min, max)
+        }
+    }
+}
+
+impl Default for WalkBehavior {
+    fn default() -> Self {
+        WalkBehavior {
+            depth: DepthBehavior::default(),
+            link: LinkBehavior::default(),
+            include_root: true,
+            normalize: PathNormalization::default(),
+            yield_intermediate_dirs: false,
+            strict_components: false,
+        }
+    }
 }
 
 impl From<()> for WalkBehavior {
@@ -379,3 +592,37 @@ impl From<LinkBehavior> for WalkBehavior {
         }
     }
 }
+
+impl From<PathNormalization> for WalkBehavior {
+    fn from(normalize: PathNormalization) -> Self {
+        WalkBehavior {
+            normalize,
+            ..Default::default()
+        }
+    }
+}
+
+/// Lexically cleans a path.
+///
+/// See [`PathNormalization::Clean`].
+fn clean(path: &Path) -> PathBuf {
+    let mut components: Vec<Component<'_>> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => match components.last() {
+                // The parent of the root directory is the root directory.
+                Some(Component::RootDir) => {},
+                Some(Component::Normal(_))
+                    if fs::symlink_metadata(components.iter().collect::<PathBuf>())
+                        .is_ok_and(|metadata| metadata.is_dir()) =>
+                {
+                    components.pop();
+                },
+                _ => components.push(component),
+            },
+            _ => components.push(component),
+        }
+    }
+    components.into_iter().collect()
+}
@@ -0,0 +1,310 @@
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::capture::MatchedText;
+use crate::filter::{CancelWalk, SeparatingFilterInput, TreeResidue};
+use crate::walk::{Entry, FileType, SplitPath, WalkBehavior, WalkError};
+use crate::CandidatePath;
+
+/// A file system that can be traversed by functions like [`Glob::walk_in`].
+///
+/// `Fs` abstracts the reads that are necessary to walk a directory tree. This decouples matching
+/// from the native file system, so that patterns can be matched against in-memory trees and
+/// similar fixtures. The native file system is implemented by [`StdFs`].
+///
+/// Types like [`Metadata`] and [`FileType`] cannot be constructed outside of the standard library,
+/// so `Fs` only distinguishes directories from other files.
+///
+/// # Examples
+///
+/// The following example matches a [`Glob`] against an in-memory directory tree.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use std::io;
+/// use std::path::{Path, PathBuf};
+/// use wax::walk::{Entry, Fs, WalkBehavior};
+/// use wax::Glob;
+///
+/// // Maps directory paths to the paths of their children.
+/// struct Tree(BTreeMap<PathBuf, Vec<PathBuf>>);
+///
+/// impl Fs for Tree {
+///     fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+///         self.0
+///             .get(path)
+///             .cloned()
+///             .ok_or_else(|| io::ErrorKind::NotFound.into())
+///     }
+///
+///     fn is_dir(&self, path: &Path) -> io::Result<bool> {
+///         Ok(self.0.contains_key(path))
+///     }
+/// }
+///
+/// let tree = Tree(
+///     [
+///         ("src".into(), vec!["src/lib.rs".into(), "src/walk".into()]),
+///         ("src/walk".into(), vec!["src/walk/mod.rs".into()]),
+///     ]
+///     .into_iter()
+///     .collect(),
+/// );
+/// let glob = Glob::new("**/*.rs").unwrap();
+/// for entry in glob.walk_in(&tree, "src", WalkBehavior::default()) {
+///     let entry = entry.unwrap();
+///     println!("{:?}", entry.path());
+/// }
+/// ```
+///
+/// [`FileType`]: std::fs::FileType
+/// [`Glob`]: crate::Glob
+/// [`Glob::walk_in`]: crate::Glob::walk_in
+/// [`Metadata`]: std::fs::Metadata
+/// [`StdFs`]: crate::walk::StdFs
+pub trait Fs {
+    /// Reads the paths of the files in the directory at the given path.
+    ///
+    /// The returned paths are yielded by a walk in the same order.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Determines whether or not the file at the given path is a directory.
+    fn is_dir(&self, path: &Path) -> io::Result<bool>;
+}
+
+/// The native file system.
+///
+/// `StdFs` reads the native file system via [`std::fs`]. Symbolic links are read as regular files
+/// and their targets are ignored.
+///
+/// [`std::fs`]: std::fs
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct StdFs;
+
+impl Fs for StdFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+
+    fn is_dir(&self, path: &Path) -> io::Result<bool> {
+        fs::symlink_metadata(path).map(|metadata| metadata.is_dir())
+    }
+}
+
+/// Describes a file with a path matching a [`Glob`] in a directory tree read from an [`Fs`].
+///
+/// [`Fs`] has no notion of [`Metadata`], so [`Entry::metadata`] always fails for `FsEntry`s and
+/// their [`FileType`]s only distinguish directories from other files.
+///
+/// See [`Glob::walk_in`].
+///
+/// [`Entry::metadata`]: crate::walk::Entry::metadata
+/// [`FileType`]: crate::walk::FileType
+/// [`Fs`]: crate::walk::Fs
+/// [`Glob`]: crate::Glob
+/// [`Glob::walk_in`]: crate::Glob::walk_in
+/// [`Metadata`]: std::fs::Metadata
+#[derive(Debug)]
+pub struct FsEntry {
+    path: PathBuf,
+    depth: usize,
+    pivot: usize,
+    split: SplitPath,
+    is_dir: bool,
+    matched: MatchedText<'static>,
+    component_count: usize,
+    is_intermediate: bool,
+}
+
+impl FsEntry {
+    pub(crate) fn with_match(self, matched: MatchedText<'static>, component_count: usize) -> Self {
+        FsEntry {
+            matched,
+            component_count,
+            ..self
+        }
+    }
+
+    pub(crate) fn into_intermediate(self) -> Self {
+        FsEntry {
+            is_intermediate: true,
+            ..self
+        }
+    }
+
+    /// Returns `true` if the file is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// Gets the depth of the path of the file from the invariant prefix of the glob.
+    ///
+    /// See [`GlobEntry::match_depth`].
+    ///
+    /// [`GlobEntry::match_depth`]: crate::walk::GlobEntry::match_depth
+    pub fn match_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Converts the entry to the relative [`CandidatePath`].
+    ///
+    /// See [`GlobEntry::to_candidate_path`].
+    ///
+    /// [`CandidatePath`]: crate::CandidatePath
+    /// [`GlobEntry::to_candidate_path`]: crate::walk::GlobEntry::to_candidate_path
+    pub fn to_candidate_path(&self) -> CandidatePath<'_> {
+        self.matched.to_candidate_path()
+    }
+
+    /// Gets the matched text in the path of the file.
+    pub fn matched(&self) -> &MatchedText<'static> {
+        &self.matched
+    }
+
+    /// Gets the number of components in the path of the file that are matched by individual
+    /// components of the glob.
+    ///
+    /// See [`GlobEntry::matched_component_count`].
+    ///
+    /// [`GlobEntry::matched_component_count`]: crate::walk::GlobEntry::matched_component_count
+    pub fn matched_component_count(&self) -> usize {
+        self.component_count
+    }
+
+    /// Returns `true` if the file is a directory that does not match the glob.
+    ///
+    /// See [`GlobEntry::is_intermediate`].
+    ///
+    /// [`GlobEntry::is_intermediate`]: crate::walk::GlobEntry::is_intermediate
+    pub fn is_intermediate(&self) -> bool {
+        self.is_intermediate
+    }
+}
+
+impl Entry for FsEntry {
+    fn into_path(self) -> PathBuf {
+        self.path
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn root_relative_paths(&self) -> (&Path, &Path) {
+        self.split.split(&self.path)
+    }
+
+    fn metadata(&self) -> Result<Metadata, WalkError> {
+        Err(WalkError::io(
+            Some(self.path.clone()),
+            self.depth(),
+            io::ErrorKind::Unsupported.into(),
+        ))
+    }
+
+    fn file_type(&self) -> FileType {
+        FileType::from_is_dir(self.is_dir)
+    }
+
+    fn depth(&self) -> usize {
+        self.depth
+            .checked_add(self.pivot)
+            .expect("overflow determining depth")
+    }
+}
+
+/// Iterator over the files in a directory tree read from an [`Fs`].
+///
+/// Like `WalkTree`, this iterator traverses the tree in pre-order and the most recently yielded
+/// directory can be discarded via [`CancelWalk`] before it is read.
+pub(crate) struct WalkFs<'f> {
+    fs: &'f dyn Fs,
+    pivot: usize,
+    min: usize,
+    max: Option<usize>,
+    /// Files that have yet to be read and their depths.
+    pending: Vec<(PathBuf, usize)>,
+    /// The most recently yielded directory, if any, which is read lazily.
+    tree: Option<(PathBuf, usize)>,
+}
+
+impl<'f> WalkFs<'f> {
+    pub fn with_pivot_and_behavior(
+        fs: &'f dyn Fs,
+        root: impl Into<PathBuf>,
+        pivot: usize,
+        behavior: impl Into<WalkBehavior>,
+    ) -> Self {
+        // There is no notion of symbolic links in `Fs`, so the link behavior is ignored.
+        let (min, max) = behavior.into().min_max_at_pivot(pivot);
+        WalkFs {
+            fs,
+            pivot,
+            min,
+            max,
+            pending: vec![(root.into(), 0)],
+            tree: None,
+        }
+    }
+}
+
+impl CancelWalk for WalkFs<'_> {
+    fn cancel_walk_tree(&mut self) {
+        self.tree = None;
+    }
+}
+
+impl Iterator for WalkFs<'_> {
+    type Item = Result<FsEntry, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((path, depth)) = self.tree.take() {
+                if self.max.map_or(true, |max| depth < max) {
+                    match self.fs.read_dir(&path) {
+                        Ok(paths) => self
+                            .pending
+                            .extend(paths.into_iter().rev().map(|path| (path, depth + 1))),
+                        Err(error) => return Some(Err(WalkError::io(Some(path), depth, error))),
+                    }
+                }
+            }
+            let (path, depth) = self.pending.pop()?;
+            let is_dir = match self.fs.is_dir(&path) {
+                Ok(is_dir) => is_dir,
+                Err(error) => return Some(Err(WalkError::io(Some(path), depth, error))),
+            };
+            if is_dir {
+                self.tree = Some((path.clone(), depth));
+            }
+            // Files beneath the minimum depth are read but not yielded, so they cannot be
+            // discarded via `CancelWalk`.
+            if depth >= self.min {
+                let split = SplitPath::at_depth(
+                    &path,
+                    depth
+                        .checked_add(self.pivot)
+                        .expect("overflow determining depth"),
+                );
+                return Some(Ok(FsEntry {
+                    path,
+                    depth,
+                    pivot: self.pivot,
+                    split,
+                    is_dir,
+                    // Files are matched against globs by a composing filter, which replaces this.
+                    matched: MatchedText::uncaptured(String::new()),
+                    component_count: 0,
+                    is_intermediate: false,
+                }));
+            }
+        }
+    }
+}
+
+impl SeparatingFilterInput for WalkFs<'_> {
+    type Feed = (Result<FsEntry, WalkError>, TreeResidue<FsEntry>);
+}
@@ -1,18 +1,21 @@
 use itertools::Itertools;
 use regex::Regex;
-use std::borrow::Borrow;
-use std::fs::{FileType, Metadata};
-use std::path::{Component, Path, PathBuf};
+use std::borrow::{Borrow, Cow};
+use std::cmp;
+use std::fs::{self, Metadata};
+use std::path::{Component, Path, PathBuf, StripPrefixError};
 
 use crate::capture::MatchedText;
 use crate::encode::CompileError;
-use crate::filter::{HierarchicalIterator, Separation};
-use crate::token::{Token, TokenTree, Tokenized};
+use crate::filter::{self, CancelWalk, HierarchicalIterator, SeparatingFilter, Separation};
+use crate::query::DepthVariance;
+use crate::token::{Token, TokenTree, Wildcard};
+use crate::walk::fs::WalkFs;
 use crate::walk::{
-    Entry, EntryResidue, FileIterator, JoinAndGetDepth, SplitAtDepth, TreeEntry, WalkBehavior,
-    WalkError, WalkTree,
+    Entry, EntryResidue, FileIterator, FileType, Fs, FsEntry, JoinAndGetDepth, Not, SplitPath,
+    TreeEntry, WalkBehavior, WalkError, WalkTree,
 };
-use crate::{BuildError, CandidatePath, Glob, Pattern};
+use crate::{All, AndNot, BuildError, CandidatePath, Glob, Pattern, Program};
 
 /// APIs for matching globs against directory trees.
 impl<'t> Glob<'t> {
@@ -21,9 +24,9 @@ impl<'t> Glob<'t> {
     /// Gets an iterator over matching file paths in a directory tree.
     ///
     /// This function matches a `Glob` against a directory tree, returning a [`FileIterator`] that
-    /// yields a [`GlobEntry`] for each matching file. `Glob`s are the only [`Program`]s that
-    /// support this semantic operation; it is not possible to match combinators ([`Any`]) against
-    /// directory trees.
+    /// yields a [`GlobEntry`] for each matching file. `Glob`s and the [`All`] combinator are the
+    /// only [`Program`]s that support this semantic operation; it is not possible to match [`Any`]
+    /// against directory trees.
     ///
     /// As with [`Path::join`] and [`PathBuf::push`], the base directory can be escaped or
     /// overridden by [a `Glob` that has a root][`Program::has_root`]. In many cases, the current
@@ -78,6 +81,7 @@ impl<'t> Glob<'t> {
     /// }
     /// ```
     ///
+    /// [`All`]: crate::All
     /// [`Any`]: crate::Any
     /// [`Entry::root_relative_paths`]: crate::walk::Entry::root_relative_paths
     /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
@@ -91,7 +95,10 @@ impl<'t> Glob<'t> {
     /// [`Program::has_root`]: crate::Program::has_root
     /// [`Program::is_exhaustive`]: crate::Program::is_exhaustive
     /// [`WalkBehavior`]: crate::walk::WalkBehavior
-    pub fn walk(&self, path: impl Into<PathBuf>) -> impl 'static + FileIterator<Entry = GlobEntry> {
+    pub fn walk(
+        &self,
+        path: impl Into<PathBuf>,
+    ) -> impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry> {
         self.walk_with_behavior(path, WalkBehavior::default())
     }
 
@@ -141,64 +148,448 @@ impl<'t> Glob<'t> {
         &self,
         path: impl Into<PathBuf>,
         behavior: impl Into<WalkBehavior>,
-    ) -> impl 'static + FileIterator<Entry = GlobEntry> {
-        GlobWalker {
-            anchor: self.anchor(path),
-            program: WalkProgram {
-                complete: self.program.clone(),
-                // Do not compile component programs for empty globs.
-                //
-                // An empty glob consists solely of an empty literal token and only matches empty
-                // text (""). A walk program compiled from such a glob has an empty component
-                // pattern and matches nothing. This means that walking an empty glob never yields
-                // any paths. At first blush, this seems consistent with an empty glob. However,
-                // walking conceptually matches a glob against the sub-trees in a path and there is
-                // arguably an implicit empty tree. This is also more composable when partitioning
-                // and (re)building paths.
-                //
-                // The result is that matching an empty glob against the path `foo` yields `foo`
-                // and only `foo` (assuming that the path exists).
-                components: if self.is_empty() {
-                    vec![]
-                }
-                else {
-                    WalkProgram::compile::<Tokenized<_>>(self.tree.as_ref())
-                        .expect("failed to compile walk program")
-                },
-            },
+    ) -> impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry> {
+        self.walker(path).walk_with_behavior(behavior)
+    }
+
+    /// Gets an iterator over matching files in the directory trees at many paths.
+    ///
+    /// This function is the same as [`Glob::walk_with_behavior`], but walks the directory tree at
+    /// each of the given paths in order, one after another. Each walk is independent: the root
+    /// path of each walk is its given path joined with any [invariant prefix][`Glob::partition`]
+    /// of the `Glob` and filters like [`not`] cancel walks of trees only beneath that root. The
+    /// `Glob` is compiled only once.
+    ///
+    /// Entries are not deduplicated. If the directory trees overlap, then the same file may be
+    /// yielded more than once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, WalkBehavior};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.toml").unwrap();
+    /// for entry in glob.walk_many(["./server", "./client"], WalkBehavior::default()) {
+    ///     let entry = entry.unwrap();
+    ///     println!("TOML: {:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`Glob::partition`]: crate::Glob::partition
+    /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+    /// [`not`]: crate::walk::FileIterator::not
+    pub fn walk_many<I>(
+        &self,
+        paths: I,
+        behavior: impl Into<WalkBehavior>,
+    ) -> impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry>
+    where
+        I: IntoIterator,
+        I::Item: Into<PathBuf>,
+    {
+        let behavior = behavior.into();
+        let walkers: Vec<_> = paths.into_iter().map(|path| self.walker(path)).collect();
+        ChainWalks {
+            walks: walkers
+                .into_iter()
+                .map(move |walker| walker.walk_with_behavior(behavior)),
+            walk: None,
+        }
+    }
+
+    /// Gets an iterator over matching files in a directory tree if its root path can be read.
+    ///
+    /// This function is the same as [`Glob::walk_with_behavior`], but it reads the metadata of
+    /// the root path of the walk before returning an iterator. The root path is the given
+    /// directory joined with any [invariant prefix][`Glob::partition`] of the `Glob`. This can be
+    /// used to validate a walk before starting a pipeline, rather than receiving the error from
+    /// the first iteration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metadata of the root path cannot be read, such as when the path
+    /// does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::WalkBehavior;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.log").unwrap();
+    /// match glob.try_walk("/var/log", WalkBehavior::default()) {
+    ///     Ok(walk) => {
+    ///         for entry in walk { /* ... */ }
+    ///     },
+    ///     Err(error) => eprintln!("{}", error),
+    /// }
+    /// ```
+    ///
+    /// [`Glob::partition`]: crate::Glob::partition
+    /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+    pub fn try_walk(
+        &self,
+        path: impl Into<PathBuf>,
+        behavior: impl Into<WalkBehavior>,
+    ) -> Result<impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry>, WalkError> {
+        let walker = self.walker(path);
+        let root = walker.anchor.root.as_path();
+        match fs::metadata(root) {
+            Ok(_) => Ok(walker.walk_with_behavior(behavior)),
+            Err(error) => Err(WalkError::io(Some(root.into()), 0, error)),
         }
-        .walk_with_behavior(behavior)
     }
 
-    fn anchor(&self, path: impl Into<PathBuf>) -> Anchor {
+    /// Gets the root path of a walk of the `Glob` from the given directory.
+    ///
+    /// The root path is the given directory joined with any [invariant prefix][`Glob::partition`]
+    /// of the `Glob`. This is the path from which [`Glob::walk`] and similar functions begin to
+    /// read the directory tree. If the `Glob` is rooted, then its prefix replaces the given
+    /// directory. Globs with the same root path read the same directory tree, so this can be used
+    /// to group globs and read each tree only once.
+    ///
+    /// This function does not interact with the file system.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use wax::Glob;
+    ///
+    /// let base = Path::new("project");
+    /// let a = Glob::new("src/**/*.rs").unwrap();
+    /// let b = Glob::new("src/*.{rs,toml}").unwrap();
+    /// assert_eq!(a.walk_root(base), Path::new("project/src"));
+    /// assert_eq!(a.walk_root(base), b.walk_root(base));
+    /// ```
+    ///
+    /// [`Glob::partition`]: crate::Glob::partition
+    /// [`Glob::walk`]: crate::Glob::walk
+    pub fn walk_root(&self, base: &Path) -> PathBuf {
+        Anchor::from_path_and_prefix(base, self.invariant_prefix()).root
+    }
+
+    /// Returns `true` if any file in a directory tree matches the `Glob`.
+    ///
+    /// This function walks the directory tree like [`Glob::walk_with_behavior`], but stops at the
+    /// first matching file. This is much cheaper than collecting matches when only their presence
+    /// is needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one occurs before any matching file is found. Errors that would occur
+    /// after a match are never encountered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::WalkBehavior;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("Cargo.toml").unwrap();
+    /// if glob.exists_match(".", WalkBehavior::default()).unwrap() {
+    ///     println!("Found a Cargo project.");
+    /// }
+    /// ```
+    ///
+    /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+    pub fn exists_match(
+        &self,
+        path: impl Into<PathBuf>,
+        behavior: impl Into<WalkBehavior>,
+    ) -> Result<bool, WalkError> {
+        self.walk_with_behavior(path, behavior)
+            .next()
+            .transpose()
+            .map(|entry| entry.is_some())
+    }
+
+    /// Gets an iterator over matching file paths in a directory tree read from an [`Fs`].
+    ///
+    /// This function is the same as [`Glob::walk_with_behavior`], but reads the directory tree
+    /// from the given [`Fs`] rather than the native file system. This can be used to match a
+    /// `Glob` against in-memory trees and other fixtures. Given [`StdFs`], this function reads
+    /// the native file system.
+    ///
+    /// Because [`Fs`] has no notion of symbolic links, the [`link`] field of [`WalkBehavior`] is
+    /// ignored. Depth is bounded as with [`Glob::walk_with_behavior`] and directories with paths
+    /// that cannot match the `Glob` are not read.
+    ///
+    /// The returned [`FileIterator`] yields [`FsEntry`]s and supports combinators like
+    /// [`not`] and [`filter_entry`], which discard directories before they are read from the
+    /// [`Fs`]. [`FsEntry`]s have no [`Metadata`], so combinators that read [`Metadata`], such as
+    /// [`sorted_by_mtime`], fail.
+    ///
+    /// See [`Fs`] for an example.
+    ///
+    /// [`FileIterator`]: crate::walk::FileIterator
+    /// [`filter_entry`]: crate::walk::FileIterator::filter_entry
+    /// [`Fs`]: crate::walk::Fs
+    /// [`FsEntry`]: crate::walk::FsEntry
+    /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+    /// [`link`]: crate::walk::WalkBehavior::link
+    /// [`Metadata`]: std::fs::Metadata
+    /// [`not`]: crate::walk::FileIterator::not
+    /// [`sorted_by_mtime`]: crate::walk::FileIterator::sorted_by_mtime
+    /// [`StdFs`]: crate::walk::StdFs
+    /// [`WalkBehavior`]: crate::walk::WalkBehavior
+    pub fn walk_in<'f>(
+        &self,
+        fs: &'f dyn Fs,
+        path: impl Into<PathBuf>,
+        behavior: impl Into<WalkBehavior>,
+    ) -> impl 'f + FileIterator<Entry = FsEntry, Residue = FsEntry> {
+        self.walker(path).walk_in(fs, behavior)
+    }
+
+    /// Gets an iterator over matching files in a directory tree, matching paths with a prefix
+    /// stripped.
+    ///
+    /// This function is the same as [`Glob::walk_with_behavior`], but files are matched against
+    /// their paths with the given prefix removed rather than against paths relative to the given
+    /// directory. This can be used when the directory on the file system differs from the logical
+    /// root of paths. For example, walking `/mnt/project` with the prefix `/mnt` matches the
+    /// `Glob` against paths like `project/src/lib.rs`.
+    ///
+    /// This is distinct from the [invariant prefix][`Glob::partition`] of the `Glob`, which is
+    /// still used to begin the walk as far down the tree as possible. If the `Glob` has a root,
+    /// then it replaces the given directory as in [`Glob::walk`] and the prefix is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the given directory does not begin with the given prefix. Paths are
+    /// compared nominally, so the directory and prefix are typically both absolute or both
+    /// relative.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, WalkBehavior};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("project/src/**/*.rs").unwrap();
+    /// for entry in glob
+    ///     .walk_with_strip_prefix("/mnt/project", "/mnt", WalkBehavior::default())
+    ///     .unwrap()
+    /// {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`Glob::partition`]: crate::Glob::partition
+    /// [`Glob::walk`]: crate::Glob::walk
+    /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+    pub fn walk_with_strip_prefix(
+        &self,
+        path: impl Into<PathBuf>,
+        prefix: impl AsRef<Path>,
+        behavior: impl Into<WalkBehavior>,
+    ) -> Result<
+        impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry>,
+        StripPrefixError,
+    > {
         let path = path.into();
-        let prefix: Option<PathBuf> = {
-            let (_, prefix) = self.tree.as_ref().as_token().invariant_text_prefix();
-            if prefix.is_empty() {
-                None
-            }
-            else {
-                Some(prefix.into())
-            }
-        };
-        // Establish the root path and any pivot in that root path from the given directory and any
-        // invariant prefix in the glob. The file system is traversed from this root path. The
-        // pivot partitions the root path into the given directory and any invariant prefix by
-        // specifying how many components from the end of the root path must be popped to restore
-        // the given directory. The popped components form the invariant prefix of the glob. Either
-        // partition of the root path may be empty depending on the given directory and the glob
-        // pattern. In this way, any invariant prefix of the glob becomes a postfix in the root
-        // path.
-        //
-        // Note that a rooted glob, like in `Path::join`, replaces the given directory when
-        // establishing the root path. In this case, there is no invariant prefix (the pivot is
-        // zero), as the entire root path is present in the glob expression and the given directory
-        // is completely discarded.
-        let (root, pivot) = match prefix {
-            Some(prefix) => path.join_and_get_depth(prefix),
-            _ => (path, 0),
-        };
-        Anchor { root, pivot }
+        let base = path.strip_prefix(prefix)?.to_path_buf();
+        let prefix = self.invariant_prefix();
+        Ok(match prefix {
+            Some(ref prefix) if prefix.has_root() => self.walker(path),
+            _ => GlobWalker {
+                // Any invariant prefix of the glob that extends beyond the base path can be used
+                // to begin the walk further down the tree. Otherwise, component programs discard
+                // files beneath the base path that cannot match.
+                anchor: Anchor::from_path_and_prefix(
+                    path,
+                    prefix
+                        .as_deref()
+                        .and_then(|prefix| prefix.strip_prefix(&base).ok())
+                        .filter(|prefix| !prefix.as_os_str().is_empty())
+                        .map(Path::to_path_buf),
+                ),
+                base,
+                ..self.walker(PathBuf::new())
+            },
+        }
+        .walk_with_behavior(behavior))
+    }
+
+    /// Returns `true` if files beneath the given directory path could match the `Glob`.
+    ///
+    /// This function matches the components of the given path against the corresponding leading
+    /// components of the `Glob`, up to any tree wildcard `**` or other component boundary. It
+    /// returns `false` if any of these components do not match, in which case nothing beneath the
+    /// path can match the `Glob` and it need not be read. This is the same logic that walks use to
+    /// prune directory trees, so it can be used to implement bespoke traversals.
+    ///
+    /// The path is interpreted like the paths yielded by walks: relative to the directory in
+    /// which the `Glob` is matched and **including** any [invariant prefix][`Glob::partition`].
+    /// Components of the path that are not normal components, such as roots, are ignored. Note
+    /// that this function compiles the component programs of the `Glob` with each call.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("src/*/test/**/*.rs").unwrap();
+    /// assert!(glob.could_match_under(Path::new("src/walk")));
+    /// assert!(!glob.could_match_under(Path::new("doc/walk")));
+    /// assert!(!glob.could_match_under(Path::new("src/walk/bench")));
+    /// ```
+    ///
+    /// [`Glob::partition`]: crate::Glob::partition
+    pub fn could_match_under(&self, path: &Path) -> bool {
+        WalkProgram::from_tree_and_program(self.tree.as_ref(), Regex::clone(&self.program))
+            .could_match_under(path)
+    }
+
+    fn walker(&self, path: impl Into<PathBuf>) -> GlobWalker {
+        GlobWalker {
+            anchor: Anchor::from_path_and_prefix(path, self.invariant_prefix()),
+            base: PathBuf::new(),
+            programs: vec![WalkProgram::from_tree_and_program(
+                self.tree.as_ref(),
+                Regex::clone(&self.program),
+            )],
+        }
+    }
+}
+
+/// APIs for matching combinators against directory trees.
+impl<'t> All<'t> {
+    /// Gets an iterator over file paths in a directory tree that match all component programs.
+    ///
+    /// This function is the same as [`Glob::walk`], but matches each file against every component
+    /// [`Program`] of the `All`. A directory tree is not read if **any** component [`Program`]
+    /// rejects the directory, so layering constraints can avoid unnecessary reads.
+    ///
+    /// The walk begins at the given directory joined with the longest [invariant
+    /// prefix][`Glob::invariant_prefix`] among the component [`Program`]s. The [matched
+    /// text][`GlobEntry::matched`] of each entry is that of the first component [`Program`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::Entry;
+    ///
+    /// let all = wax::all(["src/**", "**/*.rs"]).unwrap();
+    /// for entry in all.walk(".") {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`Glob::invariant_prefix`]: crate::Glob::invariant_prefix
+    /// [`Glob::walk`]: crate::Glob::walk
+    /// [`GlobEntry::matched`]: crate::walk::GlobEntry::matched
+    /// [`Program`]: crate::Program
+    pub fn walk(
+        &self,
+        path: impl Into<PathBuf>,
+    ) -> impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry> {
+        self.walk_with_behavior(path, WalkBehavior::default())
+    }
+
+    /// Gets an iterator over file paths in a directory tree that match all component programs.
+    ///
+    /// This function is the same as [`All::walk`], but it additionally accepts a [`WalkBehavior`]
+    /// that configures how the traversal interacts with symbolic links, bounds on depth, etc.
+    ///
+    /// See [`Glob::walk_with_behavior`].
+    ///
+    /// [`All::walk`]: crate::All::walk
+    /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+    /// [`WalkBehavior`]: crate::walk::WalkBehavior
+    pub fn walk_with_behavior(
+        &self,
+        path: impl Into<PathBuf>,
+        behavior: impl Into<WalkBehavior>,
+    ) -> impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry> {
+        self.walker(path).walk_with_behavior(behavior)
+    }
+
+    fn walker(&self, path: impl Into<PathBuf>) -> GlobWalker {
+        // Every matched path begins with the invariant prefix of each component program, so the
+        // walk can begin beneath the longest such prefix.
+        let prefix = self
+            .trees
+            .iter()
+            .map(|tree| tree.as_ref().invariant_text_prefix())
+            .map(|(_, prefix)| PathBuf::from(prefix))
+            .max_by_key(|prefix| prefix.components().count())
+            .filter(|prefix| !prefix.as_os_str().is_empty());
+        GlobWalker {
+            anchor: Anchor::from_path_and_prefix(path, prefix),
+            base: PathBuf::new(),
+            programs: self
+                .trees
+                .iter()
+                .zip(&self.programs)
+                .map(|(tree, program)| {
+                    WalkProgram::from_tree_and_program(tree.as_ref(), program.clone())
+                })
+                .collect(),
+        }
+    }
+}
+
+/// APIs for matching differences against directory trees.
+impl<'t> AndNot<'t> {
+    /// Gets an iterator over file paths in a directory tree that match the glob but not the
+    /// negation.
+    ///
+    /// This function is the same as [`Glob::walk`] composed with the [`not`] combinator. A
+    /// directory tree is not read if the negation is [exhaustive][`Program::is_exhaustive`] and
+    /// matches the directory, such as with `**/target/**`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::Entry;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// let sources = glob.and_not(Glob::new("target/**").unwrap());
+    /// for entry in sources.walk(".") {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`Glob::walk`]: crate::Glob::walk
+    /// [`not`]: crate::walk::FileIterator::not
+    /// [`Program::is_exhaustive`]: crate::Program::is_exhaustive
+    pub fn walk(
+        &self,
+        path: impl Into<PathBuf>,
+    ) -> impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry> {
+        self.walk_with_behavior(path, WalkBehavior::default())
+    }
+
+    /// Gets an iterator over file paths in a directory tree that match the glob but not the
+    /// negation.
+    ///
+    /// This function is the same as [`AndNot::walk`], but it additionally accepts a
+    /// [`WalkBehavior`] that configures how the traversal interacts with symbolic links, bounds on
+    /// depth, etc.
+    ///
+    /// See [`Glob::walk_with_behavior`].
+    ///
+    /// [`AndNot::walk`]: crate::AndNot::walk
+    /// [`Glob::walk_with_behavior`]: crate::Glob::walk_with_behavior
+    /// [`WalkBehavior`]: crate::walk::WalkBehavior
+    pub fn walk_with_behavior(
+        &self,
+        path: impl Into<PathBuf>,
+        behavior: impl Into<WalkBehavior>,
+    ) -> impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry> {
+        Not {
+            input: self.glob.walker(path).walk_with_behavior(behavior),
+            filter: FilterAny::from_glob(&self.negation),
+            is_absolute: false,
+        }
     }
 }
 
@@ -220,9 +611,42 @@ struct Anchor {
     /// `Glob` (this prefix becomes a postfix in the root path or, when rooted, replaces any target
     /// path).
     pivot: usize,
+    /// The number of components in any invariant prefix of the glob expression as counted by the
+    /// depth of the glob.
+    ///
+    /// Unlike the pivot, this excludes roots and so is the depth of matching paths that is already
+    /// traversed when a walk begins at the root path.
+    prefix_depth: usize,
 }
 
 impl Anchor {
+    fn from_path_and_prefix(path: impl Into<PathBuf>, prefix: Option<PathBuf>) -> Self {
+        let path = path.into();
+        // Establish the root path and any pivot in that root path from the given directory and any
+        // invariant prefix in the glob. The file system is traversed from this root path. The
+        // pivot partitions the root path into the given directory and any invariant prefix by
+        // specifying how many components from the end of the root path must be popped to restore
+        // the given directory. The popped components form the invariant prefix of the glob. Either
+        // partition of the root path may be empty depending on the given directory and the glob
+        // pattern. In this way, any invariant prefix of the glob becomes a postfix in the root
+        // path.
+        //
+        // Note that a rooted glob, like in `Path::join`, replaces the given directory when
+        // establishing the root path. In this case, there is no invariant prefix (the pivot is
+        // zero), as the entire root path is present in the glob expression and the given directory
+        // is completely discarded.
+        let prefix_depth = prefix.as_deref().map_or(0, depth_of_prefix);
+        let (root, pivot) = match prefix {
+            Some(prefix) => path.join_and_get_depth(prefix),
+            _ => (path, 0),
+        };
+        Anchor {
+            root,
+            pivot,
+            prefix_depth,
+        }
+    }
+
     pub fn walk_with_behavior(self, behavior: impl Into<WalkBehavior>) -> WalkTree {
         WalkTree::with_pivot_and_behavior(self.root, self.pivot, behavior)
     }
@@ -232,9 +656,247 @@ impl Anchor {
 struct WalkProgram {
     complete: Regex,
     components: Vec<Regex>,
+    /// The maximum depth of any matching path or `None` if unbounded.
+    max_depth: Option<usize>,
+    /// The number of leading components of the glob that precede a tree wildcard that excludes
+    /// hidden components, if nothing beneath a hidden directory that follows these components can
+    /// match. See [`WalkProgram::hidden_tree_depth`].
+    hidden_tree_depth: Option<usize>,
 }
 
 impl WalkProgram {
+    fn from_tree_and_program<'t, T>(tree: &T, complete: Regex) -> Self
+    where
+        T: TokenTree<'t>,
+    {
+        WalkProgram {
+            complete,
+            // Directories at the maximum depth are not read, because nothing beneath them can
+            // match. This prunes walks of globs with bounded depth even when component programs
+            // cannot, such as `<*/:1,3>*.rs`, which is much like a tree wildcard bounded to three
+            // levels.
+            //
+            // An empty glob only matches the root path, so the root directory is never read.
+            max_depth: if tree.as_token().is_empty() {
+                Some(0)
+            }
+            else {
+                match DepthVariance::from(tree.as_token().variance()) {
+                    DepthVariance::Invariant(depth) => Some(depth),
+                    DepthVariance::Variant(bounds) => bounds.upper().bounded().map(usize::from),
+                }
+            },
+            hidden_tree_depth: WalkProgram::hidden_tree_depth::<T>(tree),
+            // Do not compile component programs for empty globs.
+            //
+            // An empty glob consists solely of an empty literal token and only matches empty text
+            // (""). A walk program compiled from such a glob has an empty component pattern and
+            // matches nothing. This means that walking an empty glob never yields any paths. At
+            // first blush, this seems consistent with an empty glob. However, walking conceptually
+            // matches a glob against the sub-trees in a path and there is arguably an implicit
+            // empty tree. This is also more composable when partitioning and (re)building paths.
+            //
+            // The result is that matching an empty glob against the path `foo` yields `foo` and
+            // only `foo` (assuming that the path exists).
+            components: if tree.as_token().is_empty() {
+                vec![]
+            }
+            else {
+                WalkProgram::compile::<T>(tree).expect("failed to compile walk program")
+            },
+        }
+    }
+
+    /// Matches the root-relative path of a file at the given depth in a walk.
+    ///
+    /// Returns the matched text and the number of path components matched by component programs
+    /// if the path matches or otherwise the residue that describes how the file is discarded. The
+    /// residue is [`EntryResidue::Tree`] if a component of the path does not match its
+    /// corresponding component program, because nothing beneath such a directory can match.
+    fn match_or_residue(
+        &self,
+        path: &Path,
+        depth: usize,
+    ) -> Result<(MatchedText<'static>, usize), EntryResidue> {
+        use itertools::EitherOrBoth::{Both, Left, Right};
+        use itertools::Position::{First, Last, Middle, Only};
+
+        let depth = depth.saturating_sub(1);
+        // Components of the path above the given depth have already been matched against the
+        // corresponding component programs (when their ancestors were matched).
+        let mut n = cmp::min(depth, self.components.len());
+        for (position, candidate) in path
+            .components()
+            // Component programs never match roots, so roots are not counted when skipping
+            // components. Otherwise, the components of rooted paths are misaligned with their
+            // component programs.
+            .filter(|component| !matches!(component, Component::Prefix(_) | Component::RootDir))
+            .skip(depth)
+            .filter_map(|component| match component {
+                Component::Normal(component) => Some(CandidatePath::from(component)),
+                _ => None,
+            })
+            .zip_longest(self.components.iter().skip(depth))
+            .with_position()
+        {
+            match (position, candidate) {
+                (First | Middle, Both(candidate, program)) => {
+                    if !program.is_match(candidate.as_ref()) {
+                        // Do not walk directories that do not match the corresponding component
+                        // program.
+                        return Err(EntryResidue::Tree);
+                    }
+                    n += 1;
+                },
+                (Last | Only, Both(candidate, program)) => {
+                    return if program.is_match(candidate.as_ref()) {
+                        self.captures(path)
+                            .map(|matched| (matched, n + 1))
+                            .ok_or(EntryResidue::File)
+                    }
+                    else {
+                        // Do not walk directories that do not match the corresponding component
+                        // program.
+                        Err(EntryResidue::Tree)
+                    };
+                },
+                (_, Left(_candidate)) => {
+                    return self
+                        .captures(path)
+                        .map(|matched| (matched, n))
+                        .ok_or_else(|| {
+                            // Do not walk hidden directories that can only be matched by a tree
+                            // wildcard that excludes hidden components.
+                            if self.is_under_hidden_tree(path) {
+                                EntryResidue::Tree
+                            }
+                            else {
+                                EntryResidue::File
+                            }
+                        });
+                },
+                (_, Right(_program)) => {
+                    return Err(EntryResidue::File);
+                },
+            }
+        }
+        // If the component loop is not entered, then check for a match. This may indicate that the
+        // `Glob` is empty and a single invariant path may be matched.
+        self.captures(path)
+            .map(|matched| (matched, n))
+            .ok_or(EntryResidue::File)
+    }
+
+    /// Counts the leading components of the given root-relative path that match their
+    /// corresponding component programs.
+    fn component_count(&self, path: &Path) -> usize {
+        path.components()
+            .filter_map(|component| match component {
+                Component::Normal(component) => Some(CandidatePath::from(component)),
+                _ => None,
+            })
+            .zip(self.components.iter())
+            .take_while(|(candidate, program)| program.is_match(candidate.as_ref()))
+            .count()
+    }
+
+    /// Returns `false` if a component of the given root-relative path does not match its
+    /// corresponding component program, because nothing beneath such a directory can match.
+    fn could_match_under(&self, path: &Path) -> bool {
+        path.components()
+            .filter_map(|component| match component {
+                Component::Normal(component) => Some(CandidatePath::from(component)),
+                _ => None,
+            })
+            .zip(self.components.iter())
+            .all(|(candidate, program)| program.is_match(candidate.as_ref()))
+            && !self.is_under_hidden_tree(path)
+    }
+
+    /// Returns `true` if a component of the given root-relative path that follows the components
+    /// matched by component programs is hidden and nothing beneath such a directory can match.
+    fn is_under_hidden_tree(&self, path: &Path) -> bool {
+        self.hidden_tree_depth.is_some_and(|depth| {
+            path.components()
+                .filter_map(|component| match component {
+                    Component::Normal(component) => Some(component),
+                    _ => None,
+                })
+                .skip(depth)
+                .any(|component| component.as_encoded_bytes().starts_with(b"."))
+        })
+    }
+
+    /// Gets the number of leading components of the given token tree that precede a tree wildcard
+    /// that excludes hidden components, if the tree wildcard is followed by at most one component
+    /// with no boundaries, as in `src/**` or `src/**/*.rs`.
+    ///
+    /// Any hidden component that follows the leading components of such a tree, other than the
+    /// final component, can only be matched by the tree wildcard. Because the tree wildcard does
+    /// not match hidden components, nothing beneath a hidden directory there can match. For
+    /// example, nothing beneath `src/.git` nor `src/a/.git` can match `src/**/*.rs`.
+    fn hidden_tree_depth<'t, T>(tree: &T) -> Option<usize>
+    where
+        T: TokenTree<'t>,
+    {
+        let components: Vec<_> = tree.as_token().components().collect();
+        let depth = components
+            .iter()
+            .position(|component| component.tokens().iter().any(Token::has_boundary))?;
+        let (wildcard, rest) = components[depth..].split_first()?;
+        let excludes_hidden = matches!(
+            wildcard.tokens(),
+            [token] if matches!(
+                token.as_wildcard(),
+                Some(Wildcard::Tree {
+                    excludes_hidden: true,
+                    ..
+                }),
+            ),
+        );
+        (excludes_hidden
+            && rest.len() <= 1
+            && rest
+                .iter()
+                .all(|component| !component.tokens().iter().any(Token::has_boundary)))
+        .then_some(depth)
+    }
+
+    /// Matches the root-relative path of a file at the given depth against all of the given
+    /// programs.
+    ///
+    /// Returns the match of the first program if all programs match. Otherwise, the residue is
+    /// [`EntryResidue::Tree`] if any program discards the tree.
+    fn match_all_or_residue(
+        programs: &[Self],
+        path: &Path,
+        depth: usize,
+    ) -> Result<(MatchedText<'static>, usize), EntryResidue> {
+        let mut matched = None;
+        let mut residue = None;
+        for program in programs {
+            match program.match_or_residue(path, depth) {
+                Ok(text) => {
+                    matched.get_or_insert(text);
+                },
+                Err(EntryResidue::Tree) => return Err(EntryResidue::Tree),
+                Err(EntryResidue::File) => residue = Some(EntryResidue::File),
+            }
+        }
+        match residue {
+            Some(residue) => Err(residue),
+            _ => Ok(matched.expect("no walk programs")),
+        }
+    }
+
+    fn captures(&self, path: &Path) -> Option<MatchedText<'static>> {
+        let candidate = CandidatePath::from(path);
+        self.complete
+            .captures(candidate.as_ref())
+            .map(MatchedText::from)
+            .map(MatchedText::into_owned)
+    }
+
     fn compile<'t, T>(tree: impl Borrow<T>) -> Result<Vec<Regex>, CompileError>
     where
         T: TokenTree<'t>,
@@ -243,7 +905,10 @@ impl WalkProgram {
         for component in tree.borrow().as_token().components() {
             if component.tokens().iter().any(Token::has_boundary) {
                 // Stop at component boundaries, such as tree wildcards or any boundary within a
-                // branch token.
+                // branch token. Components that follow a tree wildcard can match at any depth, so
+                // they cannot be used to discard directory trees. For example, every directory
+                // must be read when walking `**/target/**`, because any directory may contain a
+                // `target` directory.
                 break;
             }
             regexes.push(Glob::compile(component)?);
@@ -256,10 +921,36 @@ impl WalkProgram {
 #[derive(Clone, Debug)]
 struct GlobWalker {
     anchor: Anchor,
-    program: WalkProgram,
+    /// A path that is joined with the root-relative paths of files before they are matched.
+    ///
+    /// This path is empty unless a prefix is stripped from the paths of files when walking.
+    base: PathBuf,
+    /// The walk programs that files must match. There is more than one program when walking an
+    /// `All` combinator.
+    programs: Vec<WalkProgram>,
 }
 
 impl GlobWalker {
+    /// Bounds the depth of the given behavior by the maximum depth of matching paths.
+    ///
+    /// The depth of a glob includes the components of its invariant prefix and any base path, but
+    /// the walk begins beneath these at the root path. The depth behavior is relative to the
+    /// target path and is offset by the pivot when the walk begins, so the maximum depth relative
+    /// to the root path is offset by the pivot here. Paths of files are matched against every
+    /// program, so the least maximum depth bounds the walk.
+    fn bound_depth(&self, behavior: impl Into<WalkBehavior>) -> WalkBehavior {
+        let mut behavior = behavior.into();
+        if let Some(max) = self.programs.iter().filter_map(|program| program.max_depth).min() {
+            let max = max
+                .saturating_sub(self.anchor.prefix_depth)
+                .saturating_sub(depth_of_prefix(&self.base))
+                .checked_add(self.anchor.pivot)
+                .expect("overflow determining maximum depth");
+            behavior.depth = behavior.depth.bounded_by_max(max);
+        }
+        behavior
+    }
+
     /// Converts a walker into an iterator over matching files in its directory tree.
     ///
     /// See [`Glob::walk_with_behavior`].
@@ -269,13 +960,18 @@ impl GlobWalker {
         self,
         behavior: impl Into<WalkBehavior>,
     ) -> impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry> {
-        let pivot = self.anchor.pivot;
-        self.anchor
+        let behavior = self.bound_depth(behavior);
+        let GlobWalker {
+            anchor,
+            base,
+            programs,
+        } = self;
+        let pivot = anchor.pivot;
+        let yield_intermediate_dirs = behavior.yield_intermediate_dirs;
+        let strict_components = behavior.strict_components;
+        anchor
             .walk_with_behavior(behavior)
             .filter_map_tree(move |cancellation, separation| {
-                use itertools::EitherOrBoth::{Both, Left, Right};
-                use itertools::Position::{First, Last, Middle, Only};
-
                 let filtrate = match separation.filtrate() {
                     Some(filtrate) => match filtrate.transpose() {
                         Ok(filtrate) => filtrate,
@@ -287,107 +983,162 @@ impl GlobWalker {
                     _ => unreachable!(),
                 };
                 let entry = filtrate.as_ref();
-                let (_, path) = self::root_relative_paths(entry.path(), entry.depth(), pivot);
-                let depth = entry.depth().saturating_sub(1);
-                for (position, candidate) in path
-                    .components()
-                    .skip(depth)
-                    .filter_map(|component| match component {
-                        Component::Normal(component) => Some(CandidatePath::from(component)),
-                        _ => None,
-                    })
-                    .zip_longest(self.program.components.iter().skip(depth))
-                    .with_position()
-                {
-                    match (position, candidate) {
-                        (First | Middle, Both(candidate, program)) => {
-                            if !program.is_match(candidate.as_ref()) {
-                                // Do not walk directories that do not match the corresponding
-                                // component program.
-                                return filtrate.filter_tree(cancellation).into();
-                            }
-                        },
-                        (Last | Only, Both(candidate, program)) => {
-                            return if program.is_match(candidate.as_ref()) {
-                                let candidate = CandidatePath::from(path);
-                                if let Some(matched) = self
-                                    .program
-                                    .complete
-                                    .captures(candidate.as_ref())
-                                    .map(MatchedText::from)
-                                    .map(MatchedText::into_owned)
-                                {
-                                    filtrate
-                                        .map(|entry| {
-                                            Ok(GlobEntry {
-                                                entry,
-                                                pivot,
-                                                matched,
-                                            })
-                                        })
-                                        .into()
-                                }
-                                else {
-                                    filtrate.filter_node().into()
-                                }
-                            }
-                            else {
-                                // Do not walk directories that do not match the corresponding
-                                // component program.
-                                filtrate.filter_tree(cancellation).into()
-                            };
-                        },
-                        (_, Left(_candidate)) => {
-                            let candidate = CandidatePath::from(path);
-                            return if let Some(matched) = self
-                                .program
-                                .complete
-                                .captures(candidate.as_ref())
-                                .map(MatchedText::from)
-                                .map(MatchedText::into_owned)
-                            {
-                                filtrate
-                                    .map(|entry| {
-                                        Ok(GlobEntry {
-                                            entry,
-                                            pivot,
-                                            matched,
-                                        })
-                                    })
-                                    .into()
-                            }
-                            else {
-                                filtrate.filter_node().into()
-                            };
-                        },
-                        (_, Right(_pattern)) => {
-                            return filtrate.filter_node().into();
-                        },
-                    }
+                let split = SplitPath::at_depth(entry.path(), depth_at_pivot(entry.depth(), pivot));
+                let (_, path) = split.split(entry.path());
+                let path = join_base(&base, path);
+                if strict_components && has_abnormal_component(&path) {
+                    let error =
+                        WalkError::unexpected_component(entry.path().to_path_buf(), entry.depth());
+                    return filtrate.map(|_| Err(error)).into();
                 }
-                // If the component loop is not entered, then check for a match. This may indicate
-                // that the `Glob` is empty and a single invariant path may be matched.
-                let candidate = CandidatePath::from(path);
-                if let Some(matched) = self
-                    .program
-                    .complete
-                    .captures(candidate.as_ref())
-                    .map(MatchedText::from)
-                    .map(MatchedText::into_owned)
-                {
-                    return filtrate
+                match WalkProgram::match_all_or_residue(&programs, &path, entry.depth()) {
+                    Ok((matched, component_count)) => filtrate
                         .map(|entry| {
                             Ok(GlobEntry {
                                 entry,
                                 pivot,
+                                split,
                                 matched,
+                                component_count,
+                                is_intermediate: false,
                             })
                         })
-                        .into();
+                        .into(),
+                    // Directories that do not match may still contain matching files.
+                    Err(EntryResidue::File)
+                        if yield_intermediate_dirs && entry.file_type().is_dir() =>
+                    {
+                        let matched = MatchedText::uncaptured(CandidatePath::from(&*path).as_ref());
+                        let component_count = programs[0].component_count(&path);
+                        filtrate
+                            .map(|entry| {
+                                Ok(GlobEntry {
+                                    entry,
+                                    pivot,
+                                    split,
+                                    matched,
+                                    component_count,
+                                    is_intermediate: true,
+                                })
+                            })
+                            .into()
+                    },
+                    Err(EntryResidue::File) => filtrate.filter_node().into(),
+                    Err(EntryResidue::Tree) => filtrate.filter_tree(cancellation).into(),
                 }
-                filtrate.filter_node().into()
             })
     }
+
+    /// Converts a walker into an iterator over matching files in a directory tree read from an
+    /// [`Fs`].
+    ///
+    /// See [`Glob::walk_in`].
+    ///
+    /// [`Fs`]: crate::walk::Fs
+    /// [`Glob::walk_in`]: crate::Glob::walk_in
+    pub fn walk_in(
+        self,
+        fs: &dyn Fs,
+        behavior: impl Into<WalkBehavior>,
+    ) -> impl '_ + FileIterator<Entry = FsEntry, Residue = FsEntry> {
+        let behavior = self.bound_depth(behavior);
+        let GlobWalker {
+            anchor,
+            base,
+            programs,
+        } = self;
+        let pivot = anchor.pivot;
+        let yield_intermediate_dirs = behavior.yield_intermediate_dirs;
+        let strict_components = behavior.strict_components;
+        WalkFs::with_pivot_and_behavior(fs, anchor.root, pivot, behavior).filter_map_tree(
+            move |cancellation, separation| {
+                let filtrate = match separation.filtrate() {
+                    Some(filtrate) => match filtrate.transpose() {
+                        Ok(filtrate) => filtrate,
+                        Err(error) => {
+                            return Separation::from(error.map(Err));
+                        },
+                    },
+                    // `WalkFs` yields no residue.
+                    _ => unreachable!(),
+                };
+                let entry = filtrate.as_ref();
+                let (_, path) = entry.root_relative_paths();
+                let path = join_base(&base, path);
+                if strict_components && has_abnormal_component(&path) {
+                    let error = WalkError::unexpected_component(
+                        entry.path().to_path_buf(),
+                        entry.match_depth(),
+                    );
+                    return filtrate.map(|_| Err(error)).into();
+                }
+                match WalkProgram::match_all_or_residue(&programs, &path, entry.match_depth()) {
+                    Ok((matched, component_count)) => filtrate
+                        .map(|entry| Ok(entry.with_match(matched, component_count)))
+                        .into(),
+                    // Directories that do not match may still contain matching files.
+                    Err(EntryResidue::File) if yield_intermediate_dirs && entry.is_dir() => {
+                        let matched = MatchedText::uncaptured(CandidatePath::from(&*path).as_ref());
+                        let component_count = programs[0].component_count(&path);
+                        filtrate
+                            .map(|entry| {
+                                Ok(entry.with_match(matched, component_count).into_intermediate())
+                            })
+                            .into()
+                    },
+                    Err(EntryResidue::File) => filtrate.filter_node().into(),
+                    Err(EntryResidue::Tree) => filtrate.filter_tree(cancellation).into(),
+                }
+            },
+        )
+    }
+}
+
+/// Iterator over the files of a sequence of walks, which are read one after another.
+struct ChainWalks<I, W> {
+    walks: I,
+    walk: Option<W>,
+}
+
+impl<I, W> CancelWalk for ChainWalks<I, W>
+where
+    W: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        // Separations are only fed from the current walk, so cancellation always applies to it.
+        if let Some(walk) = self.walk.as_mut() {
+            walk.cancel_walk_tree();
+        }
+    }
+}
+
+impl<I, W> SeparatingFilter for ChainWalks<I, W>
+where
+    I: Iterator<Item = W>,
+    W: FileIterator<Entry = GlobEntry, Residue = TreeEntry>,
+{
+    type Feed = W::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        loop {
+            if let Some(separation) = self.walk.as_mut().and_then(SeparatingFilter::feed) {
+                return Some(separation);
+            }
+            self.walk = Some(self.walks.next()?);
+        }
+    }
+}
+
+impl<I, W> Iterator for ChainWalks<I, W>
+where
+    I: Iterator<Item = W>,
+    W: FileIterator<Entry = GlobEntry, Residue = TreeEntry>,
+{
+    type Item = W::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -505,6 +1256,33 @@ impl FilterAny {
             .map(|program| FilterAny { program })
     }
 
+    /// Constructs a `FilterAny` from a compiled [`Glob`].
+    ///
+    /// Unlike [`FilterAny::any`], this function does not partition the alternatives of the
+    /// [`Glob`] and so cannot fail, but only discards trees if the [`Glob`] is always exhaustive.
+    ///
+    /// [`FilterAny::any`]: crate::walk::glob::FilterAny::any
+    /// [`Glob`]: crate::Glob
+    pub fn from_glob(glob: &Glob<'_>) -> Self {
+        let program = Regex::clone(&glob.program);
+        FilterAny {
+            program: if glob.is_exhaustive().is_always() {
+                FilterAnyProgram::Exhaustive(program)
+            }
+            else {
+                FilterAnyProgram::Nonexhaustive(program)
+            },
+        }
+    }
+
+    /// Returns `true` if the filter discards every file, such as when negating `**`.
+    ///
+    /// The filter matches all paths if an exhaustive pattern matches the empty root-relative path
+    /// of the root, because every other path is beneath it.
+    pub fn matches_all(&self) -> bool {
+        matches!(self.program.residue("".into()), Some(EntryResidue::Tree))
+    }
+
     /// Gets the appropriate [`EntryResidue`] for the given [`Entry`].
     ///
     /// Notably, this function returns [`EntryResidue::Tree`] if the [`Entry`] matches an
@@ -518,6 +1296,20 @@ impl FilterAny {
         let candidate = CandidatePath::from(entry.root_relative_paths().1);
         self.program.residue(candidate)
     }
+
+    /// Gets the appropriate [`EntryResidue`] for the full path of the given [`Entry`].
+    ///
+    /// This function is the same as [`FilterAny::residue`], but matches against the full path of
+    /// the [`Entry`] (see [`Entry::path`]) rather than its root-relative path.
+    ///
+    /// [`Entry`]: crate::walk::Entry
+    /// [`Entry::path`]: crate::walk::Entry::path
+    /// [`EntryResidue`]: crate::walk::EntryResidue
+    /// [`FilterAny::residue`]: crate::walk::glob::FilterAny::residue
+    pub fn absolute_residue(&self, entry: &dyn Entry) -> Option<EntryResidue> {
+        let candidate = CandidatePath::from(entry.path());
+        self.program.residue(candidate)
+    }
 }
 
 /// Describes a file with a path matching a [`Glob`] in a directory tree.
@@ -530,7 +1322,10 @@ impl FilterAny {
 pub struct GlobEntry {
     entry: TreeEntry,
     pivot: usize,
+    split: SplitPath,
     matched: MatchedText<'static>,
+    component_count: usize,
+    is_intermediate: bool,
 }
 
 impl GlobEntry {
@@ -552,6 +1347,72 @@ impl GlobEntry {
     pub fn matched(&self) -> &MatchedText<'static> {
         &self.matched
     }
+
+    /// Gets the number of components in the path of the file that are matched by individual
+    /// components of the glob.
+    ///
+    /// Leading components of a glob, up to any tree wildcard `**` or other component boundary,
+    /// are matched individually against the components of a path relative to [the root
+    /// path][`Entry::root_relative_paths`]. This function counts those components, including any
+    /// that are in an invariant prefix of the glob. Unlike [`Entry::depth`], this does not count
+    /// components matched by a tree wildcard. For example, the glob `src/*/**/*.rs` matches two
+    /// components of the path `src/walk/glob/mod.rs`.
+    ///
+    /// [`Entry::depth`]: crate::walk::Entry::depth
+    /// [`Entry::root_relative_paths`]: crate::walk::Entry::root_relative_paths
+    pub fn matched_component_count(&self) -> usize {
+        self.component_count
+    }
+
+    /// Gets the depth of the path of the file from the invariant prefix of the glob.
+    ///
+    /// Unlike [`Entry::depth`], which is relative to [the root path
+    /// segment][`Entry::root_relative_paths`], this depth is relative to where variant matching
+    /// begins and so does not count components in any invariant prefix of the glob. For example,
+    /// when walking the glob `tests/**`, the path `tests/harness/mod.rs` has a depth of three but
+    /// a match depth of two. If the glob has no invariant prefix or has a root, then this is the
+    /// same as [`Entry::depth`].
+    ///
+    /// [`Entry::depth`]: crate::walk::Entry::depth
+    /// [`Entry::root_relative_paths`]: crate::walk::Entry::root_relative_paths
+    pub fn match_depth(&self) -> usize {
+        self.entry.depth()
+    }
+
+    /// Returns `true` if the file is a directory that does not match the glob.
+    ///
+    /// Intermediate directories are only yielded if [`WalkBehavior::yield_intermediate_dirs`] is
+    /// `true`. Such a directory may contain matching files and is yielded before them. The
+    /// [matched text][`GlobEntry::matched`] of an intermediate directory is its relative path and
+    /// has no captures.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, WalkBehavior};
+    /// use wax::Glob;
+    ///
+    /// let behavior = WalkBehavior {
+    ///     yield_intermediate_dirs: true,
+    ///     ..Default::default()
+    /// };
+    /// for entry in Glob::new("**/*.rs").unwrap().walk_with_behavior(".", behavior) {
+    ///     let entry = entry.unwrap();
+    ///     let indent = "  ".repeat(entry.depth());
+    ///     if entry.is_intermediate() {
+    ///         println!("{}{}/", indent, entry.path().display());
+    ///     }
+    ///     else {
+    ///         println!("{}{}", indent, entry.path().display());
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`GlobEntry::matched`]: crate::walk::GlobEntry::matched
+    /// [`WalkBehavior::yield_intermediate_dirs`]: crate::walk::WalkBehavior::yield_intermediate_dirs
+    pub fn is_intermediate(&self) -> bool {
+        self.is_intermediate
+    }
 }
 
 impl Entry for GlobEntry {
@@ -564,7 +1425,7 @@ impl Entry for GlobEntry {
     }
 
     fn root_relative_paths(&self) -> (&Path, &Path) {
-        self::root_relative_paths(self.path(), self.entry.depth(), self.pivot)
+        self.split.split(self.path())
     }
 
     fn file_type(&self) -> FileType {
@@ -572,14 +1433,11 @@ impl Entry for GlobEntry {
     }
 
     fn metadata(&self) -> Result<Metadata, WalkError> {
-        self.entry.metadata().map_err(WalkError::from)
+        self.entry.metadata()
     }
 
     fn depth(&self) -> usize {
-        self.entry
-            .depth()
-            .checked_add(self.pivot)
-            .expect("overflow determining depth")
+        depth_at_pivot(self.entry.depth(), self.pivot)
     }
 }
 
@@ -589,10 +1447,38 @@ impl From<GlobEntry> for TreeEntry {
     }
 }
 
-fn root_relative_paths(path: &Path, depth: usize, pivot: usize) -> (&Path, &Path) {
-    path.split_at_depth(
-        depth
-            .checked_add(pivot)
-            .expect("overflow determining root and relative paths"),
-    )
+/// Joins a base path with the root-relative path of a file.
+///
+/// Unlike `Path::join`, this function does not append a separator if the root-relative path is
+/// empty.
+fn join_base<'p>(base: &'p Path, path: &'p Path) -> Cow<'p, Path> {
+    if base.as_os_str().is_empty() {
+        Cow::Borrowed(path)
+    }
+    else if path.as_os_str().is_empty() {
+        Cow::Borrowed(base)
+    }
+    else {
+        Cow::Owned(base.join(path))
+    }
+}
+
+/// Returns `true` if any component of the given root-relative path is not normal.
+///
+/// Such components are skipped when matching paths. See `WalkBehavior::strict_components`.
+fn has_abnormal_component(path: &Path) -> bool {
+    path.components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+}
+
+// Gets the number of components in the given prefix of a path as counted by the depth of a glob.
+fn depth_of_prefix(prefix: &Path) -> usize {
+    prefix
+        .components()
+        .filter(|component| !matches!(component, Component::Prefix(_) | Component::RootDir))
+        .count()
+}
+
+fn depth_at_pivot(depth: usize, pivot: usize) -> usize {
+    depth.checked_add(pivot).expect("overflow determining depth")
 }
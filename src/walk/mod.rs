@@ -66,24 +66,35 @@
 #![cfg_attr(docsrs, doc(cfg(feature = "walk")))]
 
 mod behavior;
+mod fs;
 mod glob;
 
-use std::fs::{FileType, Metadata};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs::Metadata;
 use std::io;
+use std::mem;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::{Arc, OnceLock};
+use std::time::{Instant, SystemTime};
+use std::vec;
 use thiserror::Error;
 use walkdir::{self, DirEntry, WalkDir};
 
 use crate::filter::{
-    self, CancelWalk, HierarchicalIterator, Isomeric, SeparatingFilter, SeparatingFilterInput,
-    Separation, TreeResidue, WalkCancellation,
+    self, CancelWalk, Filtrate, HierarchicalIterator, Isomeric, SeparatingFilter,
+    SeparatingFilterInput, Separation, TreeResidue, WalkCancellation,
 };
 use crate::walk::glob::FilterAny;
-use crate::{BuildError, Pattern};
+use crate::{BuildError, BuildErrorKind, Glob, Pattern};
 
 pub use crate::walk::behavior::{
-    DepthBehavior, DepthMax, DepthMin, DepthMinMax, LinkBehavior, WalkBehavior,
+    DepthBehavior, DepthMax, DepthMin, DepthMinMax, LinkBehavior, PathNormalization, WalkBehavior,
 };
+pub use crate::walk::fs::{Fs, FsEntry, StdFs};
 pub use crate::walk::glob::GlobEntry;
 
 type FileFiltrate<T> = Result<T, WalkError>;
@@ -113,6 +124,34 @@ where
     }
 }
 
+/// Identifies a file independently of the paths that refer to it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum FileId {
+    #[cfg(unix)]
+    Inode { device: u64, inode: u64 },
+    // File indices are not exposed by stable Rust on other platforms, so canonical paths are used
+    // instead. These resolve links, but not hard links.
+    #[cfg(not(unix))]
+    Path(PathBuf),
+}
+
+impl FileId {
+    #[cfg(unix)]
+    fn of(entry: &dyn Entry) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt as _;
+
+        entry.metadata().ok().map(|metadata| FileId::Inode {
+            device: metadata.dev(),
+            inode: metadata.ino(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn of(entry: &dyn Entry) -> Option<Self> {
+        std::fs::canonicalize(entry.path()).ok().map(FileId::Path)
+    }
+}
+
 trait SplitAtDepth {
     fn split_at_depth(&self, depth: usize) -> (&Path, &Path);
 }
@@ -125,6 +164,57 @@ impl SplitAtDepth for Path {
     }
 }
 
+/// The root and relative segments of a path split at some depth.
+///
+/// Splitting a path at a depth iterates over its components. `SplitPath` records the byte ranges
+/// of both segments so that the split can be cheaply applied to the same path again.
+///
+/// **A `SplitPath` must only be applied to the path from which it was constructed.**
+#[derive(Clone, Debug)]
+pub(crate) struct SplitPath {
+    root: Range<usize>,
+    relative: Range<usize>,
+}
+
+impl SplitPath {
+    pub fn at_depth(path: &Path, depth: usize) -> Self {
+        fn range(path: &[u8], segment: &Path) -> Range<usize> {
+            let segment = segment.as_os_str().as_encoded_bytes();
+            if segment.is_empty() {
+                path.len()..path.len()
+            }
+            else {
+                // Both segments are sub-slices of `path`, so their offsets are derived from their
+                // addresses.
+                let start = (segment.as_ptr() as usize)
+                    .checked_sub(path.as_ptr() as usize)
+                    .expect("segment is not a sub-path");
+                start..(start + segment.len())
+            }
+        }
+
+        let bytes = path.as_os_str().as_encoded_bytes();
+        let (root, relative) = path.split_at_depth(depth);
+        SplitPath {
+            root: range(bytes, root),
+            relative: range(bytes, relative),
+        }
+    }
+
+    pub fn split<'p>(&self, path: &'p Path) -> (&'p Path, &'p Path) {
+        let bytes = path.as_os_str().as_encoded_bytes();
+        let (root, relative) = (&bytes[self.root.clone()], &bytes[self.relative.clone()]);
+        // SAFETY: The ranges are those of sub-paths of the same path as split by `Path` APIs, so
+        //         the bytes are split at boundaries that are valid for `OsStr`.
+        unsafe {
+            (
+                Path::new(OsStr::from_encoded_bytes_unchecked(root)),
+                Path::new(OsStr::from_encoded_bytes_unchecked(relative)),
+            )
+        }
+    }
+}
+
 trait JoinAndGetDepth {
     fn join_and_get_depth(&self, path: impl AsRef<Path>) -> (PathBuf, usize);
 }
@@ -178,6 +268,93 @@ impl WalkError {
     pub fn depth(&self) -> usize {
         self.depth
     }
+
+    /// Returns `true` if the error occurred because permission to read a file was denied.
+    ///
+    /// Such errors typically occur when reading a directory that is not readable by the current
+    /// user. See [`FileIterator::skip_unreadable`].
+    ///
+    /// [`FileIterator::skip_unreadable`]: crate::walk::FileIterator::skip_unreadable
+    pub fn is_permission_denied(&self) -> bool {
+        matches!(self.kind, WalkErrorKind::PermissionDenied { .. })
+    }
+
+    /// Returns `true` if the error occurred because the walk exceeded its deadline.
+    ///
+    /// See [`FileIterator::with_deadline`].
+    ///
+    /// [`FileIterator::with_deadline`]: crate::walk::FileIterator::with_deadline
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.kind, WalkErrorKind::Timeout)
+    }
+
+    /// Returns `true` if the error occurred because the path of a file has a component that is
+    /// not normal.
+    ///
+    /// See [`WalkBehavior::strict_components`].
+    ///
+    /// [`WalkBehavior::strict_components`]: crate::walk::WalkBehavior::strict_components
+    pub fn is_unexpected_component(&self) -> bool {
+        matches!(self.kind, WalkErrorKind::UnexpectedComponent { .. })
+    }
+
+    /// Gets the underlying I/O error, if any.
+    ///
+    /// Returns `None` if the error did not occur when reading a file, such as when a symbolic link
+    /// cycle is detected or the walk exceeds its deadline. The [`io::ErrorKind`] of the error can
+    /// be used to distinguish errors like [`NotFound`] and [`PermissionDenied`].
+    ///
+    /// [`io::ErrorKind`]: std::io::ErrorKind
+    /// [`NotFound`]: std::io::ErrorKind::NotFound
+    /// [`PermissionDenied`]: std::io::ErrorKind::PermissionDenied
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self.kind {
+            WalkErrorKind::Io { ref error, .. }
+            | WalkErrorKind::PermissionDenied { ref error, .. } => Some(error),
+            WalkErrorKind::LinkCycle { .. }
+            | WalkErrorKind::Timeout
+            | WalkErrorKind::UnexpectedComponent { .. } => None,
+        }
+    }
+
+    /// Converts the error into the underlying I/O error, if any.
+    ///
+    /// Returns `None` if the error did not occur when reading a file. See [`WalkError::io_error`].
+    ///
+    /// Unlike the conversion into [`io::Error`] implemented by `WalkError`, this function
+    /// discards the path and depth of the error and returns the I/O error as is.
+    ///
+    /// [`io::Error`]: std::io::Error
+    /// [`WalkError::io_error`]: crate::walk::WalkError::io_error
+    pub fn into_io_error(self) -> Option<io::Error> {
+        match self.kind {
+            WalkErrorKind::Io { error, .. } | WalkErrorKind::PermissionDenied { error, .. } => {
+                Some(error)
+            },
+            WalkErrorKind::LinkCycle { .. }
+            | WalkErrorKind::Timeout
+            | WalkErrorKind::UnexpectedComponent { .. } => None,
+        }
+    }
+
+    pub(crate) fn unexpected_component(path: PathBuf, depth: usize) -> Self {
+        WalkError {
+            depth,
+            kind: WalkErrorKind::UnexpectedComponent { path },
+        }
+    }
+
+    fn io(path: Option<PathBuf>, depth: usize, error: io::Error) -> Self {
+        WalkError {
+            depth,
+            kind: if let io::ErrorKind::PermissionDenied = error.kind() {
+                WalkErrorKind::PermissionDenied { path, error }
+            }
+            else {
+                WalkErrorKind::Io { path, error }
+            },
+        }
+    }
 }
 
 impl From<walkdir::Error> for WalkError {
@@ -185,13 +362,11 @@ impl From<walkdir::Error> for WalkError {
         let depth = error.depth();
         let path = error.path().map(From::from);
         if error.io_error().is_some() {
-            WalkError {
+            WalkError::io(
+                path,
                 depth,
-                kind: WalkErrorKind::Io {
-                    path,
-                    error: error.into_io_error().expect("incongruent error kind"),
-                },
-            }
+                error.into_io_error().expect("incongruent error kind"),
+            )
         }
         else {
             WalkError {
@@ -211,7 +386,9 @@ impl From<walkdir::Error> for WalkError {
 impl From<WalkError> for io::Error {
     fn from(error: WalkError) -> Self {
         let kind = match error.kind {
-            WalkErrorKind::Io { ref error, .. } => error.kind(),
+            WalkErrorKind::Io { ref error, .. }
+            | WalkErrorKind::PermissionDenied { ref error, .. } => error.kind(),
+            WalkErrorKind::Timeout => io::ErrorKind::TimedOut,
             _ => io::ErrorKind::Other,
         };
         io::Error::new(kind, error)
@@ -228,13 +405,25 @@ enum WalkErrorKind {
     },
     #[error("symbolic link cycle detected from `{root}` to `{leaf}`")]
     LinkCycle { root: PathBuf, leaf: PathBuf },
+    #[error("permission denied reading file at `{path:?}`: {error}")]
+    PermissionDenied {
+        path: Option<PathBuf>,
+        error: io::Error,
+    },
+    #[error("deadline exceeded")]
+    Timeout,
+    #[error("unexpected component that is not normal in path `{path:?}`")]
+    UnexpectedComponent { path: PathBuf },
 }
 
 impl WalkErrorKind {
     pub fn path(&self) -> Option<&Path> {
         match self {
-            WalkErrorKind::Io { ref path, .. } => path.as_ref().map(PathBuf::as_ref),
+            WalkErrorKind::Io { ref path, .. }
+            | WalkErrorKind::PermissionDenied { ref path, .. } => path.as_ref().map(PathBuf::as_ref),
             WalkErrorKind::LinkCycle { ref leaf, .. } => Some(leaf.as_ref()),
+            WalkErrorKind::UnexpectedComponent { ref path } => Some(path.as_ref()),
+            WalkErrorKind::Timeout => None,
         }
     }
 }
@@ -290,6 +479,47 @@ pub trait PathExt {
     /// [`PathExt::walk`]: crate::walk::PathExt::walk
     /// [`WalkBehavior`]: crate::walk::WalkBehavior
     fn walk_with_behavior(&self, behavior: impl Into<WalkBehavior>) -> WalkTree;
+
+    /// Gets an iterator over files in the directory tree at the path that match a glob
+    /// expression.
+    ///
+    /// This function is a shorthand for [`Glob::new`] and [`Glob::walk`] that walks the path
+    /// without constructing a [`Glob`] separately. The glob is matched against paths relative to
+    /// the path, as with [`Glob::walk`]. To discard matching files instead, see
+    /// [`FileIterator::not`].
+    ///
+    /// This function is provided for implementors that can be referenced as a [`Path`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the glob expression fails to build.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use wax::walk::{Entry, PathExt};
+    ///
+    /// for entry in Path::new("./src").walk_matching("**/*.rs").unwrap() {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`FileIterator::not`]: crate::walk::FileIterator::not
+    /// [`Glob`]: crate::Glob
+    /// [`Glob::new`]: crate::Glob::new
+    /// [`Glob::walk`]: crate::Glob::walk
+    /// [`Path`]: std::path::Path
+    fn walk_matching(
+        &self,
+        expression: &str,
+    ) -> Result<impl 'static + FileIterator<Entry = GlobEntry, Residue = TreeEntry>, BuildError>
+    where
+        Self: AsRef<Path>,
+    {
+        Glob::new(expression).map(|glob| glob.walk(self.as_ref()))
+    }
 }
 
 impl PathExt for Path {
@@ -329,9 +559,20 @@ pub trait Entry {
     /// [`Program::has_root`]: crate::Program::has_root
     fn root_relative_paths(&self) -> (&Path, &Path);
 
+    /// Gets the relative segment of the path of the file.
+    ///
+    /// This is the descendant of the root segment. See [`root_relative_paths`].
+    ///
+    /// [`root_relative_paths`]: crate::walk::Entry::root_relative_paths
+    fn relative_path(&self) -> &Path {
+        self.root_relative_paths().1
+    }
+
     /// Gets the [`Metadata`] of the file.
     ///
-    /// This may require an additional read from the file system on some platforms.
+    /// This may require an additional read from the file system on some platforms. Entries
+    /// yielded by walks cache the [`Metadata`] once it has been read, so that combinators and
+    /// other code that query the [`Metadata`] of the same entry do not read it again.
     ///
     /// [`Metadata`]: std::fs::Metadata
     fn metadata(&self) -> Result<Metadata, WalkError>;
@@ -344,6 +585,39 @@ pub trait Entry {
     /// [`metadata`]: crate::walk::Entry::metadata
     fn file_type(&self) -> FileType;
 
+    /// Reads the target of the file if it is a symbolic link.
+    ///
+    /// Returns `None` if the file is not a symbolic link. When following links via
+    /// [`LinkBehavior::ReadTarget`], entries describe link targets rather than links, and so this
+    /// function returns `None` for such entries. This function always reads from the file system.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, LinkBehavior};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**").unwrap();
+    /// for entry in glob.walk_with_behavior(".", LinkBehavior::ReadFile) {
+    ///     let entry = entry.unwrap();
+    ///     if let Some(target) = entry.read_link().unwrap() {
+    ///         println!("{:?} -> {:?}", entry.path(), target);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`LinkBehavior::ReadTarget`]: crate::walk::LinkBehavior::ReadTarget
+    fn read_link(&self) -> Result<Option<PathBuf>, WalkError> {
+        if self.file_type().is_symlink() {
+            std::fs::read_link(self.path())
+                .map(Some)
+                .map_err(|error| WalkError::io(Some(self.path().into()), self.depth(), error))
+        }
+        else {
+            Ok(None)
+        }
+    }
+
     /// Gets the depth of the path of the file from the root segment.
     ///
     /// See [`root_relative_paths`].
@@ -352,33 +626,186 @@ pub trait Entry {
     fn depth(&self) -> usize;
 }
 
+/// Type of a file (regular vs. directory vs. symbolic link).
+///
+/// `FileType` is much like [`std::fs::FileType`], but can also describe files read from an [`Fs`],
+/// for which no [`std::fs::FileType`] can be constructed. Such files are either directories or
+/// regular files and are never symbolic links.
+///
+/// [`Fs`]: crate::walk::Fs
+/// [`std::fs::FileType`]: std::fs::FileType
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FileType {
+    kind: FileTypeKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum FileTypeKind {
+    Std(std::fs::FileType),
+    Fs { is_dir: bool },
+}
+
+impl FileType {
+    pub(crate) fn from_is_dir(is_dir: bool) -> Self {
+        FileType {
+            kind: FileTypeKind::Fs { is_dir },
+        }
+    }
+
+    /// Returns `true` if the file is a directory.
+    pub fn is_dir(&self) -> bool {
+        match self.kind {
+            FileTypeKind::Std(file_type) => file_type.is_dir(),
+            FileTypeKind::Fs { is_dir } => is_dir,
+        }
+    }
+
+    /// Returns `true` if the file is a regular file.
+    pub fn is_file(&self) -> bool {
+        match self.kind {
+            FileTypeKind::Std(file_type) => file_type.is_file(),
+            FileTypeKind::Fs { is_dir } => !is_dir,
+        }
+    }
+
+    /// Returns `true` if the file is a symbolic link.
+    pub fn is_symlink(&self) -> bool {
+        match self.kind {
+            FileTypeKind::Std(file_type) => file_type.is_symlink(),
+            FileTypeKind::Fs { .. } => false,
+        }
+    }
+
+    /// Gets the [`std::fs::FileType`] of the file, if any.
+    ///
+    /// Returns `None` if the file was read from an [`Fs`]. The [`std::fs::FileType`] can be used
+    /// to query platform-specific file types, such as sockets on Unix.
+    ///
+    /// [`Fs`]: crate::walk::Fs
+    /// [`std::fs::FileType`]: std::fs::FileType
+    pub fn to_std(&self) -> Option<std::fs::FileType> {
+        match self.kind {
+            FileTypeKind::Std(file_type) => Some(file_type),
+            FileTypeKind::Fs { .. } => None,
+        }
+    }
+}
+
+impl From<std::fs::FileType> for FileType {
+    fn from(file_type: std::fs::FileType) -> Self {
+        FileType {
+            kind: FileTypeKind::Std(file_type),
+        }
+    }
+}
+
 /// Describes a file yielded from a [`WalkTree`] iterator.
 ///
 /// [`WalkTree`]: crate::walk::WalkTree
 #[derive(Clone, Debug)]
 pub struct TreeEntry {
     entry: DirEntry,
+    /// The normalized path of the file or `None` if the path of the `DirEntry` is used verbatim.
+    path: Option<PathBuf>,
+    split: SplitPath,
+    /// `true` if the metadata of a link file that is not followed describes its target.
+    is_target_read: bool,
+    /// The [`Metadata`] of the file once it has been read.
+    ///
+    /// [`Metadata`]: std::fs::Metadata
+    metadata: OnceLock<Metadata>,
+}
+
+impl TreeEntry {
+    fn new(entry: DirEntry, link: LinkBehavior) -> Self {
+        let split = SplitPath::at_depth(entry.path(), entry.depth());
+        TreeEntry {
+            is_target_read: TreeEntry::is_target_read(&entry, link),
+            entry,
+            path: None,
+            split,
+            metadata: OnceLock::new(),
+        }
+    }
+
+    fn with_normalized_root(
+        entry: DirEntry,
+        link: LinkBehavior,
+        root: &Path,
+        normalized: &Path,
+    ) -> Self {
+        // Paths in the walk are always joined to the root path, so the relative path segment can
+        // be joined to the normalized root path instead.
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .expect("walked path is not beneath root path");
+        let path = if relative.as_os_str().is_empty() {
+            if normalized.as_os_str().is_empty() {
+                PathBuf::from(".")
+            }
+            else {
+                normalized.to_path_buf()
+            }
+        }
+        else {
+            normalized.join(relative)
+        };
+        let split = SplitPath::at_depth(&path, entry.depth());
+        TreeEntry {
+            is_target_read: TreeEntry::is_target_read(&entry, link),
+            entry,
+            path: Some(path),
+            split,
+            metadata: OnceLock::new(),
+        }
+    }
+
+    fn is_target_read(entry: &DirEntry, link: LinkBehavior) -> bool {
+        matches!(link, LinkBehavior::ReadTargetNoDescend) && entry.path_is_symlink()
+    }
 }
 
 impl Entry for TreeEntry {
     fn into_path(self) -> PathBuf {
-        self.entry.into_path()
+        match self.path {
+            Some(path) => path,
+            _ => self.entry.into_path(),
+        }
     }
 
     fn path(&self) -> &Path {
-        self.entry.path()
+        self.path.as_deref().unwrap_or_else(|| self.entry.path())
     }
 
     fn root_relative_paths(&self) -> (&Path, &Path) {
-        self.path().split_at_depth(self.depth())
+        self.split.split(self.path())
     }
 
     fn metadata(&self) -> Result<Metadata, WalkError> {
-        self.entry.metadata().map_err(From::from)
+        if let Some(metadata) = self.metadata.get() {
+            return Ok(metadata.clone());
+        }
+        let metadata = if self.is_target_read {
+            std::fs::metadata(self.entry.path())
+                .map_err(|error| WalkError::io(Some(self.path().into()), self.depth(), error))?
+        }
+        else {
+            self.entry.metadata()?
+        };
+        Ok(self.metadata.get_or_init(|| metadata).clone())
     }
 
     fn file_type(&self) -> FileType {
-        self.entry.file_type()
+        if self.is_target_read {
+            // Fall back to the link file if its target cannot be read, such as a broken link.
+            self.metadata()
+                .map_or_else(|_| self.entry.file_type(), |metadata| metadata.file_type())
+                .into()
+        }
+        else {
+            self.entry.file_type().into()
+        }
     }
 
     fn depth(&self) -> usize {
@@ -410,6 +837,10 @@ impl Entry for TreeEntry {
 #[derive(Debug)]
 pub struct WalkTree {
     is_dir: bool,
+    yielded: usize,
+    /// The normalized root path or `None` if paths are yielded verbatim.
+    normalized: Option<PathBuf>,
+    builder: WalkTreeBuilder,
     input: walkdir::IntoIter,
 }
 
@@ -423,24 +854,80 @@ impl WalkTree {
         pivot: usize,
         behavior: impl Into<WalkBehavior>,
     ) -> Self {
+        let behavior = behavior.into();
+        let (min, max) = behavior.min_max_at_pivot(pivot);
         let root = root.into();
-        let WalkBehavior { link, depth } = behavior.into();
-        let builder = WalkDir::new(root.as_path()).follow_links(match link {
-            LinkBehavior::ReadFile => false,
-            LinkBehavior::ReadTarget => true,
-        });
-        let builder = match depth {
-            DepthBehavior::Max(max) => builder.max_depth(max.max_at_pivot(pivot)),
-            DepthBehavior::Min(min) => builder.min_depth(min.min_at_pivot(pivot)),
-            DepthBehavior::MinMax(minmax) => {
-                let (min, max) = minmax.min_max_at_pivot(pivot);
-                builder.min_depth(min).max_depth(max)
-            },
-            DepthBehavior::Unbounded => builder,
+        let normalized = behavior.normalize.normalize_root(&root);
+        let builder = WalkTreeBuilder {
+            root,
+            link: behavior.link,
+            min,
+            max,
         };
         WalkTree {
             is_dir: false,
-            input: builder.into_iter(),
+            yielded: 0,
+            normalized,
+            input: builder.build().into_iter(),
+            builder,
+        }
+    }
+
+    /// Counts the number of files that remain in the walk by reading the directory tree again.
+    ///
+    /// **This function is expensive.** It reads the **entire** directory tree from the root of
+    /// the walk a second time, counts its files, and subtracts the number of items that the walk
+    /// has already yielded. Prefer collecting or iterating the walk directly unless an up front
+    /// count is worth the cost of the additional I/O.
+    ///
+    /// The count is not a bound. It does not account for directory trees that have been
+    /// cancelled (so it may be too large) nor for changes to the directory tree during the walk.
+    /// For this reason, the [`Iterator::size_hint`] of `WalkTree` is unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use wax::walk::{Entry, PathExt};
+    ///
+    /// let walk = Path::new(".").walk();
+    /// let total = walk.count_remaining();
+    /// for (n, entry) in walk.enumerate() {
+    ///     let entry = entry.unwrap();
+    ///     println!("[{}/{}] {:?}", n + 1, total, entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`Iterator::size_hint`]: std::iter::Iterator::size_hint
+    pub fn count_remaining(&self) -> usize {
+        self.builder
+            .build()
+            .into_iter()
+            .count()
+            .saturating_sub(self.yielded)
+    }
+}
+
+/// Parameters from which a [`WalkTree`] constructs its [`WalkDir`].
+///
+/// These parameters are retained so that the directory tree can be read again, such as when
+/// counting the number of files that remain in a walk.
+#[derive(Clone, Debug)]
+struct WalkTreeBuilder {
+    root: PathBuf,
+    link: LinkBehavior,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl WalkTreeBuilder {
+    fn build(&self) -> WalkDir {
+        let builder = WalkDir::new(self.root.as_path())
+            .follow_links(self.link.is_following())
+            .min_depth(self.min);
+        match self.max {
+            Some(max) => builder.max_depth(max),
+            _ => builder,
         }
     }
 }
@@ -462,12 +949,29 @@ impl Iterator for WalkTree {
     fn next(&mut self) -> Option<Self::Item> {
         let (is_dir, next) = match self.input.next() {
             Some(result) => match result {
-                Ok(entry) => (entry.file_type().is_dir(), Some(Ok(TreeEntry { entry }))),
+                Ok(entry) => {
+                    // The file type of the `DirEntry` determines whether or not the walk descends
+                    // into the file. Links to directories that are not followed are not descended
+                    // into, even if their entries describe their targets.
+                    let is_dir = entry.file_type().is_dir();
+                    let link = self.builder.link;
+                    let entry = match self.normalized {
+                        Some(ref normalized) => TreeEntry::with_normalized_root(
+                            entry,
+                            link,
+                            &self.builder.root,
+                            normalized,
+                        ),
+                        _ => TreeEntry::new(entry, link),
+                    };
+                    (is_dir, Some(Ok(entry)))
+                },
                 Err(error) => (false, Some(Err(error.into()))),
             },
             _ => (false, None),
         };
         self.is_dir = is_dir;
+        self.yielded += usize::from(next.is_some());
         next
     }
 }
@@ -575,6 +1079,49 @@ pub trait FileIterator:
         FilterEntry { input: self, f }
     }
 
+    /// Filters and maps file entries and controls the traversal of the directory tree.
+    ///
+    /// This function is like [`filter_entry`], but the given function also maps the entries that
+    /// it does not discard into a value. The function returns `Ok(Some(value))` to yield an entry
+    /// with the value, `Ok(None)` to discard only the entry, or an [`EntryResidue`] to discard the
+    /// entry and control traversal exactly as with [`filter_entry`]. The combinator yields
+    /// [`MappedEntry`]s, which implement [`Entry`] and carry their values.
+    ///
+    /// Filtering is monotonic: the function is called for entries discarded by composing
+    /// filters, but can only further discard a directory tree and cannot restore such entries.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use wax::walk::{Entry, EntryResidue, FileIterator, PathExt as _};
+    ///
+    /// // Get the size of each file in a directory tree, but do not read `target` directories.
+    /// for entry in Path::new(".").walk().filter_map_entry(|entry| {
+    ///     if entry.path().ends_with("target") {
+    ///         Err(EntryResidue::Tree)
+    ///     }
+    ///     else {
+    ///         Ok(entry.metadata().ok().map(|metadata| metadata.len()))
+    ///     }
+    /// }) {
+    ///     let entry = entry.unwrap();
+    ///     println!("{}: {:?}", entry.value(), entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`Entry`]: crate::walk::Entry
+    /// [`EntryResidue`]: crate::walk::EntryResidue
+    /// [`filter_entry`]: crate::walk::FileIterator::filter_entry
+    /// [`MappedEntry`]: crate::walk::MappedEntry
+    fn filter_map_entry<F, U>(self, f: F) -> FilterMapEntry<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&dyn Entry) -> Result<Option<U>, EntryResidue>,
+    {
+        FilterMapEntry { input: self, f }
+    }
+
     /// Filters file entries against a negated glob.
     ///
     /// This function constructs a combinator that discards files with paths that match the given
@@ -588,11 +1135,18 @@ pub trait FileIterator:
     /// **Prefer this combinator over matching each file entry against [`Program`]s, since it
     /// avoids potentially large and unnecessary reads and may have better performance.**
     ///
+    /// The pattern is matched against the path of each file relative to the root of the walk. Use
+    /// [`not_absolute`] to match against full paths instead.
+    ///
     /// # Errors
     ///
     /// Returns an error if the pattern fails to build. If the pattern is a compiled [`Program`]
     /// type such as [`Glob`], then this only occurs if the combinator program is too large.
     ///
+    /// Returns an error if the pattern matches all paths, such as `**`, because such a negation
+    /// discards every file and is almost certainly a mistake. Use [`not_allowing_all`] to negate
+    /// such patterns.
+    ///
     /// # Examples
     ///
     /// Because glob expressions do not support general negations, it is sometimes impossible to
@@ -613,9 +1167,32 @@ pub trait FileIterator:
     /// ```
     ///
     /// [`Glob`]: crate::Glob
+    /// [`not_absolute`]: crate::walk::FileIterator::not_absolute
+    /// [`not_allowing_all`]: crate::walk::FileIterator::not_allowing_all
     /// [`Program`]: crate::Program
     /// [`Program::is_exhaustive`]: crate::Program::is_exhaustive
     fn not<'t, T>(self, pattern: T) -> Result<Not<Self>, BuildError>
+    where
+        Self: Sized,
+        T: Pattern<'t>,
+    {
+        self.not_allowing_all(pattern).and_then(Not::matching_some)
+    }
+
+    /// Filters file entries against a negated glob that may match all paths.
+    ///
+    /// This function is the same as [`not`], but does not reject patterns that match all paths.
+    /// Negating such a pattern, such as `**`, discards every file and yields an empty walk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern fails to build. If the pattern is a compiled [`Program`]
+    /// type such as [`Glob`], then this only occurs if the combinator program is too large.
+    ///
+    /// [`Glob`]: crate::Glob
+    /// [`not`]: crate::walk::FileIterator::not
+    /// [`Program`]: crate::Program
+    fn not_allowing_all<'t, T>(self, pattern: T) -> Result<Not<Self>, BuildError>
     where
         Self: Sized,
         T: Pattern<'t>,
@@ -624,437 +1201,3282 @@ pub trait FileIterator:
         FilterAny::any(tree.into_alternatives()).map(|filter| Not {
             input: self,
             filter,
+            is_absolute: false,
         })
     }
-}
-
-impl<T, R, I> FileIterator for I
-where
-    T: Entry,
-    R: Entry + From<T>,
-    I: HierarchicalIterator<Feed = FileFeed<T, R>> + Iterator<Item = FileFiltrate<T>>,
-{
-    type Entry = T;
-    type Residue = R;
-}
 
-// TODO: Implement this using combinators provided by the `filter` module and RPITIT once it lands
-//       in stable Rust. Remove any use of `WalkCancellation::unchecked`.
-/// Iterator combinator that filters file entries and controls the traversal of directory trees.
-///
-/// This combinator is returned by [`FileIterator::filter_entry`] and implements [`FileIterator`].
-///
-/// [`FileIterator`]: crate::walk::FileIterator
-/// [`FileIterator::filter_entry`]: crate::walk::FileIterator::filter_entry
-#[derive(Clone, Debug)]
-pub struct FilterEntry<I, F> {
-    input: I,
-    f: F,
-}
+    /// Filters file entries against a negated glob that is matched against full paths.
+    ///
+    /// This function is the same as [`not`], but the pattern is matched against the full path of
+    /// each file (see [`Entry::path`]) rather than its path relative to the root of the walk (see
+    /// [`Entry::root_relative_paths`]). This allows negations that refer to absolute locations,
+    /// such as `/tmp/**`.
+    ///
+    /// Full paths are joined with the path given to walk functions like [`Glob::walk`] and so are
+    /// only absolute if that path is absolute. Prefer [`not`] for negations that are independent
+    /// of the location of a walk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern fails to build or matches all paths. See [`not`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::FileIterator;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.log").unwrap();
+    /// for entry in glob.walk("/var").not_absolute("/var/cache/**").unwrap() {
+    ///     let entry = entry.unwrap();
+    ///     // ...
+    /// }
+    /// ```
+    ///
+    /// [`Entry::path`]: crate::walk::Entry::path
+    /// [`Entry::root_relative_paths`]: crate::walk::Entry::root_relative_paths
+    /// [`Glob::walk`]: crate::Glob::walk
+    /// [`not`]: crate::walk::FileIterator::not
+    fn not_absolute<'t, T>(self, pattern: T) -> Result<Not<Self>, BuildError>
+    where
+        Self: Sized,
+        T: Pattern<'t>,
+    {
+        self.not_allowing_all(pattern)
+            .and_then(Not::matching_some)
+            .map(|not| Not {
+                is_absolute: true,
+                ..not
+            })
+    }
+
+    /// Filters file entries against any of the given negated [`Glob`]s.
+    ///
+    /// This function is the same as [`not`], but accepts any number of compiled [`Glob`]s. The
+    /// [`Glob`]s are not parsed again: their alternatives are combined into a filter as is, so
+    /// directory trees are **not** read when a directory matches an exhaustive alternative of any
+    /// of the [`Glob`]s.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the combined program is too large or if any of the [`Glob`]s match all
+    /// paths (see [`not`]).
+    ///
+    /// # Examples
+    ///
+    /// A set of compiled [`Glob`]s can be applied as a negation to many walks.
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let negations = vec![Glob::new("**/target/**").unwrap(), Glob::new("**/*.lock").unwrap()];
+    /// for directory in ["crates/a", "crates/b"] {
+    ///     let glob = Glob::new("**/*.toml").unwrap();
+    ///     for entry in glob.walk(directory).not_globs(negations.clone()).unwrap() {
+    ///         let entry = entry.unwrap();
+    ///         println!("{:?}", entry.path());
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`Glob`]: crate::Glob
+    /// [`not`]: crate::walk::FileIterator::not
+    fn not_globs<'t, I>(self, globs: I) -> Result<Not<Self>, BuildError>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = Glob<'t>>,
+    {
+        FilterAny::any(
+            globs
+                .into_iter()
+                .flat_map(|glob| glob.tree.into_alternatives()),
+        )
+        .map(|filter| Not {
+            input: self,
+            filter,
+            is_absolute: false,
+        })
+        .and_then(Not::matching_some)
+    }
+
+    /// Filters file entries that are deeper than the given depth.
+    ///
+    /// This function constructs a combinator that discards files with a [depth][`Entry::depth`]
+    /// that is greater than `max`. Directories beneath this depth are **not** read from the file
+    /// system. Unlike [`DepthBehavior`], which bounds a walk when it is constructed, this
+    /// combinator can be composed with other combinators like [`not`] and applies to the
+    /// entries that it observes.
+    ///
+    /// Note that directories **at** the given depth are still read, because their contents must
+    /// be observed to be discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.md").unwrap();
+    /// for entry in glob.walk("doc").not("**/private/**").unwrap().max_depth(2) {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`DepthBehavior`]: crate::walk::DepthBehavior
+    /// [`Entry::depth`]: crate::walk::Entry::depth
+    /// [`not`]: crate::walk::FileIterator::not
+    fn max_depth(self, max: usize) -> MaxDepth<Self>
+    where
+        Self: Sized,
+    {
+        MaxDepth { input: self, max }
+    }
+
+    /// Discards errors that occur when permission to read a file is denied.
+    ///
+    /// This function constructs a combinator that discards [`WalkError`]s for which
+    /// [`WalkError::is_permission_denied`] is `true` rather than yielding them. A directory that
+    /// cannot be read has no entries, so its tree is effectively pruned from the walk. Other
+    /// errors are yielded as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.log").unwrap();
+    /// for entry in glob.walk("/var/log").skip_unreadable() {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`WalkError`]: crate::walk::WalkError
+    /// [`WalkError::is_permission_denied`]: crate::walk::WalkError::is_permission_denied
+    fn skip_unreadable(self) -> SkipUnreadable<Self>
+    where
+        Self: Sized,
+    {
+        SkipUnreadable { input: self }
+    }
+
+    /// Discards file entries that refer to files that have already been yielded.
+    ///
+    /// This function constructs a combinator that identifies files by device and inode on Unix
+    /// and by canonical path on other platforms. When links are followed (see
+    /// [`LinkBehavior::ReadTarget`]), the same file may be reached through different paths and
+    /// this combinator yields only the first such entry. Directory trees that have already been
+    /// read are **not** read again. Entries that cannot be identified are yielded as is.
+    ///
+    /// **This combinator queries the [`Metadata`] of every entry** (or canonicalizes its path on
+    /// platforms other than Unix), which requires an additional read from the file system per
+    /// entry on most platforms. The identities of yielded files are retained for the duration of
+    /// the walk.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator, LinkBehavior};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// for entry in glob
+    ///     .walk_with_behavior(".", LinkBehavior::ReadTarget)
+    ///     .not("**/target/**")
+    ///     .unwrap()
+    ///     .unique_by_inode()
+    /// {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`LinkBehavior::ReadTarget`]: crate::walk::LinkBehavior::ReadTarget
+    /// [`Metadata`]: std::fs::Metadata
+    fn unique_by_inode(self) -> UniqueByInode<Self>
+    where
+        Self: Sized,
+    {
+        UniqueByInode {
+            input: self,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Calls a function with the path of each directory that is entered by the walk.
+    ///
+    /// This function constructs a combinator that calls the given function when it observes a
+    /// directory entry that has not been discarded with [`EntryResidue::Tree`], including
+    /// directories that have been discarded with [`EntryResidue::File`] and so are still read.
+    /// This can be used to report progress, such as the directory that is currently being read,
+    /// without otherwise affecting the walk.
+    ///
+    /// The function is called before the contents of the directory are read. Filtering
+    /// combinators applied **after** this combinator may still discard the directory tree, in
+    /// which case the directory is reported but is not read. Apply this combinator after any
+    /// filtering combinators to report only directories that are read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.(?i){jpg,jpeg}").unwrap();
+    /// for entry in glob
+    ///     .walk("./Pictures")
+    ///     .not("**/.*/**")
+    ///     .unwrap()
+    ///     .on_descend(|path| eprintln!("reading {:?}...", path))
+    /// {
+    ///     let entry = entry.unwrap();
+    ///     println!("JPEG: {:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`EntryResidue::File`]: crate::walk::EntryResidue::File
+    /// [`EntryResidue::Tree`]: crate::walk::EntryResidue::Tree
+    fn on_descend<F>(self, f: F) -> OnDescend<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&Path),
+    {
+        OnDescend { input: self, f }
+    }
+
+    /// Calls a function with each file entry that is discarded by the walk.
+    ///
+    /// This function constructs a combinator that calls the given function when it observes an
+    /// entry that has been separated into residue by preceding combinators, such as [`not`] and
+    /// [`filter_entry`]. The function receives the entry and an [`EntryResidue`] that describes how
+    /// it was discarded: [`EntryResidue::Tree`] indicates that the directory tree of the entry is
+    /// not read. Entries beneath a discarded tree are never read and so are never observed.
+    ///
+    /// **This combinator is intended as a debugging aid**, such as for understanding why a file is
+    /// not yielded by a walk. It does not otherwise affect the walk. Only residue produced by
+    /// combinators applied **before** this combinator is observed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// for entry in glob
+    ///     .walk(".")
+    ///     .not("target/**")
+    ///     .unwrap()
+    ///     .inspect_residue(|entry, residue| {
+    ///         eprintln!("discarded {:?} ({:?})", entry.path(), residue);
+    ///     })
+    /// {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`EntryResidue`]: crate::walk::EntryResidue
+    /// [`EntryResidue::Tree`]: crate::walk::EntryResidue::Tree
+    /// [`filter_entry`]: crate::walk::FileIterator::filter_entry
+    /// [`not`]: crate::walk::FileIterator::not
+    fn inspect_residue<F>(self, f: F) -> InspectResidue<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&dyn Entry, EntryResidue),
+    {
+        InspectResidue { input: self, f }
+    }
+
+    /// Stops the walk once the given deadline has passed.
+    ///
+    /// This function constructs a combinator that checks the clock before reading each entry.
+    /// Once the deadline has passed, the combinator yields a final [`WalkError`] for which
+    /// [`WalkError::is_timeout`] is `true` and then yields no more entries. No files are read
+    /// after the deadline, so the walk never descends into another directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::time::{Duration, Instant};
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// for entry in glob.walk(".").with_deadline(deadline) {
+    ///     match entry {
+    ///         Ok(entry) => println!("{:?}", entry.path()),
+    ///         Err(error) if error.is_timeout() => eprintln!("walk is incomplete"),
+    ///         Err(error) => panic!("{}", error),
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`WalkError`]: crate::walk::WalkError
+    /// [`WalkError::is_timeout`]: crate::walk::WalkError::is_timeout
+    fn with_deadline(self, deadline: Instant) -> Deadline<Self>
+    where
+        Self: Sized,
+    {
+        Deadline {
+            input: Some(self),
+            deadline,
+        }
+    }
+
+    /// Stops a walk once a number of entries have been yielded.
+    ///
+    /// Unlike [`Iterator::take`], this combinator cancels the walk of the directory tree of the
+    /// final entry and then discards its input, so no files are read after `n` entries have been
+    /// yielded. This avoids reading directories for which no entries would be yielded, which can
+    /// be expensive when only the first matches in a large directory tree are needed. Errors are
+    /// yielded, but are not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// for entry in glob.walk(".").limit(100) {
+    ///     println!("{:?}", entry.unwrap().path());
+    /// }
+    /// ```
+    ///
+    /// [`Iterator::take`]: std::iter::Iterator::take
+    fn limit(self, n: usize) -> Limit<Self>
+    where
+        Self: Sized,
+    {
+        Limit {
+            input: (n > 0).then_some(self),
+            remaining: n,
+        }
+    }
+
+    /// Counts the directories, entries, and errors of a walk.
+    ///
+    /// This function constructs a combinator and a shared [`WalkStats`] that is updated as the
+    /// combinator is iterated. The combinator observes the entries and errors yielded by its
+    /// input as well as the directory trees that are pruned (cancelled) by its input, so it is
+    /// typically applied **after** any filtering combinators like [`filter_entry`] and [`not`].
+    /// Directory trees that are pruned by combinators applied after it are also counted.
+    ///
+    /// # Examples
+    ///
+    /// Statistics can be used to verify that filters prune directory trees rather than only
+    /// discarding their files.
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.rs").unwrap();
+    /// let (walk, stats) = glob.walk(".").not("target/**").unwrap().with_stats();
+    /// for entry in walk {
+    ///     println!("{:?}", entry.unwrap().path());
+    /// }
+    /// eprintln!(
+    ///     "read {} directories and pruned {}",
+    ///     stats.directories_read(),
+    ///     stats.directories_pruned(),
+    /// );
+    /// ```
+    ///
+    /// [`filter_entry`]: crate::walk::FileIterator::filter_entry
+    /// [`not`]: crate::walk::FileIterator::not
+    /// [`WalkStats`]: crate::walk::WalkStats
+    fn with_stats(self) -> (WithStats<Self>, Arc<WalkStats>)
+    where
+        Self: Sized,
+    {
+        let stats = Arc::new(WalkStats::default());
+        (
+            WithStats {
+                input: self,
+                stats: stats.clone(),
+                is_dir: false,
+            },
+            stats,
+        )
+    }
+
+    /// Orders directory entries with respect to their contents.
+    ///
+    /// This function constructs a combinator that yields directories either before their contents
+    /// (pre-order) or after their contents (post-order). See [`DirOrdering`]. Traversal is
+    /// always pre-order: in post-order, directory entries are buffered until their contents have
+    /// been yielded. Errors are yielded immediately and are never buffered.
+    ///
+    /// The combinator is **not** a [`FileIterator`] and so must be applied **after** any
+    /// filtering combinators like [`filter_entry`] and [`not`]. Because filtering occurs before
+    /// reordering, discarding a directory with [`EntryResidue::Tree`] cancels the walk into that
+    /// directory in both orderings.
+    ///
+    /// # Examples
+    ///
+    /// Post-order can be used to compute the sizes of directory trees in a single pass.
+    ///
+    /// ```rust,no_run
+    /// use std::collections::HashMap;
+    /// use std::path::Path;
+    /// use wax::walk::{DirOrdering, Entry, FileIterator, PathExt as _};
+    ///
+    /// let mut sizes = HashMap::new();
+    /// for entry in Path::new(".").walk().ordering(DirOrdering::PostOrder) {
+    ///     let entry = entry.unwrap();
+    ///     let size = if entry.file_type().is_dir() {
+    ///         // The contents of the directory have already been visited.
+    ///         sizes.get(entry.path()).copied().unwrap_or(0)
+    ///     }
+    ///     else {
+    ///         entry.metadata().unwrap().len()
+    ///     };
+    ///     if let Some(parent) = entry.path().parent() {
+    ///         *sizes.entry(parent.to_path_buf()).or_insert(0) += size;
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`DirOrdering`]: crate::walk::DirOrdering
+    /// [`EntryResidue::Tree`]: crate::walk::EntryResidue::Tree
+    /// [`FileIterator`]: crate::walk::FileIterator
+    /// [`filter_entry`]: crate::walk::FileIterator::filter_entry
+    /// [`not`]: crate::walk::FileIterator::not
+    fn ordering(self, ordering: DirOrdering) -> Ordered<Self>
+    where
+        Self: Sized,
+    {
+        Ordered {
+            input: self,
+            ordering,
+            trees: vec![],
+            pending: None,
+        }
+    }
+
+    /// Maps matched file entries to the text of a capture.
+    ///
+    /// This function constructs a combinator that yields the [matched text][`MatchedText::get`]
+    /// of the capture at the given index for each entry. Entries with no text for the capture,
+    /// such as when the capture is within an alternative that did not match, are discarded.
+    /// Errors are yielded as is.
+    ///
+    /// Like [`ordering`], the combinator is **not** a [`FileIterator`] and so must be applied
+    /// **after** any filtering combinators.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::FileIterator;
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/lib*.so.*").unwrap();
+    /// for version in glob.walk("/usr/lib").captures(3) {
+    ///     println!("version: {}", version.unwrap());
+    /// }
+    /// ```
+    ///
+    /// [`FileIterator`]: crate::walk::FileIterator
+    /// [`MatchedText::get`]: crate::MatchedText::get
+    /// [`ordering`]: crate::walk::FileIterator::ordering
+    fn captures(self, index: usize) -> Captures<Self>
+    where
+        Self: Sized + FileIterator<Entry = GlobEntry>,
+    {
+        Captures { input: self, index }
+    }
+
+    /// Groups consecutive file entries by their parent directory.
+    ///
+    /// This function constructs a combinator that yields the path of a parent directory and the
+    /// file entries in that directory. Entries are grouped as they are walked **without**
+    /// buffering the entire walk: a group is yielded when an entry with a different parent is
+    /// walked or the walk ends. Errors are yielded as soon as they occur, before the group in
+    /// which they occurred.
+    ///
+    /// Because traversal is depth-first, the entries of a directory can be interrupted by the
+    /// contents of its sub-directories. When this happens, the parent directory is yielded in
+    /// more than one group. For example, the entries `a/b`, `a/b/c`, and `a/d` are grouped as
+    /// `(a, [a/b])`, `(a/b, [a/b/c])`, and `(a, [a/d])`.
+    ///
+    /// Like [`ordering`], the combinator is **not** a [`FileIterator`] and so must be applied
+    /// **after** any filtering combinators.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.{md,txt}").unwrap();
+    /// for group in glob.walk("doc").group_by_parent() {
+    ///     let (parent, entries) = group.unwrap();
+    ///     println!("{}:", parent.display());
+    ///     for entry in entries {
+    ///         println!("  {:?}", entry.path().file_name().unwrap());
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`FileIterator`]: crate::walk::FileIterator
+    /// [`ordering`]: crate::walk::FileIterator::ordering
+    fn group_by_parent(self) -> GroupByParent<Self>
+    where
+        Self: Sized,
+    {
+        GroupByParent {
+            input: Some(self),
+            group: None,
+        }
+    }
+
+    /// Collects file entries and sorts them by their modification times.
+    ///
+    /// This function reads the **entire** walk and the [`Metadata`] of **each** entry before
+    /// returning an iterator over the entries ordered from least to most recently modified.
+    /// Entries with the same modification time are yielded in the order in which they were
+    /// walked. The returned iterator is double-ended, so entries can be yielded from most to least
+    /// recently modified via [`Iterator::rev`]. To do so while retaining the walk order of entries
+    /// with the same modification time, see [`sorted_by_mtime_descending`].
+    ///
+    /// Because the walk is fully materialized, this function should be applied **after** any
+    /// filtering combinators.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the walk yields an error or the modification time of an entry cannot
+    /// be read. Collection stops at the first such error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.log").unwrap();
+    /// let recent = glob
+    ///     .walk("/var/log")
+    ///     .sorted_by_mtime()
+    ///     .unwrap()
+    ///     .rev()
+    ///     .take(10);
+    /// for entry in recent {
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`Iterator::rev`]: std::iter::Iterator::rev
+    /// [`Metadata`]: std::fs::Metadata
+    /// [`sorted_by_mtime_descending`]: crate::walk::FileIterator::sorted_by_mtime_descending
+    fn sorted_by_mtime(self) -> Result<vec::IntoIter<Self::Entry>, WalkError>
+    where
+        Self: Sized,
+    {
+        sort_by_mtime(self, |modified| modified)
+    }
+
+    /// Collects file entries and sorts them by their modification times in descending order.
+    ///
+    /// This function behaves like [`sorted_by_mtime`], but returns an iterator over the entries
+    /// ordered from most to least recently modified. Unlike reversing the iterator returned by
+    /// [`sorted_by_mtime`], entries with the same modification time are still yielded in the
+    /// order in which they were walked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the walk yields an error or the modification time of an entry cannot
+    /// be read. Collection stops at the first such error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.log").unwrap();
+    /// let recent = glob
+    ///     .walk("/var/log")
+    ///     .sorted_by_mtime_descending()
+    ///     .unwrap()
+    ///     .take(10);
+    /// for entry in recent {
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// ```
+    ///
+    /// [`sorted_by_mtime`]: crate::walk::FileIterator::sorted_by_mtime
+    fn sorted_by_mtime_descending(self) -> Result<vec::IntoIter<Self::Entry>, WalkError>
+    where
+        Self: Sized,
+    {
+        sort_by_mtime(self, Reverse)
+    }
+
+    /// Collects file entries and errors separately.
+    ///
+    /// This function reads the **entire** walk and returns the entries and [`WalkError`]s that it
+    /// yields, each in the order in which they were walked. Unlike collecting into a [`Result`],
+    /// errors do not stop the walk, so a few unreadable directories do not discard every other
+    /// match.
+    ///
+    /// Because the walk is fully materialized, this function should be applied **after** any
+    /// filtering combinators.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use wax::walk::{Entry, FileIterator};
+    /// use wax::Glob;
+    ///
+    /// let glob = Glob::new("**/*.log").unwrap();
+    /// let (entries, errors) = glob.walk("/var/log").partition_results();
+    /// for entry in entries {
+    ///     println!("{:?}", entry.path());
+    /// }
+    /// for error in errors {
+    ///     eprintln!("{}", error);
+    /// }
+    /// ```
+    ///
+    /// [`Result`]: std::result::Result
+    /// [`WalkError`]: crate::walk::WalkError
+    fn partition_results(self) -> (Vec<Self::Entry>, Vec<WalkError>)
+    where
+        Self: Sized,
+    {
+        let mut entries = vec![];
+        let mut errors = vec![];
+        for entry in self {
+            match entry {
+                Ok(entry) => entries.push(entry),
+                Err(error) => errors.push(error),
+            }
+        }
+        (entries, errors)
+    }
+}
+
+impl<T, R, I> FileIterator for I
+where
+    T: Entry,
+    R: Entry + From<T>,
+    I: HierarchicalIterator<Feed = FileFeed<T, R>> + Iterator<Item = FileFiltrate<T>>,
+{
+    type Entry = T;
+    type Residue = R;
+}
+
+fn sort_by_mtime<I, K>(
+    files: I,
+    mut f: impl FnMut(SystemTime) -> K,
+) -> Result<vec::IntoIter<I::Entry>, WalkError>
+where
+    I: FileIterator,
+    K: Ord,
+{
+    let mut entries = files
+        .map(|entry| {
+            let entry = entry?;
+            let modified = entry
+                .metadata()?
+                .modified()
+                .map_err(|error| WalkError::io(Some(entry.path().into()), entry.depth(), error))?;
+            Ok((modified, entry))
+        })
+        .collect::<Result<Vec<_>, WalkError>>()?;
+    // `sort_by_cached_key` is stable, so entries with the same modification time retain the order
+    // in which they were walked.
+    entries.sort_by_cached_key(|(modified, _)| f(*modified));
+    Ok(entries
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect::<Vec<_>>()
+        .into_iter())
+}
+
+// TODO: Implement this using combinators provided by the `filter` module and RPITIT once it lands
+//       in stable Rust. Remove any use of `WalkCancellation::unchecked`.
+/// Iterator combinator that filters file entries and controls the traversal of directory trees.
+///
+/// This combinator is returned by [`FileIterator::filter_entry`] and implements [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::filter_entry`]: crate::walk::FileIterator::filter_entry
+#[derive(Clone, Debug)]
+pub struct FilterEntry<I, F> {
+    input: I,
+    f: F,
+}
+
+impl<I, F> CancelWalk for FilterEntry<I, F>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, I, F> SeparatingFilter for FilterEntry<I, F>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+    F: FnMut(&dyn Entry) -> Option<EntryResidue>,
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        self.input
+            .feed()
+            .map(|separation| match separation.transpose_filtrate() {
+                Ok(separation) => separation
+                    .filter_tree_by_substituent(
+                        WalkCancellation::unchecked(&mut self.input),
+                        |substituent| (self.f)(substituent).map(From::from),
+                    )
+                    .map_filtrate(Ok),
+                Err(error) => error.map(Err).into(),
+            })
+    }
+}
+
+impl<T, R, I, F> Iterator for FilterEntry<I, F>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+    F: FnMut(&dyn Entry) -> Option<EntryResidue>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+// TODO: Implement this using combinators provided by the `filter` module and RPITIT once it lands
+//       in stable Rust. Remove any use of `WalkCancellation::unchecked`.
+/// Iterator combinator that filters and maps file entries and controls the traversal of directory
+/// trees.
+///
+/// This combinator is returned by [`FileIterator::filter_map_entry`] and implements
+/// [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::filter_map_entry`]: crate::walk::FileIterator::filter_map_entry
+#[derive(Clone, Debug)]
+pub struct FilterMapEntry<I, F> {
+    input: I,
+    f: F,
+}
+
+impl<I, F> CancelWalk for FilterMapEntry<I, F>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, U, I, F> SeparatingFilter for FilterMapEntry<I, F>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T> + From<MappedEntry<T, U>>,
+    I: FileIterator<Entry = T, Residue = R>,
+    F: FnMut(&dyn Entry) -> Result<Option<U>, EntryResidue>,
+{
+    type Feed = FileFeed<MappedEntry<T, U>, R>;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        self.input.feed().map(|separation| match separation {
+            Separation::Filtrate(filtrate) => match filtrate.transpose() {
+                Ok(filtrate) => match (self.f)(filtrate.get()) {
+                    Ok(Some(value)) => filtrate
+                        .map(|entry| Ok(MappedEntry { entry, value }))
+                        .into(),
+                    Ok(None) | Err(EntryResidue::File) => filtrate.filter_node().into(),
+                    Err(EntryResidue::Tree) => filtrate
+                        .filter_tree(WalkCancellation::unchecked(&mut self.input))
+                        .into(),
+                },
+                Err(error) => error.map(Err).into(),
+            },
+            // Residue cannot be mapped, but the function may still discard its tree.
+            Separation::Residue(residue) => match (self.f)(residue.get().get()) {
+                Err(EntryResidue::Tree) => residue
+                    .filter_tree(WalkCancellation::unchecked(&mut self.input))
+                    .into(),
+                _ => residue.into(),
+            },
+        })
+    }
+}
+
+impl<T, R, U, I, F> Iterator for FilterMapEntry<I, F>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T> + From<MappedEntry<T, U>>,
+    I: FileIterator<Entry = T, Residue = R>,
+    F: FnMut(&dyn Entry) -> Result<Option<U>, EntryResidue>,
+{
+    type Item = FileFiltrate<MappedEntry<T, U>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+/// Describes a file entry and a value mapped from it.
+///
+/// See [`FileIterator::filter_map_entry`].
+///
+/// [`FileIterator::filter_map_entry`]: crate::walk::FileIterator::filter_map_entry
+#[derive(Clone, Debug)]
+pub struct MappedEntry<E, T> {
+    entry: E,
+    value: T,
+}
+
+impl<E, T> MappedEntry<E, T> {
+    /// Gets the file entry.
+    pub fn entry(&self) -> &E {
+        &self.entry
+    }
+
+    /// Gets the value mapped from the file entry.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Converts the entry into the value mapped from the file entry.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Converts the entry into the file entry and the value mapped from it, in that order.
+    pub fn into_entry_and_value(self) -> (E, T) {
+        (self.entry, self.value)
+    }
+}
+
+impl<E, T> Entry for MappedEntry<E, T>
+where
+    E: Entry,
+{
+    fn into_path(self) -> PathBuf {
+        self.entry.into_path()
+    }
+
+    fn path(&self) -> &Path {
+        self.entry.path()
+    }
+
+    fn root_relative_paths(&self) -> (&Path, &Path) {
+        self.entry.root_relative_paths()
+    }
+
+    fn metadata(&self) -> Result<Metadata, WalkError> {
+        self.entry.metadata()
+    }
+
+    fn file_type(&self) -> FileType {
+        self.entry.file_type()
+    }
+
+    fn depth(&self) -> usize {
+        self.entry.depth()
+    }
+}
+
+impl<E, T> From<MappedEntry<E, T>> for TreeEntry
+where
+    TreeEntry: From<E>,
+{
+    fn from(entry: MappedEntry<E, T>) -> Self {
+        entry.entry.into()
+    }
+}
+
+// TODO: Implement this using combinators provided by the `filter` module and RPITIT once it lands
+//       in stable Rust. Remove any use of `WalkCancellation::unchecked`.
+/// Iterator combinator that filters file entries with paths that match patterns.
+///
+/// This combinator is returned by [`FileIterator::not`] and implements [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::not`]: crate::walk::FileIterator::not
+#[derive(Clone, Debug)]
+pub struct Not<I> {
+    input: I,
+    filter: FilterAny,
+    is_absolute: bool,
+}
+
+impl<I> Not<I> {
+    fn matching_some(self) -> Result<Self, BuildError> {
+        if self.filter.matches_all() {
+            Err(BuildErrorKind::NegationMatchesAll.into())
+        }
+        else {
+            Ok(self)
+        }
+    }
+}
+
+impl<I> CancelWalk for Not<I>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, I> SeparatingFilter for Not<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        self.input
+            .feed()
+            .map(|separation| match separation.transpose_filtrate() {
+                Ok(separation) => separation
+                    .filter_tree_by_substituent(
+                        WalkCancellation::unchecked(&mut self.input),
+                        |substituent| {
+                            if self.is_absolute {
+                                self.filter.absolute_residue(substituent)
+                            }
+                            else {
+                                self.filter.residue(substituent)
+                            }
+                            .map(From::from)
+                        },
+                    )
+                    .map_filtrate(Ok),
+                Err(error) => error.map(Err).into(),
+            })
+    }
+}
+
+impl<T, R, I> Iterator for Not<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+// TODO: Implement this using combinators provided by the `filter` module and RPITIT once it lands
+//       in stable Rust. Remove any use of `WalkCancellation::unchecked`.
+/// Iterator combinator that filters file entries that are deeper than a maximum depth.
+///
+/// This combinator is returned by [`FileIterator::max_depth`] and implements [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::max_depth`]: crate::walk::FileIterator::max_depth
+#[derive(Clone, Debug)]
+pub struct MaxDepth<I> {
+    input: I,
+    max: usize,
+}
+
+impl<I> CancelWalk for MaxDepth<I>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, I> SeparatingFilter for MaxDepth<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        let max = self.max;
+        self.input
+            .feed()
+            .map(|separation| match separation.transpose_filtrate() {
+                Ok(separation) => separation
+                    .filter_tree_by_substituent(
+                        WalkCancellation::unchecked(&mut self.input),
+                        |substituent| {
+                            (substituent.depth() > max)
+                                .then_some(EntryResidue::Tree)
+                                .map(From::from)
+                        },
+                    )
+                    .map_filtrate(Ok),
+                Err(error) => error.map(Err).into(),
+            })
+    }
+}
+
+impl<T, R, I> Iterator for MaxDepth<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+/// Iterator combinator that discards errors that occur when permission to read a file is denied.
+///
+/// This combinator is returned by [`FileIterator::skip_unreadable`] and implements
+/// [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::skip_unreadable`]: crate::walk::FileIterator::skip_unreadable
+#[derive(Clone, Debug)]
+pub struct SkipUnreadable<I> {
+    input: I,
+}
+
+impl<I> CancelWalk for SkipUnreadable<I>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, I> SeparatingFilter for SkipUnreadable<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        loop {
+            match self.input.feed()?.transpose_filtrate() {
+                Ok(separation) => return Some(separation.map_filtrate(Ok)),
+                Err(error) => {
+                    if !error.get().is_permission_denied() {
+                        return Some(error.map(Err).into());
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl<T, R, I> Iterator for SkipUnreadable<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+// TODO: Implement this using combinators provided by the `filter` module and RPITIT once it lands
+//       in stable Rust. Remove any use of `WalkCancellation::unchecked`.
+/// Iterator combinator that discards file entries that refer to files that have already been
+/// yielded.
+///
+/// This combinator is returned by [`FileIterator::unique_by_inode`] and implements
+/// [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::unique_by_inode`]: crate::walk::FileIterator::unique_by_inode
+#[derive(Clone, Debug)]
+pub struct UniqueByInode<I> {
+    input: I,
+    seen: HashSet<FileId>,
+}
+
+impl<I> CancelWalk for UniqueByInode<I>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, I> SeparatingFilter for UniqueByInode<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        let seen = &mut self.seen;
+        self.input
+            .feed()
+            .map(|separation| match separation.transpose_filtrate() {
+                Ok(separation) => {
+                    // Residue is only considered if it is a directory that may yet be read, so
+                    // files discarded by composing filters do not discard other paths to them.
+                    let is_observed = match separation {
+                        Separation::Filtrate(_) => true,
+                        Separation::Residue(ref residue) => match residue.get() {
+                            TreeResidue::Node(ref entry) => entry.file_type().is_dir(),
+                            TreeResidue::Tree(_) => false,
+                        },
+                    };
+                    separation
+                        .filter_tree_by_substituent(
+                            WalkCancellation::unchecked(&mut self.input),
+                            |substituent| {
+                                (is_observed
+                                    && FileId::of(substituent).is_some_and(|id| !seen.insert(id)))
+                                .then_some(EntryResidue::Tree)
+                                .map(From::from)
+                            },
+                        )
+                        .map_filtrate(Ok)
+                },
+                Err(error) => error.map(Err).into(),
+            })
+    }
+}
+
+impl<T, R, I> Iterator for UniqueByInode<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+/// Iterator combinator that calls a function with the path of each directory that is entered by a
+/// walk.
+///
+/// This combinator is returned by [`FileIterator::on_descend`] and implements [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::on_descend`]: crate::walk::FileIterator::on_descend
+#[derive(Clone, Debug)]
+pub struct OnDescend<I, F> {
+    input: I,
+    f: F,
+}
+
+impl<I, F> CancelWalk for OnDescend<I, F>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, I, F> SeparatingFilter for OnDescend<I, F>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+    F: FnMut(&Path),
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        let separation = self.input.feed()?;
+        let entry: Option<&dyn Entry> = match separation {
+            Separation::Filtrate(ref filtrate) => filtrate.get().as_ref().ok().map(|entry| {
+                let entry: &dyn Entry = entry;
+                entry
+            }),
+            Separation::Residue(ref residue) => match residue.get() {
+                TreeResidue::Node(ref entry) => Some(entry),
+                TreeResidue::Tree(_) => None,
+            },
+        };
+        if let Some(entry) = entry.filter(|entry| entry.file_type().is_dir()) {
+            (self.f)(entry.path());
+        }
+        Some(separation)
+    }
+}
+
+impl<T, R, I, F> Iterator for OnDescend<I, F>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+    F: FnMut(&Path),
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+/// Iterator combinator that calls a function with each file entry that is discarded by a walk.
+///
+/// This combinator is returned by [`FileIterator::inspect_residue`] and implements
+/// [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::inspect_residue`]: crate::walk::FileIterator::inspect_residue
+#[derive(Clone, Debug)]
+pub struct InspectResidue<I, F> {
+    input: I,
+    f: F,
+}
+
+impl<I, F> CancelWalk for InspectResidue<I, F>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, I, F> SeparatingFilter for InspectResidue<I, F>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+    F: FnMut(&dyn Entry, EntryResidue),
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        let separation = self.input.feed()?;
+        if let Separation::Residue(ref residue) = separation {
+            match residue.get() {
+                TreeResidue::Node(ref entry) => (self.f)(entry, EntryResidue::File),
+                TreeResidue::Tree(ref entry) => (self.f)(entry, EntryResidue::Tree),
+            }
+        }
+        Some(separation)
+    }
+}
+
+impl<T, R, I, F> Iterator for InspectResidue<I, F>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+    F: FnMut(&dyn Entry, EntryResidue),
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+/// Iterator combinator that stops a walk once a deadline has passed.
+///
+/// This combinator is returned by [`FileIterator::with_deadline`] and implements
+/// [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::with_deadline`]: crate::walk::FileIterator::with_deadline
+#[derive(Clone, Debug)]
+pub struct Deadline<I> {
+    /// The input iterator or `None` if the deadline has passed.
+    input: Option<I>,
+    deadline: Instant,
+}
+
+impl<I> CancelWalk for Deadline<I>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        if let Some(input) = self.input.as_mut() {
+            input.cancel_walk_tree()
+        }
+    }
+}
+
+impl<T, R, I> SeparatingFilter for Deadline<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        if Instant::now() >= self.deadline {
+            // Discard the input so that nothing more is read from the file system.
+            self.input.take().map(|_| {
+                Filtrate::new(Err(WalkError {
+                    depth: 0,
+                    kind: WalkErrorKind::Timeout,
+                }))
+                .into()
+            })
+        }
+        else {
+            self.input.as_mut()?.feed()
+        }
+    }
+}
+
+impl<T, R, I> Iterator for Deadline<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+/// Iterator combinator that stops a walk once a number of entries have been yielded.
+///
+/// This combinator is returned by [`FileIterator::limit`] and implements [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::limit`]: crate::walk::FileIterator::limit
+#[derive(Clone, Debug)]
+pub struct Limit<I> {
+    /// The input iterator or `None` if the limit has been reached.
+    input: Option<I>,
+    remaining: usize,
+}
+
+impl<I> CancelWalk for Limit<I>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        if let Some(input) = self.input.as_mut() {
+            input.cancel_walk_tree()
+        }
+    }
+}
+
+impl<T, R, I> SeparatingFilter for Limit<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        let input = self.input.as_mut()?;
+        let separation = input.feed()?;
+        if separation
+            .as_filtrate()
+            .is_some_and(|filtrate| filtrate.get().is_ok())
+        {
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                // Cancel the walk of the final entry and discard the input so that nothing more
+                // is read from the file system.
+                input.cancel_walk_tree();
+                self.input = None;
+            }
+        }
+        Some(separation)
+    }
+}
+
+impl<T, R, I> Iterator for Limit<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+/// Iterator combinator that counts the directories, entries, and errors of a walk.
+///
+/// This combinator is returned by [`FileIterator::with_stats`] and implements [`FileIterator`].
+///
+/// [`FileIterator`]: crate::walk::FileIterator
+/// [`FileIterator::with_stats`]: crate::walk::FileIterator::with_stats
+#[derive(Clone, Debug)]
+pub struct WithStats<I> {
+    input: I,
+    stats: Arc<WalkStats>,
+    /// Whether or not the most recent filtrate is a directory that may yet be cancelled.
+    is_dir: bool,
+}
+
+impl<I> CancelWalk for WithStats<I>
+where
+    I: CancelWalk,
+{
+    fn cancel_walk_tree(&mut self) {
+        if mem::take(&mut self.is_dir) {
+            self.stats.directories_read.fetch_sub(1, atomic::Ordering::Relaxed);
+            self.stats.directories_pruned.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+        self.input.cancel_walk_tree()
+    }
+}
+
+impl<T, R, I> SeparatingFilter for WithStats<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Feed = I::Feed;
+
+    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
+        let separation = self.input.feed()?;
+        let stats = &self.stats;
+        self.is_dir = false;
+        let counter = match separation {
+            Separation::Filtrate(ref filtrate) => match filtrate.get() {
+                Ok(ref entry) => {
+                    stats.entries_yielded.fetch_add(1, atomic::Ordering::Relaxed);
+                    self.is_dir = entry.file_type().is_dir();
+                    self.is_dir.then_some(&stats.directories_read)
+                },
+                Err(_) => Some(&stats.errors),
+            },
+            Separation::Residue(ref residue) => match residue.get() {
+                TreeResidue::Node(ref entry) => {
+                    entry.file_type().is_dir().then_some(&stats.directories_read)
+                },
+                TreeResidue::Tree(ref entry) => {
+                    entry.file_type().is_dir().then_some(&stats.directories_pruned)
+                },
+            },
+        };
+        if let Some(counter) = counter {
+            counter.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+        Some(separation)
+    }
+}
+
+impl<T, R, I> Iterator for WithStats<I>
+where
+    T: 'static + Entry,
+    R: 'static + Entry + From<T>,
+    I: FileIterator<Entry = T, Residue = R>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        filter::filtrate(self)
+    }
+}
+
+/// Statistics of a walk.
+///
+/// `WalkStats` is returned by [`FileIterator::with_stats`] and is updated as the walk is
+/// iterated. Counts are those observed by the combinator that it is shared with.
+///
+/// [`FileIterator::with_stats`]: crate::walk::FileIterator::with_stats
+#[derive(Debug, Default)]
+pub struct WalkStats {
+    directories_read: AtomicUsize,
+    directories_pruned: AtomicUsize,
+    entries_yielded: AtomicUsize,
+    errors: AtomicUsize,
+}
+
+impl WalkStats {
+    /// Gets the number of directories that have been walked and not pruned.
+    ///
+    /// Directories at the maximum depth of a walk are counted, though their contents are not
+    /// read.
+    pub fn directories_read(&self) -> usize {
+        self.directories_read.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Gets the number of directories that have been pruned, such that their contents are never
+    /// read.
+    pub fn directories_pruned(&self) -> usize {
+        self.directories_pruned.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Gets the number of entries that have been yielded, including directories.
+    pub fn entries_yielded(&self) -> usize {
+        self.entries_yielded.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Gets the number of errors that have been yielded.
+    pub fn errors(&self) -> usize {
+        self.errors.load(atomic::Ordering::Relaxed)
+    }
+}
+
+/// Iterator combinator that orders directory entries with respect to their contents.
+///
+/// This combinator is returned by [`FileIterator::ordering`].
+///
+/// [`FileIterator::ordering`]: crate::walk::FileIterator::ordering
+#[derive(Clone, Debug)]
+pub struct Ordered<I>
+where
+    I: FileIterator,
+{
+    input: I,
+    ordering: DirOrdering,
+    // Buffered directory entries in post-order. Each directory is an ancestor of the directories
+    // above it in this stack, because traversal is pre-order.
+    trees: Vec<I::Entry>,
+    pending: Option<I::Entry>,
+}
+
+impl<I> Iterator for Ordered<I>
+where
+    I: FileIterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let DirOrdering::PreOrder = self.ordering {
+            return self.input.next();
+        }
+        loop {
+            if let Some(entry) = self.pending.take() {
+                // An entry that is not a descendant of the most recently buffered directory
+                // follows its tree, so that tree has been completely traversed. Depth alone is not
+                // sufficient, because filters may discard intermediate directories, as in a walk
+                // of `{a,b/c/d}/**` that yields `a` followed by `b/c/d`.
+                if self
+                    .trees
+                    .last()
+                    .is_some_and(|tree| !entry.path().starts_with(tree.path()))
+                {
+                    self.pending = Some(entry);
+                    return self.trees.pop().map(Ok);
+                }
+                if entry.file_type().is_dir() {
+                    self.trees.push(entry);
+                    continue;
+                }
+                return Some(Ok(entry));
+            }
+            match self.input.next() {
+                Some(Ok(entry)) => self.pending = Some(entry),
+                Some(Err(error)) => return Some(Err(error)),
+                None => return self.trees.pop().map(Ok),
+            }
+        }
+    }
+}
+
+/// Iterator combinator that maps matched file entries to the text of a capture.
+///
+/// This combinator is returned by [`FileIterator::captures`].
+///
+/// [`FileIterator::captures`]: crate::walk::FileIterator::captures
+#[derive(Clone, Debug)]
+pub struct Captures<I> {
+    input: I,
+    index: usize,
+}
+
+impl<I> Iterator for Captures<I>
+where
+    I: FileIterator<Entry = GlobEntry>,
+{
+    type Item = Result<String, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.input.next()? {
+                Ok(entry) => {
+                    if let Some(text) = entry.matched().get(self.index) {
+                        return Some(Ok(text.into()));
+                    }
+                },
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Iterator combinator that groups consecutive file entries by their parent directory.
+///
+/// This combinator is returned by [`FileIterator::group_by_parent`].
+///
+/// [`FileIterator::group_by_parent`]: crate::walk::FileIterator::group_by_parent
+#[derive(Clone, Debug)]
+pub struct GroupByParent<I>
+where
+    I: FileIterator,
+{
+    // The input is discarded when it is exhausted, so that the final group is yielded only once.
+    input: Option<I>,
+    group: Option<(PathBuf, Vec<I::Entry>)>,
+}
+
+impl<I> Iterator for GroupByParent<I>
+where
+    I: FileIterator,
+{
+    type Item = Result<(PathBuf, Vec<I::Entry>), WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(input) = self.input.as_mut() {
+            match input.next() {
+                Some(Ok(entry)) => {
+                    let parent = entry.path().parent().unwrap_or_else(|| Path::new(""));
+                    match self.group {
+                        Some((ref path, ref mut entries)) if path == parent => {
+                            entries.push(entry);
+                        },
+                        _ => {
+                            let group = self.group.replace((parent.into(), vec![entry]));
+                            if group.is_some() {
+                                return group.map(Ok);
+                            }
+                        },
+                    }
+                },
+                Some(Err(error)) => return Some(Err(error)),
+                None => {
+                    self.input = None;
+                },
+            }
+        }
+        self.group.take().map(Ok)
+    }
+}
+
+/// Order in which directories are yielded with respect to their contents.
+///
+/// See [`FileIterator::ordering`].
+///
+/// # Defaults
+///
+/// The default ordering is [`PreOrder`], which is the order in which a walk reads directory trees.
+///
+/// [`FileIterator::ordering`]: crate::walk::FileIterator::ordering
+/// [`PreOrder`]: crate::walk::DirOrdering::PreOrder
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum DirOrdering {
+    /// Yield directories **before** their contents.
+    #[default]
+    PreOrder,
+    /// Yield directories **after** their contents.
+    ///
+    /// Directory entries are buffered until their contents have been yielded.
+    PostOrder,
+}
+
+/// Describes how file entries are read and discarded by [`FileIterator::filter_entry`].
+///
+/// [`FileIterator::filter_entry`]: crate::walk::FileIterator::filter_entry
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EntryResidue {
+    /// Discard the file.
+    ///
+    /// The entry for the given file is discarded. Only this particular file is ignored and if the
+    /// entry refers to a directory, then its tree is still read from the file system.
+    File,
+    /// Discard the file **and its directory tree**, if any.
+    ///
+    /// The entry for the given file is discarded. If the entry refers to a directory, then its
+    /// entire tree is ignored and is **not** read from the file system.
+    ///
+    /// If the entry refers to a normal file (not a directory), then this is the same as
+    /// [`EntryResidue::File`].
+    ///
+    /// [`EntryResidue::File`]: crate::walk::EntryResidue::File
+    Tree,
+}
+
+impl From<EntryResidue> for TreeResidue<()> {
+    fn from(residue: EntryResidue) -> Self {
+        match residue {
+            EntryResidue::File => TreeResidue::Node(()),
+            EntryResidue::Tree => TreeResidue::Tree(()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod harness {
+    use build_fs_tree::{Build, FileSystemTree};
+    use std::cell::RefCell;
+    use std::collections::{BTreeMap, BTreeSet, HashSet};
+    use std::env;
+    use std::io;
+    use std::ops::Deref;
+    use std::path::{Path, PathBuf};
+    use tempfile::{self, TempDir};
+
+    use crate::walk::{Entry, Fs, FsEntry, WalkError};
+
+    macro_rules! assert_set_eq {
+        ($left:expr, $right:expr $(,)?) => {{
+            match (&$left, &$right) {
+                (left, right) if !(*left == *right) => {
+                    let lrdiff: Vec<_> = left.difference(right).collect();
+                    let rldiff: Vec<_> = right.difference(left).collect();
+                    panic!(
+                        "assertion `left == right` failed\n\
+                         left: {:#?}\n\
+                         right: {:#?}\n\
+                         left - right: {:#?}\n\
+                         right - left: {:#?}",
+                        left, right, lrdiff, rldiff,
+                    )
+                },
+                _ => {},
+            }
+        }};
+    }
+    pub(crate) use assert_set_eq;
+
+    #[derive(Debug)]
+    pub struct TempTree {
+        _root: TempDir,
+        path: PathBuf,
+    }
+
+    impl TempTree {
+        pub fn join_all<'a, I>(&'a self, paths: I) -> impl 'a + Iterator<Item = PathBuf>
+        where
+            I: IntoIterator,
+            I::IntoIter: 'a,
+            I::Item: AsRef<Path>,
+        {
+            paths.into_iter().map(|path| self.join(path))
+        }
+    }
+
+    impl AsRef<Path> for TempTree {
+        fn as_ref(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Deref for TempTree {
+        type Target = Path;
+
+        fn deref(&self) -> &Self::Target {
+            &self.path
+        }
+    }
+
+    pub fn temptree<P, C>(path: impl AsRef<Path>, tree: FileSystemTree<P, C>) -> TempTree
+    where
+        P: AsRef<Path> + Ord,
+        C: AsRef<[u8]>,
+    {
+        temptree_in(env::temp_dir(), path, tree)
+    }
+
+    /// Writes a testing directory tree to a temporary location beneath the given directory.
+    pub fn temptree_in<P, C>(
+        dir: impl AsRef<Path>,
+        path: impl AsRef<Path>,
+        tree: FileSystemTree<P, C>,
+    ) -> TempTree
+    where
+        P: AsRef<Path> + Ord,
+        C: AsRef<[u8]>,
+    {
+        let root = tempfile::tempdir_in(dir).expect("failed to create temporary directory");
+        let path = root.path().join(path);
+        tree.build(&path)
+            .expect("failed to write tree in temporary directory");
+        TempTree { _root: root, path }
+    }
+
+    pub fn assert_walk_paths_eq<T, I>(
+        walk: impl Iterator<Item = Result<T, WalkError>>,
+        expected: I,
+    ) where
+        T: Entry,
+        I: IntoIterator,
+        I::Item: Into<PathBuf>,
+    {
+        let paths: HashSet<_> = walk
+            .map(|entry| entry.expect("failed to read file"))
+            .map(Entry::into_path)
+            .collect();
+        assert_set_eq!(paths, expected.into_iter().map(Into::into).collect());
+    }
+
+    /// An in-memory [`Fs`] that records the directories that it reads.
+    #[derive(Debug)]
+    pub struct VirtualTree {
+        path: PathBuf,
+        trees: BTreeMap<PathBuf, Vec<PathBuf>>,
+        unreadable: BTreeSet<PathBuf>,
+        reads: RefCell<Vec<PathBuf>>,
+    }
+
+    impl VirtualTree {
+        pub fn join_all<'a, I>(&'a self, paths: I) -> impl 'a + Iterator<Item = PathBuf>
+        where
+            I: IntoIterator,
+            I::IntoIter: 'a,
+            I::Item: AsRef<Path>,
+        {
+            paths.into_iter().map(|path| self.path.join(path))
+        }
+
+        pub fn reads(&self) -> HashSet<PathBuf> {
+            self.reads.borrow().iter().cloned().collect()
+        }
+
+        /// Denies permission to read the directories at the given relative paths.
+        pub fn with_unreadable_trees<'a>(
+            mut self,
+            paths: impl IntoIterator<Item = &'a str>,
+        ) -> Self {
+            let path = &self.path;
+            self.unreadable
+                .extend(paths.into_iter().map(|unreadable| path.join(unreadable)));
+            self
+        }
+    }
+
+    impl AsRef<Path> for VirtualTree {
+        fn as_ref(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Fs for VirtualTree {
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+            self.reads.borrow_mut().push(path.to_path_buf());
+            if self.unreadable.contains(path) {
+                return Err(io::ErrorKind::PermissionDenied.into());
+            }
+            self.trees
+                .get(path)
+                .cloned()
+                .ok_or_else(|| io::ErrorKind::NotFound.into())
+        }
+
+        fn is_dir(&self, path: &Path) -> io::Result<bool> {
+            Ok(self.trees.contains_key(path))
+        }
+    }
+
+    /// Constructs a [`VirtualTree`] at the given path from the relative paths of its files.
+    ///
+    /// Directories are not given explicitly and are instead the ancestors of these files.
+    pub fn virtual_tree<'a>(
+        path: impl AsRef<Path>,
+        files: impl IntoIterator<Item = &'a str>,
+    ) -> VirtualTree {
+        let path = path.as_ref().to_path_buf();
+        let mut trees: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        trees.insert(path.clone(), vec![]);
+        for file in files {
+            let file = path.join(file);
+            for (child, parent) in file.ancestors().zip(file.ancestors().skip(1)) {
+                let children = trees.entry(parent.to_path_buf()).or_default();
+                if !children.iter().any(|path| path == child) {
+                    children.push(child.to_path_buf());
+                }
+                if parent == path {
+                    break;
+                }
+            }
+        }
+        VirtualTree {
+            path,
+            trees,
+            unreadable: BTreeSet::new(),
+            reads: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn assert_walk_in_paths_eq<I>(
+        walk: impl Iterator<Item = Result<FsEntry, WalkError>>,
+        expected: I,
+    ) where
+        I: IntoIterator,
+        I::Item: Into<PathBuf>,
+    {
+        let paths: HashSet<_> = walk
+            .map(|entry| entry.expect("failed to read file"))
+            .map(FsEntry::into_path)
+            .collect();
+        assert_set_eq!(paths, expected.into_iter().map(Into::into).collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use build_fs_tree::{dir, file};
+    use itertools::{Either, Itertools as _};
+    use rstest::{fixture, rstest};
+    use std::collections::{HashMap, HashSet};
+    use std::env;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
+
+    use crate::filter::{HierarchicalIterator, Separation, TreeResidue};
+    use crate::query::TerminalKind;
+    use crate::walk::behavior::{DepthBehavior, DepthMax, DepthMin, DepthMinMax, LinkBehavior};
+    use crate::walk::harness::{self, assert_set_eq, TempTree, VirtualTree};
+    use crate::walk::{
+        DirOrdering, Entry, EntryResidue, FileIterator, PathExt, PathNormalization, StdFs,
+        WalkBehavior,
+    };
+    use crate::{BuildError, BuildErrorKind, GlobBuilder, Pattern};
+
+    const ALL: [&str; 11] = [
+        "",
+        "doc",
+        "doc/guide.md",
+        "src",
+        "src/glob.rs",
+        "src/lib.rs",
+        "tests",
+        "tests/harness",
+        "tests/harness/mod.rs",
+        "tests/walk.rs",
+        "README.md",
+    ];
 
-impl<I, F> CancelWalk for FilterEntry<I, F>
-where
-    I: CancelWalk,
-{
-    fn cancel_walk_tree(&mut self) {
-        self.input.cancel_walk_tree()
+    fn except<'t>(paths: impl IntoIterator<Item = &'t str>) -> impl Iterator<Item = &'t str> {
+        let paths: HashSet<_> = paths.into_iter().collect();
+        ALL.into_iter().filter(move |path| !paths.contains(path))
     }
-}
 
-impl<T, R, I, F> SeparatingFilter for FilterEntry<I, F>
-where
-    T: 'static + Entry,
-    R: 'static + Entry + From<T>,
-    I: FileIterator<Entry = T, Residue = R>,
-    F: FnMut(&dyn Entry) -> Option<EntryResidue>,
-{
-    type Feed = I::Feed;
+    // TODO: Rust's testing framework does not provide a mechanism for maintaining shared state nor
+    //       hooks for the start and end of testing. This means that tests that write to the file
+    //       system must do so for each test rather than writing before and after all tests have
+    //       run. The `once` attribute can be applied to fixtures to prevent this, but because
+    //       `rstest` has no way to know when testing has completed, such fixtures leak their
+    //       outputs and the temporary files are not removed. Is there a way to both share the
+    //       fixture and also drop its output?
+    //
+    //       See https://github.com/la10736/rstest/issues/209
+    /// Writes a testing directory tree to a temporary location on the file system.
+    #[fixture]
+    fn temptree() -> TempTree {
+        harness::temptree::<&str, &str>(
+            "project",
+            dir! {
+                "doc" => dir! {
+                    "guide.md" => file!(""),
+                },
+                "src" => dir! {
+                    "glob.rs" => file!(""),
+                    "lib.rs" => file!(""),
+                },
+                "tests" => dir! {
+                    "harness" => dir! {
+                        "mod.rs" => file!(""),
+                    },
+                    "walk.rs" => file!(""),
+                },
+                "README.md" => file!(""),
+            },
+        )
+    }
 
-    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
-        self.input
-            .feed()
-            .map(|separation| match separation.transpose_filtrate() {
-                Ok(separation) => separation
-                    .filter_tree_by_substituent(
-                        WalkCancellation::unchecked(&mut self.input),
-                        |substituent| (self.f)(substituent).map(From::from),
-                    )
-                    .map_filtrate(Ok),
-                Err(error) => error.map(Err).into(),
-            })
+    /// Constructs an in-memory testing directory tree that is the same as [`temptree`].
+    #[fixture]
+    fn virtual_tree() -> VirtualTree {
+        harness::virtual_tree(
+            "project",
+            [
+                "doc/guide.md",
+                "src/glob.rs",
+                "src/lib.rs",
+                "tests/harness/mod.rs",
+                "tests/walk.rs",
+                "README.md",
+            ],
+        )
+    }
+
+    /// Writes a testing directory tree that includes a re-entrant symbolic link to a temporary
+    /// location on the file system.
+    #[cfg(any(unix, windows))]
+    #[fixture]
+    fn temptree_with_cyclic_link() -> TempTree {
+        use std::io;
+        use std::path::Path;
+
+        #[cfg(unix)]
+        fn link(target: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+            std::os::unix::fs::symlink(target, link)
+        }
+
+        #[cfg(windows)]
+        fn link(target: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+            std::os::windows::fs::symlink_dir(target, link)
+        }
+
+        // Get a temporary tree and create a re-entrant symbolic link.
+        let temptree = temptree();
+        link(&temptree, temptree.join("tests/cycle"))
+            .expect("failed to write symbolic link in temporary tree");
+        temptree
+    }
+
+    /// Writes a testing directory tree that includes a symbolic link to a directory within the
+    /// tree to a temporary location on the file system.
+    #[cfg(unix)]
+    #[fixture]
+    fn temptree_with_dir_link() -> TempTree {
+        let temptree = temptree();
+        std::os::unix::fs::symlink(temptree.join("src"), temptree.join("doc/src"))
+            .expect("failed to write symbolic link in temporary tree");
+        temptree
+    }
+
+    /// Writes a testing directory tree that includes a file with a non-UTF-8 name to a temporary
+    /// location on the file system.
+    #[cfg(unix)]
+    #[fixture]
+    fn temptree_with_non_unicode_file() -> TempTree {
+        use std::ffi::OsStr;
+        use std::fs;
+        use std::os::unix::ffi::OsStrExt as _;
+
+        let temptree = temptree();
+        fs::write(temptree.join("src").join(OsStr::from_bytes(b"\xFF.rs")), "")
+            .expect("failed to write non-UTF-8 file in temporary tree");
+        temptree
+    }
+
+    #[rstest]
+    fn walk_path_includes_all_paths(temptree: TempTree) {
+        harness::assert_walk_paths_eq(temptree.walk(), temptree.join_all(ALL));
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(3)]
+    #[case(ALL.len())]
+    fn walk_path_count_remaining_eq(temptree: TempTree, #[case] n: usize) {
+        let mut walk = temptree.walk();
+        walk.by_ref().take(n).for_each(drop);
+        assert_eq!(walk.count_remaining(), ALL.len() - n);
+        assert_eq!(walk.count(), ALL.len() - n);
+    }
+
+    #[rstest]
+    #[case::subtree(
+        "tests/**",
+        [
+            "",
+            "doc",
+            "doc/guide.md",
+            "src",
+            "src/glob.rs",
+            "src/lib.rs",
+            "README.md",
+        ],
+    )]
+    #[case::extension_from_root("*.md", except(["README.md"]))]
+    #[case::extension_from_any_tree("**/*.md", except(["doc/guide.md", "README.md"]))]
+    #[case::subtree_at_any_depth(
+        "{**/,}harness/**",
+        except(["tests/harness", "tests/harness/mod.rs"]),
+    )]
+    #[case::any(
+        crate::any(["**/*.rs", "tests/**"]),
+        ["", "doc", "doc/guide.md", "src", "README.md"],
+    )]
+    // The root path ("") must **not** be present, because the empty `not` pattern matches the
+    // empty relative path at the root.
+    #[case::empty("", except([""]))]
+    fn walk_path_with_not_excludes_only_matching_paths<'t>(
+        temptree: TempTree,
+        #[case] pattern: impl Pattern<'t>,
+        #[case] expected: impl IntoIterator<Item = &'t str>,
+    ) {
+        harness::assert_walk_paths_eq(
+            temptree.walk().not(pattern).unwrap(),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[rstest]
+    #[case::max(DepthMax(1), ["", "doc", "src", "tests", "README.md"])]
+    #[case::min(
+        DepthMin::from_min_or_unbounded(2),
+        [
+            "doc/guide.md",
+            "src/glob.rs",
+            "src/lib.rs",
+            "tests/harness",
+            "tests/harness/mod.rs",
+            "tests/walk.rs",
+        ],
+    )]
+    #[case::minmax(
+        DepthMinMax::from_depths_or_max(2, 2),
+        ["doc/guide.md", "src/glob.rs", "src/lib.rs", "tests/harness", "tests/walk.rs"],
+    )]
+    fn walk_path_with_depth_behavior_includes_only_paths_at_depth<'t>(
+        temptree: TempTree,
+        #[case] depth: impl Into<DepthBehavior>,
+        #[case] expected: impl IntoIterator<Item = &'t str>,
+    ) {
+        harness::assert_walk_paths_eq(
+            temptree.walk_with_behavior(depth.into()),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[rstest]
+    #[case::tree("**", ALL)]
+    #[case::bounded_terminating_component("**/*.md", ["doc/guide.md", "README.md"])]
+    #[case::invariant_intermediate_component("**/src/**/*.rs", ["src/glob.rs", "src/lib.rs"])]
+    #[case::invariant("src/lib.rs", ["src/lib.rs"])]
+    fn walk_glob_includes_only_matching_paths<'t>(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: impl IntoIterator<Item = &'t str>,
+    ) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok(expression).walk(temptree.as_ref()),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[rstest]
+    #[case::tree("**", "tests/harness/mod.rs", 0)]
+    #[case::tree_and_component("**/*.rs", "tests/harness/mod.rs", 0)]
+    #[case::component_and_tree("tests/**/*.rs", "tests/harness/mod.rs", 1)]
+    #[case::components_and_tree("tests/*/**/*.rs", "tests/harness/mod.rs", 2)]
+    #[case::components("tests/*/*.rs", "tests/harness/mod.rs", 3)]
+    #[case::invariant("src/lib.rs", "src/lib.rs", 2)]
+    fn walk_glob_entry_matched_component_count_eq(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: usize,
+    ) {
+        let path = temptree.join(path);
+        let entry = crate::harness::assert_new_glob_is_ok(expression)
+            .walk(temptree.as_ref())
+            .filter_map(Result::ok)
+            .find(|entry| entry.path() == path)
+            .unwrap_or_else(|| panic!("no entry for path: `{}`", path.display()));
+        assert_eq!(entry.matched_component_count(), expected);
+    }
+
+    #[rstest]
+    #[case::complete("src/*.rs", 0, ["src/glob.rs", "src/lib.rs"])]
+    #[case::wildcard("src/*.rs", 1, ["glob", "lib"])]
+    #[case::alternation("{doc,src}/*.{md,rs}", 3, ["md", "rs", "rs"])]
+    #[case::out_of_range("src/*.rs", 2, [])]
+    fn walk_glob_captures_eq<'t>(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] index: usize,
+        #[case] expected: impl IntoIterator<Item = &'t str>,
+    ) {
+        let mut captures: Vec<_> = crate::harness::assert_new_glob_is_ok(expression)
+            .walk(temptree.as_ref())
+            .captures(index)
+            .map(Result::unwrap)
+            .collect();
+        captures.sort();
+        assert_eq!(captures, expected.into_iter().collect::<Vec<_>>());
+    }
+
+    #[rstest]
+    #[case::tree_and_extension(["src/**", "**/*.rs"], ["src/glob.rs", "src/lib.rs"])]
+    #[case::extension_and_tree(["**/*.rs", "tests/**"], ["tests/harness/mod.rs", "tests/walk.rs"])]
+    #[case::extension_from_root(["**/*.md", "*"], ["README.md"])]
+    #[case::disjoint(["doc/**", "src/**"], [])]
+    #[case::empty([], ALL)]
+    fn walk_all_includes_only_paths_matching_all_programs<'t, const N: usize>(
+        temptree: TempTree,
+        #[case] patterns: [&str; N],
+        #[case] expected: impl IntoIterator<Item = &'t str>,
+    ) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_all_is_ok(patterns).walk(temptree.as_ref()),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[rstest]
+    fn walk_all_cancels_walk_of_trees_rejected_by_any_program(temptree: TempTree) {
+        let mut filtered = vec![];
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_all_is_ok(["**/*.rs", "{doc,src}/**"])
+                .walk(temptree.as_ref())
+                .filter_entry(|entry| {
+                    filtered.push(entry.path().to_path_buf());
+                    None
+                }),
+            temptree.join_all(["src/glob.rs", "src/lib.rs"]),
+        );
+        assert!(filtered.contains(&temptree.join("doc/guide.md")));
+        assert!(!filtered.contains(&temptree.join("tests/walk.rs")));
+        assert!(!filtered.contains(&temptree.join("tests/harness/mod.rs")));
+    }
+
+    #[rstest]
+    #[case::extension("**/*.rs", "tests/**", ["src/glob.rs", "src/lib.rs"])]
+    #[case::tree("**", "{doc,tests}/**", ["", "src", "src/glob.rs", "src/lib.rs", "README.md"])]
+    #[case::file("src/*.rs", "src/lib.rs", ["src/glob.rs"])]
+    fn walk_glob_and_not_excludes_paths_matching_negation<'t, const N: usize>(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] negation: &str,
+        #[case] expected: [&'t str; N],
+    ) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok(expression)
+                .and_not(crate::harness::assert_new_glob_is_ok(negation))
+                .walk(temptree.as_ref()),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[rstest]
+    #[case::empty([], ALL.to_vec())]
+    #[case::extension(["**/*.md"], except(["doc/guide.md", "README.md"]).collect())]
+    #[case::many(
+        ["**/*.rs", "{doc,tests}/**"],
+        vec!["", "src", "README.md"],
+    )]
+    fn walk_path_with_not_globs_excludes_only_matching_paths<const N: usize>(
+        temptree: TempTree,
+        #[case] expressions: [&str; N],
+        #[case] expected: Vec<&str>,
+    ) {
+        harness::assert_walk_paths_eq(
+            temptree
+                .walk()
+                .not_globs(expressions.map(crate::harness::assert_new_glob_is_ok))
+                .unwrap(),
+            temptree.join_all(expected),
+        );
     }
-}
 
-impl<T, R, I, F> Iterator for FilterEntry<I, F>
-where
-    T: 'static + Entry,
-    R: 'static + Entry + From<T>,
-    I: FileIterator<Entry = T, Residue = R>,
-    F: FnMut(&dyn Entry) -> Option<EntryResidue>,
-{
-    type Item = I::Item;
+    #[rstest]
+    #[case("**")]
+    #[case("**/*")]
+    #[case("*/**")]
+    fn walk_path_with_not_matching_all_is_err(temptree: TempTree, #[case] expression: &str) {
+        assert!(temptree
+            .walk()
+            .not(expression)
+            .expect_err("negation matching all paths is `Ok`")
+            .is_negation_matches_all());
+        assert!(matches!(
+            temptree.walk().not(expression),
+            Err(BuildError {
+                kind: BuildErrorKind::NegationMatchesAll,
+            }),
+        ));
+        assert!(matches!(
+            temptree
+                .walk()
+                .not_globs([crate::harness::assert_new_glob_is_ok(expression)]),
+            Err(BuildError {
+                kind: BuildErrorKind::NegationMatchesAll,
+            }),
+        ));
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        filter::filtrate(self)
+    #[rstest]
+    #[case("**/a/**")]
+    #[case("a/**")]
+    #[case("a*/**")]
+    fn walk_path_with_not_matching_some_is_ok(temptree: TempTree, #[case] expression: &str) {
+        assert!(temptree.walk().not(expression).is_ok());
     }
-}
 
-// TODO: Implement this using combinators provided by the `filter` module and RPITIT once it lands
-//       in stable Rust. Remove any use of `WalkCancellation::unchecked`.
-/// Iterator combinator that filters file entries with paths that match patterns.
-///
-/// This combinator is returned by [`FileIterator::not`] and implements [`FileIterator`].
-///
-/// [`FileIterator`]: crate::walk::FileIterator
-/// [`FileIterator::not`]: crate::walk::FileIterator::not
-#[derive(Clone, Debug)]
-pub struct Not<I> {
-    input: I,
-    filter: FilterAny,
-}
+    #[rstest]
+    fn walk_path_with_not_allowing_all_matching_all_is_empty(temptree: TempTree) {
+        harness::assert_walk_paths_eq(
+            temptree.walk().not_allowing_all("**").unwrap(),
+            Vec::<PathBuf>::new(),
+        );
+    }
 
-impl<I> CancelWalk for Not<I>
-where
-    I: CancelWalk,
-{
-    fn cancel_walk_tree(&mut self) {
-        self.input.cancel_walk_tree()
+    #[rstest]
+    #[case::tree(
+        "**/*.rs",
+        ["src/glob.rs", "src/lib.rs", "tests/harness/mod.rs", "tests/walk.rs"],
+    )]
+    #[case::component("*.rs", ["src/glob.rs", "src/lib.rs", "tests/walk.rs"])]
+    #[case::empty("", ["src", "tests"])]
+    fn walk_glob_many_includes_matching_paths_in_each_tree<'t, const N: usize>(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: [&'t str; N],
+    ) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok(expression).walk_many(
+                temptree.join_all(["src", "tests"]),
+                WalkBehavior::default(),
+            ),
+            temptree.join_all(expected),
+        );
     }
-}
 
-impl<T, R, I> SeparatingFilter for Not<I>
-where
-    T: 'static + Entry,
-    R: 'static + Entry + From<T>,
-    I: FileIterator<Entry = T, Residue = R>,
-{
-    type Feed = I::Feed;
+    #[rstest]
+    fn walk_glob_many_with_not_excludes_matching_paths_in_each_tree(temptree: TempTree) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**")
+                .walk_many(temptree.join_all(["src", "tests"]), WalkBehavior::default())
+                .not("{glob.rs,harness/**}")
+                .unwrap(),
+            temptree.join_all(["src", "src/lib.rs", "tests", "tests/walk.rs"]),
+        );
+    }
 
-    fn feed(&mut self) -> Option<Separation<Self::Feed>> {
-        self.input
-            .feed()
-            .map(|separation| match separation.transpose_filtrate() {
-                Ok(separation) => separation
-                    .filter_tree_by_substituent(
-                        WalkCancellation::unchecked(&mut self.input),
-                        |substituent| self.filter.residue(substituent).map(From::from),
-                    )
-                    .map_filtrate(Ok),
-                Err(error) => error.map(Err).into(),
-            })
+    #[cfg(unix)]
+    #[rstest]
+    fn walk_glob_with_not_absolute_cancels_walk_of_trees_matching_absolute_negation(
+        temptree: TempTree,
+    ) {
+        let root = temptree.to_str().expect("temporary path is not UTF-8");
+        let mut filtered = vec![];
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**/*.{md,rs}")
+                .walk(temptree.as_ref())
+                .not_absolute(format!("{}/tests/**", crate::escape(root)).as_str())
+                .unwrap()
+                .filter_entry(|entry| {
+                    filtered.push(entry.path().to_path_buf());
+                    None
+                }),
+            temptree.join_all(["doc/guide.md", "src/glob.rs", "src/lib.rs", "README.md"]),
+        );
+        assert!(!filtered.contains(&temptree.join("tests/walk.rs")));
+        assert!(!filtered.contains(&temptree.join("tests/harness/mod.rs")));
     }
-}
 
-impl<T, R, I> Iterator for Not<I>
-where
-    T: 'static + Entry,
-    R: 'static + Entry + From<T>,
-    I: FileIterator<Entry = T, Residue = R>,
-{
-    type Item = I::Item;
+    #[rstest]
+    fn walk_glob_with_not_absolute_does_not_match_root_relative_paths(temptree: TempTree) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**/*.rs")
+                .walk(temptree.as_ref())
+                .not_absolute("tests/**")
+                .unwrap(),
+            temptree.join_all([
+                "src/glob.rs",
+                "src/lib.rs",
+                "tests/harness/mod.rs",
+                "tests/walk.rs",
+            ]),
+        );
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        filter::filtrate(self)
+    #[rstest]
+    fn walk_glob_with_not_globs_cancels_walk_of_trees_matching_exhaustive_alternative(
+        temptree: TempTree,
+    ) {
+        let mut filtered = vec![];
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**/*.{md,rs}")
+                .walk(temptree.as_ref())
+                .not_globs([
+                    crate::harness::assert_new_glob_is_ok("**/guide.md"),
+                    // Only one alternative of this glob is exhaustive.
+                    crate::harness::assert_new_glob_is_ok("{tests/**,README.md}"),
+                ])
+                .unwrap()
+                .filter_entry(|entry| {
+                    filtered.push(entry.path().to_path_buf());
+                    None
+                }),
+            temptree.join_all(["src/glob.rs", "src/lib.rs"]),
+        );
+        assert!(!filtered.contains(&temptree.join("tests/walk.rs")));
+        assert!(!filtered.contains(&temptree.join("tests/harness/mod.rs")));
     }
-}
 
-/// Describes how file entries are read and discarded by [`FileIterator::filter_entry`].
-///
-/// [`FileIterator::filter_entry`]: crate::walk::FileIterator::filter_entry
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub enum EntryResidue {
-    /// Discard the file.
-    ///
-    /// The entry for the given file is discarded. Only this particular file is ignored and if the
-    /// entry refers to a directory, then its tree is still read from the file system.
-    File,
-    /// Discard the file **and its directory tree**, if any.
-    ///
-    /// The entry for the given file is discarded. If the entry refers to a directory, then its
-    /// entire tree is ignored and is **not** read from the file system.
-    ///
-    /// If the entry refers to a normal file (not a directory), then this is the same as
-    /// [`EntryResidue::File`].
-    ///
-    /// [`EntryResidue::File`]: crate::walk::EntryResidue::File
-    Tree,
-}
+    #[rstest]
+    fn walk_glob_and_not_cancels_walk_of_trees_matching_exhaustive_negation(temptree: TempTree) {
+        let mut filtered = vec![];
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**/*.rs")
+                .and_not(crate::harness::assert_new_glob_is_ok("tests/**"))
+                .walk(temptree.as_ref())
+                .filter_entry(|entry| {
+                    filtered.push(entry.path().to_path_buf());
+                    None
+                }),
+            temptree.join_all(["src/glob.rs", "src/lib.rs"]),
+        );
+        assert!(!filtered.contains(&temptree.join("tests/walk.rs")));
+        assert!(!filtered.contains(&temptree.join("tests/harness/mod.rs")));
+    }
 
-impl From<EntryResidue> for TreeResidue<()> {
-    fn from(residue: EntryResidue) -> Self {
-        match residue {
-            EntryResidue::File => TreeResidue::Node(()),
-            EntryResidue::Tree => TreeResidue::Tree(()),
-        }
+    // Case-sensitive literals are variant on Windows, so the walk reads the directory and matches
+    // its entries rather than resolving the literal via case-insensitive file system APIs.
+    #[cfg(windows)]
+    #[rstest]
+    #[case("(?-i)README.md", ["README.md"])]
+    #[case("(?-i)readme.md", [])]
+    #[case("(?-i)Readme.(?i)MD", [])]
+    #[case("(?-i)README.(?i)MD", ["README.md"])]
+    fn walk_glob_with_case_sensitive_flag_includes_only_matching_paths<'t>(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: impl IntoIterator<Item = &'t str>,
+    ) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok(expression).walk(temptree.as_ref()),
+            temptree.join_all(expected),
+        );
     }
-}
 
-#[cfg(test)]
-pub mod harness {
-    use build_fs_tree::{Build, FileSystemTree};
-    use std::collections::HashSet;
-    use std::ops::Deref;
-    use std::path::{Path, PathBuf};
-    use tempfile::{self, TempDir};
+    #[cfg(unix)]
+    #[rstest]
+    fn walk_glob_with_non_unicode_file_includes_lossy_matching_paths(
+        #[from(temptree_with_non_unicode_file)] temptree: TempTree,
+    ) {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt as _;
 
-    use crate::walk::{Entry, FileIterator};
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("src/?.rs").walk(temptree.as_ref()),
+            [temptree.join("src").join(OsStr::from_bytes(b"\xFF.rs"))],
+        );
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("src/[!gl]*.rs").walk(temptree.as_ref()),
+            [temptree.join("src").join(OsStr::from_bytes(b"\xFF.rs"))],
+        );
+    }
 
-    macro_rules! assert_set_eq {
-        ($left:expr, $right:expr $(,)?) => {{
-            match (&$left, &$right) {
-                (left, right) if !(*left == *right) => {
-                    let lrdiff: Vec<_> = left.difference(right).collect();
-                    let rldiff: Vec<_> = right.difference(left).collect();
-                    panic!(
-                        "assertion `left == right` failed\n\
-                         left: {:#?}\n\
-                         right: {:#?}\n\
-                         left - right: {:#?}\n\
-                         right - left: {:#?}",
-                        left, right, lrdiff, rldiff,
-                    )
-                },
-                _ => {},
-            }
-        }};
+    #[rstest]
+    #[case::tree("**", ALL)]
+    #[case::bounded_terminating_component("**/*.md", ["doc/guide.md", "README.md"])]
+    #[case::invariant_intermediate_component("**/src/**/*.rs", ["src/glob.rs", "src/lib.rs"])]
+    #[case::invariant("src/lib.rs", ["src/lib.rs"])]
+    fn walk_in_glob_includes_only_matching_paths<'t>(
+        virtual_tree: VirtualTree,
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: impl Clone + IntoIterator<Item = &'t str>,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        harness::assert_walk_in_paths_eq(
+            glob.walk_in(&virtual_tree, virtual_tree.as_ref(), WalkBehavior::default()),
+            virtual_tree.join_all(expected.clone()),
+        );
+        harness::assert_walk_in_paths_eq(
+            glob.walk_in(&StdFs, temptree.as_ref(), WalkBehavior::default()),
+            temptree.join_all(expected),
+        );
     }
-    pub(crate) use assert_set_eq;
 
-    #[derive(Debug)]
-    pub struct TempTree {
-        _root: TempDir,
-        path: PathBuf,
+    #[rstest]
+    #[case::non_zero_max(DepthMax(1), "**", ["", "doc", "src", "tests", "README.md"])]
+    #[case::zero_max(DepthMax(0), "**", [""])]
+    #[case::prefixed_minmax(
+        DepthMinMax::from_depths_or_max(2, 2),
+        "tests/**",
+        ["tests/harness", "tests/walk.rs"],
+    )]
+    fn walk_in_glob_with_depth_behavior_includes_only_paths_at_depth<'t>(
+        virtual_tree: VirtualTree,
+        #[case] depth: impl Into<DepthBehavior>,
+        #[case] expression: &str,
+        #[case] expected: impl IntoIterator<Item = &'t str>,
+    ) {
+        harness::assert_walk_in_paths_eq(
+            crate::harness::assert_new_glob_is_ok(expression).walk_in(
+                &virtual_tree,
+                virtual_tree.as_ref(),
+                depth.into(),
+            ),
+            virtual_tree.join_all(expected),
+        );
     }
 
-    impl TempTree {
-        pub fn join_all<'a, I>(&'a self, paths: I) -> impl 'a + Iterator<Item = PathBuf>
-        where
-            I: IntoIterator,
-            I::IntoIter: 'a,
-            I::Item: AsRef<Path>,
-        {
-            paths.into_iter().map(|path| self.join(path))
-        }
+    #[rstest]
+    #[case::max(
+        "<*/:0,1>*",
+        [
+            "",
+            "doc",
+            "doc/guide.md",
+            "src",
+            "src/glob.rs",
+            "src/lib.rs",
+            "tests",
+            "tests/harness",
+            "tests/walk.rs",
+            "README.md",
+        ],
+        ["", "doc", "src", "tests"],
+    )]
+    #[case::minmax(
+        "<*/:1,1>*",
+        ["doc/guide.md", "src/glob.rs", "src/lib.rs", "tests/harness", "tests/walk.rs"],
+        ["", "doc", "src", "tests"],
+    )]
+    #[case::minmax_terminating_component(
+        "<*/:1,2>*.rs",
+        ["src/glob.rs", "src/lib.rs", "tests/harness/mod.rs", "tests/walk.rs"],
+        ["", "doc", "src", "tests", "tests/harness"],
+    )]
+    #[case::prefixed_minmax(
+        "tests/<*/:1,1>*",
+        ["tests/harness/mod.rs"],
+        ["tests", "tests/harness"],
+    )]
+    fn walk_in_glob_with_component_repetition_includes_only_paths_at_depth<'t>(
+        virtual_tree: VirtualTree,
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: impl Clone + IntoIterator<Item = &'t str>,
+        #[case] reads: impl IntoIterator<Item = &'t str>,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        harness::assert_walk_in_paths_eq(
+            glob.walk_in(&virtual_tree, virtual_tree.as_ref(), WalkBehavior::default()),
+            virtual_tree.join_all(expected.clone()),
+        );
+        // Directories at the maximum depth of the repetition are not read.
+        assert_set_eq!(virtual_tree.reads(), virtual_tree.join_all(reads).collect());
+        harness::assert_walk_paths_eq(glob.walk(temptree.as_ref()), temptree.join_all(expected));
+    }
+
+    // Globs that only match directories only match paths that terminate with a separator, which
+    // paths yielded by walks never do.
+    #[rstest]
+    fn walk_glob_with_directory_terminal_kind_includes_no_paths(temptree: TempTree) {
+        let glob = crate::harness::assert_new_glob_is_ok("tests/*/");
+        assert_eq!(glob.terminal_kind(), TerminalKind::Directory);
+        harness::assert_walk_paths_eq(glob.walk(temptree.as_ref()), Vec::<PathBuf>::new());
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("tests/*")
+                .walk(temptree.as_ref())
+                .filter_entry(|entry| (!entry.file_type().is_dir()).then_some(EntryResidue::File)),
+            temptree.join_all(["tests/harness"]),
+        );
+    }
+
+    #[rstest]
+    #[case::prefixed("tests/*.rs", ["tests/walk.rs"])]
+    #[case::nested_prefixed("tests/harness/*.rs", ["tests/harness/mod.rs"])]
+    #[case::prefixed_minmax("tests/<*/:1,1>*.rs", ["tests/harness/mod.rs"])]
+    fn walk_glob_with_invariant_prefix_and_bounded_depth_includes_paths(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: impl IntoIterator<Item = &'static str>,
+    ) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok(expression).walk(temptree.as_ref()),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[cfg(unix)]
+    #[rstest]
+    #[case::rooted("tests/*.rs", ["tests/walk.rs"])]
+    #[case::rooted_minmax("tests/<*/:1,1>*.rs", ["tests/harness/mod.rs"])]
+    #[case::rooted_invariant("tests/walk.rs", ["tests/walk.rs"])]
+    #[case::rooted_tree("tests/**/*.rs", ["tests/harness/mod.rs", "tests/walk.rs"])]
+    fn walk_rooted_glob_with_bounded_depth_includes_paths(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: impl IntoIterator<Item = &'static str>,
+    ) {
+        let root = temptree.to_str().expect("temporary path is not UTF-8");
+        let expression = format!("{}/{}", crate::escape(root), expression);
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok(expression.as_str()).walk("/"),
+            temptree.join_all(expected),
+        );
     }
 
-    impl AsRef<Path> for TempTree {
-        fn as_ref(&self) -> &Path {
-            &self.path
-        }
+    #[rstest]
+    fn walk_in_glob_does_not_read_discarded_trees(virtual_tree: VirtualTree) {
+        harness::assert_walk_in_paths_eq(
+            crate::harness::assert_new_glob_is_ok("{doc,src}/*").walk_in(
+                &virtual_tree,
+                virtual_tree.as_ref(),
+                WalkBehavior::default(),
+            ),
+            virtual_tree.join_all(["doc/guide.md", "src/glob.rs", "src/lib.rs"]),
+        );
+        assert_set_eq!(
+            virtual_tree.reads(),
+            virtual_tree.join_all(["", "doc", "src"]).collect(),
+        );
     }
 
-    impl Deref for TempTree {
-        type Target = Path;
-
-        fn deref(&self) -> &Self::Target {
-            &self.path
-        }
+    #[rstest]
+    fn walk_in_glob_with_not_and_filter_entry_does_not_read_discarded_trees(
+        virtual_tree: VirtualTree,
+    ) {
+        harness::assert_walk_in_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**")
+                .walk_in(
+                    &virtual_tree,
+                    virtual_tree.as_ref(),
+                    WalkBehavior::default(),
+                )
+                .not("tests/**")
+                .expect("failed to build negation")
+                .filter_entry(|entry| entry.path().ends_with("doc").then_some(EntryResidue::Tree)),
+            virtual_tree.join_all(["", "src", "src/glob.rs", "src/lib.rs", "README.md"]),
+        );
+        assert_set_eq!(virtual_tree.reads(), virtual_tree.join_all(["", "src"]).collect());
     }
 
-    pub fn temptree<P, C>(path: impl AsRef<Path>, tree: FileSystemTree<P, C>) -> TempTree
-    where
-        P: AsRef<Path> + Ord,
-        C: AsRef<[u8]>,
-    {
-        let root = tempfile::tempdir().expect("failed to create temporary directory");
-        let path = root.path().join(path);
-        tree.build(&path)
-            .expect("failed to write tree in temporary directory");
-        TempTree { _root: root, path }
+    #[test]
+    fn walk_in_glob_with_strict_components_yields_unexpected_component_errors() {
+        let virtual_tree = harness::virtual_tree("project", ["src/lib.rs", "src/../README.md"]);
+        let walk = |strict_components| {
+            crate::harness::assert_new_glob_is_ok("**/*.{md,rs}").walk_in(
+                &virtual_tree,
+                virtual_tree.as_ref(),
+                WalkBehavior {
+                    strict_components,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // Components that are not normal are skipped by default.
+        harness::assert_walk_in_paths_eq(
+            walk(false),
+            virtual_tree.join_all(["src/lib.rs", "src/../README.md"]),
+        );
+
+        let (paths, errors): (HashSet<_>, HashSet<_>) =
+            walk(true).partition_map(|entry| match entry {
+                Ok(entry) => Either::Left(entry.into_path()),
+                Err(error) => {
+                    assert!(error.is_unexpected_component());
+                    Either::Right(error.path().unwrap().to_path_buf())
+                },
+            });
+        assert_set_eq!(paths, virtual_tree.join_all(["src/lib.rs"]).collect());
+        assert_set_eq!(
+            errors,
+            virtual_tree.join_all(["src/..", "src/../README.md"]).collect(),
+        );
     }
 
-    pub fn assert_walk_paths_eq<I>(walk: impl FileIterator, expected: I)
-    where
-        I: IntoIterator,
-        I::Item: Into<PathBuf>,
-    {
-        let paths: HashSet<_> = walk
-            .map(|entry| entry.expect("failed to read file"))
+    #[rstest]
+    fn walk_glob_sorted_by_mtime_yields_entries_in_order(temptree: TempTree) {
+        use std::fs::File;
+        use std::time::{Duration, SystemTime};
+
+        let paths = ["tests/walk.rs", "src/lib.rs", "tests/harness/mod.rs", "src/glob.rs"];
+        for (n, path) in (0u64..).zip(paths) {
+            File::options()
+                .write(true)
+                .open(temptree.join(path))
+                .and_then(|file| file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(n)))
+                .expect("failed to write modification time");
+        }
+        let glob = crate::harness::assert_new_glob_is_ok("**/*.rs");
+        let sorted: Vec<_> = glob
+            .walk(temptree.as_ref())
+            .sorted_by_mtime()
+            .expect("failed to read file")
             .map(Entry::into_path)
             .collect();
-        assert_set_eq!(paths, expected.into_iter().map(Into::into).collect());
+        assert_eq!(sorted, temptree.join_all(paths).collect::<Vec<_>>());
+        let sorted: Vec<_> = glob
+            .walk(temptree.as_ref())
+            .sorted_by_mtime()
+            .expect("failed to read file")
+            .rev()
+            .map(Entry::into_path)
+            .collect();
+        assert_eq!(sorted, temptree.join_all(paths.into_iter().rev()).collect::<Vec<_>>());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use build_fs_tree::{dir, file};
-    use rstest::{fixture, rstest};
-    use std::collections::HashSet;
+    #[rstest]
+    fn walk_glob_sorted_by_mtime_descending_yields_entries_in_order(temptree: TempTree) {
+        use std::fs::File;
+        use std::time::{Duration, SystemTime};
 
-    use crate::filter::{HierarchicalIterator, Separation, TreeResidue};
-    use crate::walk::behavior::{DepthBehavior, DepthMax, DepthMin, DepthMinMax, LinkBehavior};
-    use crate::walk::harness::{self, assert_set_eq, TempTree};
-    use crate::walk::{Entry, FileIterator, PathExt};
-    use crate::Pattern;
+        let paths = ["src/glob.rs", "src/lib.rs", "tests/walk.rs"];
+        for (n, path) in [0u64, 1, 1].into_iter().zip(paths) {
+            File::options()
+                .write(true)
+                .open(temptree.join(path))
+                .and_then(|file| file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(n)))
+                .expect("failed to write modification time");
+        }
+        let glob = crate::harness::assert_new_glob_is_ok("{src,tests}/*.rs");
+        let walked: Vec<_> = glob
+            .walk(temptree.as_ref())
+            .map(|entry| entry.expect("failed to read file").into_path())
+            .filter(|path| path != &temptree.join(paths[0]))
+            .collect();
+        let sorted: Vec<_> = glob
+            .walk(temptree.as_ref())
+            .sorted_by_mtime_descending()
+            .expect("failed to read file")
+            .map(Entry::into_path)
+            .collect();
+        // Entries with the same modification time are yielded in the order in which they were
+        // walked.
+        assert_eq!(sorted[..2], walked[..]);
+        assert_eq!(sorted[2], temptree.join(paths[0]));
+    }
 
-    const ALL: [&str; 11] = [
-        "",
-        "doc",
-        "doc/guide.md",
-        "src",
-        "src/glob.rs",
-        "src/lib.rs",
-        "tests",
-        "tests/harness",
-        "tests/harness/mod.rs",
-        "tests/walk.rs",
-        "README.md",
-    ];
+    #[rstest]
+    fn walk_path_with_stats_counts_pruned_directories(temptree: TempTree) {
+        let (walk, stats) = temptree.walk().not("tests/**").unwrap().with_stats();
+        assert_eq!(walk.count(), 7);
+        assert_eq!(stats.directories_read(), 3);
+        assert_eq!(stats.directories_pruned(), 1);
+        assert_eq!(stats.entries_yielded(), 7);
+        assert_eq!(stats.errors(), 0);
 
-    fn except<'t>(paths: impl IntoIterator<Item = &'t str>) -> impl Iterator<Item = &'t str> {
-        let paths: HashSet<_> = paths.into_iter().collect();
-        ALL.into_iter().filter(move |path| !paths.contains(path))
+        // Directory trees that are pruned by later combinators are also counted, but the pruned
+        // directory has already been yielded by the combinator.
+        let (walk, stats) = temptree.walk().with_stats();
+        assert_eq!(walk.not("tests/**").unwrap().count(), 7);
+        assert_eq!(stats.directories_read(), 3);
+        assert_eq!(stats.directories_pruned(), 1);
+        assert_eq!(stats.entries_yielded(), 8);
     }
 
-    // TODO: Rust's testing framework does not provide a mechanism for maintaining shared state nor
-    //       hooks for the start and end of testing. This means that tests that write to the file
-    //       system must do so for each test rather than writing before and after all tests have
-    //       run. The `once` attribute can be applied to fixtures to prevent this, but because
-    //       `rstest` has no way to know when testing has completed, such fixtures leak their
-    //       outputs and the temporary files are not removed. Is there a way to both share the
-    //       fixture and also drop its output?
-    //
-    //       See https://github.com/la10736/rstest/issues/209
-    /// Writes a testing directory tree to a temporary location on the file system.
-    #[fixture]
-    fn temptree() -> TempTree {
-        harness::temptree::<&str, &str>(
+    #[rstest]
+    #[case("**/*.rs", ["src/lib.rs"], 2)]
+    #[case("src/**", ["src", "src/lib.rs"], 1)]
+    #[case("**/.*", [".git", "src/.cache"], 0)]
+    #[case("**/.git/**", [".git", ".git/config"], 0)]
+    fn walk_glob_with_tree_excluding_hidden_prunes_hidden_directories<const N: usize>(
+        #[case] expression: &str,
+        #[case] expected: [&str; N],
+        #[case] pruned: usize,
+    ) {
+        let temptree = harness::temptree::<&str, &str>(
             "project",
             dir! {
-                "doc" => dir! {
-                    "guide.md" => file!(""),
+                ".git" => dir! {
+                    "config" => file!(""),
                 },
                 "src" => dir! {
-                    "glob.rs" => file!(""),
-                    "lib.rs" => file!(""),
-                },
-                "tests" => dir! {
-                    "harness" => dir! {
-                        "mod.rs" => file!(""),
+                    ".cache" => dir! {
+                        "lib.rs" => file!(""),
                     },
-                    "walk.rs" => file!(""),
+                    "lib.rs" => file!(""),
                 },
-                "README.md" => file!(""),
             },
-        )
+        );
+        let glob = crate::harness::assert_glob_builder_is_ok(
+            GlobBuilder::new().tree_matches_hidden(false),
+            expression,
+        );
+        let mut descended = vec![];
+        let (walk, stats) = glob
+            .walk(temptree.as_ref())
+            .on_descend(|path| descended.push(path.to_path_buf()))
+            .with_stats();
+        assert_set_eq!(
+            walk.map(|entry| entry.expect("failed to read file").into_path())
+                .collect::<HashSet<_>>(),
+            temptree.join_all(expected).collect(),
+        );
+        assert_eq!(stats.directories_pruned(), pruned);
+        // The contents of pruned hidden directories are never read.
+        if pruned > 0 {
+            assert!(!descended.contains(&temptree.join("src/.cache")));
+        }
+    }
+
+    #[rstest]
+    fn walk_glob_group_by_parent_includes_entries_grouped_by_parent(temptree: TempTree) {
+        let groups: Vec<_> = crate::harness::assert_new_glob_is_ok("**/*.rs")
+            .walk(temptree.as_ref())
+            .group_by_parent()
+            .map(|group| {
+                let (parent, entries) = group.expect("failed to read file");
+                let paths: HashSet<_> = entries.into_iter().map(Entry::into_path).collect();
+                (parent, paths)
+            })
+            .collect();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            groups.into_iter().collect::<HashMap<_, _>>(),
+            [
+                (temptree.join("src"), temptree.join_all(["src/glob.rs", "src/lib.rs"]).collect()),
+                (temptree.join("tests"), temptree.join_all(["tests/walk.rs"]).collect()),
+                (
+                    temptree.join("tests/harness"),
+                    temptree.join_all(["tests/harness/mod.rs"]).collect(),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
     }
 
-    /// Writes a testing directory tree that includes a re-entrant symbolic link to a temporary
-    /// location on the file system.
     #[cfg(any(unix, windows))]
-    #[fixture]
-    fn temptree_with_cyclic_link() -> TempTree {
+    #[rstest]
+    fn walk_glob_partition_results_includes_entries_and_errors(
+        #[from(temptree_with_cyclic_link)] temptree: TempTree,
+    ) {
+        let (entries, errors) = crate::harness::assert_new_glob_is_ok("**/*.rs")
+            .walk_with_behavior(temptree.as_ref(), LinkBehavior::ReadTarget)
+            .partition_results();
+        assert_set_eq!(
+            entries.into_iter().map(Entry::into_path).collect::<HashSet<_>>(),
+            temptree
+                .join_all(["src/glob.rs", "src/lib.rs", "tests/harness/mod.rs", "tests/walk.rs"])
+                .collect(),
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path(), Some(temptree.join("tests/cycle").as_path()));
+    }
+
+    #[rstest]
+    fn walk_in_glob_with_leading_tree_reads_all_directories(virtual_tree: VirtualTree) {
+        harness::assert_walk_in_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**/harness/**").walk_in(
+                &virtual_tree,
+                virtual_tree.as_ref(),
+                WalkBehavior::default(),
+            ),
+            virtual_tree.join_all(["tests/harness", "tests/harness/mod.rs"]),
+        );
+        // A leading tree wildcard may match any directory, so every directory must be read to
+        // discover the `harness` directory at any depth. No directory tree can be discarded.
+        assert_set_eq!(
+            virtual_tree.reads(),
+            virtual_tree
+                .join_all(["", "doc", "src", "tests", "tests/harness"])
+                .collect(),
+        );
+    }
+
+    #[rstest]
+    fn walk_in_glob_with_unreadable_tree_is_permission_denied_err(virtual_tree: VirtualTree) {
         use std::io;
-        use std::path::Path;
 
-        #[cfg(unix)]
-        fn link(target: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
-            std::os::unix::fs::symlink(target, link)
-        }
+        let virtual_tree = virtual_tree.with_unreadable_trees(["src"]);
+        let mut errors: Vec<_> = crate::harness::assert_new_glob_is_ok("**/*.rs")
+            .walk_in(&virtual_tree, virtual_tree.as_ref(), WalkBehavior::default())
+            .filter_map(Result::err)
+            .collect();
+        assert_eq!(errors.len(), 1);
+        let error = errors.pop().unwrap();
+        assert!(error.is_permission_denied());
+        assert_eq!(error.path(), Some(virtual_tree.as_ref().join("src").as_ref()));
+        assert_eq!(
+            error.io_error().map(io::Error::kind),
+            Some(io::ErrorKind::PermissionDenied),
+        );
+        assert_eq!(io::Error::from(error).kind(), io::ErrorKind::PermissionDenied);
+    }
 
-        #[cfg(windows)]
-        fn link(target: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
-            std::os::windows::fs::symlink_dir(target, link)
+    #[rstest]
+    fn walk_in_glob_with_skip_unreadable_excludes_unreadable_trees(virtual_tree: VirtualTree) {
+        let virtual_tree = virtual_tree.with_unreadable_trees(["tests"]);
+        let glob = crate::harness::assert_new_glob_is_ok("**/*.rs");
+        let errors = glob
+            .walk_in(&virtual_tree, virtual_tree.as_ref(), WalkBehavior::default())
+            .filter(Result::is_err)
+            .count();
+        assert_eq!(errors, 1);
+        harness::assert_walk_paths_eq(
+            glob.walk_in(
+                &virtual_tree,
+                virtual_tree.as_ref(),
+                WalkBehavior::default(),
+            )
+            .skip_unreadable(),
+            virtual_tree.join_all(["src/glob.rs", "src/lib.rs"]),
+        );
+    }
+
+    // Permission to read files cannot be denied to privileged users, so this test is skipped when
+    // run by such users. See `walk_in_glob_with_skip_unreadable_excludes_unreadable_trees`.
+    #[cfg(unix)]
+    #[rstest]
+    fn walk_glob_with_skip_unreadable_excludes_unreadable_trees(temptree: TempTree) {
+        use std::fs::{self, Permissions};
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let path = temptree.join("tests");
+        fs::set_permissions(&path, Permissions::from_mode(0o000)).unwrap();
+        if fs::read_dir(&path).is_ok() {
+            fs::set_permissions(&path, Permissions::from_mode(0o755)).unwrap();
+            eprintln!("skipped: permission to read files cannot be denied to this user");
+            return;
         }
+        let errors = crate::harness::assert_new_glob_is_ok("**/*.rs")
+            .walk(temptree.as_ref())
+            .filter(Result::is_err)
+            .count();
+        let paths: HashSet<_> = crate::harness::assert_new_glob_is_ok("**/*.rs")
+            .walk(temptree.as_ref())
+            .skip_unreadable()
+            .map(|entry| entry.expect("failed to read file").into_path())
+            .collect();
+        fs::set_permissions(&path, Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(errors, 1);
+        assert_set_eq!(paths, temptree.join_all(["src/glob.rs", "src/lib.rs"]).collect());
+    }
 
-        // Get a temporary tree and create a re-entrant symbolic link.
-        let temptree = temptree();
-        link(&temptree, temptree.join("tests/cycle"))
-            .expect("failed to write symbolic link in temporary tree");
-        temptree
+    #[rstest]
+    fn walk_empty_glob_includes_only_root_path(temptree: TempTree, virtual_tree: VirtualTree) {
+        let glob = crate::Glob::empty();
+        harness::assert_walk_paths_eq(glob.walk(temptree.as_ref()), [temptree.to_path_buf()]);
+        harness::assert_walk_in_paths_eq(
+            glob.walk_in(&virtual_tree, virtual_tree.as_ref(), WalkBehavior::default()),
+            virtual_tree.join_all([""]),
+        );
+        // No directory is read beneath the root path.
+        assert!(virtual_tree
+            .reads()
+            .iter()
+            .all(|path| path == virtual_tree.as_ref()));
     }
 
     #[rstest]
-    fn walk_path_includes_all_paths(temptree: TempTree) {
-        harness::assert_walk_paths_eq(temptree.walk(), temptree.join_all(ALL));
+    fn walk_empty_partitioned_glob_at_non_empty_prefix_includes_only_prefix(temptree: TempTree) {
+        let (prefix, glob) =
+            crate::harness::assert_new_glob_is_ok("src/lib.rs").partition_or_empty();
+        harness::assert_walk_paths_eq(
+            glob.walk(temptree.join(prefix)),
+            [temptree.join("src/lib.rs")],
+        );
     }
 
     #[rstest]
-    #[case::subtree(
-        "tests/**",
-        [
-            "",
-            "doc",
-            "doc/guide.md",
-            "src",
-            "src/glob.rs",
-            "src/lib.rs",
-            "README.md",
-        ],
-    )]
-    #[case::extension_from_root("*.md", except(["README.md"]))]
-    #[case::extension_from_any_tree("**/*.md", except(["doc/guide.md", "README.md"]))]
-    #[case::any(
-        crate::any(["**/*.rs", "tests/**"]),
-        ["", "doc", "doc/guide.md", "src", "README.md"],
-    )]
-    // The root path ("") must **not** be present, because the empty `not` pattern matches the
-    // empty relative path at the root.
-    #[case::empty("", except([""]))]
-    fn walk_path_with_not_excludes_only_matching_paths<'t>(
+    #[case::zero(0, [""])]
+    #[case::one(1, ["", "doc", "src", "tests", "README.md"])]
+    #[case::two(2, except(["tests/harness/mod.rs"]))]
+    fn walk_path_with_max_depth_includes_only_paths_at_depth<'t>(
         temptree: TempTree,
-        #[case] pattern: impl Pattern<'t>,
+        #[case] max: usize,
         #[case] expected: impl IntoIterator<Item = &'t str>,
     ) {
+        harness::assert_walk_paths_eq(temptree.walk().max_depth(max), temptree.join_all(expected));
+    }
+
+    #[rstest]
+    fn walk_glob_with_not_and_max_depth_cancels_walk(temptree: TempTree) {
+        let mut filtered = vec![];
         harness::assert_walk_paths_eq(
-            temptree.walk().not(pattern).unwrap(),
-            temptree.join_all(expected),
+            crate::harness::assert_new_glob_is_ok("**/*.{md,rs}")
+                .walk(temptree.as_ref())
+                .not("doc/**")
+                .unwrap()
+                .max_depth(1)
+                .filter_entry(|entry| {
+                    filtered.push(entry.path().to_path_buf());
+                    None
+                }),
+            [temptree.join("README.md")],
+        );
+        // Directories at the maximum depth are read, but nothing beneath their contents is read.
+        assert!(filtered.contains(&temptree.join("tests/harness")));
+        assert!(!filtered.contains(&temptree.join("tests/harness/mod.rs")));
+    }
+
+    #[rstest]
+    fn walk_path_with_post_order_yields_directories_after_contents(temptree: TempTree) {
+        let paths: Vec<_> = temptree
+            .walk()
+            .ordering(DirOrdering::PostOrder)
+            .map(|entry| entry.expect("failed to read file"))
+            .map(Entry::into_path)
+            .collect();
+        assert_set_eq!(
+            paths.iter().cloned().collect::<HashSet<_>>(),
+            temptree.join_all(ALL).collect(),
+        );
+        for (n, path) in paths.iter().enumerate() {
+            assert!(
+                paths[n..].iter().all(|other| !other.starts_with(path) || other == path),
+                "directory yielded before its contents: {:?}: walk: {:#?}",
+                path,
+                paths,
+            );
+        }
+        assert_eq!(paths.last(), Some(&temptree.to_path_buf()));
+    }
+
+    // Both cases are tested, because the order in which `a` and `d` are read is unspecified and
+    // a directory is only yielded out of order when it is read before the other tree.
+    #[rstest]
+    #[case("{a,d/e/f}/**")]
+    #[case("{d,a/b/c}/**")]
+    fn walk_glob_with_post_order_and_sparse_trees_yields_directories_after_contents(
+        #[case] expression: &str,
+    ) {
+        let temptree = harness::temptree::<&str, &str>(
+            "project",
+            dir! {
+                "a" => dir! {
+                    "b" => dir! {
+                        "c" => dir! {
+                            "x" => file!(""),
+                        },
+                    },
+                },
+                "d" => dir! {
+                    "e" => dir! {
+                        "f" => dir! {
+                            "y" => file!(""),
+                        },
+                    },
+                },
+            },
+        );
+        let paths: Vec<_> = crate::harness::assert_new_glob_is_ok(expression)
+            .walk(temptree.as_ref())
+            .ordering(DirOrdering::PostOrder)
+            .map(|entry| entry.expect("failed to read file"))
+            .map(Entry::into_path)
+            .collect();
+        // Each directory is yielded immediately after its contents.
+        for (n, path) in paths.iter().enumerate() {
+            let contents = paths
+                .iter()
+                .filter(|other| other.starts_with(path) && *other != path)
+                .count();
+            assert!(
+                paths[..n]
+                    .iter()
+                    .rev()
+                    .take(contents)
+                    .all(|other| other.starts_with(path)),
+                "directory not yielded immediately after its contents: {:?}: walk: {:#?}",
+                path,
+                paths,
+            );
+        }
+    }
+
+    #[rstest]
+    #[case::pre_order(DirOrdering::PreOrder)]
+    #[case::post_order(DirOrdering::PostOrder)]
+    fn walk_path_with_ordering_and_tree_residue_cancels_walk(
+        temptree: TempTree,
+        #[case] ordering: DirOrdering,
+    ) {
+        let mut filtered = vec![];
+        harness::assert_walk_paths_eq(
+            temptree
+                .walk()
+                .filter_entry(|entry| {
+                    filtered.push(entry.path().to_path_buf());
+                    entry
+                        .path()
+                        .ends_with("tests")
+                        .then_some(EntryResidue::Tree)
+                })
+                .ordering(ordering),
+            temptree.join_all(except([
+                "tests",
+                "tests/harness",
+                "tests/harness/mod.rs",
+                "tests/walk.rs",
+            ])),
+        );
+        // The directory tree beneath `tests` must not be read at all.
+        assert!(filtered
+            .iter()
+            .all(|path| !path.starts_with(temptree.join("tests")) || path.ends_with("tests")));
+    }
+
+    #[rstest]
+    fn walk_glob_with_filter_entry_reads_metadata_once(temptree: TempTree) {
+        let mut lens = vec![];
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("README.md")
+                .walk(temptree.as_ref())
+                .filter_entry(|entry| {
+                    lens.push(entry.metadata().expect("failed to read metadata").len());
+                    // Write to the file between queries. The cached metadata must not change.
+                    std::fs::write(entry.path(), "modified").expect("failed to write file");
+                    lens.push(entry.metadata().expect("failed to read metadata").len());
+                    None
+                }),
+            [temptree.join("README.md")],
+        );
+        assert_eq!(lens, [0, 0]);
+    }
+
+    #[rstest]
+    fn walk_glob_with_not_and_inspect_residue_observes_discarded_entries(temptree: TempTree) {
+        let mut residue = HashSet::new();
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**/*.{md,rs}")
+                .walk(temptree.as_ref())
+                .not("tests/**")
+                .unwrap()
+                .inspect_residue(|entry, kind| {
+                    residue.insert((entry.root_relative_paths().1.to_path_buf(), kind));
+                }),
+            temptree.join_all(["doc/guide.md", "src/glob.rs", "src/lib.rs", "README.md"]),
+        );
+        assert_set_eq!(
+            residue,
+            [
+                ("", EntryResidue::File),
+                ("doc", EntryResidue::File),
+                ("src", EntryResidue::File),
+                ("tests", EntryResidue::Tree),
+            ]
+            .into_iter()
+            .map(|(path, kind)| (PathBuf::from(path), kind))
+            .collect(),
         );
     }
 
     #[rstest]
-    #[case::max(DepthMax(1), ["", "doc", "src", "tests", "README.md"])]
-    #[case::min(
-        DepthMin::from_min_or_unbounded(2),
-        [
-            "doc/guide.md",
-            "src/glob.rs",
-            "src/lib.rs",
-            "tests/harness",
-            "tests/harness/mod.rs",
-            "tests/walk.rs",
-        ],
-    )]
-    #[case::minmax(
-        DepthMinMax::from_depths_or_max(2, 2),
-        ["doc/guide.md", "src/glob.rs", "src/lib.rs", "tests/harness", "tests/walk.rs"],
-    )]
-    fn walk_path_with_depth_behavior_includes_only_paths_at_depth<'t>(
-        temptree: TempTree,
-        #[case] depth: impl Into<DepthBehavior>,
-        #[case] expected: impl IntoIterator<Item = &'t str>,
-    ) {
+    fn walk_path_with_past_deadline_is_timeout_err(temptree: TempTree) {
+        let mut descended = vec![];
+        let mut entries = temptree
+            .walk()
+            .on_descend(|path| descended.push(path.to_path_buf()))
+            .with_deadline(Instant::now());
+        let error = entries
+            .next()
+            .expect("walk is empty")
+            .expect_err("walk with past deadline is `Ok`");
+        assert!(error.is_timeout());
+        assert!(error.io_error().is_none());
+        assert!(entries.next().is_none());
+        drop(entries);
+        assert!(descended.is_empty());
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(3)]
+    fn walk_path_with_limit_includes_only_limit(temptree: TempTree, #[case] n: usize) {
+        assert_eq!(temptree.walk().limit(n).flatten().count(), n);
+    }
+
+    #[rstest]
+    fn walk_path_with_limit_cancels_walk_of_final_entry(temptree: TempTree) {
+        let (entries, stats) = temptree.walk().with_stats();
+        let entries: Vec<_> = entries
+            .limit(1)
+            .map(|entry| entry.expect("failed to read file").into_path())
+            .collect();
+        // The root is the first and only entry. Its tree is cancelled, so it is never read.
+        assert_eq!(entries, [temptree.to_path_buf()]);
+        assert_eq!(stats.directories_read(), 0);
+        assert_eq!(stats.directories_pruned(), 1);
+    }
+
+    #[rstest]
+    fn walk_path_with_limit_beyond_walk_includes_all_paths(temptree: TempTree) {
+        harness::assert_walk_paths_eq(temptree.walk().limit(usize::MAX), temptree.join_all(ALL));
+    }
+
+    #[rstest]
+    fn walk_path_with_future_deadline_includes_all_paths(temptree: TempTree) {
         harness::assert_walk_paths_eq(
-            temptree.walk_with_behavior(depth.into()),
-            temptree.join_all(expected),
+            temptree
+                .walk()
+                .with_deadline(Instant::now() + Duration::from_secs(3600)),
+            temptree.join_all(ALL),
         );
     }
 
     #[rstest]
-    #[case::tree("**", ALL)]
-    #[case::bounded_terminating_component("**/*.md", ["doc/guide.md", "README.md"])]
-    #[case::invariant_intermediate_component("**/src/**/*.rs", ["src/glob.rs", "src/lib.rs"])]
-    #[case::invariant("src/lib.rs", ["src/lib.rs"])]
-    fn walk_glob_includes_only_matching_paths<'t>(
-        temptree: TempTree,
-        #[case] expression: &str,
-        #[case] expected: impl IntoIterator<Item = &'t str>,
-    ) {
-        harness::assert_walk_paths_eq(
-            crate::harness::assert_new_glob_is_ok(expression).walk(temptree.as_ref()),
-            temptree.join_all(expected),
+    fn walk_path_with_filter_map_entry_maps_and_cancels_walk(temptree: TempTree) {
+        let mut filtered = vec![];
+        let mut entries: Vec<_> = temptree
+            .walk()
+            .filter_map_entry(|entry| {
+                filtered.push(entry.path().to_path_buf());
+                if entry.path().ends_with("tests") {
+                    Err(EntryResidue::Tree)
+                }
+                else {
+                    Ok(entry.file_type().is_file().then(|| entry.depth()))
+                }
+            })
+            .map(|entry| entry.expect("failed to read file"))
+            .map(|entry| {
+                let (entry, depth) = entry.into_entry_and_value();
+                (entry.into_path(), depth)
+            })
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            [
+                ("README.md", 1),
+                ("doc/guide.md", 2),
+                ("src/glob.rs", 2),
+                ("src/lib.rs", 2),
+            ]
+            .map(|(path, depth)| (temptree.join(path), depth)),
         );
+        // The directory tree beneath `tests` must not be read at all.
+        assert!(!filtered.contains(&temptree.join("tests/walk.rs")));
     }
 
     #[rstest]
-    fn walk_empty_partitioned_glob_at_non_empty_prefix_includes_only_prefix(temptree: TempTree) {
-        let (prefix, glob) =
-            crate::harness::assert_new_glob_is_ok("src/lib.rs").partition_or_empty();
+    fn walk_glob_with_not_and_filter_map_entry_cancels_walk_of_residue(temptree: TempTree) {
+        let mut filtered = vec![];
         harness::assert_walk_paths_eq(
-            glob.walk(temptree.join(prefix)),
-            [temptree.join("src/lib.rs")],
+            crate::harness::assert_new_glob_is_ok("**/*.rs")
+                .walk(temptree.as_ref())
+                .not("tests")
+                .unwrap()
+                .filter_map_entry(|entry| {
+                    filtered.push(entry.path().to_path_buf());
+                    if entry.path().ends_with("tests") {
+                        Err(EntryResidue::Tree)
+                    }
+                    else {
+                        Ok(Some(()))
+                    }
+                }),
+            temptree.join_all(["src/glob.rs", "src/lib.rs"]),
         );
+        assert!(filtered.contains(&temptree.join("tests")));
+        assert!(!filtered.contains(&temptree.join("tests/walk.rs")));
     }
 
     #[rstest]
@@ -1154,6 +4576,384 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case::prefixed_tree("src/**/*.rs", [("src/glob.rs", 2, 1), ("src/lib.rs", 2, 1)])]
+    #[case::unprefixed(
+        "*/*.rs",
+        [("src/glob.rs", 2, 2), ("src/lib.rs", 2, 2), ("tests/walk.rs", 2, 2)],
+    )]
+    #[case::prefixed(
+        "tests/**",
+        [
+            ("tests", 1, 0),
+            ("tests/harness", 2, 1),
+            ("tests/harness/mod.rs", 3, 2),
+            ("tests/walk.rs", 2, 1),
+        ],
+    )]
+    #[case::nested_prefixed("tests/harness/*.rs", [("tests/harness/mod.rs", 3, 1)])]
+    fn walk_glob_with_prefix_has_match_depth_from_prefix<'t, const N: usize>(
+        temptree: TempTree,
+        virtual_tree: VirtualTree,
+        #[case] expression: &str,
+        #[case] expected: [(&'t str, usize, usize); N],
+    ) {
+        let expected: HashSet<_> = expected
+            .into_iter()
+            .map(|(path, depth, match_depth)| (PathBuf::from(path), depth, match_depth))
+            .collect();
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        let depths: HashSet<_> = glob
+            .walk(temptree.as_ref())
+            .map(|entry| entry.expect("failed to read file"))
+            .map(|entry| {
+                (
+                    entry.relative_path().to_path_buf(),
+                    entry.depth(),
+                    entry.match_depth(),
+                )
+            })
+            .collect();
+        assert_set_eq!(depths, expected.clone());
+        let depths: HashSet<_> = glob
+            .walk_in(&virtual_tree, virtual_tree.as_ref(), WalkBehavior::default())
+            .map(|entry| entry.expect("failed to read file"))
+            .map(|entry| {
+                (
+                    entry.relative_path().to_path_buf(),
+                    entry.depth(),
+                    entry.match_depth(),
+                )
+            })
+            .collect();
+        assert_set_eq!(depths, expected);
+    }
+
+    #[rstest]
+    #[case::tree("**", DepthBehavior::Unbounded, except([""]).collect::<Vec<_>>())]
+    #[case::empty("", DepthBehavior::Unbounded, vec![])]
+    #[case::prefixed("src/**", DepthBehavior::Unbounded, vec!["src", "src/glob.rs", "src/lib.rs"])]
+    #[case::min(
+        "**",
+        DepthMin::from_min_or_unbounded(2),
+        except(["", "doc", "src", "tests", "README.md"]).collect::<Vec<_>>(),
+    )]
+    #[case::max("**", DepthMax(1).into(), vec!["doc", "src", "tests", "README.md"])]
+    fn walk_glob_excluding_root_excludes_only_root_path(
+        temptree: TempTree,
+        virtual_tree: VirtualTree,
+        #[case] expression: &str,
+        #[case] depth: DepthBehavior,
+        #[case] expected: Vec<&str>,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        let behavior = WalkBehavior {
+            depth,
+            include_root: false,
+            ..Default::default()
+        };
+        harness::assert_walk_paths_eq(
+            glob.walk_with_behavior(temptree.as_ref(), behavior),
+            temptree.join_all(expected.iter().copied()),
+        );
+        harness::assert_walk_in_paths_eq(
+            glob.walk_in(&virtual_tree, virtual_tree.as_ref(), behavior),
+            virtual_tree.join_all(expected),
+        );
+    }
+
+    #[rstest]
+    #[case::tree(
+        "**/*.rs",
+        vec!["", "doc", "src", "tests", "tests/harness"],
+        vec!["src/glob.rs", "src/lib.rs", "tests/harness/mod.rs", "tests/walk.rs"],
+    )]
+    #[case::prefixed("src/*.rs", vec!["src"], vec!["src/glob.rs", "src/lib.rs"])]
+    #[case::components(
+        "tests/*/mod.rs",
+        vec!["tests", "tests/harness"],
+        vec!["tests/harness/mod.rs"],
+    )]
+    #[case::terminal("src", vec![], vec!["src"])]
+    fn walk_glob_with_intermediate_dirs_includes_intermediate_dirs(
+        temptree: TempTree,
+        virtual_tree: VirtualTree,
+        #[case] expression: &str,
+        #[case] intermediate: Vec<&str>,
+        #[case] matched: Vec<&str>,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        let behavior = WalkBehavior {
+            yield_intermediate_dirs: true,
+            ..Default::default()
+        };
+        let expected: HashSet<_> = intermediate
+            .iter()
+            .map(|path| (PathBuf::from(path), true))
+            .chain(matched.iter().map(|path| (PathBuf::from(path), false)))
+            .collect();
+        let paths: HashSet<_> = glob
+            .walk_with_behavior(temptree.as_ref(), behavior)
+            .map(|entry| entry.expect("failed to read file"))
+            .map(|entry| (entry.relative_path().to_path_buf(), entry.is_intermediate()))
+            .collect();
+        assert_set_eq!(paths, expected.clone());
+        let paths: HashSet<_> = glob
+            .walk_in(&virtual_tree, virtual_tree.as_ref(), behavior)
+            .map(|entry| entry.expect("failed to read file"))
+            .map(|entry| (entry.relative_path().to_path_buf(), entry.is_intermediate()))
+            .collect();
+        assert_set_eq!(paths, expected);
+    }
+
+    #[rstest]
+    #[case("**/*.rs", vec!["src/glob.rs", "src/lib.rs", "tests/harness/mod.rs", "tests/walk.rs"])]
+    #[case("src/*", vec!["src/glob.rs", "src/lib.rs"])]
+    #[case("*.md", vec!["README.md"])]
+    fn walk_path_matching_includes_only_matching_paths(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        harness::assert_walk_paths_eq(
+            temptree
+                .walk_matching(expression)
+                .expect("failed to build glob expression"),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[rstest]
+    fn walk_path_matching_with_invalid_expression_is_err(temptree: TempTree) {
+        assert!(temptree.walk_matching("**/{a").is_err());
+    }
+
+    #[rstest]
+    fn walk_non_empty_tree_glob_excludes_only_root_path(temptree: TempTree) {
+        harness::assert_walk_paths_eq(
+            crate::harness::assert_new_glob_is_ok("**")
+                .non_empty()
+                .walk(temptree.as_ref()),
+            temptree.join_all(except([""])),
+        );
+    }
+
+    #[rstest]
+    fn walk_path_excluding_root_excludes_only_root_path(temptree: TempTree) {
+        harness::assert_walk_paths_eq(
+            temptree.walk_with_behavior(WalkBehavior {
+                include_root: false,
+                ..Default::default()
+            }),
+            temptree.join_all(except([""])),
+        );
+    }
+
+    #[rstest]
+    #[case::tree("**", ALL.to_vec())]
+    #[case::prefixed("src/**", vec!["src", "src/glob.rs", "src/lib.rs"])]
+    #[case::nested_prefixed("tests/harness/*.rs", vec!["tests/harness/mod.rs"])]
+    fn walk_glob_splits_paths_at_root(
+        temptree: TempTree,
+        virtual_tree: VirtualTree,
+        #[case] expression: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        let paths: HashSet<_> = glob
+            .walk(temptree.as_ref())
+            .map(|entry| entry.expect("failed to read file"))
+            .map(|entry| {
+                let (root, relative) = entry.root_relative_paths();
+                assert_eq!(root, temptree.as_ref());
+                assert_eq!(relative, entry.relative_path());
+                relative.to_path_buf()
+            })
+            .collect();
+        assert_set_eq!(paths, expected.iter().map(PathBuf::from).collect());
+        let paths: HashSet<_> = glob
+            .walk_in(&virtual_tree, virtual_tree.as_ref(), WalkBehavior::default())
+            .map(|entry| entry.expect("failed to read file"))
+            .map(|entry| {
+                let (root, relative) = entry.root_relative_paths();
+                assert_eq!(root, virtual_tree.as_ref());
+                assert_eq!(relative, entry.relative_path());
+                relative.to_path_buf()
+            })
+            .collect();
+        assert_set_eq!(paths, expected.into_iter().map(PathBuf::from).collect());
+    }
+
+    #[rstest]
+    #[case("project", vec![""])]
+    #[case("project/src/*.rs", vec!["src/glob.rs", "src/lib.rs"])]
+    #[case("project/**/*.md", vec!["README.md", "doc/guide.md"])]
+    #[case(
+        "*/tests/**",
+        vec!["tests", "tests/harness", "tests/harness/mod.rs", "tests/walk.rs"],
+    )]
+    #[case("src/**", vec![])]
+    #[case("other/**", vec![])]
+    fn walk_glob_with_strip_prefix_matches_stripped_paths(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        let prefix = temptree.parent().expect("temporary directory has no parent");
+        harness::assert_walk_paths_eq(
+            glob.walk_with_strip_prefix(temptree.as_ref(), prefix, WalkBehavior::default())
+                .expect("failed to strip prefix"),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[rstest]
+    #[case(".", "**/*.md", vec!["doc/guide.md", "README.md"])]
+    #[case("./src/../.", "**/*.md", vec!["doc/guide.md", "README.md"])]
+    #[case("tests/./harness", "*.rs", vec!["tests/harness/mod.rs"])]
+    #[case("tests/harness/../..", "src/*.rs", vec!["src/glob.rs", "src/lib.rs"])]
+    fn walk_glob_with_clean_normalization_yields_clean_paths(
+        temptree: TempTree,
+        #[case] root: &str,
+        #[case] expression: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        harness::assert_walk_paths_eq(
+            glob.walk_with_behavior(temptree.join(root), PathNormalization::Clean),
+            temptree.join_all(expected),
+        );
+    }
+
+    #[test]
+    fn walk_glob_with_relative_to_current_dir_normalization_yields_relative_paths() {
+        let cwd = env::current_dir().expect("failed to read current directory");
+        let temptree = harness::temptree_in::<&str, &str>(
+            &cwd,
+            "project",
+            dir! {
+                "src" => dir! {
+                    "lib.rs" => file!(""),
+                },
+            },
+        );
+        let relative = temptree
+            .strip_prefix(&cwd)
+            .expect("temporary directory is not beneath current directory");
+        let glob = crate::harness::assert_new_glob_is_ok("**/*.rs");
+        harness::assert_walk_paths_eq(
+            glob.walk_with_behavior(
+                temptree.join("./src/.."),
+                PathNormalization::RelativeToCurrentDir,
+            ),
+            [relative.join("src/lib.rs")],
+        );
+    }
+
+    #[rstest]
+    fn walk_glob_with_relative_to_current_dir_normalization_outside_current_dir_yields_clean_paths(
+        temptree: TempTree,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok("src/*.rs");
+        // The temporary tree is not beneath the current directory, so the root is only cleaned.
+        harness::assert_walk_paths_eq(
+            glob.walk_with_behavior(
+                temptree.join("tests/harness/../.."),
+                PathNormalization::RelativeToCurrentDir,
+            ),
+            temptree.join_all(["src/glob.rs", "src/lib.rs"]),
+        );
+    }
+
+    #[cfg(unix)]
+    #[rstest]
+    fn walk_glob_with_clean_normalization_preserves_parent_of_link(
+        temptree_with_dir_link: TempTree,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok("*.md");
+        // The parent of the link target `src` is the root of the tree, not `doc`, so `doc/src/..`
+        // must not be collapsed.
+        harness::assert_walk_paths_eq(
+            glob.walk_with_behavior(
+                temptree_with_dir_link.join("doc/src/.."),
+                PathNormalization::Clean,
+            ),
+            [temptree_with_dir_link.join("doc/src/../README.md")],
+        );
+    }
+
+    #[rstest]
+    #[case("**/*.md", true)]
+    #[case("src/lib.rs", true)]
+    #[case("tests/harness", true)]
+    #[case("**/*.toml", false)]
+    #[case("doc/**/*.rs", false)]
+    fn glob_exists_match_eq(temptree: TempTree, #[case] expression: &str, #[case] expected: bool) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.exists_match(temptree.as_ref(), WalkBehavior::default())
+                .expect("failed to read file"),
+            expected,
+            "unexpected result of `Glob::exists_match`: expression: `{}`",
+            expression,
+        );
+    }
+
+    #[rstest]
+    fn try_walk_glob_with_existing_root_is_ok(temptree: TempTree) {
+        let glob = crate::harness::assert_new_glob_is_ok("src/*.rs");
+        harness::assert_walk_paths_eq(
+            glob.try_walk(temptree.as_ref(), WalkBehavior::default())
+                .expect("failed to read root path"),
+            temptree.join_all(["src/glob.rs", "src/lib.rs"]),
+        );
+    }
+
+    #[rstest]
+    #[case("missing", "**")]
+    #[case("", "missing/**")]
+    fn try_walk_glob_with_missing_root_is_not_found_err(
+        temptree: TempTree,
+        #[case] path: &str,
+        #[case] expression: &str,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        let error = match glob.try_walk(temptree.join(path), WalkBehavior::default()) {
+            Ok(_) => panic!("`Glob::try_walk` is `Ok`, but expected `Err`"),
+            Err(error) => error,
+        };
+        assert_eq!(
+            error.io_error().map(io::Error::kind),
+            Some(io::ErrorKind::NotFound),
+        );
+        assert_eq!(
+            error.into_io_error().map(|error| error.kind()),
+            Some(io::ErrorKind::NotFound),
+        );
+    }
+
+    #[rstest]
+    #[case("**/*.rs", "project", "project")]
+    #[case("src/**/*.rs", "project", "project/src")]
+    #[case("src/walk/*.rs", "project", "project/src/walk")]
+    #[case("src/lib.rs", "project", "project/src/lib.rs")]
+    #[case("{src,tests}/**", "project", "project")]
+    #[case("src/*.rs", "", "src")]
+    #[cfg_attr(unix, case("/var/log/**", "project", "/var/log"))]
+    fn walk_root_glob_eq(#[case] expression: &str, #[case] base: &str, #[case] expected: &str) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        assert_eq!(glob.walk_root(Path::new(base)), Path::new(expected));
+    }
+
+    #[rstest]
+    fn walk_glob_with_strip_prefix_outside_path_is_err(temptree: TempTree) {
+        let glob = crate::harness::assert_new_glob_is_ok("**");
+        assert!(glob
+            .walk_with_strip_prefix(temptree.as_ref(), "/mnt/project", WalkBehavior::default())
+            .is_err());
+    }
+
     #[cfg(any(unix, windows))]
     #[rstest]
     fn walk_glob_with_read_link_file_behavior_includes_link_file(
@@ -1179,6 +4979,71 @@ mod tests {
         );
     }
 
+    #[cfg(any(unix, windows))]
+    #[rstest]
+    fn walk_glob_with_read_link_target_no_descend_behavior_includes_link_file_as_target(
+        #[from(temptree_with_cyclic_link)] temptree: TempTree,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok("**");
+        // Collect paths into a `Vec` so that duplicates can be detected. Errors, such as cycles,
+        // are not expected.
+        let mut entries: Vec<_> = glob
+            .walk_with_behavior(temptree.as_ref(), LinkBehavior::ReadTargetNoDescend)
+            .map(|entry| entry.expect("failed to read file"))
+            .collect();
+        entries.sort_unstable_by(|left, right| left.path().cmp(right.path()));
+        assert_eq!(
+            entries.iter().map(Entry::path).collect::<Vec<_>>(),
+            temptree
+                .join_all([
+                    "",
+                    "README.md",
+                    "doc",
+                    "doc/guide.md",
+                    "src",
+                    "src/glob.rs",
+                    "src/lib.rs",
+                    "tests",
+                    "tests/cycle",
+                    "tests/harness",
+                    "tests/harness/mod.rs",
+                    "tests/walk.rs",
+                ])
+                .collect::<Vec<_>>(),
+        );
+        let link = entries
+            .iter()
+            .find(|entry| entry.path() == temptree.join("tests/cycle"))
+            .expect("no link file");
+        assert!(link.file_type().is_dir());
+        assert!(link.metadata().expect("failed to read metadata").is_dir());
+    }
+
+    #[cfg(any(unix, windows))]
+    #[rstest]
+    #[case(LinkBehavior::ReadFile, Some(""))]
+    #[case(LinkBehavior::ReadTarget, None)]
+    #[case(LinkBehavior::ReadTargetNoDescend, None)]
+    fn walk_glob_entry_read_link_eq(
+        #[from(temptree_with_cyclic_link)] temptree: TempTree,
+        #[case] link: LinkBehavior,
+        #[case] expected: Option<&str>,
+    ) {
+        for entry in crate::harness::assert_new_glob_is_ok("**")
+            .walk_with_behavior(temptree.as_ref(), link)
+            // Following the cyclic link yields an error, which is ignored here.
+            .filter_map(Result::ok)
+        {
+            let target = entry.read_link().expect("failed to read link");
+            if entry.path() == temptree.join("tests/cycle") {
+                assert_eq!(target, expected.map(|expected| temptree.join(expected)));
+            }
+            else {
+                assert_eq!(target, None);
+            }
+        }
+    }
+
     #[cfg(any(unix, windows))]
     #[rstest]
     fn walk_glob_with_read_link_target_behavior_excludes_cyclic_link_target(
@@ -1212,4 +5077,96 @@ mod tests {
         paths.sort_unstable();
         assert_eq!(paths, expected);
     }
+
+    #[cfg(unix)]
+    #[rstest]
+    fn walk_path_with_unique_by_inode_excludes_files_read_through_links(
+        #[from(temptree_with_dir_link)] temptree: TempTree,
+    ) {
+        // Without the combinator, the linked directory tree is read twice.
+        assert_eq!(
+            temptree
+                .walk_with_behavior(LinkBehavior::ReadTarget)
+                .flatten()
+                .count(),
+            ALL.len() + 3,
+        );
+        // The linked directory tree may be read through either path first, so the yielded paths
+        // are compared by their targets.
+        let paths: Vec<_> = temptree
+            .walk_with_behavior(LinkBehavior::ReadTarget)
+            .unique_by_inode()
+            .map(|entry| entry.expect("failed to read file"))
+            .map(|entry| {
+                entry
+                    .path()
+                    .canonicalize()
+                    .expect("failed to canonicalize path")
+            })
+            .collect();
+        let root = temptree
+            .canonicalize()
+            .expect("failed to canonicalize path");
+        assert_eq!(paths.len(), ALL.len());
+        assert_set_eq!(
+            paths.into_iter().collect::<HashSet<_>>(),
+            ALL.into_iter().map(|path| root.join(path)).collect(),
+        );
+    }
+
+    #[rstest]
+    #[case("**", [], ["", "doc", "src", "tests", "tests/harness"])]
+    #[case("**/*.md", [], ["", "doc", "src", "tests", "tests/harness"])]
+    #[case("**", ["tests/**"], ["", "doc", "src"])]
+    #[case("**", ["**/harness/**"], ["", "doc", "src", "tests"])]
+    #[case("", [], [""])]
+    fn walk_glob_with_on_descend_reports_only_read_directories<const N: usize, const M: usize>(
+        temptree: TempTree,
+        #[case] expression: &str,
+        #[case] not: [&str; N],
+        #[case] expected: [&str; M],
+    ) {
+        let mut paths = HashSet::new();
+        crate::harness::assert_new_glob_is_ok(expression)
+            .walk(temptree.as_ref())
+            .not_globs(not.map(crate::harness::assert_new_glob_is_ok))
+            .expect("failed to build negation")
+            .on_descend(|path| {
+                assert!(
+                    paths.insert(path.to_path_buf()),
+                    "directory `{}` reported more than once",
+                    path.display(),
+                );
+            })
+            .for_each(drop);
+        assert_set_eq!(paths, temptree.join_all(expected).collect());
+    }
+
+    #[rstest]
+    #[case("src/*.rs", "", true)]
+    #[case("src/*.rs", "src", true)]
+    #[case("src/*.rs", "doc", false)]
+    #[case("src/*/test/**/*.rs", "src/walk", true)]
+    #[case("src/*/test/**/*.rs", "src/walk/test/a/b", true)]
+    #[case("src/*/test/**/*.rs", "src/walk/bench", false)]
+    #[case("{doc,src}/**", "src/walk", true)]
+    #[case("{doc,src}/**", "tests/harness", false)]
+    #[case("**/*.md", "doc/guide", true)]
+    #[case("**/{doc,src}", "tests/harness", true)]
+    #[case("/usr/*/x", "/usr/lib", true)]
+    #[case("/usr/*/x", "/opt/lib", false)]
+    fn glob_could_match_under_eq(
+        #[case] expression: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        let glob = crate::harness::assert_new_glob_is_ok(expression);
+        assert_eq!(
+            glob.could_match_under(path.as_ref()),
+            expected,
+            "unexpected result of `Glob::could_match_under`: expression: `{}`, path: `{}`",
+            expression,
+            path,
+        );
+    }
 }